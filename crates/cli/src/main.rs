@@ -1,7 +1,13 @@
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use colored::Colorize;
-use std::path::PathBuf;
+use mamba_error::{output, Diagnostic, ErrorCode, Renderer, Severity};
+use mamba_parser::annotate;
+use mamba_parser::cache::AnalysisCache;
+use mamba_parser::checker::CheckMode;
+use mamba_parser::lint::{self, Lint};
+use mamba_parser::modules::{CheckedModule, ModuleLoader};
+use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
 #[command(name = "mamba")]
@@ -45,19 +51,79 @@ enum Commands {
     Check {
         /// File to check
         file: PathBuf,
+
+        /// Disable colored output (also honored via the NO_COLOR environment variable)
+        #[arg(long)]
+        no_color: bool,
+
+        /// Wrap diagnostic messages to this many columns
+        #[arg(long, value_name = "COLUMNS")]
+        max_width: Option<usize>,
+
+        /// How to report diagnostics
+        #[arg(long, value_enum, default_value_t = MessageFormat::Human)]
+        message_format: MessageFormat,
+
+        /// Enable an opt-in lint (e.g. rebind-type); may be repeated
+        #[arg(long = "lint", value_name = "LINT", value_parser = parse_lint)]
+        lints: Vec<Lint>,
+
+        /// Require type annotations on every function signature
+        #[arg(long)]
+        strict: bool,
+
+        /// Analyze every module again instead of reusing the results cached in .mamba_cache next to the file
+        #[arg(long)]
+        no_cache: bool,
+    },
+
+    /// Add the types inference finds as annotations where they are missing
+    Annotate {
+        /// File to annotate in place
+        file: PathBuf,
+
+        /// Print the annotated source instead of writing the file
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Explain an error code (e.g. E0201)
+    Explain {
+        /// Error code to explain
+        code: String,
     },
 }
 
+/// Output format for diagnostics
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum MessageFormat {
+    /// Rendered snippets for terminals
+    Human,
+    /// One JSON object per diagnostic, one per line
+    Json,
+    /// A single SARIF 2.1.0 log
+    Sarif,
+}
+
 fn main() -> Result<()> {
     // Initialize logger
     env_logger::init();
 
     let cli = Cli::parse();
 
+    // Machine-readable output must not be mixed with the banner
+    let machine_output = matches!(
+        cli.command,
+        Some(Commands::Check { message_format: MessageFormat::Json | MessageFormat::Sarif, .. })
+            | Some(Commands::Annotate { dry_run: true, .. })
+    );
+
     // TODO: Implement actual compilation pipeline
-    println!("{}", "Mamba v0.1.0".green().bold());
-    println!("{}", "Python syntax. Rust speed. One tool.".dimmed());
-    println!();
+    if !machine_output {
+        println!("{}", "Mamba v0.1.0".green().bold());
+        println!("{}", "Python syntax. Rust speed. One tool.".dimmed());
+        println!();
+    }
 
     match &cli.command {
         Some(Commands::Build { file, output }) => {
@@ -71,10 +137,94 @@ fn main() -> Result<()> {
             println!("Running: {}", file.display());
             println!("{}", "Not yet implemented".yellow());
         }
-        Some(Commands::Check { file }) => {
-            println!("Checking: {}", file.display());
-            println!("{}", "Not yet implemented".yellow());
+        Some(Commands::Check { file, no_color, max_width, message_format, lints, strict, no_cache }) => {
+            let mode = if *strict { CheckMode::Strict } else { CheckMode::Lenient };
+            let modules = check_program(file, lints, mode, !no_cache)?;
+            let diagnostics: Vec<(String, &str, &Diagnostic)> = modules
+                .iter()
+                .flat_map(|module| {
+                    let name = module.path.display().to_string();
+                    module.diagnostics.iter().map(move |d| (name.clone(), module.source.as_str(), d))
+                })
+                .collect();
+            let error_count = diagnostics.iter().filter(|(_, _, d)| d.severity == Severity::Error).count();
+
+            match message_format {
+                MessageFormat::Human => {
+                    println!("Checking: {}", file.display());
+                    let mut renderer =
+                        Renderer::new().with_color(!no_color && std::env::var_os("NO_COLOR").is_none());
+                    if let Some(width) = max_width {
+                        renderer = renderer.with_max_width(*width);
+                    }
+                    for (name, source, diagnostic) in &diagnostics {
+                        eprintln!("{}\n", renderer.render(diagnostic, name, source));
+                    }
+
+                    let warning_count = diagnostics.len() - error_count;
+                    if warning_count > 0 {
+                        eprintln!("{}", format!("Found {} warning(s)", warning_count).yellow().bold());
+                    }
+                    if error_count == 0 {
+                        println!("{}", "No errors found".green());
+                    } else {
+                        eprintln!("{}", format!("Found {} error(s)", error_count).red().bold());
+                    }
+                }
+                MessageFormat::Json => {
+                    for (name, _, diagnostic) in &diagnostics {
+                        println!("{}", output::to_json_line(diagnostic, name));
+                    }
+                }
+                MessageFormat::Sarif => {
+                    let log = output::to_sarif(diagnostics.iter().map(|(name, _, d)| (name.as_str(), *d)));
+                    println!("{}", serde_json::to_string_pretty(&log)?);
+                }
+            }
+
+            if error_count > 0 {
+                std::process::exit(1);
+            }
         }
+        Some(Commands::Annotate { file, dry_run }) => {
+            let source = std::fs::read_to_string(file)?;
+            let annotated = match annotate::annotate(&source) {
+                Ok(annotated) => annotated,
+                Err(errors) => {
+                    let renderer = Renderer::new().with_color(std::env::var_os("NO_COLOR").is_none());
+                    let name = file.display().to_string();
+                    for diagnostic in errors.iter().filter_map(|error| error.diagnostic()) {
+                        eprintln!("{}\n", renderer.render(diagnostic, &name, &source));
+                    }
+                    eprintln!("{}", format!("Found {} error(s)", errors.len()).red().bold());
+                    std::process::exit(1);
+                }
+            };
+
+            if *dry_run {
+                print!("{}", annotated.source);
+            } else {
+                println!("Annotating: {}", file.display());
+                for annotation in &annotated.annotations {
+                    println!("{}:{}: {}", file.display(), annotation.line, annotation);
+                }
+                if !annotated.annotations.is_empty() {
+                    std::fs::write(file, &annotated.source)?;
+                }
+                println!("{}", format!("Added {} annotation(s)", annotated.annotations.len()).green());
+            }
+        }
+        Some(Commands::Explain { code }) => match ErrorCode::from_code(code) {
+            Some(code) => {
+                println!("{}", format!("{}: {}", code, code.title()).bold());
+                println!();
+                println!("{}", code.explanation());
+            }
+            None => {
+                println!("{}", format!("Unknown error code '{}'", code).red());
+                std::process::exit(1);
+            }
+        },
         None => {
             if let Some(file) = &cli.file {
                 println!("Compiling and running: {}", file.display());
@@ -88,3 +238,30 @@ fn main() -> Result<()> {
 
     Ok(())
 }
+
+/// Run the lexer, parser, semantic analyzer, type checker and requested
+/// lints over a file and every module it imports from the search path,
+/// collecting each module's diagnostics. Lints run only on modules that parse.
+/// With `cache`, modules unchanged since the last check are not analyzed again.
+fn check_program(file: &Path, lints: &[Lint], mode: CheckMode, cache: bool) -> Result<Vec<CheckedModule>> {
+    let mut loader = ModuleLoader::new(ModuleLoader::search_path_for(file)).with_type_checking(mode);
+    if cache {
+        loader = loader.with_cache(AnalysisCache::for_file(file));
+    }
+    loader.check_file(file)?;
+    let mut modules = loader.into_modules();
+    for checked in &mut modules {
+        if let Some(module) = &checked.module {
+            checked.diagnostics.extend(lint::run(module, lints));
+        }
+    }
+    Ok(modules)
+}
+
+/// Parse a `--lint` value
+fn parse_lint(name: &str) -> Result<Lint, String> {
+    Lint::from_name(name).ok_or_else(|| {
+        let known: Vec<&str> = Lint::ALL.iter().map(Lint::name).collect();
+        format!("unknown lint '{}' (available: {})", name, known.join(", "))
+    })
+}
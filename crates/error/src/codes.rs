@@ -0,0 +1,592 @@
+//! Stable error codes
+//!
+//! Every diagnostic carries an `ErrorCode`. Codes never change meaning once
+//! published; `mamba explain <CODE>` prints the long-form explanation below.
+//!
+//! Ranges:
+//! - `E00xx`: lexer (tokenization) errors
+//! - `E01xx`: parser (syntax) errors
+//! - `E02xx`: semantic analysis errors
+//! - `W00xx`: warnings (lints and flow analysis)
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Stable identifier for a kind of diagnostic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ErrorCode {
+    /// E0001: character that cannot start any token
+    UnexpectedCharacter,
+    /// E0002: string literal without closing quote
+    UnterminatedString,
+    /// E0003: malformed numeric literal
+    InvalidNumber,
+    /// E0004: mixed tabs/spaces or inconsistent dedent
+    InvalidIndentation,
+    /// E0005: identifier starts with an invalid character
+    InvalidIdentifier,
+    /// E0100: token stream does not match the grammar
+    InvalidSyntax,
+    /// E0101: left-hand side cannot be assigned to
+    InvalidAssignmentTarget,
+    /// E0102: malformed or misordered parameter list
+    InvalidParameters,
+    /// E0200: name used but never defined
+    UndefinedName,
+    /// E0201: name declared twice in the same scope
+    Redeclaration,
+    /// E0202: invalid scope operation
+    InvalidScope,
+    /// E0203: nonlocal used at module level
+    NonlocalAtModuleLevel,
+    /// E0204: nonlocal name has no enclosing binding
+    NonlocalNotFound,
+    /// E0205: global declaration at module level
+    GlobalAtModuleLevel,
+    /// E0206: local name read before it is assigned
+    UnboundLocal,
+    /// E0207: `break` outside a loop
+    BreakOutsideLoop,
+    /// E0208: `continue` outside a loop
+    ContinueOutsideLoop,
+    /// E0209: `return` outside a function
+    ReturnOutsideFunction,
+    /// E0210: `nonlocal` in a class body with no enclosing function
+    NonlocalInClassBody,
+    /// E0211: call passes more positional arguments than the function takes
+    TooManyArguments,
+    /// E0212: call leaves a required parameter without a value
+    MissingArguments,
+    /// E0213: call passes a keyword the function does not accept
+    UnexpectedKeyword,
+    /// E0214: call passes a value for the same parameter twice
+    DuplicateArgument,
+    /// E0215: positional-only parameter passed by keyword
+    PositionalOnlyAsKeyword,
+    /// E0216: attribute read on `self` that the class never defines
+    UnknownAttribute,
+    /// E0217: bases admit no consistent method resolution order
+    InconsistentMro,
+    /// E0218: method without a `self` or `cls` parameter
+    MissingSelf,
+    /// E0219: import of a module that cannot be found
+    UnknownModule,
+    /// E0220: `from m import name` where `m` does not define `name`
+    UnknownImportName,
+    /// E0221: module-level import that leads back to the importing module
+    ImportCycle,
+    /// E0222: annotation names a type that is not defined
+    UnknownType,
+    /// E0223: annotation that is not a valid type
+    InvalidAnnotation,
+    /// E0224: call argument whose type does not match the parameter
+    ArgumentType,
+    /// E0225: returned value whose type does not match the return annotation
+    ReturnType,
+    /// E0226: annotated variable assigned a value of another type
+    AssignmentType,
+    /// E0227: attribute that the type of the value does not have
+    UnknownMember,
+    /// E0228: operator applied to operands of types it does not support
+    UnsupportedOperand,
+    /// E0229: value that may be None used where None is not accepted
+    PossiblyNone,
+    /// E0230: function signature without annotations in strict mode
+    MissingAnnotation,
+    /// E0231: type variable solved to a type outside its bound or constraints
+    TypeVarMismatch,
+    /// E0232: dataclass or named tuple field without a default after one with a default
+    FieldOrder,
+    /// E0233: assignment to a field of a frozen dataclass or named tuple, or to an enum member
+    FrozenAssignment,
+    /// W0001: name rebound to a value of a different type
+    TypeChangingRebinding,
+    /// W0002: name bound on some paths only
+    PossiblyUnbound,
+    /// W0003: statement that can never run
+    UnreachableCode,
+    /// W0004: function can end without returning a value
+    MissingReturn,
+}
+
+impl ErrorCode {
+    /// All known codes, in numeric order
+    pub const ALL: &'static [ErrorCode] = &[
+        ErrorCode::UnexpectedCharacter,
+        ErrorCode::UnterminatedString,
+        ErrorCode::InvalidNumber,
+        ErrorCode::InvalidIndentation,
+        ErrorCode::InvalidIdentifier,
+        ErrorCode::InvalidSyntax,
+        ErrorCode::InvalidAssignmentTarget,
+        ErrorCode::InvalidParameters,
+        ErrorCode::UndefinedName,
+        ErrorCode::Redeclaration,
+        ErrorCode::InvalidScope,
+        ErrorCode::NonlocalAtModuleLevel,
+        ErrorCode::NonlocalNotFound,
+        ErrorCode::GlobalAtModuleLevel,
+        ErrorCode::UnboundLocal,
+        ErrorCode::BreakOutsideLoop,
+        ErrorCode::ContinueOutsideLoop,
+        ErrorCode::ReturnOutsideFunction,
+        ErrorCode::NonlocalInClassBody,
+        ErrorCode::TooManyArguments,
+        ErrorCode::MissingArguments,
+        ErrorCode::UnexpectedKeyword,
+        ErrorCode::DuplicateArgument,
+        ErrorCode::PositionalOnlyAsKeyword,
+        ErrorCode::UnknownAttribute,
+        ErrorCode::InconsistentMro,
+        ErrorCode::MissingSelf,
+        ErrorCode::UnknownModule,
+        ErrorCode::UnknownImportName,
+        ErrorCode::ImportCycle,
+        ErrorCode::UnknownType,
+        ErrorCode::InvalidAnnotation,
+        ErrorCode::ArgumentType,
+        ErrorCode::ReturnType,
+        ErrorCode::AssignmentType,
+        ErrorCode::UnknownMember,
+        ErrorCode::UnsupportedOperand,
+        ErrorCode::PossiblyNone,
+        ErrorCode::MissingAnnotation,
+        ErrorCode::TypeVarMismatch,
+        ErrorCode::FieldOrder,
+        ErrorCode::FrozenAssignment,
+        ErrorCode::TypeChangingRebinding,
+        ErrorCode::PossiblyUnbound,
+        ErrorCode::UnreachableCode,
+        ErrorCode::MissingReturn,
+    ];
+
+    /// The code as printed in diagnostics (e.g. "E0001")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::UnexpectedCharacter => "E0001",
+            ErrorCode::UnterminatedString => "E0002",
+            ErrorCode::InvalidNumber => "E0003",
+            ErrorCode::InvalidIndentation => "E0004",
+            ErrorCode::InvalidIdentifier => "E0005",
+            ErrorCode::InvalidSyntax => "E0100",
+            ErrorCode::InvalidAssignmentTarget => "E0101",
+            ErrorCode::InvalidParameters => "E0102",
+            ErrorCode::UndefinedName => "E0200",
+            ErrorCode::Redeclaration => "E0201",
+            ErrorCode::InvalidScope => "E0202",
+            ErrorCode::NonlocalAtModuleLevel => "E0203",
+            ErrorCode::NonlocalNotFound => "E0204",
+            ErrorCode::GlobalAtModuleLevel => "E0205",
+            ErrorCode::UnboundLocal => "E0206",
+            ErrorCode::BreakOutsideLoop => "E0207",
+            ErrorCode::ContinueOutsideLoop => "E0208",
+            ErrorCode::ReturnOutsideFunction => "E0209",
+            ErrorCode::NonlocalInClassBody => "E0210",
+            ErrorCode::TooManyArguments => "E0211",
+            ErrorCode::MissingArguments => "E0212",
+            ErrorCode::UnexpectedKeyword => "E0213",
+            ErrorCode::DuplicateArgument => "E0214",
+            ErrorCode::PositionalOnlyAsKeyword => "E0215",
+            ErrorCode::UnknownAttribute => "E0216",
+            ErrorCode::InconsistentMro => "E0217",
+            ErrorCode::MissingSelf => "E0218",
+            ErrorCode::UnknownModule => "E0219",
+            ErrorCode::UnknownImportName => "E0220",
+            ErrorCode::ImportCycle => "E0221",
+            ErrorCode::UnknownType => "E0222",
+            ErrorCode::InvalidAnnotation => "E0223",
+            ErrorCode::ArgumentType => "E0224",
+            ErrorCode::ReturnType => "E0225",
+            ErrorCode::AssignmentType => "E0226",
+            ErrorCode::UnknownMember => "E0227",
+            ErrorCode::UnsupportedOperand => "E0228",
+            ErrorCode::PossiblyNone => "E0229",
+            ErrorCode::MissingAnnotation => "E0230",
+            ErrorCode::TypeVarMismatch => "E0231",
+            ErrorCode::FieldOrder => "E0232",
+            ErrorCode::FrozenAssignment => "E0233",
+            ErrorCode::TypeChangingRebinding => "W0001",
+            ErrorCode::PossiblyUnbound => "W0002",
+            ErrorCode::UnreachableCode => "W0003",
+            ErrorCode::MissingReturn => "W0004",
+        }
+    }
+
+    /// Look up a code by its printed form (case-insensitive)
+    pub fn from_code(code: &str) -> Option<ErrorCode> {
+        ErrorCode::ALL
+            .iter()
+            .copied()
+            .find(|c| c.as_str().eq_ignore_ascii_case(code.trim()))
+    }
+
+    /// Short, one-line title
+    pub fn title(&self) -> &'static str {
+        match self {
+            ErrorCode::UnexpectedCharacter => "unexpected character",
+            ErrorCode::UnterminatedString => "unterminated string literal",
+            ErrorCode::InvalidNumber => "invalid number literal",
+            ErrorCode::InvalidIndentation => "invalid indentation",
+            ErrorCode::InvalidIdentifier => "invalid identifier",
+            ErrorCode::InvalidSyntax => "invalid syntax",
+            ErrorCode::InvalidAssignmentTarget => "invalid assignment target",
+            ErrorCode::InvalidParameters => "invalid parameter list",
+            ErrorCode::UndefinedName => "undefined name",
+            ErrorCode::Redeclaration => "redeclaration",
+            ErrorCode::InvalidScope => "invalid scope",
+            ErrorCode::NonlocalAtModuleLevel => "nonlocal at module level",
+            ErrorCode::NonlocalNotFound => "no binding for nonlocal",
+            ErrorCode::GlobalAtModuleLevel => "global at module level",
+            ErrorCode::UnboundLocal => "local referenced before assignment",
+            ErrorCode::BreakOutsideLoop => "break outside loop",
+            ErrorCode::ContinueOutsideLoop => "continue outside loop",
+            ErrorCode::ReturnOutsideFunction => "return outside function",
+            ErrorCode::NonlocalInClassBody => "nonlocal in class body",
+            ErrorCode::TooManyArguments => "too many arguments",
+            ErrorCode::MissingArguments => "missing arguments",
+            ErrorCode::UnexpectedKeyword => "unexpected keyword argument",
+            ErrorCode::DuplicateArgument => "duplicate argument",
+            ErrorCode::PositionalOnlyAsKeyword => "positional-only argument passed by keyword",
+            ErrorCode::UnknownAttribute => "unknown attribute",
+            ErrorCode::InconsistentMro => "inconsistent method resolution order",
+            ErrorCode::MissingSelf => "missing self parameter",
+            ErrorCode::UnknownModule => "unknown module",
+            ErrorCode::UnknownImportName => "unknown import name",
+            ErrorCode::ImportCycle => "import cycle",
+            ErrorCode::UnknownType => "unknown type",
+            ErrorCode::InvalidAnnotation => "invalid type annotation",
+            ErrorCode::ArgumentType => "incompatible argument type",
+            ErrorCode::ReturnType => "incompatible return type",
+            ErrorCode::AssignmentType => "incompatible assignment",
+            ErrorCode::UnknownMember => "unknown member",
+            ErrorCode::UnsupportedOperand => "unsupported operand types",
+            ErrorCode::PossiblyNone => "value may be None",
+            ErrorCode::MissingAnnotation => "missing type annotation",
+            ErrorCode::TypeVarMismatch => "type variable bound not satisfied",
+            ErrorCode::FieldOrder => "field without default follows default",
+            ErrorCode::FrozenAssignment => "assignment to frozen field",
+            ErrorCode::TypeChangingRebinding => "type-changing rebinding",
+            ErrorCode::PossiblyUnbound => "possibly unbound",
+            ErrorCode::UnreachableCode => "unreachable code",
+            ErrorCode::MissingReturn => "missing return",
+        }
+    }
+
+    /// Long-form explanation with examples, shown by `mamba explain`
+    pub fn explanation(&self) -> &'static str {
+        match self {
+            ErrorCode::UnexpectedCharacter => {
+                "A character was found that cannot start any token.\n\n\
+                 Erroneous example:\n\n    x = 5 $ 3\n\n\
+                 Only Python operators and delimiters are allowed. A lone '!' is also\n\
+                 rejected: use 'not' for negation or '!=' for inequality."
+            }
+            ErrorCode::UnterminatedString => {
+                "A string literal was opened but never closed.\n\n\
+                 Erroneous example:\n\n    name = \"mamba\n\n\
+                 Close the string with the same quote it was opened with. Single- and\n\
+                 double-quoted strings must end on the same line; use triple quotes\n\
+                 (\"\"\"...\"\"\") for multi-line strings."
+            }
+            ErrorCode::InvalidNumber => {
+                "A numeric literal is malformed or out of range.\n\n\
+                 Erroneous examples:\n\n    a = 0x\n    b = 0o9\n    c = 0b102\n\n\
+                 Hexadecimal literals need at least one digit 0-9/a-f, octal literals\n\
+                 only allow digits 0-7 and binary literals only allow 0 and 1. Integer\n\
+                 literals must fit in a signed 64-bit integer."
+            }
+            ErrorCode::InvalidIndentation => {
+                "The indentation of a line is invalid.\n\n\
+                 Erroneous example:\n\n    if x:\n            y = 1\n        z = 2\n\n\
+                 A dedent must return to a previously used indentation level, and a\n\
+                 line must not mix tabs and spaces. Use four spaces per level."
+            }
+            ErrorCode::InvalidIdentifier => {
+                "An identifier starts with a character that is not allowed.\n\n\
+                 Identifiers must start with a letter (any Unicode letter) or an\n\
+                 underscore, followed by letters, digits or underscores."
+            }
+            ErrorCode::InvalidSyntax => {
+                "The parser found a token that does not fit the grammar at this point.\n\n\
+                 Erroneous example:\n\n    if x == 5\n        print(x)\n\n\
+                 Here the ':' after the condition is missing. The message names the\n\
+                 token that was expected and the token that was found."
+            }
+            ErrorCode::InvalidAssignmentTarget => {
+                "The left-hand side of an assignment cannot be assigned to.\n\n\
+                 Erroneous examples:\n\n    5 = x\n    f() = 1\n    a, *b, *c = items\n\n\
+                 Only names, attributes (obj.attr), subscripts (items[0]) and tuples or\n\
+                 lists of those can be assigned. At most one starred target is allowed."
+            }
+            ErrorCode::InvalidParameters => {
+                "A function's parameter list is malformed.\n\n\
+                 Erroneous example:\n\n    def f(a=1, b):\n        pass\n\n\
+                 Parameters must appear in the order: positional-only, '/', regular,\n\
+                 '*' or *args, keyword-only, **kwargs. A parameter without a default\n\
+                 cannot follow one with a default, and each marker may appear once."
+            }
+            ErrorCode::UndefinedName => {
+                "A name is used but is not defined in any enclosing scope.\n\n\
+                 Erroneous example:\n\n    print(count)\n\n\
+                 Define the name before using it, import it, or check the spelling."
+            }
+            ErrorCode::Redeclaration => {
+                "A name is declared twice in a way that conflicts.\n\n\
+                 Erroneous example:\n\n    def f(x, x):\n        pass\n\n\
+                 Each parameter name must be unique, and a name cannot be declared\n\
+                 global or nonlocal after it has been bound locally. Plain\n\
+                 reassignment (x = 1 followed by x = 2) is not a redeclaration."
+            }
+            ErrorCode::InvalidScope => {
+                "A scope operation is invalid in the current context.\n\n\
+                 This usually indicates an internal error in the analyzer; please\n\
+                 report it together with the source that triggered it."
+            }
+            ErrorCode::NonlocalAtModuleLevel => {
+                "A 'nonlocal' statement appears at module level.\n\n\
+                 Erroneous example:\n\n    nonlocal x\n\n\
+                 'nonlocal' refers to a variable of an enclosing function and is only\n\
+                 valid inside a nested function. Use 'global' at module level."
+            }
+            ErrorCode::NonlocalNotFound => {
+                "A 'nonlocal' name has no binding in any enclosing function.\n\n\
+                 Erroneous example:\n\n    def outer():\n        def inner():\n            nonlocal x\n\n\
+                 Bind the name in an enclosing function first. Module-level names are\n\
+                 not considered; use 'global' for those."
+            }
+            ErrorCode::GlobalAtModuleLevel => {
+                "A name is used prior to its 'global' declaration.\n\n\
+                 Declare names global at the start of the function, before any use."
+            }
+            ErrorCode::UnboundLocal => {
+                "A local name is read before it has been assigned.\n\n\
+                 Example:\n\n    count = 0\n\n    def bump():\n        print(count)\n        count = count + 1\n\n\
+                 Any assignment in a function makes the name local to the whole\n\
+                 function, so the first 'count' does not refer to the module-level\n\
+                 variable. Declare it 'global count' (or 'nonlocal count' in a nested\n\
+                 function) to use the outer variable, or assign before reading."
+            }
+            ErrorCode::BreakOutsideLoop => {
+                "'break' appears outside the body of a 'for' or 'while' loop.\n\n\
+                 A loop's 'else' clause and the body of a function defined inside a\n\
+                 loop do not count as being in the loop. Use 'return' to leave a\n\
+                 function early."
+            }
+            ErrorCode::ContinueOutsideLoop => {
+                "'continue' appears outside the body of a 'for' or 'while' loop.\n\n\
+                 A loop's 'else' clause and the body of a function defined inside a\n\
+                 loop do not count as being in the loop."
+            }
+            ErrorCode::ReturnOutsideFunction => {
+                "'return' appears at module level or directly in a class body.\n\n\
+                 Example:\n\n    class Config:\n        return None\n\n\
+                 'return' is only valid inside a function or method."
+            }
+            ErrorCode::NonlocalInClassBody => {
+                "A class body declares a name 'nonlocal', but the class is not\n\
+                 defined inside a function, so there is no enclosing function scope\n\
+                 for the name to refer to.\n\n\
+                 Use 'global' to refer to a module-level name."
+            }
+            ErrorCode::TooManyArguments => {
+                "A call passes more positional arguments than the function accepts.\n\n\
+                 Example:\n\n    def area(width, height):\n        return width * height\n\n    area(2, 3, 4)\n\n\
+                 Remove the extra arguments, or give the function a '*args' parameter\n\
+                 to accept any number of them."
+            }
+            ErrorCode::MissingArguments => {
+                "A call does not supply a value for a parameter that has no default.\n\n\
+                 Example:\n\n    def greet(name, *, punctuation):\n        ...\n\n    greet(\"Ada\")\n\n\
+                 Both positional parameters and keyword-only parameters (those after\n\
+                 '*' or '*args') must be given unless they have a default value."
+            }
+            ErrorCode::UnexpectedKeyword => {
+                "A call passes a keyword argument that matches no parameter of the\n\
+                 function, and the function has no '**kwargs' parameter to collect it.\n\n\
+                 Example:\n\n    def connect(host, port=80):\n        ...\n\n    connect(\"localhost\", prot=8080)\n\n\
+                 Check the spelling against the function definition."
+            }
+            ErrorCode::DuplicateArgument => {
+                "A call gives the same parameter a value twice: the same keyword is\n\
+                 repeated, or a keyword names a parameter already filled by position.\n\n\
+                 Example:\n\n    def move(x, y):\n        ...\n\n    move(1, x=2)\n\n\
+                 Pass each parameter once."
+            }
+            ErrorCode::PositionalOnlyAsKeyword => {
+                "A call passes a positional-only parameter (one before '/' in the\n\
+                 definition) by keyword.\n\n\
+                 Example:\n\n    def clamp(value, /, low, high):\n        ...\n\n    clamp(value=5, low=0, high=10)\n\n\
+                 Pass positional-only parameters by position."
+            }
+            ErrorCode::UnknownAttribute => {
+                "A method reads an attribute of 'self' that neither the class nor any\n\
+                 of its bases defines: no class-level binding, no method, and no\n\
+                 'self.<name> = ...' assignment in any method.\n\n\
+                 Example:\n\n    class Point:\n        def __init__(self):\n            self.x = 0\n        def norm(self):\n            return self.x + self.y\n\n\
+                 Classes with a base whose members are unknown (an import or a builtin\n\
+                 type other than object and Exception), a '__getattr__' method, or\n\
+                 'setattr'/'__dict__' use are not checked."
+            }
+            ErrorCode::InconsistentMro => {
+                "No method resolution order satisfies the class's bases. Python\n\
+                 linearizes bases with the C3 algorithm: every class comes before its\n\
+                 own bases, and bases keep the order they are listed in.\n\n\
+                 Example:\n\n    class A: ...\n    class B(A): ...\n    class C(A, B): ...\n\n\
+                 'A' is listed before 'B' but is also a base of 'B'. List subclasses\n\
+                 before their bases: 'class C(B, A)'."
+            }
+            ErrorCode::MissingSelf => {
+                "An instance method, property or class method takes no positional\n\
+                 parameter for the instance ('self') or class ('cls') it is called on.\n\n\
+                 Example:\n\n    class Counter:\n        def reset():\n            ...\n\n\
+                 Add 'self' (or 'cls' for a class method) as the first parameter, or\n\
+                 decorate the method with '@staticmethod'."
+            }
+            ErrorCode::UnknownModule => {
+                "An import names a module that is neither a '.mmb' file on the search\n\
+                 path nor part of the Python standard library.\n\n\
+                 Example:\n\n    import helpres\n\n\
+                 A module 'a.b' is looked up as 'a/b.mmb' or 'a/b/__init__.mmb' in the\n\
+                 directory of the checked file, then in each directory listed in\n\
+                 MAMBAPATH. Check the spelling of the module name."
+            }
+            ErrorCode::UnknownImportName => {
+                "A 'from' import asks for a name that the module does not define.\n\n\
+                 Example:\n\n    # shapes.mmb defines 'area'\n    from shapes import perimeter\n\n\
+                 Only names bound at the top level of the module, and its submodules,\n\
+                 can be imported. Check the spelling or define the name in the module."
+            }
+            ErrorCode::ImportCycle => {
+                "A module imports, directly or through other modules, the module that\n\
+                 is importing it.\n\n\
+                 Example:\n\n    # a.mmb\n    from b import helper\n\n    # b.mmb\n    from a import config\n\n\
+                 When 'a' runs, 'b' starts importing 'a' before 'a' has defined\n\
+                 'config', so the import fails or sees a half-initialized module. Move\n\
+                 the shared names into a third module, or import inside the function\n\
+                 that needs them."
+            }
+            ErrorCode::UnknownType => {
+                "A type annotation names a type that is not defined anywhere visible.\n\n\
+                 Example:\n\n    def push(stack: list[\"Nod\"]) -> None:\n        ...\n\n\
+                 Names in annotations, including inside string forward references,\n\
+                 must be classes, type variables or members of 'typing'. Check the\n\
+                 spelling, or import the type."
+            }
+            ErrorCode::InvalidAnnotation => {
+                "A type annotation is not a valid type.\n\n\
+                 Example:\n\n    pairs: list[int, str] = []\n\n\
+                 'list' takes one type argument, so this should be\n\
+                 'list[tuple[int, str]]'. Other common mistakes are subscripting a\n\
+                 class that is not generic ('int[str]'), using a function or a value\n\
+                 as a type, and 'Callable' without a list of parameter types."
+            }
+            ErrorCode::ArgumentType => {
+                "A call passes an argument whose type does not match the annotation of\n\
+                 the parameter it is bound to.\n\n\
+                 Example:\n\n    def repeat(text: str, times: int) -> str:\n        return text * times\n\n    repeat(3, \"ab\")\n\n\
+                 Unannotated parameters accept any value. 'int' is accepted where\n\
+                 'float' is expected, and 'bool' where 'int' is expected."
+            }
+            ErrorCode::ReturnType => {
+                "A function returns a value whose type does not match its return\n\
+                 annotation.\n\n\
+                 Example:\n\n    def count(items: list[str]) -> int:\n        return str(len(items))\n\n\
+                 Return a value of the annotated type, or change the annotation. A bare\n\
+                 'return' returns None."
+            }
+            ErrorCode::AssignmentType => {
+                "A variable is annotated with one type and assigned a value of another.\n\n\
+                 Example:\n\n    retries: int = \"3\"\n\n\
+                 Convert the value ('int(\"3\")') or change the annotation."
+            }
+            ErrorCode::UnknownMember => {
+                "An attribute is read from a value whose type has no such attribute.\n\n\
+                 Example:\n\n    name = \"mamba\"\n    name.uppercase()\n\n\
+                 'str' has 'upper', not 'uppercase'. Builtin types and classes defined\n\
+                 in the checked modules are checked; classes with a base whose members\n\
+                 are unknown, or with a '__getattr__' method, are not."
+            }
+            ErrorCode::UnsupportedOperand => {
+                "An operator is applied to values of types that do not support it, and\n\
+                 Python would raise a TypeError.\n\n\
+                 Example:\n\n    total = \"count: \" + 3\n\n\
+                 Convert one of the operands first, for example with 'str(3)' or an\n\
+                 f-string."
+            }
+            ErrorCode::PossiblyNone => {
+                "A value whose type includes None is used in a way None does not\n\
+                 support: reading an attribute, applying an operator, calling,\n\
+                 indexing or iterating.\n\n\
+                 Example:\n\n    def greet(name: str | None) -> str:\n        return \"hello \" + name\n\n\
+                 Handle None first, for example with 'if name is not None:' or an\n\
+                 early return."
+            }
+            ErrorCode::MissingAnnotation => {
+                "In strict mode ('mamba check --strict') every parameter and every\n\
+                 return type of a function must be annotated. The receiver of a method\n\
+                 ('self' or 'cls') and the return type of '__init__' may be omitted.\n\n\
+                 Example:\n\n    def scale(value, factor: float):\n        return value * factor\n\n\
+                 Annotate 'value' and the return type. Without --strict, unannotated\n\
+                 parameters and returns are treated as 'Any'."
+            }
+            ErrorCode::TypeVarMismatch => {
+                "The arguments of a call to a generic function solve one of its type\n\
+                 variables to a type the variable does not admit: a type that is not a\n\
+                 subtype of its bound, or not one of its constraints.\n\n\
+                 Example:\n\n    N = TypeVar('N', bound=float)\n\n    def halve(value: N) -> N:\n        ...\n\n    halve(\"ten\")\n\n\
+                 'str' is not a subtype of 'float'. Pass a value of an admitted type, or\n\
+                 widen the bound of the type variable."
+            }
+            ErrorCode::FieldOrder => {
+                "A field of a dataclass or named tuple has no default, but a field\n\
+                 before it does. The generated '__init__' takes the fields in order, and\n\
+                 a parameter without a default cannot follow one with a default.\n\n\
+                 Erroneous example:\n\n    @dataclass\n    class Item:\n        count: int = 0\n        name: str\n\n\
+                 Move the field without a default before the ones with defaults, give\n\
+                 it a default, or make it keyword-only with 'field(kw_only=True)'."
+            }
+            ErrorCode::FrozenAssignment => {
+                "A field is assigned on an instance of a frozen dataclass or a named\n\
+                 tuple, or a member of an enum is reassigned. Python raises an error\n\
+                 when the assignment runs.\n\n\
+                 Erroneous example:\n\n    @dataclass(frozen=True)\n    class Point:\n        x: int\n\n    p = Point(1)\n    p.x = 3\n\n\
+                 Build a new instance instead, with 'dataclasses.replace(p, x=3)' or\n\
+                 'p._replace(x=3)' for a named tuple."
+            }
+            ErrorCode::TypeChangingRebinding => {
+                "A name is rebound to a value of a different type (lint 'rebind-type').\n\n\
+                 Example:\n\n    count = 0\n    count = \"none yet\"\n\n\
+                 Rebinding is legal, but a name that holds an int in one place and a\n\
+                 str in another is often a mistake. Use a separate name for the new\n\
+                 value. Rebinding to or from None and between int and float is not\n\
+                 reported. This lint is off by default: `mamba check --lint rebind-type`."
+            }
+            ErrorCode::PossiblyUnbound => {
+                "A name is read where it is bound on some control flow paths but not\n\
+                 others.\n\n                 Example:\n\n    if verbose:\n        level = 2\n    print(level)\n\n                 When 'verbose' is false, 'level' was never assigned and the read fails\n\
+                 at runtime. Assign the name on every path (for example in an 'else'\n\
+                 branch or before the 'if')."
+            }
+            ErrorCode::UnreachableCode => {
+                "A statement can never run.\n\n\
+                 Example:\n\n    def f():\n        return 1\n        print(\"done\")\n\n\
+                 Code after 'return', 'raise', 'break' or 'continue' in the same block,\n\
+                 after a 'while True:' loop with no 'break', and inside 'if False:' or\n\
+                 'while False:' is unreachable. Remove it or fix the control flow."
+            }
+            ErrorCode::MissingReturn => {
+                "A function annotated with a return type other than None can reach\n\
+                 the end of its body without a 'return', and would return None.\n\n\
+                 Example:\n\n    def sign(n: int) -> int:\n        if n > 0:\n            return 1\n        elif n < 0:\n            return -1\n\n\
+                 Return a value on every path, or change the annotation. Bodies that\n\
+                 are only '...', 'pass' or a docstring are treated as stubs."
+            }
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
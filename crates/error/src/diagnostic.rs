@@ -0,0 +1,169 @@
+//! Structured diagnostics
+//!
+//! A `Diagnostic` carries everything a tool needs to report a problem without
+//! parsing message text: a stable error code, a severity, the primary source span,
+//! secondary labels, notes and an optional help message.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::codes::ErrorCode;
+
+/// How serious a diagnostic is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Severity {
+    /// Lowercase name used in rendered output (e.g. "error")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A region of source code (1-based line and column, 0-based byte offset)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    pub offset: usize,
+    /// Length of the region in characters (at least 1 when rendered)
+    pub len: usize,
+}
+
+impl Span {
+    pub fn new(line: usize, column: usize, offset: usize, len: usize) -> Self {
+        Self {
+            line,
+            column,
+            offset,
+            len,
+        }
+    }
+
+    /// Span of a single character
+    pub fn point(line: usize, column: usize, offset: usize) -> Self {
+        Self::new(line, column, offset, 1)
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// A secondary location attached to a diagnostic (e.g. "first defined here")
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+impl Label {
+    pub fn new(span: Span, message: impl Into<String>) -> Self {
+        Self {
+            span,
+            message: message.into(),
+        }
+    }
+}
+
+/// A structured compiler diagnostic
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub code: ErrorCode,
+    pub severity: Severity,
+    pub message: String,
+    /// Primary location of the problem
+    pub span: Option<Span>,
+    /// Secondary locations with their own messages
+    pub labels: Vec<Label>,
+    /// Additional context shown after the snippet
+    pub notes: Vec<String>,
+    /// Suggestion for fixing the problem
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    /// Create a diagnostic with the given severity
+    pub fn new(code: ErrorCode, severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            severity,
+            message: message.into(),
+            span: None,
+            labels: Vec::new(),
+            notes: Vec::new(),
+            help: None,
+        }
+    }
+
+    /// Create an error diagnostic
+    pub fn error(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self::new(code, Severity::Error, message)
+    }
+
+    /// Create a warning diagnostic
+    pub fn warning(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self::new(code, Severity::Warning, message)
+    }
+
+    /// Set the primary span
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    /// Add a secondary label
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label::new(span, message));
+        self
+    }
+
+    /// Add a note
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    /// Set the help message
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    /// Set the help message if one is given
+    pub fn with_optional_help(mut self, help: Option<String>) -> Self {
+        self.help = help;
+        self
+    }
+}
+
+/// One-line form: "message at line:column. help"
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(span) = &self.span {
+            write!(f, " at {}", span)?;
+        }
+        if let Some(help) = &self.help {
+            write!(f, ". {}", help)?;
+        }
+        Ok(())
+    }
+}
@@ -2,20 +2,29 @@
 //!
 //! This crate provides error types, formatting, and reporting for the Mamba compiler.
 
+pub mod codes;
+pub mod diagnostic;
+pub mod output;
+pub mod render;
+
 use colored::Colorize;
 use thiserror::Error;
 
+pub use codes::ErrorCode;
+pub use diagnostic::{Diagnostic, Label, Severity, Span};
+pub use render::Renderer;
+
 /// Main error type for Mamba compilation
 #[derive(Debug, Error)]
 pub enum MambaError {
     #[error("Syntax error: {0}")]
-    SyntaxError(String),
+    SyntaxError(Box<Diagnostic>),
 
     #[error("Parse error: {0}")]
-    ParseError(String),
+    ParseError(Box<Diagnostic>),
 
     #[error("Semantic error: {0}")]
-    SemanticError(String),
+    SemanticError(Box<Diagnostic>),
 
     #[error("Transpilation error: {0}")]
     TranspileError(String),
@@ -27,16 +36,46 @@ pub enum MambaError {
     IoError(#[from] std::io::Error),
 }
 
-/// Format an error with color and position information
-pub fn format_error(error: &MambaError, file: &str, line: usize, column: usize) -> String {
-    format!(
-        "{} {}:{}:{}\n  {}",
-        "Error:".red().bold(),
-        file,
-        line,
-        column,
-        error
-    )
+impl MambaError {
+    /// Get the structured diagnostic, if this error carries one
+    pub fn diagnostic(&self) -> Option<&Diagnostic> {
+        match self {
+            MambaError::SyntaxError(diagnostic)
+            | MambaError::ParseError(diagnostic)
+            | MambaError::SemanticError(diagnostic) => Some(diagnostic),
+            _ => None,
+        }
+    }
+}
+
+/// Format an error with color and the position stored in its diagnostic
+pub fn format_error(error: &MambaError, file: &str) -> String {
+    let Some(diagnostic) = error.diagnostic() else {
+        return format!("{} {}\n  {}", "Error:".red().bold(), file, error);
+    };
+
+    let mut out = format!(
+        "{}: {}",
+        format!("{}[{}]", diagnostic.severity, diagnostic.code).red().bold(),
+        diagnostic.message
+    );
+    match &diagnostic.span {
+        Some(span) => out.push_str(&format!("\n  --> {}:{}:{}", file, span.line, span.column)),
+        None => out.push_str(&format!("\n  --> {}", file)),
+    }
+    for label in &diagnostic.labels {
+        out.push_str(&format!(
+            "\n  {}:{}:{}: {}",
+            file, label.span.line, label.span.column, label.message
+        ));
+    }
+    for note in &diagnostic.notes {
+        out.push_str(&format!("\n  = note: {}", note));
+    }
+    if let Some(help) = &diagnostic.help {
+        out.push_str(&format!("\n  = help: {}", help));
+    }
+    out
 }
 
 #[cfg(test)]
@@ -45,7 +84,53 @@ mod tests {
 
     #[test]
     fn test_error_creation() {
-        let error = MambaError::SyntaxError("unexpected token".to_string());
+        let error = MambaError::SyntaxError(Box::new(Diagnostic::error(
+            ErrorCode::UnexpectedCharacter,
+            "unexpected token",
+        )));
         assert!(error.to_string().contains("Syntax error"));
     }
+
+    #[test]
+    fn test_diagnostic_display() {
+        let diagnostic = Diagnostic::error(ErrorCode::InvalidSyntax, "Expected ':'")
+            .with_span(Span::point(3, 5, 20))
+            .with_help("Add a colon");
+        assert_eq!(diagnostic.to_string(), "Expected ':' at 3:5. Add a colon");
+    }
+
+    #[test]
+    fn test_diagnostic_accessor() {
+        let error = MambaError::ParseError(Box::new(
+            Diagnostic::error(ErrorCode::InvalidSyntax, "bad").with_span(Span::point(2, 1, 7)),
+        ));
+        let diagnostic = error.diagnostic().unwrap();
+        assert_eq!(diagnostic.code, ErrorCode::InvalidSyntax);
+        assert_eq!(diagnostic.span.unwrap().line, 2);
+        assert!(MambaError::CompileError("x".to_string()).diagnostic().is_none());
+    }
+
+    #[test]
+    fn test_error_code_lookup() {
+        for code in ErrorCode::ALL {
+            assert_eq!(ErrorCode::from_code(code.as_str()), Some(*code));
+            assert!(!code.explanation().is_empty());
+        }
+        assert_eq!(ErrorCode::from_code("e0001"), Some(ErrorCode::UnexpectedCharacter));
+        assert_eq!(ErrorCode::from_code("E9999"), None);
+    }
+
+    #[test]
+    fn test_format_error_uses_diagnostic_position() {
+        colored::control::set_override(false);
+        let error = MambaError::SemanticError(Box::new(
+            Diagnostic::error(ErrorCode::Redeclaration, "Redeclaration of 'x'")
+                .with_span(Span::point(4, 2, 30))
+                .with_label(Span::point(1, 1, 0), "first defined here"),
+        ));
+        let out = format_error(&error, "main.mmb");
+        assert!(out.contains("error[E0201]: Redeclaration of 'x'"));
+        assert!(out.contains("--> main.mmb:4:2"));
+        assert!(out.contains("main.mmb:1:1: first defined here"));
+    }
 }
@@ -0,0 +1,238 @@
+//! Machine-readable diagnostic output
+//!
+//! Two formats are supported, both built from the structured `Diagnostic`:
+//!
+//! - JSON lines: one JSON object per diagnostic (see `to_json` for the schema)
+//! - SARIF 2.1.0: a single log covering every diagnostic, for CI annotations
+//!
+//! The JSON schema is stable: fields are only ever added, never renamed or removed.
+
+use serde_json::{json, Value};
+
+use crate::codes::ErrorCode;
+use crate::diagnostic::{Diagnostic, Span};
+
+/// Version of the JSON lines schema emitted by `to_json`
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// SARIF specification version emitted by `to_sarif`
+pub const SARIF_VERSION: &str = "2.1.0";
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Convert a diagnostic to a JSON object.
+///
+/// ```text
+/// {
+///   "version": 1,
+///   "file": "main.mmb",
+///   "code": "E0201",
+///   "title": "redeclaration",
+///   "severity": "error",
+///   "message": "Redeclaration of 'x'",
+///   "span": {"line": 1, "column": 10, "offset": 9, "len": 1} | null,
+///   "labels": [{"span": {...}, "message": "first defined here"}],
+///   "notes": ["..."],
+///   "help": "..." | null
+/// }
+/// ```
+pub fn to_json(diagnostic: &Diagnostic, file: &str) -> Value {
+    json!({
+        "version": JSON_SCHEMA_VERSION,
+        "file": file,
+        "code": diagnostic.code.as_str(),
+        "title": diagnostic.code.title(),
+        "severity": diagnostic.severity.as_str(),
+        "message": diagnostic.message,
+        "span": diagnostic.span.as_ref().map(span_to_json),
+        "labels": diagnostic
+            .labels
+            .iter()
+            .map(|label| json!({ "span": span_to_json(&label.span), "message": label.message }))
+            .collect::<Vec<_>>(),
+        "notes": diagnostic.notes,
+        "help": diagnostic.help,
+    })
+}
+
+/// Convert a diagnostic to a single line of JSON (no trailing newline)
+pub fn to_json_line(diagnostic: &Diagnostic, file: &str) -> String {
+    to_json(diagnostic, file).to_string()
+}
+
+/// Build a SARIF 2.1.0 log from `(file, diagnostic)` pairs.
+///
+/// Every known error code is listed as a rule so that `ruleIndex` is stable
+/// across runs. Labels become `relatedLocations`; notes and help are kept in
+/// the result's `properties`.
+pub fn to_sarif<'a, I>(diagnostics: I) -> Value
+where
+    I: IntoIterator<Item = (&'a str, &'a Diagnostic)>,
+{
+    let rules: Vec<Value> = ErrorCode::ALL
+        .iter()
+        .map(|code| {
+            json!({
+                "id": code.as_str(),
+                "name": code.title(),
+                "shortDescription": { "text": code.title() },
+                "fullDescription": { "text": code.explanation() },
+            })
+        })
+        .collect();
+
+    let results: Vec<Value> = diagnostics
+        .into_iter()
+        .map(|(file, diagnostic)| sarif_result(file, diagnostic))
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": SARIF_VERSION,
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "mamba",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_HOMEPAGE"),
+                    "rules": rules,
+                }
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }]
+    })
+}
+
+fn sarif_result(file: &str, diagnostic: &Diagnostic) -> Value {
+    let rule_index = ErrorCode::ALL
+        .iter()
+        .position(|code| *code == diagnostic.code)
+        .unwrap_or_default();
+
+    let mut result = json!({
+        "ruleId": diagnostic.code.as_str(),
+        "ruleIndex": rule_index,
+        // SARIF levels use the same names as our severities
+        "level": diagnostic.severity.as_str(),
+        "message": { "text": diagnostic.message },
+        "locations": [sarif_location(file, diagnostic.span.as_ref())],
+    });
+
+    if !diagnostic.labels.is_empty() {
+        result["relatedLocations"] = diagnostic
+            .labels
+            .iter()
+            .enumerate()
+            .map(|(id, label)| {
+                let mut location = sarif_location(file, Some(&label.span));
+                location["id"] = json!(id);
+                location["message"] = json!({ "text": label.message });
+                location
+            })
+            .collect();
+    }
+    if !diagnostic.notes.is_empty() || diagnostic.help.is_some() {
+        result["properties"] = json!({
+            "notes": diagnostic.notes,
+            "help": diagnostic.help,
+        });
+    }
+    result
+}
+
+fn sarif_location(file: &str, span: Option<&Span>) -> Value {
+    let mut physical = json!({ "artifactLocation": { "uri": file } });
+    if let Some(span) = span {
+        physical["region"] = json!({
+            "startLine": span.line,
+            "startColumn": span.column,
+            "endColumn": span.column + span.len.max(1),
+        });
+    }
+    json!({ "physicalLocation": physical })
+}
+
+fn span_to_json(span: &Span) -> Value {
+    json!({
+        "line": span.line,
+        "column": span.column,
+        "offset": span.offset,
+        "len": span.len,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redeclaration() -> Diagnostic {
+        Diagnostic::error(ErrorCode::Redeclaration, "Redeclaration of 'x'")
+            .with_span(Span::new(1, 10, 9, 1))
+            .with_label(Span::new(1, 7, 6, 1), "first defined here")
+    }
+
+    #[test]
+    fn test_json_schema() {
+        let value = to_json(&redeclaration(), "main.mmb");
+        assert_eq!(value["version"], 1);
+        assert_eq!(value["file"], "main.mmb");
+        assert_eq!(value["code"], "E0201");
+        assert_eq!(value["severity"], "error");
+        assert_eq!(value["message"], "Redeclaration of 'x'");
+        assert_eq!(value["span"]["line"], 1);
+        assert_eq!(value["span"]["column"], 10);
+        assert_eq!(value["labels"][0]["message"], "first defined here");
+        assert_eq!(value["labels"][0]["span"]["column"], 7);
+        assert!(value["help"].is_null());
+    }
+
+    #[test]
+    fn test_json_line_is_single_line() {
+        let diagnostic = redeclaration().with_note("multi\nline note");
+        let line = to_json_line(&diagnostic, "main.mmb");
+        assert!(!line.contains('\n'));
+        let parsed: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed["notes"][0], "multi\nline note");
+    }
+
+    #[test]
+    fn test_json_without_span() {
+        let diagnostic = Diagnostic::error(ErrorCode::InvalidScope, "Cannot exit root scope");
+        assert!(to_json(&diagnostic, "main.mmb")["span"].is_null());
+    }
+
+    #[test]
+    fn test_sarif_log() {
+        let diagnostic = redeclaration().with_help("rename one of the parameters");
+        let log = to_sarif([("src/main.mmb", &diagnostic)]);
+        assert_eq!(log["version"], "2.1.0");
+
+        let run = &log["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "mamba");
+        assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), ErrorCode::ALL.len());
+
+        let result = &run["results"][0];
+        assert_eq!(result["ruleId"], "E0201");
+        let index = result["ruleIndex"].as_u64().unwrap() as usize;
+        assert_eq!(run["tool"]["driver"]["rules"][index]["id"], "E0201");
+        assert_eq!(result["level"], "error");
+
+        let region = &result["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(region["startLine"], 1);
+        assert_eq!(region["startColumn"], 10);
+        assert_eq!(region["endColumn"], 11);
+        assert_eq!(
+            result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            "src/main.mmb"
+        );
+        assert_eq!(result["relatedLocations"][0]["message"]["text"], "first defined here");
+        assert_eq!(result["properties"]["help"], "rename one of the parameters");
+    }
+
+    #[test]
+    fn test_sarif_empty_run() {
+        let log = to_sarif(std::iter::empty());
+        assert!(log["runs"][0]["results"].as_array().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,393 @@
+//! Terminal rendering of diagnostics
+//!
+//! Produces rustc-style output: a header with the error code, the file location,
+//! the offending source line(s) with a line-number gutter, an underline for the
+//! primary span (`^`), underlines for secondary labels (`-`), then notes and help.
+//!
+//! ```text
+//! error[E0201]: Redeclaration of 'x'
+//!  --> main.mmb:1:10
+//!   |
+//! 1 | def f(x, x):
+//!   |          ^
+//!   |       - first defined here
+//! ```
+
+use colored::{ColoredString, Colorize};
+use unicode_width::UnicodeWidthChar;
+
+use crate::diagnostic::{Diagnostic, Severity, Span};
+use crate::MambaError;
+
+/// Renders diagnostics against their source text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Renderer {
+    /// Emit ANSI colors
+    color: bool,
+    /// Wrap prose (messages, notes, help) to this many columns
+    max_width: Option<usize>,
+}
+
+impl Renderer {
+    /// Colored renderer without a width limit
+    pub fn new() -> Self {
+        Self {
+            color: true,
+            max_width: None,
+        }
+    }
+
+    /// Renderer without colors, suitable for CI logs
+    pub fn plain() -> Self {
+        Self::new().with_color(false)
+    }
+
+    /// Enable or disable colors
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Wrap messages, notes and help text to at most `width` columns.
+    /// Source lines are never wrapped so that underlines stay aligned.
+    pub fn with_max_width(mut self, width: usize) -> Self {
+        self.max_width = Some(width);
+        self
+    }
+
+    /// Render an error, falling back to its one-line form if it has no diagnostic
+    pub fn render_error(&self, error: &MambaError, file: &str, source: &str) -> String {
+        match error.diagnostic() {
+            Some(diagnostic) => self.render(diagnostic, file, source),
+            None => {
+                let header = format!("{}: {}", self.paint_severity(Severity::Error, "error"), error);
+                self.wrap(&header, 0).join("\n")
+            }
+        }
+    }
+
+    /// Render a diagnostic against the source it refers to
+    pub fn render(&self, diagnostic: &Diagnostic, file: &str, source: &str) -> String {
+        let lines: Vec<&str> = source.lines().collect();
+        let mut out = Vec::new();
+
+        // Header
+        let title = format!("{}[{}]", diagnostic.severity, diagnostic.code);
+        let header = format!("{}: {}", title, diagnostic.message);
+        let mut wrapped = self.wrap(&header, 0).into_iter();
+        if let Some(first) = wrapped.next() {
+            let rest = &first[title.len()..];
+            out.push(format!(
+                "{}{}",
+                self.paint_severity(diagnostic.severity, &title),
+                self.bold(rest)
+            ));
+        }
+        out.extend(wrapped.map(|line| self.bold(&line).to_string()));
+
+        // Gutter is wide enough for the largest line number shown
+        let shown = self.lines_to_show(diagnostic, lines.len());
+        let gutter_width = shown.last().map_or(1, |n| n.to_string().len());
+        let pad = " ".repeat(gutter_width);
+        let bar = self.blue("|");
+
+        match &diagnostic.span {
+            Some(span) => out.push(format!("{}{} {}:{}:{}", pad, self.blue("-->"), file, span.line, span.column)),
+            None => out.push(format!("{}{} {}", pad, self.blue("-->"), file)),
+        }
+
+        if !shown.is_empty() {
+            out.push(format!("{} {}", pad, bar));
+            let mut previous: Option<usize> = None;
+            for &line_no in &shown {
+                if let Some(prev) = previous {
+                    if line_no > prev + 1 {
+                        out.push(self.blue("...").to_string());
+                    }
+                }
+                previous = Some(line_no);
+
+                let text = lines[line_no - 1];
+                out.push(format!(
+                    "{} {} {}",
+                    self.blue(&format!("{:>width$}", line_no, width = gutter_width)),
+                    bar,
+                    text
+                ));
+
+                if let Some(span) = diagnostic.span.filter(|s| s.line == line_no) {
+                    let underline = self.underline(text, &span, '^');
+                    out.push(format!(
+                        "{} {} {}",
+                        pad,
+                        bar,
+                        self.paint_severity(diagnostic.severity, &underline)
+                    ));
+                }
+                for label in diagnostic.labels.iter().filter(|l| l.span.line == line_no) {
+                    let underline = self.underline(text, &label.span, '-');
+                    let indent = underline.chars().count() + 1;
+                    let mut message = self.wrap(&label.message, indent + gutter_width + 3).into_iter();
+                    let first = message.next().unwrap_or_default();
+                    out.push(format!(
+                        "{} {} {} {}",
+                        pad,
+                        bar,
+                        self.blue(&underline),
+                        self.blue(&first)
+                    ));
+                    for rest in message {
+                        out.push(format!("{} {} {}{}", pad, bar, " ".repeat(indent), self.blue(&rest)));
+                    }
+                }
+            }
+        }
+
+        // Labels pointing outside the source (e.g. stale positions) are still reported
+        for label in &diagnostic.labels {
+            if label.span.line == 0 || label.span.line > lines.len() {
+                out.push(format!(
+                    "{} {} {}:{}:{}: {}",
+                    pad,
+                    bar,
+                    file,
+                    label.span.line,
+                    label.span.column,
+                    label.message
+                ));
+            }
+        }
+
+        for note in &diagnostic.notes {
+            self.push_trailer(&mut out, &pad, "note", note);
+        }
+        if let Some(help) = &diagnostic.help {
+            self.push_trailer(&mut out, &pad, "help", help);
+        }
+
+        out.join("\n")
+    }
+
+    /// Line numbers (1-based, sorted, deduplicated) referenced by the diagnostic
+    fn lines_to_show(&self, diagnostic: &Diagnostic, line_count: usize) -> Vec<usize> {
+        let mut shown: Vec<usize> = diagnostic
+            .span
+            .iter()
+            .chain(diagnostic.labels.iter().map(|l| &l.span))
+            .map(|span| span.line)
+            .filter(|line| (1..=line_count).contains(line))
+            .collect();
+        shown.sort_unstable();
+        shown.dedup();
+        shown
+    }
+
+    /// Build the underline for `span` on `text`, keeping tabs and counting
+    /// each character by its display width so wide (e.g. CJK) characters
+    /// line up
+    fn underline(&self, text: &str, span: &Span, marker: char) -> String {
+        let start = span.column.saturating_sub(1);
+        let line_len = text.chars().count();
+        let width = |c: char| c.width().unwrap_or(0);
+        let mut underline = String::new();
+        for c in text.chars().take(start) {
+            match c {
+                '\t' => underline.push('\t'),
+                _ => underline.extend(std::iter::repeat_n(' ', width(c))),
+            }
+        }
+        if start < line_len {
+            let covered = text.chars().skip(start).take(span.len.clamp(1, line_len - start));
+            underline.extend(std::iter::repeat_n(marker, covered.map(width).sum::<usize>().max(1)));
+        } else {
+            // Span points past the end of the line (e.g. a missing token at EOL)
+            underline.extend(std::iter::repeat_n(' ', start - line_len));
+            underline.push(marker);
+        }
+        underline
+    }
+
+    /// Push a "= note: ..." style line, wrapping continuation lines under the text
+    fn push_trailer(&self, out: &mut Vec<String>, pad: &str, kind: &str, text: &str) {
+        let prefix = format!("{} = {}: ", pad, kind);
+        let indent = prefix.len();
+        let mut wrapped = self.wrap(text, indent).into_iter();
+        let first = wrapped.next().unwrap_or_default();
+        out.push(format!("{} {} {}: {}", pad, self.blue("="), self.bold(kind), first));
+        for rest in wrapped {
+            out.push(format!("{}{}", " ".repeat(indent), rest));
+        }
+    }
+
+    /// Word-wrap `text` so that each line fits in `max_width - indent` columns.
+    /// Existing newlines are kept; words longer than the width are not split.
+    fn wrap(&self, text: &str, indent: usize) -> Vec<String> {
+        let Some(max_width) = self.max_width else {
+            return text.lines().map(str::to_string).collect();
+        };
+        let width = max_width.saturating_sub(indent).max(10);
+
+        let mut wrapped = Vec::new();
+        for paragraph in text.lines() {
+            let mut line = String::new();
+            for word in paragraph.split_whitespace() {
+                if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+                    wrapped.push(std::mem::take(&mut line));
+                }
+                if !line.is_empty() {
+                    line.push(' ');
+                }
+                line.push_str(word);
+            }
+            wrapped.push(line);
+        }
+        wrapped
+    }
+
+    fn paint_severity(&self, severity: Severity, text: &str) -> ColoredString {
+        if !self.color {
+            return text.normal();
+        }
+        match severity {
+            Severity::Error => text.red().bold(),
+            Severity::Warning => text.yellow().bold(),
+            Severity::Note => text.green().bold(),
+        }
+    }
+
+    fn blue(&self, text: &str) -> ColoredString {
+        if self.color {
+            text.blue().bold()
+        } else {
+            text.normal()
+        }
+    }
+
+    fn bold(&self, text: &str) -> ColoredString {
+        if self.color {
+            text.bold()
+        } else {
+            text.normal()
+        }
+    }
+}
+
+impl Default for Renderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codes::ErrorCode;
+
+    #[test]
+    fn test_render_snippet_with_caret() {
+        let source = "x = 1\nif x == 5\n    print(x)\n";
+        let diagnostic = Diagnostic::error(ErrorCode::InvalidSyntax, "Expected ':' after if condition")
+            .with_span(Span::new(2, 10, 15, 1))
+            .with_help("Add ':' at the end of the line");
+        let out = Renderer::plain().render(&diagnostic, "main.mmb", source);
+        let expected = "\
+error[E0100]: Expected ':' after if condition
+ --> main.mmb:2:10
+  |
+2 | if x == 5
+  |          ^
+  = help: Add ':' at the end of the line";
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_render_secondary_label() {
+        let source = "x = 1\ndef f(a, a):\n    pass\n";
+        let diagnostic = Diagnostic::error(ErrorCode::Redeclaration, "Redeclaration of 'a'")
+            .with_span(Span::new(2, 10, 15, 1))
+            .with_label(Span::new(2, 7, 12, 1), "first defined here");
+        let out = Renderer::plain().render(&diagnostic, "main.mmb", source);
+        assert!(out.contains("2 | def f(a, a):"));
+        assert!(out.contains("  |          ^"));
+        assert!(out.contains("  |       - first defined here"));
+    }
+
+    #[test]
+    fn test_render_gap_between_lines() {
+        let source = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\n";
+        let diagnostic = Diagnostic::error(ErrorCode::Redeclaration, "Redeclaration of 'k'")
+            .with_span(Span::point(11, 1, 20))
+            .with_label(Span::point(1, 1, 0), "first defined here");
+        let out = Renderer::plain().render(&diagnostic, "main.mmb", source);
+        assert!(out.contains(" 1 | a"));
+        assert!(out.contains("..."));
+        assert!(out.contains("11 | k"));
+    }
+
+    #[test]
+    fn test_underline_spans_token_and_clamps() {
+        let source = "x = 0b102\n";
+        let diagnostic =
+            Diagnostic::error(ErrorCode::InvalidNumber, "Invalid binary literal").with_span(Span::new(1, 5, 4, 50));
+        let out = Renderer::plain().render(&diagnostic, "main.mmb", source);
+        assert!(out.ends_with("  |     ^^^^^"));
+    }
+
+    #[test]
+    fn test_underline_keeps_tabs() {
+        let source = "\tx = $\n";
+        let diagnostic =
+            Diagnostic::error(ErrorCode::UnexpectedCharacter, "Unexpected character '$'").with_span(Span::point(1, 6, 5));
+        let out = Renderer::plain().render(&diagnostic, "main.mmb", source);
+        assert!(out.ends_with("  | \t    ^"));
+    }
+
+    #[test]
+    fn test_underline_counts_display_width() {
+        // Each CJK character takes two columns of the terminal
+        let source = "名前 = 値 + $\n";
+        let diagnostic = Diagnostic::error(ErrorCode::UnexpectedCharacter, "Unexpected character '$'")
+            .with_span(Span::point(1, 10, 17));
+        let out = Renderer::plain().render(&diagnostic, "main.mmb", source);
+        assert!(out.ends_with(&format!("  | {}^", " ".repeat(12))));
+
+        let diagnostic = Diagnostic::error(ErrorCode::Redeclaration, "Redeclaration of '名前'")
+            .with_span(Span::new(1, 1, 0, 2))
+            .with_label(Span::new(1, 6, 9, 1), "this value");
+        let out = Renderer::plain().render(&diagnostic, "main.mmb", source);
+        assert!(out.contains("  | ^^^^\n"));
+        assert!(out.ends_with(&format!("  | {}-- this value", " ".repeat(7))));
+    }
+
+    #[test]
+    fn test_render_without_span() {
+        let diagnostic = Diagnostic::error(ErrorCode::InvalidScope, "Cannot exit root scope");
+        let out = Renderer::plain().render(&diagnostic, "main.mmb", "");
+        assert_eq!(out, "error[E0202]: Cannot exit root scope\n --> main.mmb");
+    }
+
+    #[test]
+    fn test_max_width_wraps_prose() {
+        let diagnostic = Diagnostic::error(ErrorCode::InvalidSyntax, "bad")
+            .with_span(Span::point(1, 1, 0))
+            .with_note("this note is long enough that it has to be wrapped onto several lines");
+        let out = Renderer::plain().with_max_width(40).render(&diagnostic, "main.mmb", "x\n");
+        let note_lines: Vec<&str> = out.lines().skip_while(|l| !l.contains("= note")).collect();
+        assert!(note_lines.len() > 1);
+        assert!(note_lines.iter().all(|l| l.chars().count() <= 40));
+    }
+
+    #[test]
+    fn test_plain_mode_has_no_ansi_codes() {
+        let diagnostic = Diagnostic::error(ErrorCode::InvalidSyntax, "bad").with_span(Span::point(1, 1, 0));
+        let out = Renderer::plain().render(&diagnostic, "main.mmb", "x\n");
+        assert!(!out.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_render_error_without_diagnostic() {
+        let error = MambaError::CompileError("linker failed".to_string());
+        let out = Renderer::plain().render_error(&error, "main.mmb", "");
+        assert_eq!(out, "error: Compilation error: linker failed");
+    }
+}
@@ -0,0 +1,40 @@
+//! Fingerprints the sources of the analyzer, so that results cached by one
+//! build are never reused by a build that analyzes differently
+
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::Hasher;
+use std::path::{Path, PathBuf};
+
+/// Everything the analysis results depend on: the parser crate and the
+/// diagnostics of the error crate
+const INPUTS: &[&str] = &["src", "stubs", "Cargo.toml", "../error/src", "../error/Cargo.toml"];
+
+fn main() {
+    let mut files = Vec::new();
+    for input in INPUTS {
+        println!("cargo:rerun-if-changed={}", input);
+        collect(Path::new(input), &mut files);
+    }
+    files.sort();
+
+    let mut hasher = DefaultHasher::new();
+    for file in files {
+        hasher.write(file.to_string_lossy().as_bytes());
+        hasher.write(&fs::read(&file).unwrap_or_default());
+    }
+    println!("cargo:rustc-env=MAMBA_ANALYZER_FINGERPRINT={:016x}", hasher.finish());
+}
+
+/// The files under a path, or the path itself if it is a file
+fn collect(path: &Path, files: &mut Vec<PathBuf>) {
+    match fs::read_dir(path) {
+        Ok(entries) => {
+            for entry in entries.flatten() {
+                collect(&entry.path(), files);
+            }
+        }
+        Err(_) if path.is_file() => files.push(path.to_path_buf()),
+        Err(_) => {}
+    }
+}
@@ -0,0 +1,524 @@
+//! Annotation Export
+//!
+//! Writes the types inference finds back into a module's source as
+//! annotations, so an untyped module can be moved to strict checking step
+//! by step:
+//!
+//! - a parameter gets the type of the arguments the calls in the module pass
+//!   to it, when they all agree on one, joined with the type of its default
+//! - a function gets the type it returns
+//! - a name assigned exactly once in a module or function gets the type of
+//!   the value
+//!
+//! Only types that can be written are added: nothing that is or contains
+//! `Any`, a callable, a type variable, or a class not visible from the
+//! module. Annotations are inserted into the source text, so comments and
+//! formatting elsewhere are kept as they are. The annotated source is checked
+//! again, and an annotation that makes the checker report a problem the
+//! source did not have is dropped. An annotated parameter can give its
+//! function a known return type, so passes repeat until one adds nothing.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::arena::{AstArena, ExprKind, NodeId, StmtKind};
+use crate::ast::ParameterKind;
+use crate::builtins::{self, BuiltinKind};
+use crate::checker::CheckMode;
+use crate::infer::{infer_types, InferredTypes};
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use crate::semantic::SemanticAnalyzer;
+use crate::symbol_table::{ScopeKind, Symbol, SymbolKind, SymbolTable};
+use crate::token::{SourcePosition, Token, TokenKind};
+use crate::types::Type;
+use mamba_error::MambaError;
+
+/// Passes after which annotating stops even if the last one added some
+const MAX_PASSES: usize = 4;
+
+/// A module's source with the inferred annotations added
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotated {
+    pub source: String,
+    /// The annotations added, in source order
+    pub annotations: Vec<Annotation>,
+}
+
+/// One annotation added to the source
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    /// Line of the annotated name; inserting annotations adds no lines
+    pub line: usize,
+    pub target: AnnotationTarget,
+    pub annotation: Type,
+}
+
+/// What an [`Annotation`] is added to
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnnotationTarget {
+    Parameter { function: String, parameter: String },
+    Return { function: String },
+    Variable { name: String },
+}
+
+impl fmt::Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.target {
+            AnnotationTarget::Parameter { function, parameter } => {
+                write!(f, "parameter '{}' of '{}': {}", parameter, function, self.annotation)
+            }
+            AnnotationTarget::Return { function } => write!(f, "'{}' returns {}", function, self.annotation),
+            AnnotationTarget::Variable { name } => write!(f, "'{}': {}", name, self.annotation),
+        }
+    }
+}
+
+/// Add the annotations inference finds to a module's source. Fails only
+/// when the source does not lex or parse.
+pub fn annotate(source: &str) -> Result<Annotated, Vec<MambaError>> {
+    let mut annotated = Annotated {
+        source: source.to_string(),
+        annotations: Vec::new(),
+    };
+    for _ in 0..MAX_PASSES {
+        let mut edits = edits(&annotated.source)?;
+        edits.sort_by_key(|edit| edit.start);
+        let edits = checked(&annotated.source, edits);
+        if edits.is_empty() {
+            break;
+        }
+        annotated.source = apply(&annotated.source, &edits);
+        annotated.annotations.extend(edits.into_iter().map(|edit| edit.annotation));
+    }
+    annotated.annotations.sort_by_key(|annotation| annotation.line);
+    Ok(annotated)
+}
+
+/// The edits, sorted by offset, that together make the checker report no
+/// problem `source` did not have. When applying all of them does, they are
+/// tried one at a time and each is kept only if it adds no problem.
+fn checked(source: &str, edits: Vec<Edit>) -> Vec<Edit> {
+    let Some(before) = problems(source) else {
+        return edits;
+    };
+    let adds_problems = |edits: &[Edit]| {
+        problems(&apply(source, edits)).is_none_or(|after| after.iter().any(|problem| !before.contains(problem)))
+    };
+    if edits.is_empty() || !adds_problems(&edits) {
+        return edits;
+    }
+    let mut kept: Vec<Edit> = Vec::new();
+    for edit in edits {
+        kept.push(edit);
+        if adds_problems(&kept) {
+            kept.pop();
+        }
+    }
+    kept
+}
+
+/// `source` with the edits, sorted by offset, applied
+fn apply(source: &str, edits: &[Edit]) -> String {
+    let mut source = source.to_string();
+    // Editing from the end keeps the offsets of earlier edits valid
+    for edit in edits.iter().rev() {
+        source.replace_range(edit.start..edit.end, &edit.text);
+    }
+    source
+}
+
+/// The problems a lenient check of `source` reports, by line, or `None`
+/// when it does not lex or parse. Annotations add no lines, so lines match
+/// between a source and its annotated version.
+fn problems(source: &str) -> Option<Vec<(usize, String)>> {
+    let tokens = Lexer::new(source).tokenize().ok()?;
+    let module = Parser::new(tokens).parse().ok()?;
+    let (_, problems) = SemanticAnalyzer::new().with_type_checking(CheckMode::Lenient).diagnose(&module);
+    Some(problems.iter().map(|problem| (problem.position().line, problem.message())).collect())
+}
+
+/// Replacement of the source between two byte offsets
+struct Edit {
+    start: usize,
+    end: usize,
+    text: String,
+    annotation: Annotation,
+}
+
+/// The annotations one pass of inference finds, as edits of `source`
+fn edits(source: &str) -> Result<Vec<Edit>, Vec<MambaError>> {
+    let tokens = Lexer::new(source).tokenize().map_err(|error| vec![error])?;
+    let module = Parser::new(tokens.clone()).parse()?;
+    let (symbol_table, _) = SemanticAnalyzer::new().diagnose(&module);
+    let arena = AstArena::from_module(&module);
+    let inferred = infer_types(&arena, &symbol_table);
+    let pass = Pass {
+        source,
+        tokens: &tokens,
+        arena: &arena,
+        symbol_table: &symbol_table,
+        inferred: &inferred,
+        symbols: symbols_by_position(&symbol_table),
+        arguments: argument_types(&arena, &inferred),
+    };
+
+    let mut edits = Vec::new();
+    for id in arena.ids() {
+        match arena.stmt(id) {
+            Some(StmtKind::FunctionDef { .. }) => pass.function(id, &mut edits),
+            Some(StmtKind::Assignment { targets, .. }) if targets.len() == 1 => {
+                edits.extend(pass.variable(targets[0]));
+            }
+            _ => {}
+        }
+    }
+    Ok(edits)
+}
+
+/// The types of the arguments calls pass to a function's parameters
+#[derive(Default)]
+struct Arguments {
+    by_parameter: HashMap<String, Vec<Type>>,
+    /// Whether a call unpacks `*args` or `**kwargs` into the parameters
+    unpacked: bool,
+}
+
+struct Pass<'a> {
+    source: &'a str,
+    tokens: &'a [Token],
+    arena: &'a AstArena,
+    symbol_table: &'a SymbolTable,
+    inferred: &'a InferredTypes,
+    symbols: HashMap<SourcePosition, Vec<&'a Symbol>>,
+    /// Arguments passed to each function, by its `FunctionDef` node
+    arguments: HashMap<NodeId, Arguments>,
+}
+
+impl Pass<'_> {
+    /// Annotate the parameters and return type of a function
+    fn function(&self, id: NodeId, edits: &mut Vec<Edit>) {
+        let Some(StmtKind::FunctionDef { name, parameters, return_type, decorators, .. }) = self.arena.stmt(id) else {
+            return;
+        };
+        let Some(&position) = self.arena.position(id) else {
+            return;
+        };
+        let arguments = self.arguments.get(&id);
+        let parent = self.arena.parent(id).and_then(|parent| self.arena.stmt(parent));
+        let in_class = matches!(parent, Some(StmtKind::ClassDef { .. }));
+        let static_method = decorators.iter().any(|&decorator| {
+            matches!(self.arena.expr(decorator), Some(ExprKind::Identifier { name }) if name == "staticmethod")
+        });
+
+        for (index, param) in parameters.iter().enumerate() {
+            let receiver = index == 0 && in_class && !static_method;
+            if receiver || param.type_annotation.is_some() || arguments.is_some_and(|arguments| arguments.unpacked) {
+                continue;
+            }
+            if !matches!(param.kind, ParameterKind::PositionalOnly | ParameterKind::Regular | ParameterKind::KwOnly) {
+                continue;
+            }
+            let default = param.default.and_then(|default| self.inferred.type_of(default)).cloned();
+            let passed = arguments
+                .and_then(|arguments| arguments.by_parameter.get(&param.name))
+                .map(|passed| Type::union(passed.iter().cloned()));
+            // A default of `None` alone says nothing about the values the
+            // parameter is meant for
+            if passed.is_none() && !default.as_ref().is_some_and(|default| *default != Type::None) {
+                continue;
+            }
+            // Calls passing values of different types leave the parameter
+            // generic rather than a union the body may not accept
+            if matches!(passed, Some(Type::Union(_))) {
+                continue;
+            }
+            let annotation = Type::union(passed.into_iter().chain(default));
+            if !self.is_writable(&annotation) {
+                continue;
+            }
+            let name_end = param.position.offset + param.name.len();
+            let (end, text) = match param.default {
+                // `x=0` becomes `x: int = 0`
+                Some(_) => {
+                    let Some(equals) = self.tokens.iter().find(|token| token.position.offset >= name_end) else {
+                        continue;
+                    };
+                    if equals.kind != TokenKind::Assign {
+                        continue;
+                    }
+                    let end = equals.position.offset + equals.lexeme.len();
+                    let space = if self.source[end..].starts_with(' ') { "" } else { " " };
+                    (end, format!(": {} ={}", annotation, space))
+                }
+                None => (name_end, format!(": {}", annotation)),
+            };
+            edits.push(Edit {
+                start: name_end,
+                end,
+                text,
+                annotation: Annotation {
+                    line: param.position.line,
+                    target: AnnotationTarget::Parameter {
+                        function: name.clone(),
+                        parameter: param.name.clone(),
+                    },
+                    annotation,
+                },
+            });
+        }
+
+        if return_type.is_some() || name == "__init__" {
+            return;
+        }
+        let Some(returns) = self.inferred.return_type(id).filter(|returns| self.is_writable(returns)) else {
+            return;
+        };
+        if let Some(end) = self.parameters_end(position) {
+            edits.push(Edit {
+                start: end,
+                end,
+                text: format!(" -> {}", returns),
+                annotation: Annotation {
+                    line: position.line,
+                    target: AnnotationTarget::Return { function: name.clone() },
+                    annotation: returns.clone(),
+                },
+            });
+        }
+    }
+
+    /// Annotate a name assigned exactly once in a module or function body
+    fn variable(&self, target: NodeId) -> Option<Edit> {
+        let Some(ExprKind::Identifier { name }) = self.arena.expr(target) else {
+            return None;
+        };
+        let position = *self.arena.position(target)?;
+        let symbol = self.symbols.get(&position)?.iter().find(|symbol| symbol.name == *name)?;
+        let scope = self.symbol_table.get_scope(symbol.scope_id)?;
+        let single = symbol.kind == SymbolKind::Variable && symbol.definitions.len() == 1;
+        if !single || symbol.is_global || symbol.is_nonlocal || symbol.declared_type.is_some() {
+            return None;
+        }
+        // An annotation in a class body would make the name a dataclass field
+        if !matches!(scope.kind, ScopeKind::Module | ScopeKind::Function) {
+            return None;
+        }
+        let value = self.inferred.type_of(target).filter(|value| **value != Type::None && self.is_writable(value))?;
+        let start = position.offset + name.len();
+        Some(Edit {
+            start,
+            end: start,
+            text: format!(": {}", value),
+            annotation: Annotation {
+                line: position.line,
+                target: AnnotationTarget::Variable { name: name.clone() },
+                annotation: value.clone(),
+            },
+        })
+    }
+
+    /// Offset just after the `)` closing the parameters of the function
+    /// defined at `position`
+    fn parameters_end(&self, position: SourcePosition) -> Option<usize> {
+        let mut tokens = self.tokens.iter().skip_while(|token| token.position.offset < position.offset);
+        tokens.find(|token| token.kind == TokenKind::Def)?;
+        tokens.find(|token| token.kind == TokenKind::LeftParen)?;
+        let mut depth = 1;
+        for token in tokens {
+            match token.kind {
+                TokenKind::LeftParen => depth += 1,
+                TokenKind::RightParen if depth == 1 => return Some(token.position.offset + token.lexeme.len()),
+                TokenKind::RightParen => depth -= 1,
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Whether a type can be written as an annotation of the module
+    fn is_writable(&self, ty: &Type) -> bool {
+        match ty {
+            Type::Int | Type::Float | Type::Str | Type::Bool | Type::None => true,
+            Type::List(element) | Type::Set(element) | Type::VarTuple(element) => self.is_writable(element),
+            Type::Dict(key, value) => self.is_writable(key) && self.is_writable(value),
+            Type::Tuple(elements) | Type::Union(elements) => elements.iter().all(|element| self.is_writable(element)),
+            Type::Instance { class, arguments } => {
+                self.is_visible_class(class) && arguments.iter().all(|argument| self.is_writable(argument))
+            }
+            Type::Callable { .. } | Type::TypeVar(_) | Type::Any | Type::Never => false,
+        }
+    }
+
+    /// Whether a class can be named at module level: a builtin class, or a
+    /// class defined in or imported into the module
+    fn is_visible_class(&self, class: &str) -> bool {
+        let module = self.symbol_table.module_scope_id().and_then(|scope| self.symbol_table.get_scope(scope));
+        match module.and_then(|scope| scope.lookup(class)) {
+            Some(symbol) => symbol.definitions.len() == 1 && symbol.class_info.is_some(),
+            None => builtins::lookup(class)
+                .is_some_and(|builtin| matches!(builtin.kind, BuiltinKind::Class | BuiltinKind::Exception)),
+        }
+    }
+}
+
+/// The symbols of every scope but the builtins, by the position of their
+/// first binding
+fn symbols_by_position(symbol_table: &SymbolTable) -> HashMap<SourcePosition, Vec<&Symbol>> {
+    let builtins = symbol_table.builtins_scope_id();
+    let mut symbols: HashMap<SourcePosition, Vec<&Symbol>> = HashMap::new();
+    for scope in symbol_table.scopes().values().filter(|scope| Some(scope.id) != builtins) {
+        for symbol in scope.symbols() {
+            symbols.entry(symbol.position).or_default().push(symbol);
+        }
+    }
+    symbols
+}
+
+/// The types of the arguments passed to the functions defined in the
+/// module, from every call whose callee is known
+fn argument_types(arena: &AstArena, inferred: &InferredTypes) -> HashMap<NodeId, Arguments> {
+    let mut functions: HashMap<NodeId, Arguments> = HashMap::new();
+    for id in arena.ids() {
+        let Some(ExprKind::Call { arguments, keywords, .. }) = arena.expr(id) else {
+            continue;
+        };
+        let Some(callee) = inferred.callee(id) else {
+            continue;
+        };
+        let Some(StmtKind::FunctionDef { parameters, .. }) = arena.stmt(callee.definition) else {
+            continue;
+        };
+        let passed = functions.entry(callee.definition).or_default();
+        let starred = arguments.iter().any(|&argument| matches!(arena.expr(argument), Some(ExprKind::Starred { .. })));
+        if starred || keywords.iter().any(|(name, _)| name.is_none()) {
+            passed.unpacked = true;
+            continue;
+        }
+        let positional = parameters
+            .iter()
+            .filter(|param| matches!(param.kind, ParameterKind::PositionalOnly | ParameterKind::Regular))
+            .skip(usize::from(callee.bound));
+        let keywords = keywords.iter().filter_map(|(name, value)| Some((name.as_deref()?, *value)));
+        let bound = positional.map(|param| param.name.as_str()).zip(arguments.iter().copied()).chain(keywords);
+        for (parameter, argument) in bound {
+            let argument = inferred.type_of(argument).cloned().unwrap_or(Type::Any);
+            passed.by_parameter.entry(parameter.to_string()).or_default().push(argument);
+        }
+    }
+    functions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn annotated(source: &str) -> String {
+        annotate(source).unwrap().source
+    }
+
+    #[test]
+    fn test_parameters_returns_and_variables() {
+        let source = "\
+# scale a value
+def scale(value, factor=2):  # keep this comment
+    result = value * factor
+    return result
+
+
+total = scale(3)
+label = 'total: ' + str(total)
+";
+        let expected = "\
+# scale a value
+def scale(value: int, factor: int = 2) -> int:  # keep this comment
+    result: int = value * factor
+    return result
+
+
+total: int = scale(3)
+label: str = 'total: ' + str(total)
+";
+        assert_eq!(annotated(source), expected);
+    }
+
+    #[test]
+    fn test_annotations_listed_in_source_order() {
+        let annotations = annotate("def half(x):\n    return x / 2\nhalf(1)\n").unwrap().annotations;
+        let listed: Vec<String> = annotations.iter().map(|annotation| format!("{}", annotation)).collect();
+        assert_eq!(listed, ["parameter 'x' of 'half': int", "'half' returns float"]);
+    }
+
+    #[test]
+    fn test_unknown_types_are_left_out() {
+        // No calls, a `None` default, a rebound name, an empty list, an
+        // unpacked call, and a method receiver
+        let source = "\
+def first(items, default=None):
+    return items[0]
+count = 0
+count = 1
+empty = []
+def add(a, b):
+    return a + b
+add(*[1, 2])
+class Box:
+    def get(self):
+        return 1
+";
+        let expected = source.replace("def get(self):", "def get(self) -> int:");
+        assert_eq!(annotated(source), expected);
+    }
+
+    #[test]
+    fn test_names_rebound_through_global_or_nonlocal_left_out() {
+        let source = "\
+count = 0
+def reset():
+    global count
+    count = 'none'
+def outer():
+    total = 0
+    def add():
+        nonlocal total
+        total = 'many'
+    return add
+";
+        let expected = source.replace("reset():", "reset() -> None:").replace("add():", "add() -> None:");
+        assert_eq!(annotated(source), expected);
+    }
+
+    #[test]
+    fn test_annotated_source_checks_cleanly() {
+        // Calls that disagree on a parameter's type, and a default that
+        // would make the parameter possibly `None` where it is added to
+        let source = "\
+def add(a, b):
+    return a + b
+add(1, 2)
+add('a', 'b')
+def inc(n):
+    return n + 1
+inc(1)
+inc(None)
+def step(n=None):
+    return n + 1
+step(1)
+";
+        let proposed: Vec<String> = edits(source).unwrap().iter().map(|edit| edit.text.clone()).collect();
+        assert_eq!(proposed, [": int | None = "]);
+        let output = annotated(source);
+        assert_eq!(output, source);
+        assert_eq!(problems(&output), Some(Vec::new()));
+    }
+
+    #[test]
+    fn test_existing_annotations_kept() {
+        let source = "def greet(name: str, times=1) -> str:\n    return name * times\ngreet('a', 2)\n";
+        assert_eq!(
+            annotated(source),
+            "def greet(name: str, times: int = 1) -> str:\n    return name * times\ngreet('a', 2)\n"
+        );
+        assert!(annotate("def broken(:\n").is_err());
+    }
+}
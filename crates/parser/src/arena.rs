@@ -0,0 +1,708 @@
+//! Arena-backed AST with stable node IDs
+//!
+//! The parser produces a tree of boxed `Expression`/`Statement` nodes, which has
+//! no notion of node identity. This module lowers that tree into a flat arena in
+//! which every statement and expression is addressed by a `NodeId`. Later passes
+//! (type inference, symbol resolution, editor tooling) attach data to nodes via
+//! side tables such as `SideTable<Type>` instead of mutating or cloning the tree.
+//!
+//! IDs are assigned in source pre-order, so lowering the same module twice yields
+//! the same IDs. Cloning an arena is a single flat copy, and passing a subtree
+//! around only requires copying its `NodeId`.
+
+use crate::ast::{
+    self, AugmentedOperator, BinaryOperator, Expression, FromImportItem, ImportItem, Literal,
+    Module, ParameterKind, Statement, UnaryOperator,
+};
+use crate::token::SourcePosition;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Index;
+
+/// Stable identifier for a statement or expression stored in an `AstArena`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(u32);
+
+impl NodeId {
+    /// Get the index of this node inside its arena
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// Per-node data attached by an analysis pass (e.g. `SideTable<Type>`)
+pub type SideTable<T> = HashMap<NodeId, T>;
+
+/// A single node stored in the arena
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub kind: NodeKind,
+    pub position: SourcePosition,
+    /// Enclosing node (None for top-level statements)
+    pub parent: Option<NodeId>,
+}
+
+/// Whether a node is a statement or an expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeKind {
+    Stmt(StmtKind),
+    Expr(ExprKind),
+}
+
+/// Function parameter with default and annotation stored as node IDs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter {
+    pub name: String,
+    pub kind: ParameterKind,
+    pub default: Option<NodeId>,
+    pub type_annotation: Option<NodeId>,
+    pub position: SourcePosition,
+}
+
+/// Comprehension clause (for target in iter [if condition])
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comprehension {
+    pub target: String,
+    pub iter: NodeId,
+    pub conditions: Vec<NodeId>,
+    pub position: SourcePosition,
+}
+
+/// Statement node; mirrors `ast::Statement` with children as node IDs
+#[derive(Debug, Clone, PartialEq)]
+pub enum StmtKind {
+    Expression(NodeId),
+    Assignment {
+        targets: Vec<NodeId>,
+        value: NodeId,
+    },
+    AnnAssignment {
+        target: String,
+        annotation: NodeId,
+        value: Option<NodeId>,
+    },
+    AugmentedAssignment {
+        target: NodeId,
+        op: AugmentedOperator,
+        value: NodeId,
+    },
+    Pass,
+    Break,
+    Continue,
+    Return {
+        value: Option<NodeId>,
+    },
+    Assert {
+        condition: NodeId,
+        message: Option<NodeId>,
+    },
+    Del {
+        targets: Vec<NodeId>,
+    },
+    Global {
+        names: Vec<String>,
+    },
+    Nonlocal {
+        names: Vec<String>,
+    },
+    Raise {
+        exception: Option<NodeId>,
+    },
+    Import {
+        items: Vec<ImportItem>,
+    },
+    FromImport {
+        module: String,
+        items: Vec<FromImportItem>,
+    },
+    If {
+        condition: NodeId,
+        then_block: Vec<NodeId>,
+        elif_blocks: Vec<(NodeId, Vec<NodeId>)>,
+        else_block: Option<Vec<NodeId>>,
+    },
+    While {
+        condition: NodeId,
+        body: Vec<NodeId>,
+        else_block: Option<Vec<NodeId>>,
+    },
+    For {
+        target: NodeId,
+        iter: NodeId,
+        body: Vec<NodeId>,
+        else_block: Option<Vec<NodeId>>,
+    },
+    FunctionDef {
+        name: String,
+        parameters: Vec<Parameter>,
+        body: Vec<NodeId>,
+        is_async: bool,
+        return_type: Option<NodeId>,
+        decorators: Vec<NodeId>,
+    },
+    ClassDef {
+        name: String,
+        bases: Vec<NodeId>,
+        body: Vec<NodeId>,
+        decorators: Vec<NodeId>,
+        metaclass: Option<NodeId>,
+    },
+}
+
+/// Expression node; mirrors `ast::Expression` with children as node IDs
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprKind {
+    Literal(Literal),
+    Identifier {
+        name: String,
+    },
+    BinaryOp {
+        left: NodeId,
+        op: BinaryOperator,
+        right: NodeId,
+    },
+    UnaryOp {
+        op: UnaryOperator,
+        operand: NodeId,
+    },
+    Parenthesized {
+        expr: NodeId,
+    },
+    Call {
+        function: NodeId,
+        arguments: Vec<NodeId>,
+        keywords: Vec<(Option<String>, NodeId)>,
+    },
+    Attribute {
+        object: NodeId,
+        attribute: String,
+    },
+    Subscript {
+        object: NodeId,
+        index: NodeId,
+    },
+    List {
+        elements: Vec<NodeId>,
+    },
+    Tuple {
+        elements: Vec<NodeId>,
+    },
+    Dict {
+        pairs: Vec<(NodeId, NodeId)>,
+    },
+    Set {
+        elements: Vec<NodeId>,
+    },
+    Lambda {
+        parameters: Vec<String>,
+        body: NodeId,
+    },
+    Conditional {
+        condition: NodeId,
+        true_expr: NodeId,
+        false_expr: NodeId,
+    },
+    AssignmentExpr {
+        target: String,
+        value: NodeId,
+    },
+    ListComp {
+        element: NodeId,
+        generators: Vec<Comprehension>,
+    },
+    DictComp {
+        key: NodeId,
+        value: NodeId,
+        generators: Vec<Comprehension>,
+    },
+    SetComp {
+        element: NodeId,
+        generators: Vec<Comprehension>,
+    },
+    GeneratorExpr {
+        element: NodeId,
+        generators: Vec<Comprehension>,
+    },
+    Starred {
+        value: NodeId,
+    },
+}
+
+/// Flat storage for all nodes of a module
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AstArena {
+    nodes: Vec<Node>,
+    /// Top-level statements of the module, in source order
+    root: Vec<NodeId>,
+}
+
+impl AstArena {
+    /// Lower a parsed module into an arena
+    pub fn from_module(module: &Module) -> Self {
+        let mut lowering = Lowering { arena: AstArena::default() };
+        let root = lowering.lower_block(&module.statements, None);
+        lowering.arena.root = root;
+        lowering.arena
+    }
+
+    /// Top-level statements of the module
+    pub fn root(&self) -> &[NodeId] {
+        &self.root
+    }
+
+    /// Get a node by ID
+    pub fn get(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.get(id.index())
+    }
+
+    /// Get the statement stored at `id`, if it is a statement
+    pub fn stmt(&self, id: NodeId) -> Option<&StmtKind> {
+        match &self.get(id)?.kind {
+            NodeKind::Stmt(stmt) => Some(stmt),
+            NodeKind::Expr(_) => None,
+        }
+    }
+
+    /// Get the expression stored at `id`, if it is an expression
+    pub fn expr(&self, id: NodeId) -> Option<&ExprKind> {
+        match &self.get(id)?.kind {
+            NodeKind::Expr(expr) => Some(expr),
+            NodeKind::Stmt(_) => None,
+        }
+    }
+
+    /// Get the source position of a node
+    pub fn position(&self, id: NodeId) -> Option<&SourcePosition> {
+        self.get(id).map(|node| &node.position)
+    }
+
+    /// Get the enclosing node of a node (None for top-level statements)
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.get(id)?.parent
+    }
+
+    /// Number of nodes in the arena
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Check if the arena contains no nodes
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Iterate over all node IDs in pre-order
+    pub fn ids(&self) -> impl Iterator<Item = NodeId> {
+        (0..self.nodes.len() as u32).map(NodeId)
+    }
+
+    /// Get the direct children of a node, in source order
+    pub fn children(&self, id: NodeId) -> Vec<NodeId> {
+        let mut children = Vec::new();
+        match self.get(id).map(|node| &node.kind) {
+            Some(NodeKind::Stmt(stmt)) => stmt_children(stmt, &mut children),
+            Some(NodeKind::Expr(expr)) => expr_children(expr, &mut children),
+            None => {}
+        }
+        children
+    }
+}
+
+impl Index<NodeId> for AstArena {
+    type Output = Node;
+
+    fn index(&self, id: NodeId) -> &Node {
+        &self.nodes[id.index()]
+    }
+}
+
+fn stmt_children(stmt: &StmtKind, out: &mut Vec<NodeId>) {
+    match stmt {
+        StmtKind::Expression(expr) => out.push(*expr),
+        StmtKind::Assignment { targets, value } => {
+            out.extend(targets);
+            out.push(*value);
+        }
+        StmtKind::AnnAssignment { annotation, value, .. } => {
+            out.push(*annotation);
+            out.extend(value);
+        }
+        StmtKind::AugmentedAssignment { target, value, .. } => {
+            out.push(*target);
+            out.push(*value);
+        }
+        StmtKind::Return { value } => out.extend(value),
+        StmtKind::Assert { condition, message } => {
+            out.push(*condition);
+            out.extend(message);
+        }
+        StmtKind::Del { targets } => out.extend(targets),
+        StmtKind::Raise { exception } => out.extend(exception),
+        StmtKind::If { condition, then_block, elif_blocks, else_block } => {
+            out.push(*condition);
+            out.extend(then_block);
+            for (elif_condition, elif_body) in elif_blocks {
+                out.push(*elif_condition);
+                out.extend(elif_body);
+            }
+            if let Some(else_body) = else_block {
+                out.extend(else_body);
+            }
+        }
+        StmtKind::While { condition, body, else_block } => {
+            out.push(*condition);
+            out.extend(body);
+            if let Some(else_body) = else_block {
+                out.extend(else_body);
+            }
+        }
+        StmtKind::For { target, iter, body, else_block } => {
+            out.push(*target);
+            out.push(*iter);
+            out.extend(body);
+            if let Some(else_body) = else_block {
+                out.extend(else_body);
+            }
+        }
+        StmtKind::FunctionDef { parameters, body, return_type, decorators, .. } => {
+            out.extend(decorators);
+            for param in parameters {
+                out.extend(param.type_annotation);
+                out.extend(param.default);
+            }
+            out.extend(return_type);
+            out.extend(body);
+        }
+        StmtKind::ClassDef { bases, body, decorators, metaclass, .. } => {
+            out.extend(decorators);
+            out.extend(bases);
+            out.extend(metaclass);
+            out.extend(body);
+        }
+        StmtKind::Pass
+        | StmtKind::Break
+        | StmtKind::Continue
+        | StmtKind::Global { .. }
+        | StmtKind::Nonlocal { .. }
+        | StmtKind::Import { .. }
+        | StmtKind::FromImport { .. } => {}
+    }
+}
+
+fn expr_children(expr: &ExprKind, out: &mut Vec<NodeId>) {
+    match expr {
+        ExprKind::Literal(_) | ExprKind::Identifier { .. } => {}
+        ExprKind::BinaryOp { left, right, .. } => {
+            out.push(*left);
+            out.push(*right);
+        }
+        ExprKind::UnaryOp { operand, .. } => out.push(*operand),
+        ExprKind::Parenthesized { expr } => out.push(*expr),
+        ExprKind::Call { function, arguments, keywords } => {
+            out.push(*function);
+            out.extend(arguments);
+            out.extend(keywords.iter().map(|(_, value)| *value));
+        }
+        ExprKind::Attribute { object, .. } => out.push(*object),
+        ExprKind::Subscript { object, index } => {
+            out.push(*object);
+            out.push(*index);
+        }
+        ExprKind::List { elements } | ExprKind::Tuple { elements } | ExprKind::Set { elements } => {
+            out.extend(elements);
+        }
+        ExprKind::Dict { pairs } => {
+            for (key, value) in pairs {
+                out.push(*key);
+                out.push(*value);
+            }
+        }
+        ExprKind::Lambda { body, .. } => out.push(*body),
+        ExprKind::Conditional { condition, true_expr, false_expr } => {
+            out.push(*condition);
+            out.push(*true_expr);
+            out.push(*false_expr);
+        }
+        ExprKind::AssignmentExpr { value, .. } => out.push(*value),
+        ExprKind::ListComp { element, generators }
+        | ExprKind::SetComp { element, generators }
+        | ExprKind::GeneratorExpr { element, generators } => {
+            out.push(*element);
+            comprehension_children(generators, out);
+        }
+        ExprKind::DictComp { key, value, generators } => {
+            out.push(*key);
+            out.push(*value);
+            comprehension_children(generators, out);
+        }
+        ExprKind::Starred { value } => out.push(*value),
+    }
+}
+
+fn comprehension_children(generators: &[Comprehension], out: &mut Vec<NodeId>) {
+    for generator in generators {
+        out.push(generator.iter);
+        out.extend(&generator.conditions);
+    }
+}
+
+/// Lowers boxed AST nodes into the arena
+///
+/// A node's slot is reserved before its children are lowered, so IDs follow
+/// source pre-order and parents always have smaller IDs than their children.
+struct Lowering {
+    arena: AstArena,
+}
+
+impl Lowering {
+    /// Reserve a slot for a node whose kind is filled in after its children
+    fn reserve(&mut self, position: &SourcePosition, parent: Option<NodeId>) -> NodeId {
+        let id = NodeId(self.arena.nodes.len() as u32);
+        self.arena.nodes.push(Node {
+            kind: NodeKind::Stmt(StmtKind::Pass),
+            position: *position,
+            parent,
+        });
+        id
+    }
+
+    fn finish(&mut self, id: NodeId, kind: NodeKind) -> NodeId {
+        self.arena.nodes[id.index()].kind = kind;
+        id
+    }
+
+    fn lower_block(&mut self, statements: &[Statement], parent: Option<NodeId>) -> Vec<NodeId> {
+        statements.iter().map(|stmt| self.lower_statement(stmt, parent)).collect()
+    }
+
+    fn lower_opt_block(&mut self, block: &Option<Vec<Statement>>, parent: NodeId) -> Option<Vec<NodeId>> {
+        block.as_ref().map(|stmts| self.lower_block(stmts, Some(parent)))
+    }
+
+    fn lower_exprs(&mut self, exprs: &[Expression], parent: NodeId) -> Vec<NodeId> {
+        exprs.iter().map(|expr| self.lower_expression(expr, parent)).collect()
+    }
+
+    fn lower_opt_expr(&mut self, expr: &Option<Expression>, parent: NodeId) -> Option<NodeId> {
+        expr.as_ref().map(|expr| self.lower_expression(expr, parent))
+    }
+
+    fn lower_statement(&mut self, statement: &Statement, parent: Option<NodeId>) -> NodeId {
+        let id = self.reserve(statement.position(), parent);
+
+        let kind = match statement {
+            Statement::Expression(expr) => StmtKind::Expression(self.lower_expression(expr, id)),
+            Statement::Assignment { targets, value, .. } => StmtKind::Assignment {
+                targets: self.lower_exprs(targets, id),
+                value: self.lower_expression(value, id),
+            },
+            Statement::AnnAssignment { target, annotation, value, .. } => StmtKind::AnnAssignment {
+                target: target.clone(),
+                annotation: self.lower_expression(annotation, id),
+                value: self.lower_opt_expr(value, id),
+            },
+            Statement::AugmentedAssignment { target, op, value, .. } => StmtKind::AugmentedAssignment {
+                target: self.lower_expression(target, id),
+                op: *op,
+                value: self.lower_expression(value, id),
+            },
+            Statement::Pass(_) => StmtKind::Pass,
+            Statement::Break(_) => StmtKind::Break,
+            Statement::Continue(_) => StmtKind::Continue,
+            Statement::Return { value, .. } => StmtKind::Return {
+                value: self.lower_opt_expr(value, id),
+            },
+            Statement::Assert { condition, message, .. } => StmtKind::Assert {
+                condition: self.lower_expression(condition, id),
+                message: self.lower_opt_expr(message, id),
+            },
+            Statement::Del { targets, .. } => StmtKind::Del {
+                targets: self.lower_exprs(targets, id),
+            },
+            Statement::Global { names, .. } => StmtKind::Global { names: names.clone() },
+            Statement::Nonlocal { names, .. } => StmtKind::Nonlocal { names: names.clone() },
+            Statement::Raise { exception, .. } => StmtKind::Raise {
+                exception: self.lower_opt_expr(exception, id),
+            },
+            Statement::Import { items, .. } => StmtKind::Import {
+                items: items.clone(),
+            },
+            Statement::FromImport { module, items, .. } => StmtKind::FromImport {
+                module: module.clone(),
+                items: items.clone(),
+            },
+            Statement::If { condition, then_block, elif_blocks, else_block, .. } => {
+                let condition = self.lower_expression(condition, id);
+                let then_block = self.lower_block(then_block, Some(id));
+                let elif_blocks = elif_blocks
+                    .iter()
+                    .map(|(elif_condition, elif_body)| {
+                        (self.lower_expression(elif_condition, id), self.lower_block(elif_body, Some(id)))
+                    })
+                    .collect();
+                StmtKind::If {
+                    condition,
+                    then_block,
+                    elif_blocks,
+                    else_block: self.lower_opt_block(else_block, id),
+                }
+            }
+            Statement::While { condition, body, else_block, .. } => StmtKind::While {
+                condition: self.lower_expression(condition, id),
+                body: self.lower_block(body, Some(id)),
+                else_block: self.lower_opt_block(else_block, id),
+            },
+            Statement::For { target, iter, body, else_block, .. } => StmtKind::For {
+                target: self.lower_expression(target, id),
+                iter: self.lower_expression(iter, id),
+                body: self.lower_block(body, Some(id)),
+                else_block: self.lower_opt_block(else_block, id),
+            },
+            Statement::FunctionDef { name, parameters, body, is_async, return_type, decorators, .. } => {
+                let decorators = self.lower_exprs(decorators, id);
+                let parameters = parameters
+                    .iter()
+                    .map(|param| self.lower_parameter(param, id))
+                    .collect();
+                let return_type = self.lower_opt_expr(return_type, id);
+                StmtKind::FunctionDef {
+                    name: name.clone(),
+                    parameters,
+                    body: self.lower_block(body, Some(id)),
+                    is_async: *is_async,
+                    return_type,
+                    decorators,
+                }
+            }
+            Statement::ClassDef { name, bases, body, decorators, metaclass, .. } => {
+                let decorators = self.lower_exprs(decorators, id);
+                let bases = self.lower_exprs(bases, id);
+                let metaclass = self.lower_opt_expr(metaclass, id);
+                StmtKind::ClassDef {
+                    name: name.clone(),
+                    bases,
+                    body: self.lower_block(body, Some(id)),
+                    decorators,
+                    metaclass,
+                }
+            }
+        };
+
+        self.finish(id, NodeKind::Stmt(kind))
+    }
+
+    fn lower_parameter(&mut self, param: &ast::Parameter, parent: NodeId) -> Parameter {
+        Parameter {
+            name: param.name.clone(),
+            kind: param.kind.clone(),
+            type_annotation: self.lower_opt_expr(&param.type_annotation, parent),
+            default: self.lower_opt_expr(&param.default, parent),
+            position: param.position,
+        }
+    }
+
+    fn lower_generators(&mut self, generators: &[ast::Comprehension], parent: NodeId) -> Vec<Comprehension> {
+        generators
+            .iter()
+            .map(|generator| Comprehension {
+                target: generator.target.clone(),
+                iter: self.lower_expression(&generator.iter, parent),
+                conditions: self.lower_exprs(&generator.conditions, parent),
+                position: generator.position,
+            })
+            .collect()
+    }
+
+    fn lower_expression(&mut self, expression: &Expression, parent: NodeId) -> NodeId {
+        let id = self.reserve(expression.position(), Some(parent));
+
+        let kind = match expression {
+            Expression::Literal(literal) => ExprKind::Literal(literal.clone()),
+            Expression::Identifier { name, .. } => ExprKind::Identifier { name: name.clone() },
+            Expression::BinaryOp { left, op, right, .. } => ExprKind::BinaryOp {
+                left: self.lower_expression(left, id),
+                op: *op,
+                right: self.lower_expression(right, id),
+            },
+            Expression::UnaryOp { op, operand, .. } => ExprKind::UnaryOp {
+                op: *op,
+                operand: self.lower_expression(operand, id),
+            },
+            Expression::Parenthesized { expr, .. } => ExprKind::Parenthesized {
+                expr: self.lower_expression(expr, id),
+            },
+            Expression::Call { function, arguments, keywords, .. } => ExprKind::Call {
+                function: self.lower_expression(function, id),
+                arguments: self.lower_exprs(arguments, id),
+                keywords: keywords
+                    .iter()
+                    .map(|keyword| (keyword.name.clone(), self.lower_expression(&keyword.value, id)))
+                    .collect(),
+            },
+            Expression::Attribute { object, attribute, .. } => ExprKind::Attribute {
+                object: self.lower_expression(object, id),
+                attribute: attribute.clone(),
+            },
+            Expression::Subscript { object, index, .. } => ExprKind::Subscript {
+                object: self.lower_expression(object, id),
+                index: self.lower_expression(index, id),
+            },
+            Expression::List { elements, .. } => ExprKind::List {
+                elements: self.lower_exprs(elements, id),
+            },
+            Expression::Tuple { elements, .. } => ExprKind::Tuple {
+                elements: self.lower_exprs(elements, id),
+            },
+            Expression::Dict { pairs, .. } => ExprKind::Dict {
+                pairs: pairs
+                    .iter()
+                    .map(|(key, value)| (self.lower_expression(key, id), self.lower_expression(value, id)))
+                    .collect(),
+            },
+            Expression::Set { elements, .. } => ExprKind::Set {
+                elements: self.lower_exprs(elements, id),
+            },
+            Expression::Lambda { parameters, body, .. } => ExprKind::Lambda {
+                parameters: parameters.clone(),
+                body: self.lower_expression(body, id),
+            },
+            Expression::Conditional { condition, true_expr, false_expr, .. } => ExprKind::Conditional {
+                condition: self.lower_expression(condition, id),
+                true_expr: self.lower_expression(true_expr, id),
+                false_expr: self.lower_expression(false_expr, id),
+            },
+            Expression::AssignmentExpr { target, value, .. } => ExprKind::AssignmentExpr {
+                target: target.clone(),
+                value: self.lower_expression(value, id),
+            },
+            Expression::ListComp { element, generators, .. } => ExprKind::ListComp {
+                element: self.lower_expression(element, id),
+                generators: self.lower_generators(generators, id),
+            },
+            Expression::DictComp { key, value, generators, .. } => ExprKind::DictComp {
+                key: self.lower_expression(key, id),
+                value: self.lower_expression(value, id),
+                generators: self.lower_generators(generators, id),
+            },
+            Expression::SetComp { element, generators, .. } => ExprKind::SetComp {
+                element: self.lower_expression(element, id),
+                generators: self.lower_generators(generators, id),
+            },
+            Expression::GeneratorExpr { element, generators, .. } => ExprKind::GeneratorExpr {
+                element: self.lower_expression(element, id),
+                generators: self.lower_generators(generators, id),
+            },
+            Expression::Starred { value, .. } => ExprKind::Starred {
+                value: self.lower_expression(value, id),
+            },
+        };
+
+        self.finish(id, NodeKind::Expr(kind))
+    }
+}
@@ -4,6 +4,7 @@
 //! Each node includes position information for error reporting.
 
 use crate::token::SourcePosition;
+use serde::{Deserialize, Serialize};
 
 /// A single import item in an import statement (module name + optional alias)
 #[derive(Debug, Clone, PartialEq)]
@@ -131,6 +132,8 @@ pub enum Statement {
     /// Function definition
     FunctionDef {
         name: String,
+        /// Type parameters declared with `def name[T](...)`
+        type_params: Vec<TypeParam>,
         parameters: Vec<Parameter>,
         body: Vec<Statement>,
         is_async: bool,
@@ -141,6 +144,8 @@ pub enum Statement {
     /// Class definition
     ClassDef {
         name: String,
+        /// Type parameters declared with `class Name[T]`
+        type_params: Vec<TypeParam>,
         bases: Vec<Expression>,
         body: Vec<Statement>,
         decorators: Vec<Expression>,
@@ -177,10 +182,11 @@ pub enum Expression {
         expr: Box<Expression>,
         position: SourcePosition,
     },
-    /// Function call (func(arg1, arg2))
+    /// Function call (func(arg1, *args, name=value, **kwargs))
     Call {
         function: Box<Expression>,
         arguments: Vec<Expression>,
+        keywords: Vec<Keyword>,
         position: SourcePosition,
     },
     /// Attribute access (obj.attr)
@@ -275,6 +281,14 @@ pub struct Comprehension {
     pub position: SourcePosition,
 }
 
+/// Keyword argument in a call (name=value, or **value when name is None)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyword {
+    pub name: Option<String>,
+    pub value: Expression,
+    pub position: SourcePosition,
+}
+
 /// Literal values
 #[derive(Debug, Clone, PartialEq)]
 pub enum Literal {
@@ -369,7 +383,7 @@ pub enum AugmentedOperator {
 }
 
 /// Parameter kind (regular, *args, **kwargs, keyword-only)
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ParameterKind {
     PositionalOnly, // before / marker
     Regular,   // x or x=default
@@ -388,6 +402,16 @@ pub struct Parameter {
     pub position: SourcePosition,
 }
 
+/// Type parameter of a generic function or class (`T`, `T: int` or
+/// `T: (int, str)` in `def f[T](...)`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeParam {
+    pub name: String,
+    /// Upper bound, or a tuple of constraints
+    pub bound: Option<Expression>,
+    pub position: SourcePosition,
+}
+
 impl Literal {
     /// Get the position of this literal
     pub fn position(&self) -> &SourcePosition {
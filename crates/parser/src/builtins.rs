@@ -0,0 +1,348 @@
+//! Builtin namespace
+//!
+//! The names a module can use without importing anything: builtin functions,
+//! types, the exception hierarchy, constants and the attributes every module
+//! has (`__name__`, `__file__`, ...). The semantic analyzer declares them in a
+//! scope enclosing the module scope, so a module may shadow any of them.
+//!
+//! The signatures of the builtin functions are declared only in the bundled
+//! stub (see [`stubs`](crate::stubs)), so calls to builtins are checked like
+//! calls to user functions.
+
+use crate::stubs;
+use crate::symbol_table::{Signature, SymbolKind};
+
+/// What a builtin name refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinKind {
+    /// A function, or a class that is called like one (`enumerate`, `zip`)
+    Function,
+    /// A builtin type (`int`, `str`, `object`, ...)
+    Class,
+    /// An exception class
+    Exception,
+    /// A constant (`True`, `None`, `NotImplemented`, ...)
+    Constant,
+    /// An attribute every module has (`__name__`, `__file__`, ...)
+    ModuleAttribute,
+}
+
+/// One entry of the builtin namespace
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Builtin {
+    pub name: &'static str,
+    pub kind: BuiltinKind,
+    /// Direct base classes, for exceptions
+    pub bases: &'static [&'static str],
+}
+
+impl Builtin {
+    const fn function(name: &'static str) -> Self {
+        Self { name, kind: BuiltinKind::Function, bases: &[] }
+    }
+
+    const fn class(name: &'static str) -> Self {
+        Self { name, kind: BuiltinKind::Class, bases: &[] }
+    }
+
+    const fn exception(name: &'static str, bases: &'static [&'static str]) -> Self {
+        Self { name, kind: BuiltinKind::Exception, bases }
+    }
+
+    const fn constant(name: &'static str) -> Self {
+        Self { name, kind: BuiltinKind::Constant, bases: &[] }
+    }
+
+    const fn module_attribute(name: &'static str) -> Self {
+        Self { name, kind: BuiltinKind::ModuleAttribute, bases: &[] }
+    }
+
+    /// The kind of symbol the name is declared as
+    pub fn symbol_kind(&self) -> SymbolKind {
+        match self.kind {
+            BuiltinKind::Function => SymbolKind::Function,
+            BuiltinKind::Class | BuiltinKind::Exception => SymbolKind::Class,
+            BuiltinKind::Constant | BuiltinKind::ModuleAttribute => SymbolKind::Variable,
+        }
+    }
+
+    /// The signature calls are checked against, as the bundled stub
+    /// declares it; `None` for functions with overloads that no single
+    /// signature describes (`max`, `min`)
+    pub fn signature(&self) -> Option<Signature> {
+        stubs::builtin_signatures().get(self.name).cloned()
+    }
+}
+
+/// The builtin namespace, as of Python 3.12
+pub const BUILTINS: &[Builtin] = &[
+    // Functions
+    Builtin::function("abs"),
+    Builtin::function("aiter"),
+    Builtin::function("all"),
+    Builtin::function("anext"),
+    Builtin::function("any"),
+    Builtin::function("ascii"),
+    Builtin::function("bin"),
+    Builtin::function("breakpoint"),
+    Builtin::function("callable"),
+    Builtin::function("chr"),
+    Builtin::function("compile"),
+    Builtin::function("delattr"),
+    Builtin::function("dir"),
+    Builtin::function("divmod"),
+    Builtin::function("eval"),
+    Builtin::function("exec"),
+    Builtin::function("format"),
+    Builtin::function("getattr"),
+    Builtin::function("globals"),
+    Builtin::function("hasattr"),
+    Builtin::function("hash"),
+    Builtin::function("help"),
+    Builtin::function("hex"),
+    Builtin::function("id"),
+    Builtin::function("input"),
+    Builtin::function("isinstance"),
+    Builtin::function("issubclass"),
+    Builtin::function("iter"),
+    Builtin::function("len"),
+    Builtin::function("locals"),
+    Builtin::function("max"),
+    Builtin::function("min"),
+    Builtin::function("next"),
+    Builtin::function("oct"),
+    Builtin::function("open"),
+    Builtin::function("ord"),
+    Builtin::function("pow"),
+    Builtin::function("print"),
+    Builtin::function("repr"),
+    Builtin::function("round"),
+    Builtin::function("setattr"),
+    Builtin::function("sorted"),
+    Builtin::function("sum"),
+    Builtin::function("vars"),
+    Builtin::function("__import__"),
+    // Classes that are almost always called rather than subclassed or
+    // used in annotations, so their calls are checked like functions
+    Builtin::function("enumerate"),
+    Builtin::function("filter"),
+    Builtin::function("map"),
+    Builtin::function("range"),
+    Builtin::function("reversed"),
+    Builtin::function("zip"),
+    // Types
+    Builtin::class("bool"),
+    Builtin::class("bytearray"),
+    Builtin::class("bytes"),
+    Builtin::class("classmethod"),
+    Builtin::class("complex"),
+    Builtin::class("dict"),
+    Builtin::class("float"),
+    Builtin::class("frozenset"),
+    Builtin::class("int"),
+    Builtin::class("list"),
+    Builtin::class("memoryview"),
+    Builtin::class("object"),
+    Builtin::class("property"),
+    Builtin::class("set"),
+    Builtin::class("slice"),
+    Builtin::class("staticmethod"),
+    Builtin::class("str"),
+    Builtin::class("super"),
+    Builtin::class("tuple"),
+    Builtin::class("type"),
+    // Exceptions
+    Builtin::exception("BaseException", &["object"]),
+    Builtin::exception("BaseExceptionGroup", &["BaseException"]),
+    Builtin::exception("GeneratorExit", &["BaseException"]),
+    Builtin::exception("KeyboardInterrupt", &["BaseException"]),
+    Builtin::exception("SystemExit", &["BaseException"]),
+    Builtin::exception("Exception", &["BaseException"]),
+    Builtin::exception("ArithmeticError", &["Exception"]),
+    Builtin::exception("FloatingPointError", &["ArithmeticError"]),
+    Builtin::exception("OverflowError", &["ArithmeticError"]),
+    Builtin::exception("ZeroDivisionError", &["ArithmeticError"]),
+    Builtin::exception("AssertionError", &["Exception"]),
+    Builtin::exception("AttributeError", &["Exception"]),
+    Builtin::exception("BufferError", &["Exception"]),
+    Builtin::exception("EOFError", &["Exception"]),
+    Builtin::exception("ExceptionGroup", &["BaseExceptionGroup", "Exception"]),
+    Builtin::exception("ImportError", &["Exception"]),
+    Builtin::exception("ModuleNotFoundError", &["ImportError"]),
+    Builtin::exception("LookupError", &["Exception"]),
+    Builtin::exception("IndexError", &["LookupError"]),
+    Builtin::exception("KeyError", &["LookupError"]),
+    Builtin::exception("MemoryError", &["Exception"]),
+    Builtin::exception("NameError", &["Exception"]),
+    Builtin::exception("UnboundLocalError", &["NameError"]),
+    Builtin::exception("OSError", &["Exception"]),
+    // Aliases of OSError kept for compatibility
+    Builtin::exception("EnvironmentError", &["OSError"]),
+    Builtin::exception("IOError", &["OSError"]),
+    Builtin::exception("BlockingIOError", &["OSError"]),
+    Builtin::exception("ChildProcessError", &["OSError"]),
+    Builtin::exception("ConnectionError", &["OSError"]),
+    Builtin::exception("BrokenPipeError", &["ConnectionError"]),
+    Builtin::exception("ConnectionAbortedError", &["ConnectionError"]),
+    Builtin::exception("ConnectionRefusedError", &["ConnectionError"]),
+    Builtin::exception("ConnectionResetError", &["ConnectionError"]),
+    Builtin::exception("FileExistsError", &["OSError"]),
+    Builtin::exception("FileNotFoundError", &["OSError"]),
+    Builtin::exception("InterruptedError", &["OSError"]),
+    Builtin::exception("IsADirectoryError", &["OSError"]),
+    Builtin::exception("NotADirectoryError", &["OSError"]),
+    Builtin::exception("PermissionError", &["OSError"]),
+    Builtin::exception("ProcessLookupError", &["OSError"]),
+    Builtin::exception("TimeoutError", &["OSError"]),
+    Builtin::exception("ReferenceError", &["Exception"]),
+    Builtin::exception("RuntimeError", &["Exception"]),
+    Builtin::exception("NotImplementedError", &["RuntimeError"]),
+    Builtin::exception("RecursionError", &["RuntimeError"]),
+    Builtin::exception("StopAsyncIteration", &["Exception"]),
+    Builtin::exception("StopIteration", &["Exception"]),
+    Builtin::exception("SyntaxError", &["Exception"]),
+    Builtin::exception("IndentationError", &["SyntaxError"]),
+    Builtin::exception("TabError", &["IndentationError"]),
+    Builtin::exception("SystemError", &["Exception"]),
+    Builtin::exception("TypeError", &["Exception"]),
+    Builtin::exception("ValueError", &["Exception"]),
+    Builtin::exception("UnicodeError", &["ValueError"]),
+    Builtin::exception("UnicodeDecodeError", &["UnicodeError"]),
+    Builtin::exception("UnicodeEncodeError", &["UnicodeError"]),
+    Builtin::exception("UnicodeTranslateError", &["UnicodeError"]),
+    Builtin::exception("Warning", &["Exception"]),
+    Builtin::exception("BytesWarning", &["Warning"]),
+    Builtin::exception("DeprecationWarning", &["Warning"]),
+    Builtin::exception("EncodingWarning", &["Warning"]),
+    Builtin::exception("FutureWarning", &["Warning"]),
+    Builtin::exception("ImportWarning", &["Warning"]),
+    Builtin::exception("PendingDeprecationWarning", &["Warning"]),
+    Builtin::exception("ResourceWarning", &["Warning"]),
+    Builtin::exception("RuntimeWarning", &["Warning"]),
+    Builtin::exception("SyntaxWarning", &["Warning"]),
+    Builtin::exception("UnicodeWarning", &["Warning"]),
+    Builtin::exception("UserWarning", &["Warning"]),
+    // Constants
+    Builtin::constant("True"),
+    Builtin::constant("False"),
+    Builtin::constant("None"),
+    Builtin::constant("Ellipsis"),
+    Builtin::constant("NotImplemented"),
+    Builtin::constant("__debug__"),
+    // Module attributes
+    Builtin::module_attribute("__name__"),
+    Builtin::module_attribute("__doc__"),
+    Builtin::module_attribute("__file__"),
+    Builtin::module_attribute("__package__"),
+    Builtin::module_attribute("__spec__"),
+    Builtin::module_attribute("__loader__"),
+    Builtin::module_attribute("__builtins__"),
+];
+
+/// Look up a builtin by name
+pub fn lookup(name: &str) -> Option<&'static Builtin> {
+    BUILTINS.iter().find(|builtin| builtin.name == name)
+}
+
+/// Whether `name` is a builtin exception class that is `ancestor` or derives
+/// from it (`is_exception_subclass("KeyError", "LookupError")`)
+pub fn is_exception_subclass(name: &str, ancestor: &str) -> bool {
+    if name == ancestor {
+        return lookup(name).is_some_and(|builtin| builtin.kind == BuiltinKind::Exception);
+    }
+    lookup(name)
+        .filter(|builtin| builtin.kind == BuiltinKind::Exception)
+        .is_some_and(|builtin| builtin.bases.iter().any(|base| is_exception_subclass(base, ancestor)))
+}
+
+/// Attributes of instances of a builtin class other than dunders, or
+/// `None` for classes whose attributes are not listed
+pub fn instance_members(class: &str) -> Option<&'static [&'static str]> {
+    const INT: &[&str] = &[
+        "as_integer_ratio", "bit_count", "bit_length", "conjugate", "denominator", "from_bytes", "imag",
+        "is_integer", "numerator", "real", "to_bytes",
+    ];
+    match class {
+        "object" | "NoneType" => Some(&[]),
+        "int" | "bool" => Some(INT),
+        "float" => Some(&["as_integer_ratio", "conjugate", "fromhex", "hex", "imag", "is_integer", "real"]),
+        "str" => Some(&[
+            "capitalize", "casefold", "center", "count", "encode", "endswith", "expandtabs", "find", "format",
+            "format_map", "index", "isalnum", "isalpha", "isascii", "isdecimal", "isdigit", "isidentifier",
+            "islower", "isnumeric", "isprintable", "isspace", "istitle", "isupper", "join", "ljust", "lower",
+            "lstrip", "maketrans", "partition", "removeprefix", "removesuffix", "replace", "rfind", "rindex",
+            "rjust", "rpartition", "rsplit", "rstrip", "split", "splitlines", "startswith", "strip", "swapcase",
+            "title", "translate", "upper", "zfill",
+        ]),
+        "list" => Some(&["append", "clear", "copy", "count", "extend", "index", "insert", "pop", "remove", "reverse", "sort"]),
+        "dict" => Some(&[
+            "clear", "copy", "fromkeys", "get", "items", "keys", "pop", "popitem", "setdefault", "update", "values",
+        ]),
+        "set" => Some(&[
+            "add", "clear", "copy", "difference", "difference_update", "discard", "intersection",
+            "intersection_update", "isdisjoint", "issubset", "issuperset", "pop", "remove", "symmetric_difference",
+            "symmetric_difference_update", "union", "update",
+        ]),
+        "frozenset" => Some(&[
+            "copy", "difference", "intersection", "isdisjoint", "issubset", "issuperset", "symmetric_difference",
+            "union",
+        ]),
+        "tuple" => Some(&["count", "index"]),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::ParameterKind;
+
+    #[test]
+    fn test_names_are_unique() {
+        let mut names: Vec<&str> = BUILTINS.iter().map(|builtin| builtin.name).collect();
+        names.sort_unstable();
+        let count = names.len();
+        names.dedup();
+        assert_eq!(names.len(), count);
+    }
+
+    #[test]
+    fn test_signatures_from_stub() {
+        let signature = lookup("sorted").unwrap().signature().unwrap();
+        let kinds: Vec<(&str, &ParameterKind, bool)> = signature
+            .parameters
+            .iter()
+            .map(|param| (param.name.as_str(), &param.kind, param.has_default))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("iterable", &ParameterKind::PositionalOnly, false),
+                ("key", &ParameterKind::KwOnly, true),
+                ("reverse", &ParameterKind::KwOnly, true),
+            ]
+        );
+
+        let print = lookup("print").unwrap().signature().unwrap();
+        assert!(print.has_var_args());
+        assert!(print.parameters.iter().skip(1).all(|param| param.kind == ParameterKind::KwOnly));
+        assert!(lookup("globals").unwrap().signature().unwrap().parameters.is_empty());
+        assert!(lookup("max").unwrap().signature().is_none());
+    }
+
+    #[test]
+    fn test_exception_hierarchy() {
+        assert!(is_exception_subclass("KeyError", "LookupError"));
+        assert!(is_exception_subclass("KeyError", "BaseException"));
+        assert!(is_exception_subclass("ExceptionGroup", "Exception"));
+        assert!(is_exception_subclass("ValueError", "ValueError"));
+        assert!(!is_exception_subclass("KeyboardInterrupt", "Exception"));
+        assert!(!is_exception_subclass("int", "int"));
+        // Every base is itself a known exception (or object)
+        for builtin in BUILTINS.iter().filter(|builtin| builtin.kind == BuiltinKind::Exception) {
+            for base in builtin.bases {
+                assert!(*base == "object" || lookup(base).is_some_and(|b| b.kind == BuiltinKind::Exception));
+            }
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! Analysis Cache
+//!
+//! Keeps the results of analyzing each module of a program — the symbols it
+//! binds at top level with their signatures, classes and types, and its
+//! diagnostics — in a directory, so that checking the program again only
+//! analyzes the modules that changed.
+//!
+//! A result is stored under a key hashed from everything its analysis
+//! depends on: the module's name, path and source, how it is type checked,
+//! the build of the analyzer, and for every module it imports either that
+//! module's key or how the import resolved. Changing a module therefore
+//! changes the key of every module importing it, directly or not.
+//!
+//! One entry is kept per module file, named after a hash of its path, and an
+//! entry whose key does not match is replaced. The cache is best effort: an
+//! entry that cannot be read or written is analyzed as if there were none.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::checker::CheckMode;
+use crate::modules::{Import, ModuleLoader};
+use crate::symbol_table::Symbol;
+use mamba_error::Diagnostic;
+
+/// Name of the cache directory, kept in the root directory of a program
+pub const DEFAULT_DIRECTORY: &str = ".mamba_cache";
+
+/// Hash of the analyzer's sources, computed by the build script, so that a
+/// rebuilt analyzer never reuses the results of another build
+const ANALYZER_FINGERPRINT: &str = env!("MAMBA_ANALYZER_FINGERPRINT");
+
+/// The analysis of one module, as cached
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedAnalysis {
+    pub key: u64,
+    /// Symbols bound in the module scope
+    pub symbols: HashMap<String, Symbol>,
+    /// The names listed in `__all__`
+    pub all: Option<Vec<String>>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// A directory of cached analyses, one entry per module file
+#[derive(Debug, Clone)]
+pub struct AnalysisCache {
+    directory: PathBuf,
+}
+
+impl AnalysisCache {
+    /// Use `directory` as the cache, creating it when the first entry is
+    /// written
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    /// The cache for checking a program starting from `file`, in its root:
+    /// the first directory of its search path
+    pub fn for_file(file: &Path) -> Self {
+        let root = ModuleLoader::search_path_for(file).into_iter().next().unwrap_or_default();
+        Self::new(root.join(DEFAULT_DIRECTORY))
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// The cached analysis of the module at `path`, if it was stored under
+    /// `key`
+    pub fn get(&self, path: &Path, key: u64) -> Option<CachedAnalysis> {
+        let entry = fs::read_to_string(self.entry(path)).ok()?;
+        serde_json::from_str::<CachedAnalysis>(&entry).ok().filter(|cached| cached.key == key)
+    }
+
+    /// Store the analysis of the module at `path`, replacing any earlier one
+    pub fn put(&self, path: &Path, analysis: &CachedAnalysis) -> io::Result<()> {
+        if !self.directory.is_dir() {
+            fs::create_dir_all(&self.directory)?;
+            // Keep the cache out of version control
+            fs::write(self.directory.join(".gitignore"), "*\n")?;
+        }
+        // Written aside and renamed, so a check running at the same time
+        // never reads half an entry
+        let entry = self.entry(path);
+        let partial = entry.with_extension(format!("{}.tmp", std::process::id()));
+        fs::write(&partial, serde_json::to_string(analysis)?)?;
+        fs::rename(&partial, &entry)
+    }
+
+    /// File of the entry for the module at `path`
+    fn entry(&self, path: &Path) -> PathBuf {
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let mut hash = Fingerprint::new();
+        hash.write_str(&path.to_string_lossy());
+        self.directory.join(format!("{:016x}.json", hash.finish()))
+    }
+}
+
+/// Key of a module's analysis. `imports` is how each module it imports
+/// resolved, and `keys` the keys of the loaded modules, by path.
+pub fn analysis_key(
+    name: &str,
+    path: &Path,
+    source: &str,
+    type_checking: Option<CheckMode>,
+    imports: &HashMap<String, Import>,
+    keys: &HashMap<PathBuf, u64>,
+) -> u64 {
+    let mut hash = Fingerprint::new();
+    hash.write_str(ANALYZER_FINGERPRINT);
+    hash.write_str(name);
+    hash.write_str(&path.to_string_lossy());
+    hash.write_str(source);
+    hash.write_u64(match type_checking {
+        None => 0,
+        Some(CheckMode::Lenient) => 1,
+        Some(CheckMode::Strict) => 2,
+    });
+
+    let mut imports: Vec<(&String, &Import)> = imports.iter().collect();
+    imports.sort_by_key(|(name, _)| *name);
+    for (name, import) in imports {
+        hash.write_str(name);
+        match import {
+            // A namespace package has no key; it defines only its path
+            Import::Loaded(interface) => match keys.get(&interface.path) {
+                Some(key) => {
+                    hash.write_u64(0);
+                    hash.write_u64(*key);
+                }
+                None => {
+                    hash.write_u64(1);
+                    hash.write_str(&interface.path.to_string_lossy());
+                }
+            },
+            Import::Opaque => hash.write_u64(2),
+            Import::Missing => hash.write_u64(3),
+            Import::Cycle(cycle) => {
+                hash.write_u64(4);
+                for module in cycle {
+                    hash.write_str(module);
+                }
+            }
+        }
+    }
+    hash.finish()
+}
+
+/// 64-bit FNV-1a hash, which unlike the standard library's hashers is the
+/// same for the same input across releases and platforms
+struct Fingerprint(u64);
+
+impl Fingerprint {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    /// Hash a string with its length, so that consecutive strings cannot
+    /// run into each other
+    fn write_str(&mut self, value: &str) {
+        self.write_u64(value.len() as u64);
+        self.write(value.as_bytes());
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mamba_error::ErrorCode;
+
+    fn temporary_directory(test: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("mamba-cache-{}-{}", test, std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        directory
+    }
+
+    #[test]
+    fn test_fingerprint_is_fnv_1a() {
+        let mut hash = Fingerprint::new();
+        hash.write(b"a");
+        assert_eq!(hash.finish(), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn test_entry_round_trip() {
+        let directory = temporary_directory("round-trip");
+        let cache = AnalysisCache::new(&directory);
+        let path = Path::new("main.mmb");
+        let analysis = CachedAnalysis {
+            key: 7,
+            symbols: HashMap::new(),
+            all: Some(vec!["f".to_string()]),
+            diagnostics: vec![Diagnostic::warning(ErrorCode::PossiblyUnbound, "'x' may be unbound")],
+        };
+        assert_eq!(cache.get(path, 7), None);
+        cache.put(path, &analysis).unwrap();
+        assert_eq!(cache.get(path, 7), Some(analysis));
+        assert_eq!(cache.get(path, 8), None);
+        assert_eq!(fs::read_to_string(directory.join(".gitignore")).unwrap(), "*\n");
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_cache_kept_in_program_root() {
+        let cache = AnalysisCache::for_file(Path::new("/project/src/main.mmb"));
+        assert_eq!(cache.directory(), Path::new("/project/src/.mamba_cache"));
+        assert_eq!(AnalysisCache::for_file(Path::new("main.mmb")).directory(), Path::new(".mamba_cache"));
+    }
+
+    #[test]
+    fn test_key_depends_on_inputs() {
+        let path = Path::new("main.mmb");
+        let imports = HashMap::from([("os".to_string(), Import::Opaque)]);
+        let key = |source: &str, mode: Option<CheckMode>, imports: &HashMap<String, Import>| {
+            analysis_key("main", path, source, mode, imports, &HashMap::new())
+        };
+        let base = key("x = 1\n", Some(CheckMode::Lenient), &imports);
+        assert_eq!(base, key("x = 1\n", Some(CheckMode::Lenient), &imports));
+        assert_ne!(base, key("x = 2\n", Some(CheckMode::Lenient), &imports));
+        assert_ne!(base, key("x = 1\n", Some(CheckMode::Strict), &imports));
+        let missing = HashMap::from([("os".to_string(), Import::Missing)]);
+        assert_ne!(base, key("x = 1\n", Some(CheckMode::Lenient), &missing));
+    }
+}
@@ -0,0 +1,1456 @@
+//! Gradual Type Checking
+//!
+//! The checker compares the types inferred for a module against its
+//! annotations and the operations applied to values:
+//!
+//! - arguments against the annotations of the parameters they bind to, with
+//!   type variables solved from the arguments and held to their bound or
+//!   constraints. Calls to builtins and to functions imported from other
+//!   modules and stubs are checked against their declared signatures.
+//! - returned values against the function's return annotation, and
+//!   annotated assignments against the annotation
+//! - attributes read from builtin values and instances of classes whose
+//!   members are all known
+//! - operators against the types of their operands
+//! - values that may be `None` used as if they were not
+//! - assignments to fields of frozen dataclasses and named tuples, and to
+//!   enum members
+//!
+//! Classes deriving `Protocol` are compared structurally: a class is
+//! compatible with a protocol when it has every member of it, with method
+//! signatures that accept the protocol's parameter types.
+//!
+//! Checking is gradual: an unannotated parameter, and the result of calling
+//! a function without a return annotation, are `Any`, which is compatible
+//! with everything. Strict mode additionally requires every function
+//! signature to be fully annotated.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+use crate::arena::{AstArena, ExprKind, NodeId, Parameter, StmtKind};
+use crate::ast::{BinaryOperator, ParameterKind, UnaryOperator};
+use crate::builtins::{self, BuiltinKind};
+use crate::classes::{self, ClassInfo, Synthesized};
+use crate::infer::{augmented_operator, binary_result, infer_types_gradually, unary_result, InferredTypes};
+use crate::semantic::{self, SemanticError};
+use crate::suggest;
+use crate::symbol_table::{Signature, SignatureParameter, SymbolTable};
+use crate::token::SourcePosition;
+use crate::types::{Type, TypeVariable};
+
+/// How strictly functions must be annotated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CheckMode {
+    /// Unannotated parameters and returns are `Any`
+    #[default]
+    Lenient,
+    /// Every parameter and return type must be annotated, except the
+    /// receiver of a method and the return type of `__init__`
+    Strict,
+}
+
+/// Type check a module that has been through semantic analysis
+pub fn check_types(arena: &AstArena, symbol_table: &SymbolTable, mode: CheckMode) -> Vec<SemanticError> {
+    let inferred = infer_types_gradually(arena, symbol_table);
+    let mut checker = TypeChecker::new(arena, symbol_table, &inferred, mode);
+    for id in arena.ids() {
+        checker.check_node(id);
+    }
+    checker.errors
+}
+
+/// Attributes the classes of the `enum` module give every enum class
+const ENUM_CLASS_MEMBERS: &[&str] = &[
+    "_generate_next_value_", "_ignore_", "_member_map_", "_member_names_", "_missing_", "_order_",
+    "_value2member_map_",
+];
+
+struct TypeChecker<'a> {
+    arena: &'a AstArena,
+    symbol_table: &'a SymbolTable,
+    types: &'a InferredTypes,
+    mode: CheckMode,
+    /// Classes defined in the module or imported into it, by name; `None`
+    /// when several classes share the name
+    classes: HashMap<&'a str, Option<&'a ClassInfo>>,
+    /// Function definitions by position, to find the methods of classes
+    definitions: HashMap<SourcePosition, NodeId>,
+    /// Declared types of annotated names, by every site that binds them,
+    /// including assignments through `global` and `nonlocal`
+    declared: HashMap<SourcePosition, (&'a str, &'a Type)>,
+    /// Classes and protocols whose compatibility is being checked
+    implementing: RefCell<HashSet<(String, String)>>,
+    errors: Vec<SemanticError>,
+}
+
+impl<'a> TypeChecker<'a> {
+    fn new(arena: &'a AstArena, symbol_table: &'a SymbolTable, types: &'a InferredTypes, mode: CheckMode) -> Self {
+        let mut classes: HashMap<&str, Option<&ClassInfo>> = HashMap::new();
+        let mut declared = HashMap::new();
+        for scope in symbol_table.scopes().values() {
+            for symbol in scope.symbols() {
+                if let Some(declared_type) = &symbol.declared_type {
+                    for &definition in &symbol.definitions {
+                        declared.insert(definition, (symbol.name.as_str(), &**declared_type));
+                    }
+                }
+                if let Some(info) = &symbol.class_info {
+                    classes
+                        .entry(symbol.name.as_str())
+                        .and_modify(|known| *known = None)
+                        .or_insert(Some(info));
+                }
+            }
+        }
+        let definitions = arena
+            .ids()
+            .filter(|&id| matches!(arena.stmt(id), Some(StmtKind::FunctionDef { .. })))
+            .filter_map(|id| Some((*arena.position(id)?, id)))
+            .collect();
+        Self {
+            arena,
+            symbol_table,
+            types,
+            mode,
+            classes,
+            definitions,
+            declared,
+            implementing: RefCell::new(HashSet::new()),
+            errors: Vec::new(),
+        }
+    }
+
+    fn check_node(&mut self, id: NodeId) {
+        let position = self.position(id);
+        if let Some(statement) = self.arena.stmt(id) {
+            match statement {
+                StmtKind::Return { value } => self.check_return(id, *value, position),
+                StmtKind::AnnAssignment { target, annotation, value: Some(value) } => {
+                    if let (Some(expected), Some(found)) = (self.annotation(*annotation), self.types.type_of(*value)) {
+                        if !self.is_assignable(found, &expected) {
+                            self.errors.push(SemanticError::AssignmentTypeMismatch {
+                                name: target.clone(),
+                                expected,
+                                found: found.clone(),
+                                position,
+                            });
+                        }
+                    }
+                }
+                StmtKind::Assignment { targets, value } => {
+                    for &target in targets {
+                        self.check_frozen(target, position);
+                        self.check_rebinding(target, *value);
+                    }
+                }
+                StmtKind::AugmentedAssignment { target, op, value } => {
+                    self.check_frozen(*target, position);
+                    let op = augmented_operator(*op);
+                    let symbol = format!("{}=", binary_symbol(op));
+                    self.check_binary(op, symbol, *target, *value, position);
+                }
+                StmtKind::For { iter, .. } => self.check_not_none(*iter, "iterate over it"),
+                StmtKind::FunctionDef { .. } if self.mode == CheckMode::Strict => self.check_signature(id),
+                _ => {}
+            }
+        } else if let Some(expression) = self.arena.expr(id) {
+            match expression {
+                ExprKind::BinaryOp { left, op, right } => {
+                    self.check_binary(*op, binary_symbol(*op).to_string(), *left, *right, position)
+                }
+                ExprKind::UnaryOp { op, operand } => self.check_unary(*op, *operand, position),
+                ExprKind::Attribute { object, attribute } if !self.is_store(id) && !self.is_receiver(*object) => {
+                    self.check_member(*object, attribute, position)
+                }
+                ExprKind::Call { function, arguments, keywords } => {
+                    self.check_not_none(*function, "call it");
+                    self.check_arguments(id, *function, arguments, keywords);
+                }
+                ExprKind::Subscript { object, .. } => self.check_not_none(*object, "index it"),
+                ExprKind::ListComp { generators, .. }
+                | ExprKind::SetComp { generators, .. }
+                | ExprKind::DictComp { generators, .. }
+                | ExprKind::GeneratorExpr { generators, .. } => {
+                    for generator in generators {
+                        self.check_not_none(generator.iter, "iterate over it");
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Check a value assigned to an annotated name, in its own scope or
+    /// through `global` or `nonlocal`, against the name's declared type
+    fn check_rebinding(&mut self, target: NodeId, value: NodeId) {
+        let Some(ExprKind::Identifier { .. }) = self.arena.expr(target) else {
+            return;
+        };
+        let position = self.position(target);
+        let (Some(&(name, expected)), Some(found)) = (self.declared.get(&position), self.types.type_of(value)) else {
+            return;
+        };
+        if !self.is_assignable(found, expected) {
+            self.errors.push(SemanticError::AssignmentTypeMismatch {
+                name: name.to_string(),
+                expected: expected.clone(),
+                found: found.clone(),
+                position,
+            });
+        }
+    }
+
+    /// Check a returned value against the enclosing function's annotation
+    fn check_return(&mut self, id: NodeId, value: Option<NodeId>, position: SourcePosition) {
+        let Some(function) = self.enclosing_function(id) else {
+            return;
+        };
+        let Some(StmtKind::FunctionDef { return_type: Some(annotation), .. }) = self.arena.stmt(function) else {
+            return;
+        };
+        let Some(expected) = self.annotation(*annotation) else {
+            return;
+        };
+        let (found, position) = match value {
+            Some(value) => match self.types.type_of(value) {
+                Some(found) => (found.clone(), self.position(value)),
+                None => return,
+            },
+            None => (Type::None, position),
+        };
+        if !self.is_assignable(&found, &expected) {
+            self.errors.push(SemanticError::ReturnTypeMismatch {
+                function: self.function_name(function),
+                expected,
+                found,
+                position,
+            });
+        }
+    }
+
+    /// Check the arguments of a call to a function defined in the module,
+    /// or declared by a builtin or imported signature, against the
+    /// annotations of the parameters they bind to
+    fn check_arguments(
+        &mut self,
+        id: NodeId,
+        function_expr: NodeId,
+        arguments: &[NodeId],
+        keywords: &[(Option<String>, NodeId)],
+    ) {
+        // The receiver of a bound method takes no argument, and the type
+        // arguments of the object it is called on fix the class's type
+        // parameters
+        let (function, parameters, receiver) = if let Some(callee) = self.types.callee(id) {
+            let Some(StmtKind::FunctionDef { parameters, .. }) = self.arena.stmt(callee.definition) else {
+                return;
+            };
+            let mut parameters: Vec<SignatureParameter> = parameters
+                .iter()
+                .map(|param| SignatureParameter {
+                    name: param.name.clone(),
+                    kind: param.kind.clone(),
+                    has_default: param.default.is_some(),
+                    annotation: param.type_annotation.and_then(|annotation| self.annotation(annotation)),
+                })
+                .collect();
+            if callee.bound && parameters.first().is_some_and(|param| is_positional(&param.kind)) {
+                parameters.remove(0);
+            }
+            // Calls to plain names are checked during semantic analysis
+            if matches!(self.arena.expr(function_expr), Some(ExprKind::Attribute { .. })) {
+                self.check_arity(id, callee.definition, &parameters, arguments, keywords);
+            }
+            let receiver = match self.arena.expr(function_expr) {
+                Some(ExprKind::Attribute { object, .. }) if callee.bound => self.types.type_of(*object),
+                _ => None,
+            };
+            (self.function_name(callee.definition), parameters, receiver)
+        } else if let Some(callee) = self.types.external_callee(id) {
+            let receiver = match self.arena.expr(function_expr) {
+                Some(ExprKind::Attribute { object, .. }) => self.types.type_of(*object),
+                _ => None,
+            };
+            (callee.name.clone(), callee.signature.parameters.clone(), receiver)
+        } else {
+            return;
+        };
+        let parameters: Vec<&SignatureParameter> = parameters.iter().collect();
+        let positional: Vec<&SignatureParameter> =
+            parameters.iter().copied().filter(|param| is_positional(&param.kind)).collect();
+        let var_args = parameters.iter().copied().find(|param| matches!(param.kind, ParameterKind::VarArgs));
+        let var_kwargs = parameters.iter().copied().find(|param| matches!(param.kind, ParameterKind::VarKwargs));
+
+        let mut bindings = Vec::new();
+        for (index, &argument) in arguments.iter().enumerate() {
+            // Positions after an unpacked argument are unknown
+            if matches!(self.arena.expr(argument), Some(ExprKind::Starred { .. })) {
+                break;
+            }
+            if let Some(parameter) = positional.get(index).copied().or(var_args) {
+                bindings.push((parameter, argument));
+            }
+        }
+        for (keyword, value) in keywords {
+            let Some(keyword) = keyword else {
+                continue;
+            };
+            let parameter = parameters
+                .iter()
+                .copied()
+                .find(|param| param.name == *keyword && matches!(param.kind, ParameterKind::Regular | ParameterKind::KwOnly))
+                .or(var_kwargs);
+            if let Some(parameter) = parameter {
+                bindings.push((parameter, *value));
+            }
+        }
+
+        let solutions = self.solve_type_vars(&function, receiver, &bindings, self.position(id));
+        for (parameter, argument) in bindings {
+            self.check_argument(&function, parameter, argument, &solutions);
+        }
+    }
+
+    /// Check the number and names of the arguments of a call to a method,
+    /// unless the call unpacks `*args` or `**kwargs`
+    fn check_arity(
+        &mut self,
+        id: NodeId,
+        definition: NodeId,
+        parameters: &[SignatureParameter],
+        arguments: &[NodeId],
+        keywords: &[(Option<String>, NodeId)],
+    ) {
+        let starred = |&argument: &NodeId| matches!(self.arena.expr(argument), Some(ExprKind::Starred { .. }));
+        let unpacks = arguments.iter().any(starred) || keywords.iter().any(|(keyword, _)| keyword.is_none());
+        if unpacks {
+            return;
+        }
+        let signature = Signature {
+            parameters: parameters.to_vec(),
+            returns: None,
+        };
+        let positions: Vec<SourcePosition> = arguments.iter().map(|&argument| self.position(argument)).collect();
+        let keywords: Vec<(String, SourcePosition)> =
+            keywords.iter().filter_map(|(keyword, value)| Some((keyword.clone()?, self.position(*value)))).collect();
+        let function = self.function_name(definition);
+        let problems = semantic::check_arguments(
+            &function,
+            self.position(id),
+            &positions,
+            &keywords,
+            &signature,
+            self.position(definition),
+        );
+        self.errors.extend(problems);
+    }
+
+    /// Solve the type variables in the parameter annotations of a call from
+    /// the arguments bound to them, reporting solutions outside a
+    /// variable's bound or constraints. Variables left unsolved or reported
+    /// are `Any` when the arguments are checked.
+    fn solve_type_vars(
+        &mut self,
+        function: &str,
+        receiver: Option<&Type>,
+        bindings: &[(&SignatureParameter, NodeId)],
+        position: SourcePosition,
+    ) -> HashMap<String, Type> {
+        let mut fixed = HashMap::new();
+        if let Some(Type::Instance { class, arguments }) = receiver {
+            if let Some(Some(info)) = self.classes.get(class.as_str()) {
+                for (index, param) in info.type_params.iter().enumerate() {
+                    fixed.insert(param.clone(), arguments.get(index).cloned().unwrap_or(Type::Any));
+                }
+            }
+        }
+
+        let mut variables: Vec<TypeVariable> = Vec::new();
+        let mut solved = HashMap::new();
+        for &(parameter, argument) in bindings {
+            let Some(expected) = &parameter.annotation else {
+                continue;
+            };
+            let expected = expected.substitute(&fixed);
+            for variable in expected.type_vars() {
+                if !variables.iter().any(|known| known.name == variable.name) {
+                    variables.push(variable.clone());
+                }
+            }
+            if let Some(found) = self.types.type_of(argument) {
+                expected.solve(found, &mut solved);
+            }
+        }
+
+        for variable in variables {
+            let admitted = match solved.get(&variable.name) {
+                Some(found) => match variable.admit(found, &|class, base| self.is_subclass(class, base)) {
+                    Some(admitted) => admitted,
+                    None => {
+                        self.errors.push(SemanticError::TypeVarMismatch {
+                            function: function.to_string(),
+                            variable: variable.clone(),
+                            found: found.clone(),
+                            position,
+                        });
+                        Type::Any
+                    }
+                },
+                None => Type::Any,
+            };
+            fixed.insert(variable.name, admitted);
+        }
+        fixed
+    }
+
+    fn check_argument(
+        &mut self,
+        function: &str,
+        parameter: &SignatureParameter,
+        argument: NodeId,
+        solutions: &HashMap<String, Type>,
+    ) {
+        let Some(expected) = &parameter.annotation else {
+            return;
+        };
+        let expected = expected.substitute(solutions);
+        let Some(found) = self.types.type_of(argument) else {
+            return;
+        };
+        if !self.is_assignable(found, &expected) {
+            self.errors.push(SemanticError::ArgumentTypeMismatch {
+                function: function.to_string(),
+                parameter: parameter.name.clone(),
+                expected,
+                found: found.clone(),
+                position: self.position(argument),
+            });
+        }
+    }
+
+    fn check_binary(&mut self, op: BinaryOperator, symbol: String, left: NodeId, right: NodeId, position: SourcePosition) {
+        let (Some(left), Some(right)) = (self.types.type_of(left), self.types.type_of(right)) else {
+            return;
+        };
+        if binary_result(op, left, right).is_some() || *left == Type::Never || *right == Type::Never {
+            return;
+        }
+        let (left_value, right_value) = (left.without_none(), right.without_none());
+        let error = if (left.is_optional() || right.is_optional())
+            && left_value != Type::Never
+            && right_value != Type::Never
+            && binary_result(op, &left_value, &right_value).is_some()
+        {
+            SemanticError::PossiblyNone {
+                operation: format!("use operator '{}'", symbol),
+                found: if left.is_optional() { left.clone() } else { right.clone() },
+                position,
+            }
+        } else {
+            SemanticError::UnsupportedOperand {
+                operator: symbol,
+                operands: vec![left.clone(), right.clone()],
+                position,
+            }
+        };
+        self.errors.push(error);
+    }
+
+    fn check_unary(&mut self, op: UnaryOperator, operand: NodeId, position: SourcePosition) {
+        let Some(operand) = self.types.type_of(operand) else {
+            return;
+        };
+        if unary_result(op, operand).is_some() || *operand == Type::Never {
+            return;
+        }
+        let value = operand.without_none();
+        let symbol = unary_symbol(op).to_string();
+        let error = if operand.is_optional() && value != Type::Never && unary_result(op, &value).is_some() {
+            SemanticError::PossiblyNone {
+                operation: format!("use operator '{}'", symbol),
+                found: operand.clone(),
+                position,
+            }
+        } else {
+            SemanticError::UnsupportedOperand {
+                operator: symbol,
+                operands: vec![operand.clone()],
+                position,
+            }
+        };
+        self.errors.push(error);
+    }
+
+    /// Check that every type an attribute is read from has it
+    fn check_member(&mut self, object: NodeId, attribute: &str, position: SourcePosition) {
+        if classes::is_dunder(attribute) {
+            return;
+        }
+        let Some(owner) = self.types.type_of(object) else {
+            return;
+        };
+        if owner.is_optional() && *owner != Type::None {
+            self.errors.push(SemanticError::PossiblyNone {
+                operation: format!("read attribute '{}'", attribute),
+                found: owner.clone(),
+                position,
+            });
+            return;
+        }
+        let owners = match owner {
+            Type::Union(members) => members.iter().collect(),
+            other => vec![other],
+        };
+        for owner in owners {
+            let Some(members) = self.members(owner) else {
+                continue;
+            };
+            if !members.contains(&attribute) {
+                self.errors.push(SemanticError::UnknownMember {
+                    owner: owner.clone(),
+                    attribute: attribute.to_string(),
+                    position,
+                    suggestion: suggest::best_match(attribute, members).map(str::to_string),
+                });
+                return;
+            }
+        }
+    }
+
+    /// Report a value that may be `None` used in a way `None` is not
+    fn check_not_none(&mut self, value: NodeId, operation: &str) {
+        let Some(found) = self.types.type_of(value) else {
+            return;
+        };
+        if found.is_optional() && *found != Type::None {
+            self.errors.push(SemanticError::PossiblyNone {
+                operation: operation.to_string(),
+                found: found.clone(),
+                position: self.position(value),
+            });
+        }
+    }
+
+    /// In strict mode, require annotations on every parameter and the
+    /// return type of a function
+    fn check_signature(&mut self, id: NodeId) {
+        let Some(StmtKind::FunctionDef { name, parameters, return_type, decorators, .. }) = self.arena.stmt(id) else {
+            return;
+        };
+        let function = self.function_name(id);
+        let is_static = decorators.iter().any(|&decorator| {
+            matches!(self.arena.expr(decorator), Some(ExprKind::Identifier { name }) if name == "staticmethod")
+        });
+        let has_receiver = self.is_method(id) && !is_static;
+        for (index, param) in parameters.iter().enumerate() {
+            if param.type_annotation.is_none() && !(index == 0 && has_receiver && is_positional(&param.kind)) {
+                self.errors.push(SemanticError::MissingAnnotation {
+                    function: function.clone(),
+                    parameter: Some(param.name.clone()),
+                    position: param.position,
+                });
+            }
+        }
+        if return_type.is_none() && name != "__init__" {
+            self.errors.push(SemanticError::MissingAnnotation {
+                function,
+                parameter: None,
+                position: self.position(id),
+            });
+        }
+    }
+
+    /// The attributes of instances of a type, or `None` when they are not
+    /// all known
+    fn members(&self, owner: &Type) -> Option<Vec<&'a str>> {
+        if let Type::Instance { class, arguments } = owner {
+            if let (Some(Type::Instance { class: object, .. }), "type") = (arguments.first(), class.as_str()) {
+                return self.class_members(object);
+            }
+        }
+        let class = owner.class_name()?;
+        match self.classes.get(class) {
+            Some(Some(info)) if !info.open => Some(info.members().collect()),
+            Some(_) => None,
+            None => builtins::instance_members(class).map(<[&str]>::to_vec),
+        }
+    }
+
+    /// The attributes of a class object: the members of its instances and
+    /// of `type`, or `None` when its metaclass or any ancestor may add
+    /// others. The members of an enum are known even though `Enum` is not
+    /// analyzed.
+    fn class_members(&self, class: &str) -> Option<Vec<&'a str>> {
+        let info = self.classes.get(class).copied().flatten()?;
+        let is_enum = matches!(self.synthesized(class), Some(Synthesized::Enum { .. }));
+        if info.open && !is_enum {
+            return None;
+        }
+        let mut members: Vec<&'a str> = info.members().chain(["mro"]).collect();
+        for ancestor in info.mro.as_ref()? {
+            match self.classes.get(ancestor.as_str()) {
+                Some(Some(ancestor)) if ancestor.metaclass.is_none() && (!ancestor.open || is_enum) => {}
+                Some(_) => return None,
+                None if ancestor == "object" => {}
+                None => {
+                    // `IntEnum` members are ints and `StrEnum` members strings
+                    let (enum_class, builtin) = match ancestor.rsplit('.').next() {
+                        Some("Enum" | "Flag" | "ReprEnum") => (true, None),
+                        Some("IntEnum" | "IntFlag") => (true, Some("int")),
+                        Some("StrEnum") => (true, Some("str")),
+                        _ => (false, Some(ancestor.as_str())),
+                    };
+                    if enum_class && !is_enum {
+                        return None;
+                    }
+                    if enum_class {
+                        members.extend(ENUM_CLASS_MEMBERS);
+                    }
+                    if let Some(builtin) = builtin {
+                        members.extend(builtins::instance_members(builtin)?);
+                    }
+                }
+            }
+        }
+        Some(members)
+    }
+
+    fn is_assignable(&self, value: &Type, target: &Type) -> bool {
+        value.is_assignable_to(target, &|class, base| self.is_subclass(class, base))
+    }
+
+    /// Whether `class` derives from `base`, or `None` when the hierarchy of
+    /// either is not fully known
+    fn is_subclass(&self, class: &str, base: &str) -> Option<bool> {
+        if class == base || base == "object" {
+            return Some(true);
+        }
+        let mro = self.mro(class)?;
+        if mro.iter().any(|ancestor| ancestor == base) {
+            return Some(true);
+        }
+        if let Some(Some(protocol)) = self.classes.get(base).filter(|info| info.is_some_and(|info| info.protocol)) {
+            return self.implements(class, protocol);
+        }
+        let known = mro.iter().all(|ancestor| self.mro(ancestor).is_some() || self.is_enum_base(class, ancestor))
+            && self.mro(base).is_some();
+        known.then_some(false)
+    }
+
+    /// Whether instances of `class` have every member of `protocol`, with
+    /// compatible signatures for the methods, or `None` when the members of
+    /// `class` are not all known
+    fn implements(&self, class: &str, protocol: &ClassInfo) -> Option<bool> {
+        // A protocol whose methods mention itself is assumed to hold while
+        // it is being checked
+        if !self.implementing.borrow_mut().insert((class.to_string(), protocol.name.clone())) {
+            return Some(true);
+        }
+        let result = self.has_protocol_members(class, protocol);
+        self.implementing.borrow_mut().remove(&(class.to_string(), protocol.name.clone()));
+        result
+    }
+
+    fn has_protocol_members(&self, class: &str, protocol: &ClassInfo) -> Option<bool> {
+        let members = self.members(&Type::instance(class))?;
+        let builtin = !self.classes.contains_key(class);
+        for member in protocol.members() {
+            if members.contains(&member) {
+                let methods = (self.method_definition(class, member), self.method_definition(&protocol.name, member));
+                if let (Some(method), Some(required)) = methods {
+                    if !self.compatible_method(method, required) {
+                        return Some(false);
+                    }
+                }
+                let attributes = (self.attribute_type(class, member), protocol.attribute_types.get(member));
+                if let (Some(attribute), Some(required)) = attributes {
+                    if !self.is_assignable(&attribute, required) {
+                        return Some(false);
+                    }
+                }
+            } else if !(builtin && classes::is_dunder(member)) {
+                // The dunders of builtin classes are not listed
+                return Some(false);
+            }
+        }
+        Some(true)
+    }
+
+    /// The definition of a method of a class defined in the module or one
+    /// of its bases
+    fn method_definition(&self, class: &str, method: &str) -> Option<NodeId> {
+        self.mro(class)?.iter().find_map(|ancestor| {
+            let info = self.classes.get(ancestor.as_str()).copied().flatten()?;
+            self.definitions.get(&info.method(method)?.position).copied()
+        })
+    }
+
+    /// The type of an attribute of the instances of a class defined in the
+    /// module: its annotation in the nearest class declaring it, or else
+    /// the types of the values assigned to it
+    fn attribute_type(&self, class: &str, attribute: &str) -> Option<Type> {
+        let mro = self.mro(class)?;
+        let annotated = mro.iter().find_map(|ancestor| {
+            let info = self.classes.get(ancestor.as_str()).copied().flatten()?;
+            info.attribute_types.get(attribute).cloned()
+        });
+        annotated.or_else(|| mro.iter().find_map(|ancestor| self.types.attributes.get(ancestor)?.get(attribute).cloned()))
+    }
+
+    /// Whether a method can be called the way a protocol's method is: it
+    /// takes the protocol's positional parameters, accepts their types and
+    /// returns a compatible type
+    fn compatible_method(&self, method: NodeId, required: NodeId) -> bool {
+        let (
+            Some(StmtKind::FunctionDef { parameters, return_type, .. }),
+            Some(StmtKind::FunctionDef { parameters: required_parameters, return_type: required_return, .. }),
+        ) = (self.arena.stmt(method), self.arena.stmt(required))
+        else {
+            return true;
+        };
+        let positional = |parameters: &'a [Parameter]| -> Vec<&'a Parameter> {
+            parameters.iter().filter(|param| is_positional(&param.kind)).skip(1).collect()
+        };
+        let (own, wanted) = (positional(parameters), positional(required_parameters));
+        let takes_var_args = parameters.iter().any(|param| matches!(param.kind, ParameterKind::VarArgs));
+        if own.len() < wanted.len() && !takes_var_args {
+            return false;
+        }
+        if own.iter().skip(wanted.len()).any(|param| param.default.is_none()) {
+            return false;
+        }
+        let annotation = |annotation: Option<NodeId>| annotation.and_then(|annotation| self.annotation(annotation));
+        // Parameters are contravariant and the return type covariant
+        let parameters_match = own.iter().zip(&wanted).all(|(own, wanted)| {
+            match (annotation(own.type_annotation), annotation(wanted.type_annotation)) {
+                (Some(own), Some(wanted)) => self.is_assignable(&wanted, &own),
+                _ => true,
+            }
+        });
+        let returns_match = match (annotation(*return_type), annotation(*required_return)) {
+            (Some(returns), Some(required)) => self.is_assignable(&returns, &required),
+            _ => true,
+        };
+        parameters_match && returns_match
+    }
+
+    /// Method resolution order of a class, if the class is known
+    fn mro(&self, class: &str) -> Option<Vec<String>> {
+        match self.classes.get(class) {
+            Some(Some(info)) => {
+                let mut mro = info.mro.clone()?;
+                // Members of an `IntEnum` or `StrEnum` are ints or strings
+                let builtin = mro.iter().enumerate().find_map(|(index, ancestor)| {
+                    let builtin = match ancestor.rsplit('.').next() {
+                        Some("IntEnum" | "IntFlag") => "int",
+                        Some("StrEnum") => "str",
+                        _ => return None,
+                    };
+                    self.is_enum_base(class, ancestor).then_some((index + 1, builtin))
+                });
+                if let Some((index, builtin)) = builtin {
+                    mro.insert(index, builtin.to_string());
+                }
+                Some(mro)
+            }
+            Some(None) => None,
+            None if class == "NoneType" => Some(vec![class.to_string(), "object".to_string()]),
+            None => builtins::lookup(class)
+                .filter(|builtin| matches!(builtin.kind, BuiltinKind::Class | BuiltinKind::Exception))
+                .map(|_| classes::builtin_mro(class)),
+        }
+    }
+
+    /// Whether `ancestor` is one of the `enum` module's classes an enum
+    /// derives from, rather than a class defined in the analyzed source
+    fn is_enum_base(&self, class: &str, ancestor: &str) -> bool {
+        !self.classes.contains_key(ancestor) && matches!(self.synthesized(class), Some(Synthesized::Enum { .. }))
+    }
+
+    /// The resolved type of an annotation node
+    fn annotation(&self, annotation: NodeId) -> Option<Type> {
+        self.symbol_table.annotation_type(self.arena.position(annotation)?).cloned()
+    }
+
+    fn position(&self, id: NodeId) -> SourcePosition {
+        self.arena.position(id).copied().unwrap_or_else(SourcePosition::start)
+    }
+
+    /// The innermost function definition containing a node
+    fn enclosing_function(&self, id: NodeId) -> Option<NodeId> {
+        let mut current = self.arena.parent(id);
+        while let Some(parent) = current {
+            if matches!(self.arena.stmt(parent), Some(StmtKind::FunctionDef { .. })) {
+                return Some(parent);
+            }
+            current = self.arena.parent(parent);
+        }
+        None
+    }
+
+    /// Whether a function definition is directly in a class body
+    fn is_method(&self, function: NodeId) -> bool {
+        let parent = self.arena.parent(function);
+        matches!(parent.and_then(|parent| self.arena.stmt(parent)), Some(StmtKind::ClassDef { .. }))
+    }
+
+    /// A function's name, qualified with its class for methods
+    fn function_name(&self, function: NodeId) -> String {
+        let Some(StmtKind::FunctionDef { name, .. }) = self.arena.stmt(function) else {
+            return String::new();
+        };
+        match self.arena.parent(function).and_then(|parent| self.arena.stmt(parent)) {
+            Some(StmtKind::ClassDef { name: class, .. }) => format!("{}.{}", class, name),
+            _ => name.clone(),
+        }
+    }
+
+    /// Check that an assignment target is not a field of a frozen dataclass
+    /// or named tuple instance, nor a member of an enum
+    fn check_frozen(&mut self, target: NodeId, position: SourcePosition) {
+        let Some(ExprKind::Attribute { object, attribute }) = self.arena.expr(target) else {
+            return;
+        };
+        let (class, on_class) = match self.types.type_of(*object) {
+            Some(Type::Instance { class, arguments }) if class == "type" => match arguments.first() {
+                Some(Type::Instance { class, .. }) => (class, true),
+                _ => return,
+            },
+            Some(Type::Instance { class, .. }) => (class, false),
+            _ => return,
+        };
+        let Some(synthesized) = self.synthesized(class) else {
+            return;
+        };
+        let kind = match synthesized {
+            Synthesized::Enum { members } if on_class && members.contains(attribute) => "enum",
+            Synthesized::Dataclass { frozen: true, .. } if !on_class => "frozen dataclass",
+            Synthesized::NamedTuple { .. } if !on_class => "named tuple",
+            _ => return,
+        };
+        if kind != "enum" && !synthesized.fields().iter().any(|field| field.name == *attribute) {
+            return;
+        }
+        self.errors.push(SemanticError::FrozenAssignment {
+            class: class.clone(),
+            attribute: attribute.clone(),
+            kind,
+            position,
+        });
+    }
+
+    /// What `@dataclass`, `NamedTuple` or `Enum` generates for a class or
+    /// its nearest base that has it
+    fn synthesized(&self, class: &str) -> Option<&'a Synthesized> {
+        let info = self.classes.get(class).copied().flatten()?;
+        let mro = info.mro.clone().unwrap_or_else(|| vec![class.to_string()]);
+        mro.iter().find_map(|ancestor| self.classes.get(ancestor.as_str()).copied().flatten()?.synthesized.as_ref())
+    }
+
+    /// Whether an expression is the receiver parameter (`self`, `cls`) of
+    /// the method it appears in; attributes read from it are checked during
+    /// semantic analysis
+    fn is_receiver(&self, object: NodeId) -> bool {
+        let Some(ExprKind::Identifier { name }) = self.arena.expr(object) else {
+            return false;
+        };
+        let Some(function) = self.enclosing_function(object) else {
+            return false;
+        };
+        let Some(StmtKind::FunctionDef { parameters, .. }) = self.arena.stmt(function) else {
+            return false;
+        };
+        self.is_method(function) && parameters.first().is_some_and(|param| param.name == *name)
+    }
+
+    /// Whether an expression is assigned or deleted rather than read
+    fn is_store(&self, id: NodeId) -> bool {
+        match self.arena.parent(id).and_then(|parent| self.arena.stmt(parent)) {
+            Some(StmtKind::Assignment { targets, .. }) | Some(StmtKind::Del { targets }) => targets.contains(&id),
+            _ => false,
+        }
+    }
+}
+
+fn is_positional(kind: &ParameterKind) -> bool {
+    matches!(kind, ParameterKind::PositionalOnly | ParameterKind::Regular)
+}
+
+/// How a binary operator is written
+pub fn binary_symbol(op: BinaryOperator) -> &'static str {
+    match op {
+        BinaryOperator::Add => "+",
+        BinaryOperator::Subtract => "-",
+        BinaryOperator::Multiply => "*",
+        BinaryOperator::Divide => "/",
+        BinaryOperator::FloorDivide => "//",
+        BinaryOperator::Modulo => "%",
+        BinaryOperator::Power => "**",
+        BinaryOperator::Equal => "==",
+        BinaryOperator::NotEqual => "!=",
+        BinaryOperator::LessThan => "<",
+        BinaryOperator::LessThanEq => "<=",
+        BinaryOperator::GreaterThan => ">",
+        BinaryOperator::GreaterThanEq => ">=",
+        BinaryOperator::And => "and",
+        BinaryOperator::Or => "or",
+        BinaryOperator::BitwiseAnd => "&",
+        BinaryOperator::BitwiseOr => "|",
+        BinaryOperator::BitwiseXor => "^",
+        BinaryOperator::LeftShift => "<<",
+        BinaryOperator::RightShift => ">>",
+        BinaryOperator::In => "in",
+        BinaryOperator::NotIn => "not in",
+        BinaryOperator::Is => "is",
+        BinaryOperator::IsNot => "is not",
+    }
+}
+
+/// How a unary operator is written
+pub fn unary_symbol(op: UnaryOperator) -> &'static str {
+    match op {
+        UnaryOperator::Minus => "-",
+        UnaryOperator::Plus => "+",
+        UnaryOperator::Not => "not",
+        UnaryOperator::BitwiseNot => "~",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::semantic::SemanticAnalyzer;
+
+    fn check(source: &str, mode: CheckMode) -> Vec<String> {
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let module = Parser::new(tokens).parse().unwrap();
+        let (_, problems) = SemanticAnalyzer::new().with_type_checking(mode).diagnose(&module);
+        problems.iter().map(SemanticError::message).collect()
+    }
+
+    fn lenient(source: &str) -> Vec<String> {
+        check(source, CheckMode::Lenient)
+    }
+
+    #[test]
+    fn test_builtin_arguments() {
+        let source = "\
+code = ord('a')
+letter = chr(str(code))
+print(letter, hex(code), sep=1)
+words = sorted(['b', 'a'], reverse=True)
+first: int = words[0]
+len(words)
+";
+        assert_eq!(
+            lenient(source),
+            vec![
+                "argument 'i' of 'chr' has type 'str', expected 'int'",
+                "argument 'sep' of 'print' has type 'int', expected 'str | None'",
+                "'first' is declared as 'int' but assigned a value of type 'str'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_argument_types() {
+        let source = "\
+def repeat(text: str, times: int = 1, *rest: str, **options: bool) -> str:
+    return text * times
+repeat('a', 2)
+repeat('a', 2.5)
+repeat(3)
+repeat('a', times='b')
+repeat('a', 1, 'b', 4)
+repeat('a', flag=1)
+repeat('a', True, flag=False)
+";
+        assert_eq!(
+            lenient(source),
+            vec![
+                "argument 'times' of 'repeat' has type 'float', expected 'int'",
+                "argument 'text' of 'repeat' has type 'int', expected 'str'",
+                "argument 'times' of 'repeat' has type 'str', expected 'int'",
+                "argument 'rest' of 'repeat' has type 'int', expected 'str'",
+                "argument 'options' of 'repeat' has type 'int', expected 'bool'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_methods_and_constructors() {
+        let source = "\
+class Account:
+    def __init__(self, owner: str):
+        self.owner = owner
+    def deposit(self, amount: float) -> None:
+        pass
+    @staticmethod
+    def parse(text: str) -> 'Account':
+        return Account(text)
+class Savings(Account):
+    pass
+a = Savings(42)
+a.deposit(10)
+a.deposit('10')
+Account.parse(1)
+Account.deposit(a, 'x')
+a.deposit()
+a.deposit(1, 2)
+a.deposit(amout=1)
+Account.parse('a', *[])
+";
+        assert_eq!(
+            lenient(source),
+            vec![
+                "argument 'owner' of 'Account.__init__' has type 'int', expected 'str'",
+                "argument 'amount' of 'Account.deposit' has type 'str', expected 'float'",
+                "argument 'text' of 'Account.parse' has type 'int', expected 'str'",
+                "argument 'amount' of 'Account.deposit' has type 'str', expected 'float'",
+                "'Account.deposit' is missing 1 required positional argument: 'amount'",
+                "'Account.deposit' takes 1 positional argument but 2 were given",
+                "'Account.deposit' got an unexpected keyword argument 'amout'",
+                "'Account.deposit' is missing 1 required positional argument: 'amount'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_return_and_assignment_types() {
+        let source = "\
+def count(items: list[str]) -> int:
+    if items:
+        return str(len(items))
+    return
+def ratio(a: int, b: int) -> float:
+    return a / b
+def maybe(flag: bool) -> int | None:
+    if flag:
+        return None
+    return 1
+limit: int = 'ten'
+scale: float = 2
+names: list[str] = []
+";
+        assert_eq!(
+            lenient(source),
+            vec![
+                "'count' returns a value of type 'str', expected 'int'",
+                "'count' returns a value of type 'None', expected 'int'",
+                "'limit' is declared as 'int' but assigned a value of type 'str'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rebinding_declared_names() {
+        let source = "\
+count: int = 0
+count = 1
+count = 'one'
+def reset():
+    global count
+    count = None
+def outer(limit: float):
+    total: int = 0
+    def add():
+        nonlocal total
+        total = 2.5
+    limit = 1
+    return add
+";
+        assert_eq!(
+            lenient(source),
+            vec![
+                "'count' is declared as 'int' but assigned a value of type 'str'",
+                "'count' is declared as 'int' but assigned a value of type 'None'",
+                "'total' is declared as 'int' but assigned a value of type 'float'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_members() {
+        let source = "\
+class Node:
+    def __init__(self):
+        self.children = []
+class Dynamic:
+    def __getattr__(self, name):
+        return name
+n = Node()
+n.children.append(n)
+n.parent
+'abc'.uppercase()
+[1].push(2)
+Dynamic().anything
+(1).__class__
+class Shape:
+    sides = 0
+    def area(self):
+        return 0
+class Square(Shape):
+    sides = 4
+class Meta(type):
+    pass
+class Configured(metaclass=Meta):
+    pass
+Square.sides
+Square.area
+Square.mro()
+Square.corners
+Node.children
+Dynamic.anything
+Configured.anything
+";
+        assert_eq!(
+            lenient(source),
+            vec![
+                "'Node' object has no attribute 'parent'",
+                "'str' object has no attribute 'uppercase'",
+                "'list[int]' object has no attribute 'push'",
+                "class 'Square' has no attribute 'corners'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_operators() {
+        let source = "\
+a = 'n: ' + 3
+b = 2 * 'ab'
+c = -'x'
+d = [1] + (2,)
+e = 1
+e += 'x'
+f = 1.5 << 2
+";
+        assert_eq!(
+            lenient(source),
+            vec![
+                "unsupported operand types for +: 'str' and 'int'",
+                "bad operand type for unary -: 'str'",
+                "unsupported operand types for +: 'list[int]' and 'tuple[int]'",
+                "unsupported operand types for +=: 'int' and 'str'",
+                "unsupported operand types for <<: 'float' and 'int'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_optional_values() {
+        let source = "\
+from typing import Optional
+def find(key: str) -> Optional[str]:
+    return None
+def size(value: str) -> int:
+    return len(value)
+name = find('a')
+name.upper()
+name + '!'
+size(name)
+for c in name:
+    pass
+callback: Optional[int] = None
+callback()
+name is None
+";
+        let found = lenient(source);
+        assert_eq!(
+            found,
+            vec![
+                "cannot read attribute 'upper': value of type 'str | None' may be None",
+                "cannot use operator '+': value of type 'str | None' may be None",
+                "argument 'value' of 'size' has type 'str | None', expected 'str'",
+                "cannot iterate over it: value of type 'str | None' may be None",
+                "cannot call it: value of type 'int | None' may be None",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_narrowed_values_are_not_none() {
+        let source = "\
+from typing import Optional
+def find(key: str) -> Optional[str]:
+    return None
+def shout(key: str) -> str:
+    name = find(key)
+    if name is None:
+        return ''
+    return name.upper()
+def greet(key: str) -> str:
+    name = find(key)
+    assert name
+    return name + '!'
+def first(key: str) -> str:
+    name = find(key)
+    return name.upper() if name is not None else ''
+def both(key: str) -> bool:
+    name = find(key)
+    return name is not None and name.startswith('a')
+def count(key: str) -> int:
+    name = find(key)
+    if isinstance(name, str):
+        return name.count('a')
+    return 0
+class Entry:
+    def __init__(self, key: str | None):
+        self.key = key
+    def size(self) -> int:
+        if self.key is not None:
+            return len(self.key.strip())
+        return 0
+    def name(self) -> str:
+        if self.key is not None:
+            return self.key
+        return ''
+";
+        assert!(lenient(source).is_empty());
+    }
+
+    #[test]
+    fn test_generic_arguments() {
+        let source = "\
+from typing import Generic, TypeVar
+T = TypeVar('T')
+N = TypeVar('N', bound=float)
+S = TypeVar('S', str, bytes)
+def halve(value: N) -> N:
+    return value
+def join(left: S, right: S) -> S:
+    return left
+def append(items: list[T], item: T) -> None:
+    pass
+class Box(Generic[T]):
+    def __init__(self, item: T):
+        self.item = item
+    def put(self, item: T) -> None:
+        self.item = item
+def limit[L: int](value: L) -> L:
+    return value
+halve(3)
+halve('ten')
+join('a', 'b')
+join(1, 2)
+append([1, 2], 3)
+box = Box(1)
+box.put(2)
+box.put('two')
+limit(True)
+limit(1.5)
+";
+        assert_eq!(
+            lenient(source),
+            vec![
+                "type variable 'N' of 'halve' cannot be 'str': it must be a subtype of 'float'",
+                "type variable 'S' of 'join' cannot be 'int': it must be one of 'str', 'bytes'",
+                "argument 'item' of 'Box.put' has type 'str', expected 'int'",
+                "type variable 'L' of 'limit' cannot be 'float': it must be a subtype of 'int'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_protocols() {
+        let source = "\
+from typing import Protocol
+class Shape(Protocol):
+    def area(self) -> float:
+        ...
+class Square:
+    def __init__(self, side: float):
+        self.side = side
+    def area(self) -> float:
+        return self.side * self.side
+class Label:
+    def area(self) -> str:
+        return 'none'
+class Point:
+    pass
+class Sized(Protocol):
+    def __len__(self) -> int:
+        ...
+class Named(Protocol):
+    name: str
+class User:
+    def __init__(self, name: str):
+        self.name = name
+class Item:
+    def __init__(self):
+        self.name = 1
+class Tagged:
+    name: str = 'tag'
+def total(shape: Shape) -> float:
+    return shape.area()
+def size(value: Sized) -> int:
+    return 0
+def greet(named: Named) -> str:
+    return named.name
+total(Square(2.0))
+total(Label())
+total(Point())
+size([1, 2])
+size(Point())
+greet(User('a'))
+greet(Item())
+greet(Tagged())
+";
+        assert_eq!(
+            lenient(source),
+            vec![
+                "argument 'shape' of 'total' has type 'Label', expected 'Shape'",
+                "argument 'shape' of 'total' has type 'Point', expected 'Shape'",
+                "argument 'value' of 'size' has type 'Point', expected 'Sized'",
+                "argument 'named' of 'greet' has type 'Item', expected 'Named'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dataclasses_and_named_tuples() {
+        let source = "\
+from dataclasses import dataclass
+from typing import NamedTuple
+@dataclass(frozen=True)
+class Point:
+    x: int
+    y: int = 0
+@dataclass
+class Labeled(Point):
+    label: str = ''
+class Pair(NamedTuple):
+    left: int
+    right: str
+p = Point('a')
+p.x = 2
+p.y += 1
+q = Labeled(1, label=2)
+q.label = 'b'
+pair = Pair(1, 'a')
+pair.right = 'b'
+";
+        assert_eq!(
+            lenient(source),
+            vec![
+                "argument 'x' of 'Point.__init__' has type 'str', expected 'int'",
+                "cannot assign field 'x' of frozen dataclass 'Point'",
+                "cannot assign field 'y' of frozen dataclass 'Point'",
+                "argument 'label' of 'Labeled.__init__' has type 'int', expected 'str'",
+                "cannot assign field 'right' of named tuple 'Pair'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_enums() {
+        let source = "\
+from enum import Enum, IntEnum
+class Color(Enum):
+    RED = 1
+    GREEN = 2
+class Level(IntEnum):
+    LOW = 1
+def paint(color: Color) -> str:
+    return color.name
+paint(Color.RED)
+paint(Level.LOW)
+low: int = Level.LOW
+green: int = Color.GREEN
+Color.RED = Color.GREEN
+for color in Color:
+    paint(color)
+paint(Color.BLUE)
+Color._member_names_
+Level.LOW.bit_length()
+Level.bit_length
+Level.HIGH
+";
+        assert_eq!(
+            lenient(source),
+            vec![
+                "argument 'color' of 'paint' has type 'Level', expected 'Color'",
+                "'green' is declared as 'int' but assigned a value of type 'Color'",
+                "cannot assign member 'RED' of enum 'Color'",
+                "class 'Color' has no attribute 'BLUE'",
+                "class 'Level' has no attribute 'HIGH'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_properties() {
+        let source = "\
+class Account:
+    def __init__(self, balance: int):
+        self._balance = balance
+    @property
+    def balance(self) -> int:
+        return self._balance
+    @balance.setter
+    def balance(self, value: int) -> None:
+        self._balance = value
+    def doubled(self) -> int:
+        return self.balance + self.balance
+a = Account(1)
+twice = a.balance * 2
+x: int = a.balance
+name: str = a.balance
+";
+        assert_eq!(lenient(source), vec!["'name' is declared as 'str' but assigned a value of type 'int'"]);
+    }
+
+    #[test]
+    fn test_unannotated_values_are_any() {
+        let source = "\
+def name():
+    return 'x'
+def twice(value: int) -> int:
+    return value * 2
+def anything(value):
+    return value.whatever + 1
+twice(name())
+";
+        assert!(lenient(source).is_empty());
+    }
+
+    #[test]
+    fn test_strict_mode_requires_annotations() {
+        let source = "\
+def add(a, b: int):
+    return a + b
+class Box:
+    def __init__(self, item: int):
+        self.item = item
+    def get(self) -> int:
+        return self.item
+    @classmethod
+    def empty(cls) -> 'Box':
+        return cls(0)
+    @staticmethod
+    def wrap(item) -> 'Box':
+        return Box(item)
+f = lambda x: x
+";
+        assert_eq!(
+            check(source, CheckMode::Strict),
+            vec![
+                "parameter 'a' of 'add' has no type annotation",
+                "'add' has no return type annotation",
+                "parameter 'item' of 'Box.wrap' has no type annotation",
+            ]
+        );
+        assert!(lenient(source).is_empty());
+    }
+
+    #[test]
+    fn test_type_checking_is_opt_in() {
+        let tokens = Lexer::new("x: int = 'a'\n").tokenize().unwrap();
+        let module = Parser::new(tokens).parse().unwrap();
+        let (_, problems) = SemanticAnalyzer::new().diagnose(&module);
+        assert!(problems.is_empty());
+    }
+}
@@ -0,0 +1,758 @@
+//! Class member model
+//!
+//! A [`ClassInfo`] summarizes a class definition for semantic analysis: the
+//! attributes bound in the class body, the attributes methods assign on
+//! `self`, each method's kind and signature, the metaclass, and the method
+//! resolution order computed by C3 linearization of the bases. Members of
+//! base classes are folded in, so a lookup answers for the whole hierarchy.
+//!
+//! Classes the standard library generates members for (`@dataclass`,
+//! `NamedTuple` and `Enum` subclasses) carry a [`Synthesized`] description,
+//! and the methods it generates are listed with the methods of the body.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::ast::{Expression, ParameterKind, Statement};
+use crate::builtins::{self, BuiltinKind};
+use crate::symbol_table::{Signature, SignatureParameter};
+use crate::token::SourcePosition;
+use crate::types::Type;
+use serde::{Deserialize, Serialize};
+
+/// How a method is bound when looked up on an instance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MethodKind {
+    /// Receives the instance (`self`)
+    Instance,
+    /// `@classmethod`, or `__init_subclass__` / `__class_getitem__`: receives the class (`cls`)
+    Class,
+    /// `@staticmethod`, or `__new__`: receives no implicit argument
+    Static,
+    /// `@property` and its `.setter` / `.deleter`
+    Property,
+}
+
+/// A method defined in a class body
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MethodInfo {
+    pub name: String,
+    pub kind: MethodKind,
+    pub signature: Signature,
+    pub position: SourcePosition,
+    /// Whether every decorator is one whose effect is known (`property`,
+    /// `classmethod`, `abstractmethod`, ...)
+    pub known_decorators: bool,
+}
+
+impl MethodInfo {
+    /// Name of the parameter that receives the instance or class, if the
+    /// method has one
+    pub fn receiver(&self) -> Option<&str> {
+        match self.kind {
+            MethodKind::Static => None,
+            _ => self
+                .signature
+                .parameters
+                .first()
+                .filter(|param| matches!(param.kind, ParameterKind::PositionalOnly | ParameterKind::Regular))
+                .map(|param| param.name.as_str()),
+        }
+    }
+}
+
+/// Names exported by the modules whose decorators and base classes generate
+/// class members, by module
+pub const SYNTHESIZING_MODULES: &[(&str, &[&str])] = &[
+    ("dataclasses", &["dataclass", "field", "KW_ONLY"]),
+    ("enum", &["Enum", "IntEnum", "StrEnum", "Flag", "IntFlag", "auto"]),
+];
+
+/// The base classes whose subclasses are enums, by qualified name
+pub const ENUM_BASES: &[&str] = &["enum.Enum", "enum.IntEnum", "enum.StrEnum", "enum.Flag", "enum.IntFlag"];
+
+/// Members the standard library generates for a class from its body
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Synthesized {
+    /// A `@dataclass`, whose `__init__` takes its fields in order
+    Dataclass {
+        fields: Vec<Field>,
+        /// Whether `__init__` is generated (`init=False` turns it off)
+        init: bool,
+        /// Whether `__eq__` is generated (`eq=False` turns it off)
+        eq: bool,
+        /// `order=True`: instances compare with `<`, `<=`, `>` and `>=`
+        order: bool,
+        /// `frozen=True`: fields cannot be assigned once initialized
+        frozen: bool,
+    },
+    /// A subclass of `typing.NamedTuple`: an immutable tuple of its fields
+    NamedTuple { fields: Vec<Field> },
+    /// A subclass of `enum.Enum`: its members are the names assigned in its
+    /// body, in order
+    Enum { members: Vec<String> },
+}
+
+impl Synthesized {
+    /// The fields of a dataclass or named tuple, in `__init__` order of
+    /// definition; an enum has none
+    pub fn fields(&self) -> &[Field] {
+        match self {
+            Synthesized::Dataclass { fields, .. } | Synthesized::NamedTuple { fields } => fields,
+            Synthesized::Enum { .. } => &[],
+        }
+    }
+
+    /// Whether the fields of instances cannot be assigned
+    pub fn is_frozen(&self) -> bool {
+        match self {
+            Synthesized::Dataclass { frozen, .. } => *frozen,
+            Synthesized::NamedTuple { .. } => true,
+            Synthesized::Enum { .. } => false,
+        }
+    }
+}
+
+/// A field of a dataclass or named tuple: a name annotated in the class body
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Field {
+    pub name: String,
+    pub position: SourcePosition,
+    /// Whether the field has a default, so `__init__` may omit it
+    pub has_default: bool,
+    /// Whether `__init__` takes the field (`field(init=False)` leaves it out)
+    pub init: bool,
+    /// Whether `__init__` takes the field by keyword only
+    pub kw_only: bool,
+}
+
+/// What is known about one base class of a class being defined
+#[derive(Debug, Clone, Copy)]
+pub enum Base<'a> {
+    /// A class defined in the analyzed source
+    Class(&'a ClassInfo),
+    /// A builtin class such as `object` or `Exception`
+    Builtin(&'static str),
+    /// Anything else: an imported or computed base whose members are unknown
+    Unknown(&'a str),
+}
+
+/// Summary of a class definition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClassInfo {
+    pub name: String,
+    pub position: SourcePosition,
+    /// Base class names as written (dotted for attribute bases)
+    pub bases: Vec<String>,
+    /// Metaclass name as written, if given
+    pub metaclass: Option<String>,
+    /// Names bound in the class body, with their first binding
+    pub class_attributes: BTreeMap<String, SourcePosition>,
+    /// Attributes assigned on `self` in methods, with their first assignment
+    pub instance_attributes: BTreeMap<String, SourcePosition>,
+    /// Methods in definition order
+    pub methods: Vec<MethodInfo>,
+    /// Method resolution order starting with the class itself, or `None` if
+    /// the bases admit no consistent linearization
+    pub mro: Option<Vec<String>>,
+    /// Every member known on instances, including inherited ones
+    members: BTreeSet<String>,
+    /// Whether instances may have members that cannot be known statically:
+    /// a base with unknown members, `__getattr__`, `__slots__` computed at
+    /// runtime, or `setattr`/`__dict__` use
+    pub open: bool,
+    /// Names of the type parameters of a generic class, in the order its
+    /// type arguments are given (`class Box[T]`, `class Box(Generic[T])`)
+    pub type_params: Vec<String>,
+    /// Whether the class derives from `Protocol`, so that any class with
+    /// its members is compatible with it
+    pub protocol: bool,
+    /// Types of the names annotated in the class body, once annotations
+    /// are resolved. Method signatures then carry their annotations too.
+    pub attribute_types: BTreeMap<String, Type>,
+    /// Members generated by `@dataclass`, `NamedTuple` or `Enum`
+    pub synthesized: Option<Synthesized>,
+}
+
+impl ClassInfo {
+    /// Summarize a class definition, given what is known about its bases
+    pub fn build(
+        name: &str,
+        position: SourcePosition,
+        bases: &[Base],
+        metaclass: Option<String>,
+        body: &[Statement],
+    ) -> Self {
+        let mut info = ClassInfo {
+            name: name.to_string(),
+            position,
+            bases: bases.iter().map(Base::name).collect(),
+            metaclass,
+            class_attributes: BTreeMap::new(),
+            instance_attributes: BTreeMap::new(),
+            methods: Vec::new(),
+            mro: None,
+            members: BTreeSet::new(),
+            open: false,
+            type_params: Vec::new(),
+            protocol: false,
+            attribute_types: BTreeMap::new(),
+            synthesized: None,
+        };
+        info.collect_body(body);
+        info.mro = linearize(name, bases);
+
+        for base in bases {
+            match base {
+                Base::Class(base) => {
+                    info.members.extend(base.members.iter().cloned());
+                    info.open |= base.open;
+                }
+                Base::Builtin(base) => match builtin_members(base) {
+                    Some(members) => info.members.extend(members.iter().map(|member| member.to_string())),
+                    None => info.open = true,
+                },
+                Base::Unknown(_) => info.open = true,
+            }
+        }
+        info.members.extend(info.class_attributes.keys().cloned());
+        info.members.extend(info.instance_attributes.keys().cloned());
+        info.open |= info.members.contains("__getattr__") || info.members.contains("__getattribute__");
+        info
+    }
+
+    /// Whether `name` is a known member of instances of this class
+    pub fn has_member(&self, name: &str) -> bool {
+        self.members.contains(name)
+    }
+
+    /// Every known member of instances, including inherited ones, sorted
+    pub fn members(&self) -> impl Iterator<Item = &str> {
+        self.members.iter().map(String::as_str)
+    }
+
+    /// Look up a method defined directly in this class
+    pub fn method(&self, name: &str) -> Option<&MethodInfo> {
+        self.methods.iter().rev().find(|method| method.name == name)
+    }
+
+    /// Add the methods and members `synthesized` generates. Methods the
+    /// class body defines itself are kept.
+    pub fn synthesize(&mut self, synthesized: Synthesized) {
+        let this = Type::instance(&self.name);
+        let other = || vec![parameter("other", ParameterKind::Regular, false)];
+        let mut methods = Vec::new();
+        let mut members: Vec<&str> = Vec::new();
+        match &synthesized {
+            Synthesized::Dataclass { fields, init, eq, order, frozen } => {
+                if *init {
+                    methods.push(("__init__", MethodKind::Instance, constructor(fields), Type::None));
+                }
+                if *eq {
+                    methods.push(("__eq__", MethodKind::Instance, other(), Type::Bool));
+                }
+                if *order {
+                    for name in ["__lt__", "__le__", "__gt__", "__ge__"] {
+                        methods.push((name, MethodKind::Instance, other(), Type::Bool));
+                    }
+                }
+                if *eq && *frozen {
+                    methods.push(("__hash__", MethodKind::Instance, Vec::new(), Type::Int));
+                }
+                methods.push(("__repr__", MethodKind::Instance, Vec::new(), Type::Str));
+                members.extend(["__dataclass_fields__", "__match_args__"]);
+            }
+            Synthesized::NamedTuple { fields } => {
+                // Named tuples are built by `__new__`, which takes the
+                // parameters `__init__` would
+                methods.push(("__init__", MethodKind::Instance, constructor(fields), Type::None));
+                let dict = Type::Dict(Box::new(Type::Str), Box::new(Type::Any));
+                methods.push(("_asdict", MethodKind::Instance, Vec::new(), dict));
+                let changes = vec![parameter("kwargs", ParameterKind::VarKwargs, false)];
+                methods.push(("_replace", MethodKind::Instance, changes, this.clone()));
+                let iterable = vec![parameter("iterable", ParameterKind::Regular, false)];
+                methods.push(("_make", MethodKind::Class, iterable, this.clone()));
+                methods.push(("__repr__", MethodKind::Instance, Vec::new(), Type::Str));
+                members.extend(["_fields", "_field_defaults", "__match_args__"]);
+            }
+            Synthesized::Enum { .. } => members.extend(["name", "value"]),
+        }
+        for (name, kind, parameters, returns) in methods {
+            if self.class_attributes.contains_key(name) {
+                continue;
+            }
+            let receiver = if kind == MethodKind::Class { "cls" } else { "self" };
+            let mut signature = Signature {
+                parameters: vec![parameter(receiver, ParameterKind::Regular, false)],
+                returns: Some(returns),
+            };
+            signature.parameters.extend(parameters);
+            self.members.insert(name.to_string());
+            self.methods.push(MethodInfo {
+                name: name.to_string(),
+                kind,
+                signature,
+                position: self.position,
+                known_decorators: true,
+            });
+        }
+        self.members.extend(members.into_iter().map(str::to_string));
+        self.synthesized = Some(synthesized);
+    }
+
+    /// Whether `method` was generated rather than defined in the class body
+    pub fn is_synthesized(&self, method: &MethodInfo) -> bool {
+        self.synthesized.is_some() && method.position == self.position
+    }
+
+    fn collect_body(&mut self, body: &[Statement]) {
+        for (name, position) in class_body_bindings(body) {
+            self.class_attributes.entry(name).or_insert(position);
+        }
+        for statement in body {
+            match statement {
+                Statement::FunctionDef { name, parameters, body, decorators, position, .. } => {
+                    let method = MethodInfo {
+                        name: name.clone(),
+                        kind: method_kind(name, decorators),
+                        signature: Signature::from_parameters(parameters),
+                        position: *position,
+                        known_decorators: decorators.iter().all(is_known_decorator),
+                    };
+                    if let Some(receiver) = method.receiver().filter(|_| method.kind != MethodKind::Class) {
+                        let receiver = receiver.to_string();
+                        self.collect_receiver_attributes(&receiver, body);
+                    }
+                    self.methods.push(method);
+                }
+                Statement::Assignment { targets, value, .. } if is_name(targets, "__slots__") => {
+                    match string_elements(value) {
+                        Some(slots) => self.instance_attributes.extend(slots),
+                        None => self.open = true,
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Record `receiver.attr = ...` assignments in a method body, and mark
+    /// the class open if the body uses `setattr` or `__dict__`
+    fn collect_receiver_attributes(&mut self, receiver: &str, body: &[Statement]) {
+        for statement in body {
+            match statement {
+                Statement::Assignment { targets, value, .. } => {
+                    for target in targets {
+                        self.collect_target(receiver, target);
+                    }
+                    self.open |= uses_dynamic_attributes(value);
+                }
+                Statement::Expression(expression) => self.open |= uses_dynamic_attributes(expression),
+                Statement::If { then_block, elif_blocks, else_block, .. } => {
+                    self.collect_receiver_attributes(receiver, then_block);
+                    for (_, block) in elif_blocks {
+                        self.collect_receiver_attributes(receiver, block);
+                    }
+                    if let Some(block) = else_block {
+                        self.collect_receiver_attributes(receiver, block);
+                    }
+                }
+                Statement::While { body, else_block, .. } | Statement::For { body, else_block, .. } => {
+                    self.collect_receiver_attributes(receiver, body);
+                    if let Some(block) = else_block {
+                        self.collect_receiver_attributes(receiver, block);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn collect_target(&mut self, receiver: &str, target: &Expression) {
+        match target {
+            Expression::Attribute { object, attribute, position } => {
+                if matches!(object.as_ref(), Expression::Identifier { name, .. } if name == receiver) {
+                    self.instance_attributes.entry(attribute.clone()).or_insert(*position);
+                }
+            }
+            Expression::Tuple { elements, .. } | Expression::List { elements, .. } => {
+                for element in elements {
+                    self.collect_target(receiver, element);
+                }
+            }
+            Expression::Starred { value, .. } | Expression::Parenthesized { expr: value, .. } => {
+                self.collect_target(receiver, value)
+            }
+            other => self.open |= uses_dynamic_attributes(other),
+        }
+    }
+}
+
+impl Base<'_> {
+    /// The base's name as written
+    pub fn name(&self) -> String {
+        match self {
+            Base::Class(info) => info.name.clone(),
+            Base::Builtin(name) => name.to_string(),
+            Base::Unknown(name) => name.to_string(),
+        }
+    }
+
+    /// The base's own method resolution order
+    fn mro(&self) -> Vec<String> {
+        match self {
+            Base::Class(info) => info.mro.clone().unwrap_or_else(|| vec![info.name.clone(), "object".to_string()]),
+            Base::Builtin(name) => builtin_mro(name),
+            Base::Unknown(name) => vec![name.to_string(), "object".to_string()],
+        }
+    }
+}
+
+/// Compute the C3 linearization of a class with the given bases
+///
+/// Returns `None` when no order keeps every base before its own bases and
+/// the bases in the order they are listed (Python raises `TypeError` for such
+/// a class). A class without bases derives from `object`.
+pub fn linearize(name: &str, bases: &[Base]) -> Option<Vec<String>> {
+    if bases.is_empty() {
+        return Some(if name == "object" {
+            vec![name.to_string()]
+        } else {
+            vec![name.to_string(), "object".to_string()]
+        });
+    }
+    let mut sequences: Vec<Vec<String>> = bases.iter().map(Base::mro).collect();
+    sequences.push(bases.iter().map(Base::name).collect());
+    merge(name, sequences)
+}
+
+/// The merge step of C3: repeatedly take the first head that appears in no
+/// sequence's tail
+fn merge(name: &str, mut sequences: Vec<Vec<String>>) -> Option<Vec<String>> {
+    let mut result = vec![name.to_string()];
+    loop {
+        sequences.retain(|sequence| !sequence.is_empty());
+        if sequences.is_empty() {
+            return Some(result);
+        }
+        let head = sequences
+            .iter()
+            .map(|sequence| &sequence[0])
+            .find(|candidate| sequences.iter().all(|sequence| !sequence[1..].contains(candidate)))?
+            .clone();
+        for sequence in &mut sequences {
+            if sequence[0] == head {
+                sequence.remove(0);
+            }
+        }
+        result.push(head);
+    }
+}
+
+/// Method resolution order of a builtin class
+pub fn builtin_mro(name: &str) -> Vec<String> {
+    match builtins::lookup(name) {
+        Some(builtin) if builtin.kind == BuiltinKind::Exception => {
+            let bases: Vec<Base> = builtin
+                .bases
+                .iter()
+                .filter(|base| **base != "object")
+                .map(|base| Base::Builtin(base))
+                .collect();
+            linearize(name, &bases).unwrap_or_else(|| vec![name.to_string(), "object".to_string()])
+        }
+        _ if name == "bool" => vec!["bool".to_string(), "int".to_string(), "object".to_string()],
+        _ => linearize(name, &[]).unwrap_or_default(),
+    }
+}
+
+/// Members of a builtin base class that are not dunders, or `None` when the
+/// class has members this model does not list
+fn builtin_members(name: &str) -> Option<&'static [&'static str]> {
+    match name {
+        "object" => Some(&[]),
+        "BaseException" | "Exception" => Some(&["args", "with_traceback", "add_note"]),
+        "tuple" => Some(&["count", "index"]),
+        _ => None,
+    }
+}
+
+/// The parameters of a generated `__init__`: the fields it takes, then the
+/// keyword-only ones. Their annotations are filled in once resolved.
+fn constructor(fields: &[Field]) -> Vec<SignatureParameter> {
+    let (keyword, positional): (Vec<&Field>, Vec<&Field>) =
+        fields.iter().filter(|field| field.init).partition(|field| field.kw_only);
+    let positional = positional.into_iter().map(|field| (field, ParameterKind::Regular));
+    let keyword = keyword.into_iter().map(|field| (field, ParameterKind::KwOnly));
+    positional.chain(keyword).map(|(field, kind)| parameter(&field.name, kind, field.has_default)).collect()
+}
+
+fn parameter(name: &str, kind: ParameterKind, has_default: bool) -> SignatureParameter {
+    SignatureParameter {
+        name: name.to_string(),
+        kind,
+        has_default,
+        annotation: None,
+    }
+}
+
+/// Whether a name assigned in the body of an enum is one of its members:
+/// dunders, `_sunder_` names and private names are not
+pub fn is_enum_member(name: &str) -> bool {
+    let sunder = name.len() > 2 && name.starts_with('_') && name.ends_with('_');
+    !is_dunder(name) && !sunder && !name.starts_with("__")
+}
+
+/// Whether `name` is a dunder such as `__class__`, which every object has or
+/// Python looks up specially
+pub fn is_dunder(name: &str) -> bool {
+    name.len() > 4 && name.starts_with("__") && name.ends_with("__")
+}
+
+/// The kind of a method from its name and decorators
+fn method_kind(name: &str, decorators: &[Expression]) -> MethodKind {
+    for decorator in decorators {
+        match dotted_name(decorator).as_deref() {
+            Some("staticmethod") => return MethodKind::Static,
+            Some("classmethod") => return MethodKind::Class,
+            Some("property" | "cached_property" | "functools.cached_property") => return MethodKind::Property,
+            Some(dotted) if [".setter", ".getter", ".deleter"].iter().any(|suffix| dotted.ends_with(suffix)) => {
+                return MethodKind::Property
+            }
+            _ => {}
+        }
+    }
+    match name {
+        "__new__" => MethodKind::Static,
+        "__init_subclass__" | "__class_getitem__" => MethodKind::Class,
+        _ => MethodKind::Instance,
+    }
+}
+
+/// Whether a method decorator's effect on the method's parameters is known
+fn is_known_decorator(decorator: &Expression) -> bool {
+    match dotted_name(decorator).as_deref() {
+        Some(name) => {
+            let last = name.rsplit('.').next().unwrap_or(name);
+            matches!(
+                last,
+                "staticmethod"
+                    | "classmethod"
+                    | "property"
+                    | "cached_property"
+                    | "setter"
+                    | "getter"
+                    | "deleter"
+                    | "abstractmethod"
+                    | "override"
+                    | "final"
+            )
+        }
+        None => false,
+    }
+}
+
+/// Dotted name of an expression such as a decorator or base class
+/// (`property`, `name.setter`, `abc.ABC`); `None` for calls and other expressions
+pub fn dotted_name(decorator: &Expression) -> Option<String> {
+    match decorator {
+        Expression::Identifier { name, .. } => Some(name.clone()),
+        Expression::Attribute { object, attribute, .. } => {
+            dotted_name(object).map(|object| format!("{}.{}", object, attribute))
+        }
+        _ => None,
+    }
+}
+
+/// Names bound directly in a class body, with their first binding site
+fn class_body_bindings(body: &[Statement]) -> Vec<(String, SourcePosition)> {
+    fn targets(target: &Expression, out: &mut Vec<(String, SourcePosition)>) {
+        match target {
+            Expression::Identifier { name, position } => out.push((name.clone(), *position)),
+            Expression::Tuple { elements, .. } | Expression::List { elements, .. } => {
+                elements.iter().for_each(|element| targets(element, out))
+            }
+            Expression::Starred { value, .. } | Expression::Parenthesized { expr: value, .. } => targets(value, out),
+            _ => {}
+        }
+    }
+
+    let mut bindings = Vec::new();
+    for statement in body {
+        match statement {
+            Statement::Assignment { targets: assigned, .. } => {
+                assigned.iter().for_each(|target| targets(target, &mut bindings))
+            }
+            Statement::AnnAssignment { target, position, .. }
+            | Statement::AugmentedAssignment { target: Expression::Identifier { name: target, .. }, position, .. } => {
+                bindings.push((target.clone(), *position))
+            }
+            Statement::FunctionDef { name, position, .. } | Statement::ClassDef { name, position, .. } => {
+                bindings.push((name.clone(), *position))
+            }
+            Statement::For { target, body, else_block, .. } => {
+                targets(target, &mut bindings);
+                bindings.extend(class_body_bindings(body));
+                bindings.extend(class_body_bindings(else_block.as_deref().unwrap_or_default()));
+            }
+            Statement::If { then_block, elif_blocks, else_block, .. } => {
+                bindings.extend(class_body_bindings(then_block));
+                for (_, block) in elif_blocks {
+                    bindings.extend(class_body_bindings(block));
+                }
+                bindings.extend(class_body_bindings(else_block.as_deref().unwrap_or_default()));
+            }
+            Statement::While { body, else_block, .. } => {
+                bindings.extend(class_body_bindings(body));
+                bindings.extend(class_body_bindings(else_block.as_deref().unwrap_or_default()));
+            }
+            Statement::Import { items, .. } => {
+                for item in items {
+                    let name = crate::semantic::import_binding(&item.module, &item.alias);
+                    bindings.push((name.to_string(), item.position));
+                }
+            }
+            Statement::FromImport { items, .. } => {
+                for item in items {
+                    let name = crate::semantic::import_binding(&item.name, &item.alias);
+                    bindings.push((name.to_string(), item.position));
+                }
+            }
+            _ => {}
+        }
+    }
+    bindings
+}
+
+fn is_name(targets: &[Expression], wanted: &str) -> bool {
+    matches!(targets, [Expression::Identifier { name, .. }] if name == wanted)
+}
+
+/// The string literals of a list or tuple display, with their positions
+pub(crate) fn string_elements(value: &Expression) -> Option<Vec<(String, SourcePosition)>> {
+    use crate::ast::Literal;
+    match value {
+        Expression::List { elements, .. } | Expression::Tuple { elements, .. } => elements
+            .iter()
+            .map(|element| match element {
+                Expression::Literal(Literal::String { value, position, .. }) => Some((value.clone(), *position)),
+                _ => None,
+            })
+            .collect(),
+        Expression::Literal(Literal::String { value, position, .. }) => Some(vec![(value.clone(), *position)]),
+        Expression::Parenthesized { expr, .. } => string_elements(expr),
+        _ => None,
+    }
+}
+
+/// Whether an expression calls `setattr` or touches `__dict__`, which add
+/// attributes this model cannot see
+fn uses_dynamic_attributes(expression: &Expression) -> bool {
+    match expression {
+        Expression::Call { function, arguments, keywords, .. } => {
+            matches!(function.as_ref(), Expression::Identifier { name, .. } if name == "setattr")
+                || uses_dynamic_attributes(function)
+                || arguments.iter().any(uses_dynamic_attributes)
+                || keywords.iter().any(|keyword| uses_dynamic_attributes(&keyword.value))
+        }
+        Expression::Attribute { object, attribute, .. } => {
+            attribute == "__dict__" || uses_dynamic_attributes(object)
+        }
+        Expression::Subscript { object, index, .. } => {
+            uses_dynamic_attributes(object) || uses_dynamic_attributes(index)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_class(name: &str, bases: &[Base]) -> ClassInfo {
+        ClassInfo::build(name, SourcePosition::start(), bases, None, &[])
+    }
+
+    #[test]
+    fn test_mro_single_inheritance() {
+        let a = user_class("A", &[]);
+        let b = user_class("B", &[Base::Class(&a)]);
+        assert_eq!(b.mro, Some(vec!["B".to_string(), "A".to_string(), "object".to_string()]));
+    }
+
+    #[test]
+    fn test_mro_diamond() {
+        let a = user_class("A", &[]);
+        let b = user_class("B", &[Base::Class(&a)]);
+        let c = user_class("C", &[Base::Class(&a)]);
+        let d = user_class("D", &[Base::Class(&b), Base::Class(&c)]);
+        assert_eq!(d.mro.unwrap(), vec!["D", "B", "C", "A", "object"]);
+    }
+
+    #[test]
+    fn test_mro_inconsistent() {
+        let a = user_class("A", &[]);
+        let b = user_class("B", &[Base::Class(&a)]);
+        // A must precede B (listed first) but also follow it (B derives from A)
+        assert_eq!(user_class("C", &[Base::Class(&a), Base::Class(&b)]).mro, None);
+        assert_eq!(user_class("C", &[Base::Class(&a), Base::Class(&a)]).mro, None);
+    }
+
+    #[test]
+    fn test_builtin_mro() {
+        assert_eq!(builtin_mro("KeyError"), vec!["KeyError", "LookupError", "Exception", "BaseException", "object"]);
+        assert_eq!(builtin_mro("object"), vec!["object"]);
+        assert_eq!(builtin_mro("int"), vec!["int", "object"]);
+    }
+
+    #[test]
+    fn test_open_classes() {
+        let known = user_class("A", &[Base::Builtin("Exception")]);
+        assert!(!known.open);
+        assert!(known.has_member("args"));
+        assert!(user_class("B", &[Base::Builtin("dict")]).open);
+        assert!(user_class("C", &[Base::Unknown("mod.Base")]).open);
+    }
+
+    #[test]
+    fn test_synthesized_constructor() {
+        let field = |name: &str, has_default, kw_only| Field {
+            name: name.to_string(),
+            position: SourcePosition::start(),
+            has_default,
+            init: name != "cache",
+            kw_only,
+        };
+        let mut point = user_class("Point", &[]);
+        point.synthesize(Synthesized::Dataclass {
+            fields: vec![
+                field("strict", false, true),
+                field("x", false, false),
+                field("cache", true, false),
+                field("y", true, false),
+            ],
+            init: true,
+            eq: true,
+            order: false,
+            frozen: true,
+        });
+        let init = point.method("__init__").unwrap();
+        let parameters: Vec<(&str, ParameterKind, bool)> = init
+            .signature
+            .parameters
+            .iter()
+            .map(|param| (param.name.as_str(), param.kind.clone(), param.has_default))
+            .collect();
+        assert_eq!(
+            parameters,
+            vec![
+                ("self", ParameterKind::Regular, false),
+                ("x", ParameterKind::Regular, false),
+                ("y", ParameterKind::Regular, true),
+                ("strict", ParameterKind::KwOnly, false),
+            ]
+        );
+        assert!(point.is_synthesized(init));
+        assert!(point.has_member("__hash__") && !point.has_member("__lt__"));
+        assert_eq!(point.method("__eq__").unwrap().signature.returns, Some(Type::Bool));
+    }
+}
@@ -0,0 +1,764 @@
+//! Control flow graphs and definite-assignment analysis
+//!
+//! A module or function body is lowered to a graph of basic blocks, each
+//! holding the name reads and bindings it performs in order. A forward
+//! dataflow pass over the graph then works out, for every read, whether the
+//! name is definitely, possibly or never bound at that point.
+//!
+//! Only code that runs as part of the body is in the graph. Nested function,
+//! class and lambda bodies run later or in their own scope; only the binding
+//! of a `def` or `class` name (and its decorators, defaults and bases) appears.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::ast::{Expression, Literal, Statement};
+use crate::semantic::import_binding;
+use crate::token::SourcePosition;
+
+/// Index of a block in `ControlFlowGraph::blocks`
+pub type BlockId = usize;
+
+/// Something a basic block does to a name
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// The name's value is read
+    Read { name: String, position: SourcePosition },
+    /// The name is bound (assignment, loop target, import, `def`, ...)
+    Bind { name: String, position: SourcePosition },
+    /// The name is unbound by `del`
+    Unbind { name: String, position: SourcePosition },
+}
+
+/// Straight-line code: events in execution order, then a jump to any successor
+#[derive(Debug, Clone, Default)]
+pub struct BasicBlock {
+    pub events: Vec<Event>,
+    pub successors: Vec<BlockId>,
+    /// Positions of the statements that start in this block
+    pub statements: Vec<SourcePosition>,
+}
+
+/// Control flow graph of one module or function body
+#[derive(Debug, Clone)]
+pub struct ControlFlowGraph {
+    pub blocks: Vec<BasicBlock>,
+    /// Where execution starts
+    pub entry: BlockId,
+    /// Reached by falling off the end, `return` or `raise`
+    pub exit: BlockId,
+    /// The block that falls off the end of the body into `exit`
+    pub end: BlockId,
+}
+
+impl ControlFlowGraph {
+    /// Build the graph of a function body
+    pub fn build(body: &[Statement]) -> Self {
+        Self::build_body(body, true)
+    }
+
+    /// Build the graph of a module body. A `return` there is reported as
+    /// misplaced on its own, so it does not cut off the code after it.
+    pub fn build_module(body: &[Statement]) -> Self {
+        Self::build_body(body, false)
+    }
+
+    fn build_body(body: &[Statement], returns: bool) -> Self {
+        let mut builder = Builder {
+            blocks: vec![BasicBlock::default(), BasicBlock::default()],
+            current: 0,
+            exit: 1,
+            loops: Vec::new(),
+            returns,
+        };
+        builder.statements(body);
+        let end = builder.current;
+        builder.edge(end, builder.exit);
+
+        ControlFlowGraph { blocks: builder.blocks, entry: 0, exit: 1, end }
+    }
+
+    /// Predecessors of every block
+    pub fn predecessors(&self) -> Vec<Vec<BlockId>> {
+        let mut predecessors = vec![Vec::new(); self.blocks.len()];
+        for (id, block) in self.blocks.iter().enumerate() {
+            for &successor in &block.successors {
+                predecessors[successor].push(id);
+            }
+        }
+        predecessors
+    }
+
+    /// The blocks reachable from the entry, each after the blocks that
+    /// reach it other than through a loop's back edge
+    pub fn reverse_postorder(&self) -> Vec<BlockId> {
+        let mut visited = vec![false; self.blocks.len()];
+        let mut order = Vec::new();
+        // Each entry is a block and the index of its next successor to visit
+        let mut stack = vec![(self.entry, 0)];
+        visited[self.entry] = true;
+        while let Some((id, next)) = stack.pop() {
+            match self.blocks[id].successors.get(next) {
+                Some(&successor) => {
+                    stack.push((id, next + 1));
+                    if !std::mem::replace(&mut visited[successor], true) {
+                        stack.push((successor, 0));
+                    }
+                }
+                None => order.push(id),
+            }
+        }
+        order.reverse();
+        order
+    }
+
+    /// Which blocks can be reached from the entry
+    pub fn reachable(&self) -> Vec<bool> {
+        let mut reachable = vec![false; self.blocks.len()];
+        let mut stack = vec![self.entry];
+        while let Some(id) = stack.pop() {
+            if !std::mem::replace(&mut reachable[id], true) {
+                stack.extend(&self.blocks[id].successors);
+            }
+        }
+        reachable
+    }
+
+    /// Positions of the statements that can never run
+    pub fn unreachable_statements(&self) -> Vec<SourcePosition> {
+        let reachable = self.reachable();
+        self.blocks
+            .iter()
+            .zip(reachable)
+            .filter(|(_, reachable)| !reachable)
+            .flat_map(|(block, _)| block.statements.iter().copied())
+            .collect()
+    }
+
+    /// Whether execution can reach the end of the body without `return` or `raise`
+    pub fn falls_off_end(&self) -> bool {
+        self.reachable()[self.end]
+    }
+}
+
+struct Builder {
+    blocks: Vec<BasicBlock>,
+    current: BlockId,
+    exit: BlockId,
+    /// `(continue target, break target)` of the enclosing loops, innermost last
+    loops: Vec<(BlockId, BlockId)>,
+    /// Whether `return` leaves the body
+    returns: bool,
+}
+
+impl Builder {
+    fn new_block(&mut self) -> BlockId {
+        self.blocks.push(BasicBlock::default());
+        self.blocks.len() - 1
+    }
+
+    fn edge(&mut self, from: BlockId, to: BlockId) {
+        self.blocks[from].successors.push(to);
+    }
+
+    fn emit(&mut self, event: Event) {
+        self.blocks[self.current].events.push(event);
+    }
+
+    /// Jump to `target` and continue in a fresh block that nothing jumps to,
+    /// so code after `return`/`break`/... is unreachable
+    fn jump(&mut self, target: BlockId) {
+        self.edge(self.current, target);
+        self.current = self.new_block();
+    }
+
+    fn statements(&mut self, body: &[Statement]) {
+        for statement in body {
+            self.statement(statement);
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        self.blocks[self.current].statements.push(*statement.position());
+        match statement {
+            Statement::Expression(expr) => self.reads(expr),
+            Statement::Assignment { targets, value, .. } => {
+                self.reads(value);
+                for target in targets {
+                    self.target(target);
+                }
+            }
+            Statement::AnnAssignment { target, value, position, .. } => {
+                if let Some(value) = value {
+                    self.reads(value);
+                    self.emit(Event::Bind { name: target.clone(), position: *position });
+                }
+            }
+            Statement::AugmentedAssignment { target, value, position, .. } => match target {
+                Expression::Identifier { name, .. } => {
+                    self.emit(Event::Read { name: name.clone(), position: *position });
+                    self.reads(value);
+                    self.emit(Event::Bind { name: name.clone(), position: *position });
+                }
+                other => {
+                    self.reads(other);
+                    self.reads(value);
+                }
+            },
+            Statement::Del { targets, .. } => {
+                for target in targets {
+                    match target {
+                        Expression::Identifier { name, position } => {
+                            self.emit(Event::Read { name: name.clone(), position: *position });
+                            self.emit(Event::Unbind { name: name.clone(), position: *position });
+                        }
+                        other => self.reads(other),
+                    }
+                }
+            }
+            Statement::Import { items, .. } => {
+                for item in items {
+                    let name = import_binding(&item.module, &item.alias).to_string();
+                    self.emit(Event::Bind { name, position: item.position });
+                }
+            }
+            Statement::FromImport { items, .. } => {
+                for item in items.iter().filter(|item| item.name != "*") {
+                    let name = import_binding(&item.name, &item.alias).to_string();
+                    self.emit(Event::Bind { name, position: item.position });
+                }
+            }
+            Statement::Assert { condition, message, .. } => {
+                self.reads(condition);
+                if let Some(message) = message {
+                    self.reads(message);
+                }
+            }
+            Statement::Return { value, .. } => {
+                if let Some(value) = value {
+                    self.reads(value);
+                }
+                if self.returns {
+                    self.jump(self.exit);
+                }
+            }
+            Statement::Raise { exception, .. } => {
+                if let Some(exception) = exception {
+                    self.reads(exception);
+                }
+                self.jump(self.exit);
+            }
+            Statement::Break(_) => {
+                // `break` outside a loop is reported by the semantic analyzer
+                if let Some(&(_, after)) = self.loops.last() {
+                    self.jump(after);
+                }
+            }
+            Statement::Continue(_) => {
+                if let Some(&(header, _)) = self.loops.last() {
+                    self.jump(header);
+                }
+            }
+            Statement::If { condition, then_block, elif_blocks, else_block, .. } => {
+                let after = self.new_block();
+                let branches = std::iter::once((condition, then_block))
+                    .chain(elif_blocks.iter().map(|(condition, block)| (condition, block)));
+                for (condition, block) in branches {
+                    self.reads(condition);
+                    let taken = self.new_block();
+                    let not_taken = self.new_block();
+                    self.edge(self.current, taken);
+                    if !is_constant_true(condition) {
+                        self.edge(self.current, not_taken);
+                    }
+
+                    self.current = taken;
+                    self.statements(block);
+                    self.edge(self.current, after);
+                    self.current = not_taken;
+                }
+                if let Some(block) = else_block {
+                    self.statements(block);
+                }
+                self.edge(self.current, after);
+                self.current = after;
+            }
+            Statement::While { condition, body, else_block, .. } => {
+                let header = self.new_block();
+                self.edge(self.current, header);
+                self.current = header;
+                self.reads(condition);
+
+                let (body_block, else_entry, after) = (self.new_block(), self.new_block(), self.new_block());
+                self.edge(header, body_block);
+                if !is_constant_true(condition) {
+                    self.edge(header, else_entry);
+                }
+                self.loop_body(body_block, header, after, |builder| builder.statements(body));
+                self.loop_else(else_entry, after, else_block.as_deref());
+            }
+            Statement::For { target, iter, body, else_block, .. } => {
+                self.reads(iter);
+                let header = self.new_block();
+                self.edge(self.current, header);
+
+                let (body_block, else_entry, after) = (self.new_block(), self.new_block(), self.new_block());
+                self.edge(header, body_block);
+                self.edge(header, else_entry);
+                self.loop_body(body_block, header, after, |builder| {
+                    builder.target(target);
+                    builder.statements(body);
+                });
+                self.loop_else(else_entry, after, else_block.as_deref());
+            }
+            Statement::FunctionDef { name, parameters, decorators, position, .. } => {
+                for decorator in decorators {
+                    self.reads(decorator);
+                }
+                for default in parameters.iter().filter_map(|param| param.default.as_ref()) {
+                    self.reads(default);
+                }
+                self.emit(Event::Bind { name: name.clone(), position: *position });
+            }
+            Statement::ClassDef { name, bases, decorators, position, .. } => {
+                for expr in decorators.iter().chain(bases) {
+                    self.reads(expr);
+                }
+                self.emit(Event::Bind { name: name.clone(), position: *position });
+            }
+            Statement::Pass(_) | Statement::Global { .. } | Statement::Nonlocal { .. } => {}
+        }
+    }
+
+    /// Lower a loop body that starts at `entry`, loops back to `header`, and
+    /// leaves through `after` on `break`
+    fn loop_body(&mut self, entry: BlockId, header: BlockId, after: BlockId, body: impl FnOnce(&mut Self)) {
+        self.loops.push((header, after));
+        self.current = entry;
+        body(self);
+        self.edge(self.current, header);
+        self.loops.pop();
+    }
+
+    /// Lower the `else` clause run when a loop ends without `break`
+    fn loop_else(&mut self, entry: BlockId, after: BlockId, else_block: Option<&[Statement]>) {
+        self.current = entry;
+        if let Some(block) = else_block {
+            self.statements(block);
+        }
+        self.edge(self.current, after);
+        self.current = after;
+    }
+
+    /// Bindings made by an assignment target
+    fn target(&mut self, target: &Expression) {
+        match target {
+            Expression::Identifier { name, position } => {
+                self.emit(Event::Bind { name: name.clone(), position: *position });
+            }
+            Expression::Tuple { elements, .. } | Expression::List { elements, .. } => {
+                for element in elements {
+                    self.target(element);
+                }
+            }
+            Expression::Starred { value, .. } | Expression::Parenthesized { expr: value, .. } => self.target(value),
+            // Attribute and subscript targets read their object
+            other => self.reads(other),
+        }
+    }
+
+    /// Reads (and walrus bindings) performed by evaluating an expression
+    fn reads(&mut self, expr: &Expression) {
+        match expr {
+            Expression::Identifier { name, position } => {
+                self.emit(Event::Read { name: name.clone(), position: *position });
+            }
+            Expression::AssignmentExpr { target, value, position } => {
+                self.reads(value);
+                self.emit(Event::Bind { name: target.clone(), position: *position });
+            }
+            Expression::BinaryOp { left, right, .. } => {
+                self.reads(left);
+                self.reads(right);
+            }
+            Expression::UnaryOp { operand: inner, .. }
+            | Expression::Parenthesized { expr: inner, .. }
+            | Expression::Attribute { object: inner, .. }
+            | Expression::Starred { value: inner, .. } => self.reads(inner),
+            Expression::Call { function, arguments, keywords, .. } => {
+                self.reads(function);
+                for argument in arguments {
+                    self.reads(argument);
+                }
+                for keyword in keywords {
+                    self.reads(&keyword.value);
+                }
+            }
+            Expression::Subscript { object, index, .. } => {
+                self.reads(object);
+                self.reads(index);
+            }
+            Expression::List { elements, .. } | Expression::Tuple { elements, .. } | Expression::Set { elements, .. } => {
+                for element in elements {
+                    self.reads(element);
+                }
+            }
+            Expression::Dict { pairs, .. } => {
+                for (key, value) in pairs {
+                    self.reads(key);
+                    self.reads(value);
+                }
+            }
+            Expression::Conditional { condition, true_expr, false_expr, .. } => {
+                self.reads(condition);
+                self.reads(true_expr);
+                self.reads(false_expr);
+            }
+            // Comprehensions run inline; reads of their own targets are
+            // recorded too but resolve to the comprehension scope
+            Expression::ListComp { element, generators, .. }
+            | Expression::SetComp { element, generators, .. }
+            | Expression::GeneratorExpr { element, generators, .. } => {
+                for generator in generators {
+                    self.reads(&generator.iter);
+                    generator.conditions.iter().for_each(|condition| self.reads(condition));
+                }
+                self.reads(element);
+            }
+            Expression::DictComp { key, value, generators, .. } => {
+                for generator in generators {
+                    self.reads(&generator.iter);
+                    generator.conditions.iter().for_each(|condition| self.reads(condition));
+                }
+                self.reads(key);
+                self.reads(value);
+            }
+            // A lambda body runs when the lambda is called
+            Expression::Lambda { .. } | Expression::Literal(_) => {}
+        }
+    }
+}
+
+/// Whether a condition is a literal that is always false, as in `if 0:`:
+/// `False`, zero, an empty string or `None`
+pub(crate) fn is_constant_false(condition: &Expression) -> bool {
+    match condition {
+        Expression::Literal(Literal::Boolean { value, .. }) => !*value,
+        Expression::Literal(Literal::Integer { value, .. }) => *value == 0,
+        Expression::Literal(Literal::Float { value, .. }) => *value == 0.0,
+        Expression::Literal(Literal::String { value, .. }) => value.is_empty(),
+        Expression::Literal(Literal::None { .. }) => true,
+        Expression::Parenthesized { expr, .. } => is_constant_false(expr),
+        _ => false,
+    }
+}
+
+/// Whether a condition is a literal that is always true, as in `while True:`
+/// or `while 1:`: `True`, a non-zero number or a non-empty string
+///
+/// Only a true condition removes an edge. The body under a false one stays
+/// reachable so that the reads in it are still checked.
+fn is_constant_true(condition: &Expression) -> bool {
+    match condition {
+        Expression::Literal(Literal::Boolean { value, .. }) => *value,
+        Expression::Literal(Literal::Integer { value, .. }) => *value != 0,
+        Expression::Literal(Literal::Float { value, .. }) => *value != 0.0,
+        Expression::Literal(Literal::String { value, .. }) => !value.is_empty(),
+        Expression::Parenthesized { expr, .. } => is_constant_true(expr),
+        _ => false,
+    }
+}
+
+/// Whether a name is bound when it is read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Assigned {
+    /// Bound on every path to the read
+    Definitely,
+    /// Bound on some paths but not others
+    Possibly,
+    /// Not bound on any path
+    Never,
+}
+
+/// Result of definite-assignment analysis over a control flow graph
+#[derive(Debug, Clone, Default)]
+pub struct DefiniteAssignment {
+    /// State of each reachable read, keyed by the read's (line, column).
+    /// Only names bound somewhere in the graph are tracked.
+    reads: HashMap<(usize, usize), Assigned>,
+}
+
+impl DefiniteAssignment {
+    /// Analyze a graph, given the names bound before it starts (parameters,
+    /// builtins)
+    ///
+    /// Only the names bound somewhere in the graph are tracked, each by its
+    /// index in a bit set, so names bound on entry and never rebound (most
+    /// builtins) cost nothing. Blocks are visited from a worklist in reverse
+    /// postorder, which settles a body without loops in a single visit.
+    pub fn analyze(cfg: &ControlFlowGraph, bound_on_entry: &HashSet<String>) -> Self {
+        let mut tracked: HashMap<&str, usize> = HashMap::new();
+        for event in cfg.blocks.iter().flat_map(|block| &block.events) {
+            if let Event::Bind { name, .. } = event {
+                let next = tracked.len();
+                tracked.entry(name.as_str()).or_insert(next);
+            }
+        }
+        let mut entry = Names::new(tracked.len());
+        for (name, &index) in &tracked {
+            if bound_on_entry.contains(*name) {
+                entry.insert(index);
+            }
+        }
+        let transfers: Vec<Transfer> = cfg.blocks.iter().map(|block| Transfer::new(block, &tracked)).collect();
+        let predecessors = cfg.predecessors();
+        let order = cfg.reverse_postorder();
+        let mut rank = vec![usize::MAX; cfg.blocks.len()];
+        for (index, &id) in order.iter().enumerate() {
+            rank[id] = index;
+        }
+
+        // Per reachable block: names bound on every path in and names bound
+        // on some path in, then the same on the way out (None = not visited)
+        let mut inputs: Vec<Option<(Names, Names)>> = vec![None; cfg.blocks.len()];
+        let mut outputs: Vec<Option<(Names, Names)>> = vec![None; cfg.blocks.len()];
+        let mut worklist = BTreeSet::from([rank[cfg.entry]]);
+        while let Some(next) = worklist.pop_first() {
+            let id = order[next];
+            let input = if id == cfg.entry {
+                (entry.clone(), entry.clone())
+            } else {
+                let mut joined: Option<(Names, Names)> = None;
+                for (must, may) in predecessors[id].iter().filter_map(|&pred| outputs[pred].as_ref()) {
+                    match &mut joined {
+                        Some((definite, possible)) => {
+                            definite.intersect_with(must);
+                            possible.union_with(may);
+                        }
+                        None => joined = Some((must.clone(), may.clone())),
+                    }
+                }
+                let Some(joined) = joined else { continue };
+                joined
+            };
+            let output = transfers[id].apply(&input);
+            inputs[id] = Some(input);
+            if outputs[id].as_ref() != Some(&output) {
+                outputs[id] = Some(output);
+                worklist.extend(cfg.blocks[id].successors.iter().map(|&successor| rank[successor]));
+            }
+        }
+
+        let mut reads = HashMap::new();
+        for (id, block) in cfg.blocks.iter().enumerate() {
+            let Some((mut must, mut may)) = inputs[id].take() else { continue };
+            for event in &block.events {
+                match event {
+                    Event::Read { name, position } => {
+                        let Some(&index) = tracked.get(name.as_str()) else { continue };
+                        let state = if must.contains(index) {
+                            Assigned::Definitely
+                        } else if may.contains(index) {
+                            Assigned::Possibly
+                        } else {
+                            Assigned::Never
+                        };
+                        reads.insert((position.line, position.column), state);
+                    }
+                    Event::Bind { name, .. } => {
+                        must.insert(tracked[name.as_str()]);
+                        may.insert(tracked[name.as_str()]);
+                    }
+                    Event::Unbind { name, .. } => {
+                        if let Some(&index) = tracked.get(name.as_str()) {
+                            must.remove(index);
+                            may.remove(index);
+                        }
+                    }
+                }
+            }
+        }
+        DefiniteAssignment { reads }
+    }
+
+    /// State of the name read at `position`, if the read is reachable and
+    /// the name is bound somewhere in the analyzed body
+    pub fn at(&self, position: &SourcePosition) -> Option<Assigned> {
+        self.reads.get(&(position.line, position.column)).copied()
+    }
+}
+
+/// A set of tracked names, by index
+#[derive(Debug, Clone, PartialEq)]
+struct Names(Vec<u64>);
+
+impl Names {
+    fn new(names: usize) -> Self {
+        Names(vec![0; names.div_ceil(64)])
+    }
+
+    fn insert(&mut self, index: usize) {
+        self.0[index / 64] |= 1 << (index % 64);
+    }
+
+    fn remove(&mut self, index: usize) {
+        self.0[index / 64] &= !(1 << (index % 64));
+    }
+
+    fn contains(&self, index: usize) -> bool {
+        self.0[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    fn intersect_with(&mut self, other: &Names) {
+        self.0.iter_mut().zip(&other.0).for_each(|(word, other)| *word &= other);
+    }
+
+    fn union_with(&mut self, other: &Names) {
+        self.0.iter_mut().zip(&other.0).for_each(|(word, other)| *word |= other);
+    }
+}
+
+/// What a block's bindings do to the names bound on entry: the names it
+/// leaves bound and the names it leaves unbound
+struct Transfer {
+    bound: Names,
+    unbound: Names,
+}
+
+impl Transfer {
+    fn new(block: &BasicBlock, tracked: &HashMap<&str, usize>) -> Self {
+        let mut bound = Names::new(tracked.len());
+        let mut unbound = Names::new(tracked.len());
+        for event in &block.events {
+            match event {
+                Event::Bind { name, .. } => {
+                    bound.insert(tracked[name.as_str()]);
+                    unbound.remove(tracked[name.as_str()]);
+                }
+                Event::Unbind { name, .. } => {
+                    if let Some(&index) = tracked.get(name.as_str()) {
+                        unbound.insert(index);
+                        bound.remove(index);
+                    }
+                }
+                Event::Read { .. } => {}
+            }
+        }
+        Transfer { bound, unbound }
+    }
+
+    /// The names bound on every and on some path out of the block
+    fn apply(&self, (definite, possible): &(Names, Names)) -> (Names, Names) {
+        let apply = |names: &Names| {
+            let words = names.0.iter().zip(&self.bound.0).zip(&self.unbound.0);
+            Names(words.map(|((word, bound), unbound)| (word & !unbound) | bound).collect())
+        };
+        (apply(definite), apply(possible))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn graph(code: &str) -> ControlFlowGraph {
+        let tokens = Lexer::new(code).tokenize().expect("Tokenize should succeed");
+        let module = Parser::new(tokens).parse().expect("Parse should succeed");
+        ControlFlowGraph::build(&module.statements)
+    }
+
+    fn states(code: &str, name: &str) -> Vec<Assigned> {
+        let cfg = graph(code);
+        let analysis = DefiniteAssignment::analyze(&cfg, &HashSet::new());
+        let mut reads: Vec<&SourcePosition> = cfg
+            .blocks
+            .iter()
+            .flat_map(|block| &block.events)
+            .filter_map(|event| match event {
+                Event::Read { name: read, position } if read == name => Some(position),
+                _ => None,
+            })
+            .collect();
+        reads.sort_by_key(|position| (position.line, position.column));
+        reads.into_iter().filter_map(|position| analysis.at(position)).collect()
+    }
+
+    #[test]
+    fn test_straight_line_graph() {
+        let cfg = graph("x = 1\ny = x\n");
+        assert_eq!(cfg.blocks[cfg.entry].successors, vec![cfg.exit]);
+        assert_eq!(cfg.blocks[cfg.entry].events.len(), 3);
+    }
+
+    #[test]
+    fn test_code_after_raise_unreachable() {
+        let code = "x = 1\nraise x\ny = 2\n";
+        let cfg = graph(code);
+        let reachable = cfg.reachable();
+        let unreachable_events = cfg
+            .blocks
+            .iter()
+            .enumerate()
+            .filter(|(id, _)| !reachable[*id])
+            .flat_map(|(_, block)| &block.events)
+            .count();
+        assert_eq!(unreachable_events, 1);
+    }
+
+    #[test]
+    fn test_unreachable_statements() {
+        let cfg = graph("while True:\n    pass\nx = 1\n");
+        let unreachable = cfg.unreachable_statements();
+        assert_eq!(unreachable.len(), 1);
+        assert_eq!((unreachable[0].line, unreachable[0].column), (3, 1));
+
+        assert!(graph("while True:\n    break\nx = 1\n").unreachable_statements().is_empty());
+        assert_eq!(graph("while 1:\n    pass\nx = 1\n").unreachable_statements().len(), 1);
+        assert!(graph("while 0:\n    pass\nx = 1\n").unreachable_statements().is_empty());
+    }
+
+    #[test]
+    fn test_falls_off_end() {
+        assert!(graph("x = 1\n").falls_off_end());
+        assert!(!graph("if c:\n    raise c\nelse:\n    raise c\n").falls_off_end());
+        assert!(graph("if c:\n    raise c\n").falls_off_end());
+    }
+
+    #[test]
+    fn test_assignment_states() {
+        assert_eq!(states("print(x)\nx = 1\nprint(x)\n", "x"), vec![Assigned::Never, Assigned::Definitely]);
+        assert_eq!(states("if c:\n    x = 1\nprint(x)\n", "x"), vec![Assigned::Possibly]);
+        assert_eq!(states("while c:\n    print(x)\n    x = 1\n", "x"), vec![Assigned::Possibly]);
+    }
+
+    #[test]
+    fn test_continue_skips_rest_of_body() {
+        let code = "while c:\n    if c:\n        continue\n    x = 1\nelse:\n    print(x)\n";
+        assert_eq!(states(code, "x"), vec![Assigned::Possibly]);
+    }
+
+    #[test]
+    fn test_reverse_postorder() {
+        let cfg = graph("while c:\n    if c:\n        break\n    x = 1\nprint(x)\n");
+        let order = cfg.reverse_postorder();
+        let reachable = cfg.reachable();
+        assert_eq!(order.len(), reachable.iter().filter(|&&reachable| reachable).count());
+        assert_eq!(order.first(), Some(&cfg.entry));
+        assert_eq!(order.last(), Some(&cfg.exit));
+    }
+
+    #[test]
+    fn test_states_of_many_names() {
+        // More names than fit in one word of the bit sets
+        let mut code: String = (0..100).map(|index| format!("x{} = 1\n", index)).collect();
+        code.push_str("if c:\n    del x70\nprint(x70, x99)\n");
+        assert_eq!(states(&code, "x70"), vec![Assigned::Definitely, Assigned::Possibly]);
+        assert_eq!(states(&code, "x99"), vec![Assigned::Definitely]);
+    }
+
+    #[test]
+    fn test_untracked_names_have_no_state() {
+        assert_eq!(states("print(y)\n", "y"), vec![]);
+    }
+}
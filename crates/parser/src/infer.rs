@@ -0,0 +1,2629 @@
+//! Local Type Inference
+//!
+//! This module infers the type of every expression of a module lowered into
+//! an [`AstArena`], storing the results in side tables keyed by node. Types
+//! flow forward through the module the way values do:
+//!
+//! - literals and container displays have their own types (`[1, 2.0]` is
+//!   `list[int | float]`), and comprehensions take the element type of what
+//!   they iterate over
+//! - an assignment gives the target the value's type until the next
+//!   assignment; after an `if` or a loop a name has the union of the types
+//!   it may have on each path
+//! - conditions narrow the names they test: after `if x is not None`,
+//!   `isinstance(x, C)`, `assert x` or an `if` whose body returns, `x` only
+//!   has the types that can reach that point, and paths ending in `return`,
+//!   `raise`, `break` or `continue` do not flow on. Attribute paths such as
+//!   `self.v` are narrowed the same way until they or their base are
+//!   assigned, or a call that may reassign them is made.
+//! - operators follow Python's rules (`int + float` is `float`, `int / int`
+//!   is `float`, `str * int` is `str`)
+//! - annotations win over inferred types, and a function without a return
+//!   annotation returns the union of its `return` values
+//! - names imported from other modules and stubs, and calls to builtins,
+//!   have the types their declarations are annotated with
+//! - calls to generic functions solve the type variables of the parameters
+//!   from the arguments (`first([1, 2])` is `int` for
+//!   `def first(items: list[T]) -> T`), and instances of generic classes
+//!   substitute their type arguments into their members
+//! - dataclasses and named tuples are built from their annotated fields,
+//!   and the members of an enum are instances of it (`Color.RED` is
+//!   `Color`, and its `.value` has the type of the values assigned)
+//!
+//! Anything that cannot be inferred is `Any`.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::arena::{AstArena, ExprKind, NodeId, SideTable, StmtKind};
+use crate::ast::{AugmentedOperator, BinaryOperator, Literal, ParameterKind, UnaryOperator};
+use crate::builtins::{self, BuiltinKind};
+use crate::classes::{self, ClassInfo, MethodInfo, MethodKind, Synthesized};
+use crate::symbol_table::{Signature, Symbol, SymbolTable};
+use crate::token::SourcePosition;
+use crate::types::Type;
+
+/// Types inferred for a module
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InferredTypes {
+    /// Type of every expression node. Assignment targets have the type of
+    /// the value bound to them, except augmented assignment targets, which
+    /// have the type they are read with.
+    pub expressions: SideTable<Type>,
+    /// Return type of every function, keyed by its `FunctionDef` node
+    pub returns: SideTable<Type>,
+    /// The function each call runs, when it is one defined in the module
+    pub callees: SideTable<Callee>,
+    /// The function each call runs, when it is declared outside the module
+    pub external_callees: SideTable<ExternalCallee>,
+    /// Type of each module-level variable at the end of the module
+    pub module_variables: HashMap<String, Type>,
+    /// Types of the attributes of each class's instances, by class name:
+    /// the annotated type joined with the values assigned through `self`
+    pub attributes: HashMap<String, HashMap<String, Type>>,
+}
+
+impl InferredTypes {
+    /// The type inferred for an expression node
+    pub fn type_of(&self, id: NodeId) -> Option<&Type> {
+        self.expressions.get(&id)
+    }
+
+    /// The return type of a function definition node
+    pub fn return_type(&self, id: NodeId) -> Option<&Type> {
+        self.returns.get(&id)
+    }
+
+    /// The function a call node runs
+    pub fn callee(&self, id: NodeId) -> Option<Callee> {
+        self.callees.get(&id).copied()
+    }
+
+    /// The builtin or imported function a call node runs
+    pub fn external_callee(&self, id: NodeId) -> Option<&ExternalCallee> {
+        self.external_callees.get(&id)
+    }
+}
+
+/// A function definition that a call runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Callee {
+    /// The `FunctionDef` node
+    pub definition: NodeId,
+    /// Whether the first parameter receives the object the function was
+    /// looked up on (a method called on an instance, a class method, or
+    /// `__init__` of a constructor call) rather than the first argument
+    pub bound: bool,
+}
+
+/// A function declared rather than defined by a `def` in the module that a
+/// call runs: a builtin, a function, method or class imported from another
+/// module or a stub, or a class whose `__init__` `@dataclass` generates
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalCallee {
+    /// The function's name, qualified by its class for methods
+    pub name: String,
+    /// The parameters the arguments bind to, without the receiver of a
+    /// method or constructor
+    pub signature: Signature,
+}
+
+/// Infer the types of a module. `symbol_table` is the result of semantic
+/// analysis of the same module and provides the resolved annotations.
+pub fn infer_types(arena: &AstArena, symbol_table: &SymbolTable) -> InferredTypes {
+    infer(arena, symbol_table, false)
+}
+
+/// Infer the types of a module for gradual checking: a call to a function
+/// without a return annotation has type `Any` rather than the type its body
+/// returns. The inferred return types are still recorded.
+pub fn infer_types_gradually(arena: &AstArena, symbol_table: &SymbolTable) -> InferredTypes {
+    infer(arena, symbol_table, true)
+}
+
+fn infer(arena: &AstArena, symbol_table: &SymbolTable, gradual: bool) -> InferredTypes {
+    let mut inference = Inference::new(arena, symbol_table);
+    inference.gradual = gradual;
+    inference.collect_definitions(arena.root());
+    inference.infer_block(arena.root());
+
+    let module_variables = inference.frames.remove(0).variables;
+    let attributes = inference.classes.into_iter().map(|(class, members)| (class, members.attributes)).collect();
+    InferredTypes {
+        expressions: inference.types,
+        returns: inference.returns,
+        callees: inference.callees,
+        external_callees: inference.external_callees,
+        module_variables,
+        attributes,
+    }
+}
+
+/// The types of the variables of one scope
+#[derive(Debug, Clone)]
+struct Frame {
+    kind: FrameKind,
+    variables: HashMap<String, Type>,
+    /// Types of the attribute paths (`self.v`) narrowed by a condition
+    paths: HashMap<String, Type>,
+    /// Variables currently bound by a `def` or `class` statement
+    definitions: HashMap<String, NodeId>,
+    /// Variables currently bound by an import, with the position of the
+    /// imported name
+    imports: HashMap<String, SourcePosition>,
+    /// Whether control can reach the current point, which is false after
+    /// `return`, `raise`, `break` and `continue`
+    reachable: bool,
+}
+
+impl Frame {
+    fn new(kind: FrameKind, variables: HashMap<String, Type>) -> Self {
+        Self {
+            kind,
+            variables,
+            paths: HashMap::new(),
+            definitions: HashMap::new(),
+            imports: HashMap::new(),
+            reachable: true,
+        }
+    }
+
+    /// Forget the narrowed types of an attribute path and of the paths
+    /// through it, once it is assigned
+    fn forget(&mut self, path: &str) {
+        self.paths.retain(|name, _| name != path && !name.strip_prefix(path).is_some_and(|rest| rest.starts_with('.')));
+    }
+}
+
+/// The variable types of the innermost scope at one point of the control
+/// flow
+#[derive(Debug, Clone, PartialEq)]
+struct FlowState {
+    variables: HashMap<String, Type>,
+    paths: HashMap<String, Type>,
+    reachable: bool,
+}
+
+/// The states at the jumps out of the loop being inferred
+#[derive(Debug, Default)]
+struct LoopExits {
+    breaks: Vec<FlowState>,
+    continues: Vec<FlowState>,
+}
+
+/// The types a condition narrows names to, when it is true and when it
+/// is false
+#[derive(Debug, Default)]
+struct Narrowing {
+    when_true: HashMap<String, Type>,
+    when_false: HashMap<String, Type>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FrameKind {
+    Module,
+    Function,
+    /// Class bodies are not visible from their methods
+    Class,
+    /// Comprehension and lambda scopes see through to the enclosing scope
+    Inline,
+}
+
+/// What is known about the members of a class
+#[derive(Debug, Clone, Default)]
+struct ClassTypes {
+    /// Names of the bases, which are looked up for members the class does
+    /// not define itself
+    bases: Vec<String>,
+    /// Method definitions by name
+    methods: HashMap<String, NodeId>,
+    /// Types assigned to attributes of `self` in any method
+    attributes: HashMap<String, Type>,
+    /// Type parameters of a generic class, which the type arguments of its
+    /// instances are substituted for
+    type_params: Vec<String>,
+    /// Methods of a class imported from another module, or generated by
+    /// `@dataclass` or `NamedTuple`, with their annotated signatures
+    declared_methods: HashMap<String, MethodInfo>,
+    /// Members of an enum, in order
+    enum_members: Vec<String>,
+    /// Type of the values of an enum's members; `None` for other classes
+    enum_value: Option<Type>,
+}
+
+struct Inference<'a> {
+    arena: &'a AstArena,
+    symbol_table: &'a SymbolTable,
+    types: SideTable<Type>,
+    returns: SideTable<Type>,
+    callees: SideTable<Callee>,
+    external_callees: SideTable<ExternalCallee>,
+    /// Symbols bound by imports, by the position of the imported name
+    imported: HashMap<SourcePosition, Vec<&'a Symbol>>,
+    /// Variable types of the enclosing scopes, innermost last; the first
+    /// frame is the module
+    frames: Vec<Frame>,
+    /// Module-level functions and classes, for names read before the
+    /// definition is reached (from inside a function called later)
+    definitions: HashMap<String, NodeId>,
+    /// Members of every class seen so far, by class name
+    classes: HashMap<String, ClassTypes>,
+    /// Classes as summarized by semantic analysis, by the position of
+    /// their `class` statement
+    class_infos: HashMap<SourcePosition, &'a ClassInfo>,
+    /// The class each method is defined in
+    method_classes: HashMap<NodeId, String>,
+    /// Functions whose return type is being inferred, which are `Any` to
+    /// recursive calls
+    in_progress: HashSet<NodeId>,
+    /// Types of the values returned so far by each function being inferred
+    returned: Vec<Vec<Type>>,
+    /// Exits of the enclosing loops of the current function, innermost last
+    loops: Vec<LoopExits>,
+    /// Whether calls to functions without a return annotation are `Any`
+    gradual: bool,
+}
+
+impl<'a> Inference<'a> {
+    fn new(arena: &'a AstArena, symbol_table: &'a SymbolTable) -> Self {
+        Self {
+            arena,
+            symbol_table,
+            types: SideTable::new(),
+            returns: SideTable::new(),
+            callees: SideTable::new(),
+            external_callees: SideTable::new(),
+            imported: imported_symbols(symbol_table),
+            frames: vec![Frame::new(FrameKind::Module, HashMap::new())],
+            definitions: HashMap::new(),
+            classes: HashMap::new(),
+            class_infos: symbol_table
+                .scopes()
+                .values()
+                .flat_map(|scope| scope.symbols())
+                .filter_map(|symbol| symbol.class_info.as_deref())
+                .map(|info| (info.position, info))
+                .collect(),
+            method_classes: HashMap::new(),
+            in_progress: HashSet::new(),
+            returned: Vec::new(),
+            loops: Vec::new(),
+            gradual: false,
+        }
+    }
+
+    /// Record the module-level functions and classes, and the methods of
+    /// every class
+    fn collect_definitions(&mut self, body: &[NodeId]) {
+        for &id in body {
+            match self.arena.stmt(id) {
+                Some(StmtKind::FunctionDef { name, .. }) => {
+                    self.definitions.insert(name.clone(), id);
+                }
+                Some(StmtKind::ClassDef { name, bases, body, .. }) => {
+                    self.definitions.insert(name.clone(), id);
+                    self.collect_members(id, name, bases, body);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn collect_members(&mut self, id: NodeId, class: &str, bases: &[NodeId], body: &[NodeId]) {
+        let mut members = ClassTypes::default();
+        let info = self.arena.position(id).and_then(|position| self.class_infos.get(position));
+        match info {
+            // Bases as analyzed, without `Generic` and `Protocol`
+            Some(info) => {
+                members.bases = info.bases.clone();
+                members.type_params = info.type_params.clone();
+                members.attributes = info.attribute_types.iter().map(|(name, ty)| (name.clone(), ty.clone())).collect();
+                let generated = info.methods.iter().filter(|method| info.is_synthesized(method));
+                members.declared_methods = generated.map(|method| (method.name.clone(), method.clone())).collect();
+                if let Some(Synthesized::Enum { members: names }) = &info.synthesized {
+                    members.enum_members = names.clone();
+                    members.enum_value = Some(Type::Any);
+                }
+            }
+            None => {
+                for &base in bases {
+                    if let Some(ExprKind::Identifier { name }) = self.arena.expr(base) {
+                        members.bases.push(name.clone());
+                    }
+                }
+            }
+        }
+        for &id in body {
+            if let Some(StmtKind::FunctionDef { name, decorators, .. }) = self.arena.stmt(id) {
+                // The setter and deleter of a property keep its getter
+                let accessor = decorators.iter().any(|&decorator| match self.arena.expr(decorator) {
+                    Some(ExprKind::Attribute { attribute, .. }) => attribute == "setter" || attribute == "deleter",
+                    _ => false,
+                });
+                if !accessor || !members.methods.contains_key(name) {
+                    members.methods.insert(name.clone(), id);
+                }
+                self.method_classes.insert(id, class.to_string());
+            }
+        }
+        self.classes.entry(class.to_string()).or_insert(members);
+    }
+
+    // Statements
+
+    fn infer_block(&mut self, body: &[NodeId]) {
+        for &id in body {
+            self.infer_statement(id);
+        }
+    }
+
+    fn infer_statement(&mut self, id: NodeId) {
+        let Some(statement) = self.arena.stmt(id) else {
+            return;
+        };
+        match statement {
+            StmtKind::Expression(expr) => {
+                self.infer_expression(*expr);
+            }
+            StmtKind::Assignment { targets, value } => {
+                let value_type = self.infer_expression(*value);
+                for &target in targets {
+                    self.assign(target, value_type.clone());
+                }
+            }
+            StmtKind::AnnAssignment { target, annotation, value } => {
+                let declared = self.annotation(*annotation);
+                let inferred = value.map(|value| self.infer_expression(value));
+                if let Some(bound) = declared.or(inferred) {
+                    self.bind(target, bound);
+                }
+            }
+            StmtKind::AugmentedAssignment { target, op, value } => {
+                let current = self.infer_expression(*target);
+                let value_type = self.infer_expression(*value);
+                let result = binary_result(augmented_operator(*op), &current, &value_type).unwrap_or(Type::Any);
+                self.assign(*target, result);
+                // The target is read first, so its node keeps the type read
+                self.types.insert(*target, current);
+            }
+            StmtKind::Return { value } => {
+                let returned = match value {
+                    Some(value) => self.infer_expression(*value),
+                    None => Type::None,
+                };
+                if self.reachable() {
+                    if let Some(returned_types) = self.returned.last_mut() {
+                        returned_types.push(returned);
+                    }
+                }
+                self.set_unreachable();
+            }
+            StmtKind::Assert { condition, message } => {
+                self.infer_expression(*condition);
+                if let Some(message) = message {
+                    self.infer_expression(*message);
+                }
+                // Past the assert the condition holds
+                let narrowing = self.narrowing(*condition);
+                self.narrow(&narrowing.when_true);
+                if is_constant(self.arena, *condition, false) {
+                    self.set_unreachable();
+                }
+            }
+            StmtKind::Del { targets } => {
+                for &target in targets {
+                    self.infer_expression(target);
+                    if let Some(path) = narrowed_name(self.arena, target) {
+                        self.frames.last_mut().expect("module frame").forget(&path);
+                    }
+                }
+            }
+            StmtKind::Raise { exception } => {
+                if let Some(exception) = exception {
+                    self.infer_expression(*exception);
+                }
+                self.set_unreachable();
+            }
+            StmtKind::Break | StmtKind::Continue => {
+                let state = self.state();
+                if let Some(exits) = self.loops.last_mut() {
+                    match statement {
+                        StmtKind::Break => exits.breaks.push(state),
+                        _ => exits.continues.push(state),
+                    }
+                }
+                self.set_unreachable();
+            }
+            StmtKind::If { condition, then_block, elif_blocks, else_block } => {
+                let original = self.state();
+                let mut branches = Vec::new();
+                for (condition, block) in std::iter::once((condition, then_block)).chain(elif_blocks.iter().map(|(c, b)| (c, b))) {
+                    self.infer_expression(*condition);
+                    let narrowing = self.narrowing(*condition);
+                    let before = self.state();
+                    self.narrow(&narrowing.when_true);
+                    self.infer_block(block);
+                    branches.push(self.state());
+                    self.restore(before);
+                    self.narrow(&narrowing.when_false);
+                }
+                match else_block {
+                    Some(block) => {
+                        self.infer_block(block);
+                        branches.push(self.state());
+                    }
+                    // Falling through comes first, so an `if` that may
+                    // rebind a name gives the original type first
+                    None => branches.insert(0, self.state()),
+                }
+                let mut joined = self.join(branches);
+                // Narrowed paths that meet again give back the type as it
+                // was written
+                for (name, value) in joined.variables.iter_mut() {
+                    if let Some(before) = original.variables.get(name) {
+                        if same_members(before, value) {
+                            *value = before.clone();
+                        }
+                    }
+                }
+                self.restore(joined);
+            }
+            StmtKind::While { condition, body, else_block } => {
+                let breaks = self.infer_loop(|inference| {
+                    inference.infer_expression(*condition);
+                    let narrowing = inference.narrowing(*condition);
+                    inference.narrow(&narrowing.when_true);
+                    inference.infer_block(body);
+                });
+                // The loop ends normally when the condition is false
+                let narrowing = self.narrowing(*condition);
+                self.narrow(&narrowing.when_false);
+                if is_constant(self.arena, *condition, true) {
+                    self.set_unreachable();
+                }
+                if let Some(block) = else_block {
+                    self.infer_block(block);
+                }
+                let exits = std::iter::once(self.state()).chain(breaks).collect();
+                let joined = self.join(exits);
+                self.restore(joined);
+            }
+            StmtKind::For { target, iter, body, else_block } => {
+                let iterable = self.infer_expression(*iter);
+                let element = self.element_type(&iterable);
+                let breaks = self.infer_loop(|inference| {
+                    inference.assign(*target, element.clone());
+                    inference.infer_block(body);
+                });
+                if let Some(block) = else_block {
+                    self.infer_block(block);
+                }
+                let exits = std::iter::once(self.state()).chain(breaks).collect();
+                let joined = self.join(exits);
+                self.restore(joined);
+            }
+            StmtKind::FunctionDef { name, decorators, .. } => {
+                for &decorator in decorators {
+                    self.infer_expression(decorator);
+                }
+                let function_type = self.function_type(id, false);
+                // Decorators may replace the function with anything
+                if decorators.is_empty() {
+                    self.bind_definition(name, function_type, id);
+                } else {
+                    self.bind(name, Type::Any);
+                }
+            }
+            StmtKind::ClassDef { name, bases, body, decorators, metaclass } => {
+                for &expr in bases.iter().chain(decorators).chain(metaclass) {
+                    self.infer_expression(expr);
+                }
+                self.collect_members(id, name, bases, body);
+                self.frames.push(Frame::new(FrameKind::Class, HashMap::new()));
+                self.infer_block(body);
+                self.frames.pop();
+                let value = self.enum_value(id, body);
+                if let Some(members) = self.classes.get_mut(name).filter(|members| members.enum_value.is_some()) {
+                    members.enum_value = Some(value);
+                }
+                self.bind_definition(name, class_object(Type::instance(name)), id);
+            }
+            StmtKind::FromImport { items, .. } => {
+                for item in items {
+                    self.bind_import(&item.name, item.alias.as_deref(), item.position);
+                }
+            }
+            StmtKind::Import { .. }
+            | StmtKind::Pass
+            | StmtKind::Global { .. }
+            | StmtKind::Nonlocal { .. } => {}
+        }
+    }
+
+    /// Run a loop body until the types at the top of the loop stop
+    /// changing, or three times at most. The body may also not run at all.
+    /// Leaves the state at the top of the loop and returns the states at
+    /// each `break`.
+    fn infer_loop(&mut self, mut body: impl FnMut(&mut Self)) -> Vec<FlowState> {
+        let mut head = self.state();
+        let mut breaks = Vec::new();
+        for _ in 0..3 {
+            self.restore(head.clone());
+            self.loops.push(LoopExits::default());
+            body(self);
+            let exits = self.loops.pop().unwrap_or_default();
+            breaks = exits.breaks;
+            let states = [head.clone(), self.state()].into_iter().chain(exits.continues).collect();
+            let next = self.join(states);
+            let stable = next == head;
+            head = next;
+            if stable {
+                break;
+            }
+        }
+        self.restore(head);
+        breaks
+    }
+
+    /// Bind the names in an assignment target to a value of type `value`
+    fn assign(&mut self, target: NodeId, value: Type) {
+        match self.arena.expr(target) {
+            Some(ExprKind::Identifier { name }) => {
+                let name = name.clone();
+                self.bind(&name, value.clone());
+            }
+            Some(ExprKind::Tuple { elements }) | Some(ExprKind::List { elements }) => {
+                let elements = elements.clone();
+                let starred = elements
+                    .iter()
+                    .position(|&element| matches!(self.arena.expr(element), Some(ExprKind::Starred { .. })));
+                for (index, &element) in elements.iter().enumerate() {
+                    let element_value = match (&value, starred) {
+                        (Type::Tuple(types), None) if types.len() == elements.len() => types[index].clone(),
+                        (_, Some(star)) if star == index => Type::List(Box::new(element_type(&value))),
+                        _ => element_type(&value),
+                    };
+                    self.assign(element, element_value);
+                }
+            }
+            Some(ExprKind::Starred { value: inner }) => {
+                let inner = *inner;
+                self.assign(inner, value.clone());
+            }
+            Some(ExprKind::Parenthesized { expr }) => {
+                let expr = *expr;
+                self.assign(expr, value.clone());
+            }
+            Some(ExprKind::Attribute { object, attribute }) => {
+                let attribute = attribute.clone();
+                if let Some(path) = narrowed_name(self.arena, target) {
+                    self.frames.last_mut().expect("module frame").forget(&path);
+                }
+                if let Type::Instance { class, .. } = self.infer_expression(*object) {
+                    if let Some(members) = self.classes.get_mut(&class) {
+                        let merged = match members.attributes.remove(&attribute) {
+                            Some(existing) => Type::union([existing, value.clone()]),
+                            None => value.clone(),
+                        };
+                        members.attributes.insert(attribute, merged);
+                    }
+                }
+            }
+            Some(ExprKind::Subscript { object, index }) => {
+                self.infer_expression(*object);
+                self.infer_expression(*index);
+            }
+            _ => {}
+        }
+        self.types.insert(target, value);
+    }
+
+    /// Bind a name in the innermost scope that holds variables
+    fn bind(&mut self, name: &str, value: Type) {
+        let frame = self.frames.last_mut().expect("module frame");
+        frame.definitions.remove(name);
+        frame.imports.remove(name);
+        frame.forget(name);
+        frame.variables.insert(name.to_string(), value);
+    }
+
+    /// Bind the names of `from module import name`, or of every name for
+    /// `*`, to the types their module declares them with
+    fn bind_import(&mut self, name: &str, alias: Option<&str>, position: SourcePosition) {
+        let symbols: Vec<&Symbol> = self
+            .imported
+            .get(&position)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|symbol| name == "*" || symbol.name == alias.unwrap_or(name))
+            .collect();
+        if symbols.is_empty() && name != "*" {
+            self.bind(alias.unwrap_or(name), Type::Any);
+        }
+        for symbol in symbols {
+            let value = match (&symbol.class_info, &symbol.signature, &symbol.declared_type) {
+                (Some(info), ..) => {
+                    self.declare_class(info);
+                    class_object(Type::instance(&info.name))
+                }
+                (None, Some(signature), _) => signature.callable_type(),
+                (None, None, Some(declared)) => (**declared).clone(),
+                (None, None, None) => Type::Any,
+            };
+            self.bind(&symbol.name, value);
+            let frame = self.frames.last_mut().expect("module frame");
+            frame.imports.insert(symbol.name.clone(), position);
+        }
+    }
+
+    /// Record the members of a class imported from another module
+    fn declare_class(&mut self, info: &ClassInfo) {
+        let members = ClassTypes {
+            bases: info.bases.clone(),
+            methods: HashMap::new(),
+            attributes: info.attribute_types.iter().map(|(name, ty)| (name.clone(), ty.clone())).collect(),
+            type_params: info.type_params.clone(),
+            declared_methods: info.methods.iter().map(|method| (method.name.clone(), method.clone())).collect(),
+            enum_members: match &info.synthesized {
+                Some(Synthesized::Enum { members }) => members.clone(),
+                _ => Vec::new(),
+            },
+            enum_value: matches!(info.synthesized, Some(Synthesized::Enum { .. })).then_some(Type::Any),
+        };
+        self.classes.entry(info.name.clone()).or_insert(members);
+    }
+
+    /// The imported symbol a name read at this point is bound to, if any
+    fn imported_symbol(&self, name: &str) -> Option<&'a Symbol> {
+        let innermost = self.frames.len() - 1;
+        for (index, frame) in self.frames.iter().enumerate().rev() {
+            if frame.kind == FrameKind::Class && index != innermost {
+                continue;
+            }
+            if frame.variables.contains_key(name) {
+                let position = frame.imports.get(name)?;
+                return self.imported.get(position)?.iter().copied().find(|symbol| symbol.name == name);
+            }
+        }
+        None
+    }
+
+    /// Bind a name to the function or class a `def` or `class` statement
+    /// defines
+    fn bind_definition(&mut self, name: &str, value: Type, definition: NodeId) {
+        self.bind(name, value);
+        let frame = self.frames.last_mut().expect("module frame");
+        frame.definitions.insert(name.to_string(), definition);
+    }
+
+    // Control flow
+
+    fn state(&self) -> FlowState {
+        let frame = self.frames.last().expect("module frame");
+        FlowState {
+            variables: frame.variables.clone(),
+            paths: frame.paths.clone(),
+            reachable: frame.reachable,
+        }
+    }
+
+    fn restore(&mut self, state: FlowState) {
+        let frame = self.frames.last_mut().expect("module frame");
+        frame.variables = state.variables;
+        frame.paths = state.paths;
+        frame.reachable = state.reachable;
+    }
+
+    fn reachable(&self) -> bool {
+        self.frames.last().expect("module frame").reachable
+    }
+
+    fn set_unreachable(&mut self) {
+        self.frames.last_mut().expect("module frame").reachable = false;
+    }
+
+    /// Merge the states of control flow paths that meet. Paths that cannot
+    /// reach the meeting point are left out, unless none can.
+    fn join(&self, states: Vec<FlowState>) -> FlowState {
+        let reachable: Vec<&FlowState> = states.iter().filter(|state| state.reachable).collect();
+        let joined: Vec<&FlowState> = if reachable.is_empty() { states.iter().collect() } else { reachable };
+        let mut variables: HashMap<String, Type> = HashMap::new();
+        for state in &joined {
+            for (name, value) in &state.variables {
+                if variables.contains_key(name) {
+                    continue;
+                }
+                // A name bound on some paths only has its outer type on the
+                // others, if it has one
+                let outer = self.outer_variable(name);
+                let types = joined
+                    .iter()
+                    .filter_map(|state| state.variables.get(name).cloned().or_else(|| outer.clone()));
+                let merged = Type::union(types);
+                variables.insert(name.clone(), if merged == Type::Never { value.clone() } else { merged });
+            }
+        }
+        // An attribute path stays narrowed only if every path narrowed it
+        let mut paths = HashMap::new();
+        if let Some(first) = joined.first() {
+            for path in first.paths.keys() {
+                let types: Option<Vec<Type>> = joined.iter().map(|state| state.paths.get(path).cloned()).collect();
+                if let Some(types) = types {
+                    paths.insert(path.clone(), Type::union(types));
+                }
+            }
+        }
+        FlowState {
+            variables,
+            paths,
+            reachable: states.iter().any(|state| state.reachable),
+        }
+    }
+
+    /// The type of a variable of an enclosing scope
+    fn outer_variable(&self, name: &str) -> Option<Type> {
+        let innermost = self.frames.len() - 1;
+        self.frames[..innermost]
+            .iter()
+            .rev()
+            .filter(|frame| frame.kind != FrameKind::Class)
+            .find_map(|frame| frame.variables.get(name).cloned())
+    }
+
+    /// Give names and attribute paths their narrowed types in the
+    /// innermost scope, returning what they were before
+    fn narrow(&mut self, narrowed: &HashMap<String, Type>) -> Vec<(String, Option<Type>)> {
+        let frame = self.frames.last_mut().expect("module frame");
+        narrowed
+            .iter()
+            .map(|(name, value)| {
+                let types = if is_path(name) { &mut frame.paths } else { &mut frame.variables };
+                (name.clone(), types.insert(name.clone(), value.clone()))
+            })
+            .collect()
+    }
+
+    /// Undo a narrowing applied with `narrow`
+    fn widen(&mut self, previous: Vec<(String, Option<Type>)>) {
+        let frame = self.frames.last_mut().expect("module frame");
+        for (name, value) in previous {
+            let types = if is_path(&name) { &mut frame.paths } else { &mut frame.variables };
+            match value {
+                Some(value) => types.insert(name, value),
+                None => types.remove(&name),
+            };
+        }
+    }
+
+    /// The types the names tested by a condition have when it is true and
+    /// when it is false: `x is None` and `x is not None`, the truthiness of
+    /// `x`, `isinstance(x, C)`, and `not`, `and` and `or` of those
+    fn narrowing(&mut self, condition: NodeId) -> Narrowing {
+        let mut narrowing = Narrowing::default();
+        let Some(expression) = self.arena.expr(condition) else {
+            return narrowing;
+        };
+        match expression {
+            ExprKind::Parenthesized { expr } => return self.narrowing(*expr),
+            ExprKind::UnaryOp { op: UnaryOperator::Not, operand } => {
+                let inner = self.narrowing(*operand);
+                narrowing.when_true = inner.when_false;
+                narrowing.when_false = inner.when_true;
+            }
+            ExprKind::Identifier { .. } | ExprKind::Attribute { .. } => {
+                let Some(name) = narrowed_name(self.arena, condition) else {
+                    return narrowing;
+                };
+                let current = self.subject_type(condition, &name);
+                if current.is_optional() {
+                    narrowing.when_true.insert(name, current.without_none());
+                }
+            }
+            ExprKind::AssignmentExpr { target, .. } => {
+                let target = target.clone();
+                let current = self.lookup(&target);
+                if current.is_optional() {
+                    narrowing.when_true.insert(target, current.without_none());
+                }
+            }
+            ExprKind::BinaryOp { left, op, right } => {
+                let (left, op, right) = (*left, *op, *right);
+                match op {
+                    BinaryOperator::Is | BinaryOperator::IsNot | BinaryOperator::Equal | BinaryOperator::NotEqual => {
+                        let subject = if is_none(self.arena, right) {
+                            left
+                        } else if is_none(self.arena, left) {
+                            right
+                        } else {
+                            return narrowing;
+                        };
+                        let Some(name) = narrowed_name(self.arena, subject) else {
+                            return narrowing;
+                        };
+                        let current = self.subject_type(subject, &name);
+                        let mut when_none = HashMap::new();
+                        let mut when_not_none = HashMap::new();
+                        if current.is_optional() || current == Type::Any {
+                            when_none.insert(name.clone(), Type::None);
+                        }
+                        if current.is_optional() {
+                            when_not_none.insert(name, current.without_none());
+                        }
+                        if matches!(op, BinaryOperator::Is | BinaryOperator::Equal) {
+                            narrowing.when_true = when_none;
+                            narrowing.when_false = when_not_none;
+                        } else {
+                            narrowing.when_true = when_not_none;
+                            narrowing.when_false = when_none;
+                        }
+                    }
+                    BinaryOperator::And | BinaryOperator::Or => {
+                        let is_and = op == BinaryOperator::And;
+                        let left = self.narrowing(left);
+                        // The right operand is only evaluated when the left
+                        // one does not decide the result
+                        let previous = self.narrow(if is_and { &left.when_true } else { &left.when_false });
+                        let reassigns = self.may_reassign_attributes(right);
+                        let right = self.narrowing(right);
+                        self.widen(previous);
+                        let (left_decides, left_continues, right_decides, right_continues) = if is_and {
+                            (left.when_false, left.when_true, right.when_false, right.when_true)
+                        } else {
+                            (left.when_true, left.when_false, right.when_true, right.when_false)
+                        };
+                        // Both operands hold: the right narrowing refines the
+                        // left one, whose attribute paths a call in the right
+                        // one may reassign
+                        let mut both = left_continues.clone();
+                        if reassigns {
+                            both.retain(|name, _| !is_path(name));
+                        }
+                        both.extend(right_continues);
+                        // Either operand decides: only names narrowed on both paths
+                        let mut either = HashMap::new();
+                        for (name, left_type) in &left_decides {
+                            let right_type = right_decides
+                                .get(name)
+                                .cloned()
+                                .or_else(|| left_continues.get(name).cloned());
+                            if let Some(right_type) = right_type {
+                                either.insert(name.clone(), Type::union([left_type.clone(), right_type]));
+                            }
+                        }
+                        if is_and {
+                            narrowing.when_true = both;
+                            narrowing.when_false = either;
+                        } else {
+                            narrowing.when_true = either;
+                            narrowing.when_false = both;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            ExprKind::Call { function, arguments, keywords } => {
+                let is_isinstance = matches!(
+                    self.arena.expr(*function),
+                    Some(ExprKind::Identifier { name }) if name == "isinstance"
+                ) && self.definition_of("isinstance").is_none();
+                if !is_isinstance || arguments.len() != 2 || !keywords.is_empty() {
+                    return narrowing;
+                }
+                let (subject, classes) = (arguments[0], arguments[1]);
+                let Some(name) = narrowed_name(self.arena, subject) else {
+                    return narrowing;
+                };
+                let Some(classes) = self.isinstance_classes(classes) else {
+                    return narrowing;
+                };
+                let current = self.subject_type(subject, &name);
+                narrowing.when_true.insert(name.clone(), self.narrow_to_classes(&current, &classes));
+                let remaining = self.narrow_out_classes(&current, &classes);
+                if remaining != current && remaining != Type::Never {
+                    narrowing.when_false.insert(name, remaining);
+                }
+            }
+            _ => {}
+        }
+        narrowing
+    }
+
+    /// The type of the name or attribute path a condition tests, as it is
+    /// read at this point
+    fn subject_type(&mut self, subject: NodeId, name: &str) -> Type {
+        if is_path(name) {
+            self.types.get(&subject).cloned().unwrap_or(Type::Any)
+        } else {
+            self.lookup(name)
+        }
+    }
+
+    /// The narrowed type of an attribute path, which holds in the scope
+    /// that narrowed it and the comprehensions inside it
+    fn narrowed_path(&self, path: &str) -> Option<Type> {
+        for frame in self.frames.iter().rev() {
+            if let Some(found) = frame.paths.get(path) {
+                return Some(found.clone());
+            }
+            if frame.kind != FrameKind::Inline {
+                break;
+            }
+        }
+        None
+    }
+
+    /// Whether a call to `function` surely leaves the attributes of every
+    /// object as they are: it runs a builtin that does not set attributes
+    /// or run code
+    fn keeps_attributes(&self, function: NodeId) -> bool {
+        match self.arena.expr(function) {
+            Some(ExprKind::Identifier { name }) => {
+                self.is_builtin(name) && !matches!(name.as_str(), "setattr" | "delattr" | "exec" | "eval")
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether evaluating an expression may make a call that reassigns
+    /// attributes
+    fn may_reassign_attributes(&self, id: NodeId) -> bool {
+        if let Some(ExprKind::Call { function, .. }) = self.arena.expr(id) {
+            if !self.keeps_attributes(*function) {
+                return true;
+            }
+        }
+        self.arena.children(id).into_iter().any(|child| self.may_reassign_attributes(child))
+    }
+
+    /// Forget every narrowed attribute path a call may reassign: those of
+    /// the innermost scope and the comprehensions inside it
+    fn forget_paths(&mut self) {
+        for frame in self.frames.iter_mut().rev() {
+            frame.paths.clear();
+            if frame.kind != FrameKind::Inline {
+                break;
+            }
+        }
+    }
+
+    /// The instance types of the second argument of `isinstance`, a class
+    /// or a tuple of classes
+    fn isinstance_classes(&mut self, classes: NodeId) -> Option<Vec<Type>> {
+        match self.arena.expr(classes)? {
+            ExprKind::Parenthesized { expr } => self.isinstance_classes(*expr),
+            ExprKind::Tuple { elements } => {
+                let elements = elements.clone();
+                let mut instances = Vec::new();
+                for element in elements {
+                    instances.extend(self.isinstance_classes(element)?);
+                }
+                Some(instances)
+            }
+            ExprKind::Identifier { name } => {
+                let name = name.clone();
+                match self.lookup(&name) {
+                    Type::Instance { class, mut arguments } if class == "type" && arguments.len() == 1 => {
+                        Some(vec![arguments.remove(0)])
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// The part of `current` that is an instance of one of `classes`
+    fn narrow_to_classes(&self, current: &Type, classes: &[Type]) -> Type {
+        let members = match current {
+            Type::Union(members) => members.clone(),
+            other => vec![other.clone()],
+        };
+        let mut narrowed = Vec::new();
+        for member in members {
+            let Some(member_class) = member.class_name() else {
+                // Without a class, as for `Any`, the value may be any of them
+                narrowed.extend(classes.iter().cloned());
+                continue;
+            };
+            for class in classes {
+                let Some(class_name) = class.class_name() else {
+                    continue;
+                };
+                if self.derives_from(member_class, class_name) == Some(true) {
+                    narrowed.push(member.clone());
+                } else if self.derives_from(class_name, member_class) != Some(false) {
+                    narrowed.push(class.clone());
+                }
+            }
+        }
+        match Type::union(narrowed) {
+            Type::Never => Type::union(classes.iter().cloned()),
+            narrowed => narrowed,
+        }
+    }
+
+    /// The part of `current` that is surely not an instance of any of
+    /// `classes`
+    fn narrow_out_classes(&self, current: &Type, classes: &[Type]) -> Type {
+        let members = match current {
+            Type::Union(members) => members.clone(),
+            other => vec![other.clone()],
+        };
+        Type::union(members.into_iter().filter(|member| {
+            let Some(member_class) = member.class_name() else {
+                return true;
+            };
+            !classes.iter().any(|class| {
+                class.class_name().is_some_and(|class_name| self.derives_from(member_class, class_name) == Some(true))
+            })
+        }))
+    }
+
+    /// Whether `class` derives from `base`, or `None` when the bases of a
+    /// class involved are not known
+    fn derives_from(&self, class: &str, base: &str) -> Option<bool> {
+        self.derives_from_within(class, base, 0)
+    }
+
+    fn derives_from_within(&self, class: &str, base: &str, depth: usize) -> Option<bool> {
+        if class == base || base == "object" {
+            return Some(true);
+        }
+        // Guards against cyclic bases
+        if depth > 32 {
+            return None;
+        }
+        if let Some(members) = self.classes.get(class) {
+            let mut known = true;
+            for parent in &members.bases {
+                match self.derives_from_within(parent, base, depth + 1) {
+                    Some(true) => return Some(true),
+                    Some(false) => {}
+                    None => known = false,
+                }
+            }
+            return known.then_some(false);
+        }
+        if class == "NoneType" {
+            return Some(false);
+        }
+        match builtins::lookup(class).map(|builtin| builtin.kind) {
+            Some(BuiltinKind::Class) | Some(BuiltinKind::Exception) => {
+                Some(classes::builtin_mro(class).iter().any(|ancestor| ancestor == base))
+            }
+            _ => None,
+        }
+    }
+
+    /// The resolved type of an annotation node
+    fn annotation(&self, annotation: NodeId) -> Option<Type> {
+        let position = self.arena.position(annotation)?;
+        self.symbol_table.annotation_type(position).cloned()
+    }
+
+    // Functions
+
+    /// The callable type of a function definition, inferring its body if it
+    /// has not been inferred yet. With `lazy`, the body is inferred from a
+    /// call reached before the definition, and sees only module-level names.
+    fn function_type(&mut self, id: NodeId, lazy: bool) -> Type {
+        let Some(StmtKind::FunctionDef { parameters, return_type, .. }) = self.arena.stmt(id) else {
+            return Type::Any;
+        };
+        let declared_return = return_type.and_then(|annotation| self.annotation(annotation));
+
+        if !self.returns.contains_key(&id) && !self.in_progress.contains(&id) {
+            let outer = if lazy { self.frames.split_off(1) } else { Vec::new() };
+            self.infer_function(id);
+            self.frames.extend(outer);
+        }
+        let returns = match declared_return {
+            Some(declared) => declared,
+            None if self.gradual => Type::Any,
+            None => self.returns.get(&id).cloned().unwrap_or(Type::Any),
+        };
+
+        let positional = parameters
+            .iter()
+            .filter(|param| matches!(param.kind, ParameterKind::PositionalOnly | ParameterKind::Regular))
+            .map(|param| param.type_annotation.and_then(|annotation| self.annotation(annotation)).unwrap_or(Type::Any))
+            .collect();
+        Type::Callable {
+            parameters: Some(positional),
+            returns: Box::new(returns),
+        }
+    }
+
+    /// Infer the body of a function and record its return type
+    fn infer_function(&mut self, id: NodeId) {
+        let Some(StmtKind::FunctionDef { parameters, body, return_type, decorators, .. }) = self.arena.stmt(id)
+        else {
+            return;
+        };
+        self.in_progress.insert(id);
+
+        // The first parameter of a method is the instance, or the class
+        // for a class method
+        let receiver = self.method_classes.get(&id).and_then(|class| match method_binding(self.arena, decorators) {
+            Some(MethodBinding::Static) => None,
+            Some(MethodBinding::Class) => Some(class_object(Type::instance(class))),
+            _ => Some(Type::instance(class)),
+        });
+
+        let mut variables = HashMap::new();
+        for (index, param) in parameters.iter().enumerate() {
+            let declared = param.type_annotation.and_then(|annotation| self.annotation(annotation));
+            let param_type = match (declared, &param.kind) {
+                (Some(declared), ParameterKind::VarArgs) => Type::VarTuple(Box::new(declared)),
+                (Some(declared), ParameterKind::VarKwargs) => Type::Dict(Box::new(Type::Str), Box::new(declared)),
+                (Some(declared), _) => declared,
+                (None, ParameterKind::VarArgs) => Type::VarTuple(Box::new(Type::Any)),
+                (None, ParameterKind::VarKwargs) => Type::Dict(Box::new(Type::Str), Box::new(Type::Any)),
+                (None, _) if index == 0 && receiver.is_some() => receiver.clone().unwrap(),
+                (None, _) => Type::Any,
+            };
+            variables.insert(param.name.clone(), param_type);
+        }
+        for default in parameters.iter().filter_map(|param| param.default) {
+            self.infer_expression(default);
+        }
+
+        self.frames.push(Frame::new(FrameKind::Function, variables));
+        self.returned.push(Vec::new());
+        // Loops around the definition are not the function's own
+        let outer_loops = std::mem::take(&mut self.loops);
+        self.infer_block(body);
+        self.loops = outer_loops;
+        let mut returned = self.returned.pop().unwrap_or_default();
+        // Falling off the end of the body returns `None`
+        if self.frames.pop().is_some_and(|frame| frame.reachable) {
+            returned.push(Type::None);
+        }
+        let inferred = match return_type.and_then(|annotation| self.annotation(annotation)) {
+            Some(declared) => declared,
+            None => Type::union(returned),
+        };
+        self.returns.insert(id, inferred);
+        self.in_progress.remove(&id);
+    }
+
+    // Expressions
+
+    /// Infer the type of an expression and record it
+    fn infer_expression(&mut self, id: NodeId) -> Type {
+        let inferred = self.expression_type(id);
+        self.types.insert(id, inferred.clone());
+        inferred
+    }
+
+    fn expression_type(&mut self, id: NodeId) -> Type {
+        let Some(expression) = self.arena.expr(id) else {
+            return Type::Any;
+        };
+        match expression {
+            ExprKind::Literal(literal) => literal_type(literal),
+            ExprKind::Identifier { name } => {
+                let name = name.clone();
+                self.lookup(&name)
+            }
+            ExprKind::BinaryOp { left, op, right } => {
+                let (left_id, op, right) = (*left, *op, *right);
+                let left = self.infer_expression(left_id);
+                // The right operand of `and` and `or` only runs when the
+                // left one is true or false respectively
+                let previous = match op {
+                    BinaryOperator::And => Some(self.narrowing(left_id).when_true),
+                    BinaryOperator::Or => Some(self.narrowing(left_id).when_false),
+                    _ => None,
+                }
+                .map(|narrowed| self.narrow(&narrowed));
+                let right = self.infer_expression(right);
+                if let Some(previous) = previous {
+                    self.widen(previous);
+                }
+                binary_result(op, &left, &right).unwrap_or(Type::Any)
+            }
+            ExprKind::UnaryOp { op, operand } => {
+                let op = *op;
+                let operand = self.infer_expression(*operand);
+                unary_result(op, &operand).unwrap_or(Type::Any)
+            }
+            ExprKind::Parenthesized { expr } => self.infer_expression(*expr),
+            ExprKind::Call { function, arguments, keywords } => {
+                let (function, arguments, keywords) = (*function, arguments.clone(), keywords.clone());
+                let callee = self.infer_expression(function);
+                let argument_types: Vec<Type> =
+                    arguments.iter().map(|&argument| self.infer_expression(argument)).collect();
+                for (_, value) in &keywords {
+                    self.infer_expression(*value);
+                }
+                if let Some(callee) = self.callee(function) {
+                    self.callees.insert(id, callee);
+                } else if let Some(callee) = self.external_callee(function) {
+                    self.external_callees.insert(id, callee);
+                }
+                let builtin = match self.arena.expr(function) {
+                    Some(ExprKind::Identifier { name }) if self.is_builtin(name) => Some(name.clone()),
+                    _ => None,
+                };
+                if !self.keeps_attributes(function) {
+                    self.forget_paths();
+                }
+                match builtin {
+                    // The stub's return type covers the builtins without a
+                    // rule of their own
+                    Some(name) => match builtin_call(&name, &argument_types) {
+                        Type::Any => match self.external_callees.get(&id) {
+                            Some(callee) => {
+                                let declared = callee.signature.callable_type();
+                                call_result(&declared, &argument_types, &|class, base| self.derives_from(class, base))
+                            }
+                            None => Type::Any,
+                        },
+                        result => result,
+                    },
+                    None => match call_result(&callee, &argument_types, &|class, base| self.derives_from(class, base)) {
+                        Type::Instance { class, arguments } if arguments.is_empty() => self
+                            .generic_instance(&class, &argument_types)
+                            .unwrap_or(Type::Instance { class, arguments }),
+                        result => result,
+                    },
+                }
+            }
+            ExprKind::Attribute { object, attribute } => {
+                let attribute = attribute.clone();
+                let object = self.infer_expression(*object);
+                match narrowed_name(self.arena, id).and_then(|path| self.narrowed_path(&path)) {
+                    Some(narrowed) => narrowed,
+                    None => self.attribute_type(&object, &attribute),
+                }
+            }
+            ExprKind::Subscript { object, index } => {
+                let index_id = *index;
+                let object = self.infer_expression(*object);
+                self.infer_expression(index_id);
+                let literal_index = match self.arena.expr(index_id) {
+                    Some(ExprKind::Literal(Literal::Integer { value, .. })) => Some(*value),
+                    Some(ExprKind::UnaryOp { op: UnaryOperator::Minus, operand }) => match self.arena.expr(*operand) {
+                        Some(ExprKind::Literal(Literal::Integer { value, .. })) => Some(-*value),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                // `Color["RED"]` looks up a member of an enum by name
+                match self.enum_class(&object) {
+                    Some(class) => Type::instance(class),
+                    None => subscript_result(&object, literal_index),
+                }
+            }
+            ExprKind::List { elements } => {
+                let elements = elements.clone();
+                Type::List(Box::new(self.display_element(&elements)))
+            }
+            ExprKind::Set { elements } => {
+                let elements = elements.clone();
+                Type::Set(Box::new(self.display_element(&elements)))
+            }
+            ExprKind::Tuple { elements } => {
+                let elements = elements.clone();
+                let types: Vec<Type> = elements.iter().map(|&element| self.infer_expression(element)).collect();
+                if elements.iter().any(|&element| matches!(self.arena.expr(element), Some(ExprKind::Starred { .. }))) {
+                    Type::VarTuple(Box::new(self.display_element(&elements)))
+                } else {
+                    Type::Tuple(types)
+                }
+            }
+            ExprKind::Dict { pairs } => {
+                let pairs = pairs.clone();
+                let mut keys = Vec::new();
+                let mut values = Vec::new();
+                for (key, value) in pairs {
+                    keys.push(self.infer_expression(key));
+                    values.push(self.infer_expression(value));
+                }
+                Type::Dict(Box::new(element_or_any(keys)), Box::new(element_or_any(values)))
+            }
+            ExprKind::Lambda { parameters, body } => {
+                let (parameters, body) = (parameters.clone(), *body);
+                let variables = parameters.iter().map(|name| (name.clone(), Type::Any)).collect();
+                self.frames.push(Frame::new(FrameKind::Inline, variables));
+                let returns = self.infer_expression(body);
+                self.frames.pop();
+                Type::Callable {
+                    parameters: Some(vec![Type::Any; parameters.len()]),
+                    returns: Box::new(returns),
+                }
+            }
+            ExprKind::Conditional { condition, true_expr, false_expr } => {
+                let (condition, true_expr, false_expr) = (*condition, *true_expr, *false_expr);
+                self.infer_expression(condition);
+                let narrowing = self.narrowing(condition);
+                let previous = self.narrow(&narrowing.when_true);
+                let when_true = self.infer_expression(true_expr);
+                self.widen(previous);
+                let previous = self.narrow(&narrowing.when_false);
+                let when_false = self.infer_expression(false_expr);
+                self.widen(previous);
+                Type::union([when_true, when_false])
+            }
+            ExprKind::AssignmentExpr { target, value } => {
+                let target = target.clone();
+                let value = self.infer_expression(*value);
+                // The walrus binds in the scope containing a comprehension
+                let frame = self.frames.iter().rposition(|frame| frame.kind != FrameKind::Inline).unwrap_or(0);
+                self.frames[frame].forget(&target);
+                self.frames[frame].variables.insert(target, value.clone());
+                value
+            }
+            ExprKind::ListComp { element, generators } => {
+                let (element, generators) = (*element, generators.clone());
+                let element = self.comprehension(&generators, &[element]).remove(0);
+                Type::List(Box::new(element))
+            }
+            ExprKind::SetComp { element, generators } => {
+                let (element, generators) = (*element, generators.clone());
+                let element = self.comprehension(&generators, &[element]).remove(0);
+                Type::Set(Box::new(element))
+            }
+            ExprKind::DictComp { key, value, generators } => {
+                let (key, value, generators) = (*key, *value, generators.clone());
+                let mut types = self.comprehension(&generators, &[key, value]);
+                let value = types.remove(1);
+                Type::Dict(Box::new(types.remove(0)), Box::new(value))
+            }
+            ExprKind::GeneratorExpr { element, generators } => {
+                let (element, generators) = (*element, generators.clone());
+                let element = self.comprehension(&generators, &[element]).remove(0);
+                Type::Instance {
+                    class: "Generator".to_string(),
+                    arguments: vec![element, Type::None, Type::None],
+                }
+            }
+            ExprKind::Starred { value } => {
+                let value = self.infer_expression(*value);
+                Type::List(Box::new(element_type(&value)))
+            }
+        }
+    }
+
+    /// The common element type of a list, set or tuple display, where
+    /// `*iterable` contributes the iterable's elements
+    fn display_element(&mut self, elements: &[NodeId]) -> Type {
+        let types = elements
+            .iter()
+            .map(|&element| match self.arena.expr(element) {
+                Some(ExprKind::Starred { value }) => {
+                    let value = *value;
+                    let iterable = self.infer_expression(value);
+                    self.types.insert(element, Type::List(Box::new(element_type(&iterable))));
+                    element_type(&iterable)
+                }
+                _ => self.infer_expression(element),
+            })
+            .collect();
+        element_or_any(types)
+    }
+
+    /// Infer a comprehension, binding each target to the elements of its
+    /// iterable, and return the types of `results`
+    fn comprehension(&mut self, generators: &[crate::arena::Comprehension], results: &[NodeId]) -> Vec<Type> {
+        self.frames.push(Frame::new(FrameKind::Inline, HashMap::new()));
+        for generator in generators {
+            let iterable = self.infer_expression(generator.iter);
+            let element = self.element_type(&iterable);
+            self.bind(&generator.target, element);
+            // Later conditions and the results only see elements that
+            // passed each condition
+            for &condition in &generator.conditions {
+                self.infer_expression(condition);
+                let narrowing = self.narrowing(condition);
+                self.narrow(&narrowing.when_true);
+            }
+        }
+        let types = results.iter().map(|&result| self.infer_expression(result)).collect();
+        self.frames.pop();
+        types
+    }
+
+    /// The type of a name read at this point
+    fn lookup(&mut self, name: &str) -> Type {
+        // Class bodies are only visible directly inside them
+        let innermost = self.frames.len() - 1;
+        for (index, frame) in self.frames.iter().enumerate().rev() {
+            if frame.kind == FrameKind::Class && index != innermost {
+                continue;
+            }
+            if let Some(found) = frame.variables.get(name) {
+                return found.clone();
+            }
+        }
+        if let Some(&definition) = self.definitions.get(name) {
+            return match self.arena.stmt(definition) {
+                Some(StmtKind::FunctionDef { decorators, .. }) if decorators.is_empty() => {
+                    self.function_type(definition, true)
+                }
+                Some(StmtKind::ClassDef { .. }) => class_object(Type::instance(name)),
+                _ => Type::Any,
+            };
+        }
+        builtin_type(name)
+    }
+
+    /// The `def` or `class` statement a name read at this point is bound
+    /// by, if any
+    fn definition_of(&self, name: &str) -> Option<NodeId> {
+        let innermost = self.frames.len() - 1;
+        for (index, frame) in self.frames.iter().enumerate().rev() {
+            if frame.kind == FrameKind::Class && index != innermost {
+                continue;
+            }
+            if frame.variables.contains_key(name) {
+                return frame.definitions.get(name).copied();
+            }
+        }
+        self.definitions.get(name).copied()
+    }
+
+    /// Look up a member of a class defined in the module, then of its bases
+    fn find_member<T>(&self, class: &str, member: impl Fn(&ClassTypes) -> Option<T>) -> Option<T> {
+        let mut pending = vec![class];
+        let mut seen = HashSet::new();
+        while let Some(class) = pending.pop() {
+            let Some(members) = self.classes.get(class).filter(|_| seen.insert(class)) else {
+                continue;
+            };
+            if let Some(found) = member(members) {
+                return Some(found);
+            }
+            pending.extend(members.bases.iter().rev().map(String::as_str));
+        }
+        None
+    }
+
+    /// The nearest definition of a method along the bases of `class`
+    fn find_method(&self, class: &str, name: &str) -> Option<MethodSource> {
+        self.find_member(class, |members| match members.declared_methods.get(name) {
+            Some(method) => Some(MethodSource::Declared(method.clone())),
+            None => members.methods.get(name).copied().map(MethodSource::Defined),
+        })
+    }
+
+    /// The enum a class object is, if it is one
+    fn enum_class<'t>(&self, object: &'t Type) -> Option<&'t str> {
+        let Type::Instance { class, arguments } = object else {
+            return None;
+        };
+        match arguments.first() {
+            Some(Type::Instance { class: enum_class, .. }) if class == "type" => {
+                let members = self.classes.get(enum_class)?;
+                members.enum_value.is_some().then_some(enum_class.as_str())
+            }
+            _ => None,
+        }
+    }
+
+    /// The type of the elements of an iterable; iterating over an enum
+    /// gives its members
+    fn element_type(&self, iterable: &Type) -> Type {
+        match self.enum_class(iterable) {
+            Some(class) => Type::instance(class),
+            None => element_type(iterable),
+        }
+    }
+
+    /// The type of the values assigned to the members of an enum; `auto()`
+    /// numbers them, or names them in a `StrEnum`
+    fn enum_value(&self, class: NodeId, body: &[NodeId]) -> Type {
+        let info = self.arena.position(class).and_then(|position| self.class_infos.get(position));
+        let str_enum =
+            info.is_some_and(|info| info.bases.iter().any(|base| base.rsplit('.').next() == Some("StrEnum")));
+        let is_member = |target: &NodeId| {
+            matches!(self.arena.expr(*target), Some(ExprKind::Identifier { name }) if classes::is_enum_member(name))
+        };
+        let values: Vec<Type> = body
+            .iter()
+            .filter_map(|&id| match self.arena.stmt(id) {
+                Some(StmtKind::Assignment { targets, value }) if targets.iter().any(is_member) => Some(*value),
+                _ => None,
+            })
+            .map(|value| match self.arena.expr(value) {
+                Some(ExprKind::Call { function, .. }) if is_auto(self.arena, *function) => {
+                    if str_enum {
+                        Type::Str
+                    } else {
+                        Type::Int
+                    }
+                }
+                _ => self.types.get(&value).cloned().unwrap_or(Type::Any),
+            })
+            .collect();
+        if values.is_empty() {
+            Type::Any
+        } else {
+            Type::union(values)
+        }
+    }
+
+    /// The types substituted for the type parameters of a class and its
+    /// bases in the members of an instance with type arguments `arguments`.
+    /// Parameters without an argument are `Any`.
+    fn type_arguments(&self, class: &str, arguments: &[Type]) -> HashMap<String, Type> {
+        let mut substitution = HashMap::new();
+        let mut pending = vec![class];
+        let mut seen = HashSet::new();
+        while let Some(current) = pending.pop() {
+            let Some(members) = self.classes.get(current).filter(|_| seen.insert(current)) else {
+                continue;
+            };
+            for (index, param) in members.type_params.iter().enumerate() {
+                let argument = arguments.get(index).filter(|_| current == class).cloned().unwrap_or(Type::Any);
+                substitution.entry(param.clone()).or_insert(argument);
+            }
+            pending.extend(members.bases.iter().map(String::as_str));
+        }
+        substitution
+    }
+
+    /// The instance a call to a generic class makes, with its type
+    /// arguments solved from the arguments passed to `__init__`
+    fn generic_instance(&mut self, class: &str, arguments: &[Type]) -> Option<Type> {
+        let params = self.classes.get(class)?.type_params.clone();
+        if params.is_empty() {
+            return None;
+        }
+        let init = match self.find_method(class, "__init__")? {
+            MethodSource::Defined(init) => self.function_type(init, true),
+            MethodSource::Declared(init) => init.signature.callable_type(),
+        };
+        let Type::Callable { parameters: Some(parameters), .. } = init else {
+            return None;
+        };
+        let mut solutions = HashMap::new();
+        for (parameter, argument) in parameters.iter().skip(1).zip(arguments) {
+            parameter.solve(argument, &mut solutions);
+        }
+        if solutions.is_empty() {
+            return None;
+        }
+        Some(Type::Instance {
+            class: class.to_string(),
+            arguments: params.iter().map(|param| solutions.remove(param).unwrap_or(Type::Any)).collect(),
+        })
+    }
+
+    /// The function a call to the expression `function` runs, when it is
+    /// a function or class defined in the module
+    fn callee(&self, function: NodeId) -> Option<Callee> {
+        let method = |class: &str, name: &str| {
+            let MethodSource::Defined(definition) = self.find_method(class, name)? else {
+                return None;
+            };
+            match self.arena.stmt(definition) {
+                Some(StmtKind::FunctionDef { decorators, .. }) => method_binding(self.arena, decorators)
+                    .filter(|&binding| binding != MethodBinding::Property)
+                    .map(|binding| (definition, binding)),
+                _ => None,
+            }
+        };
+        match self.arena.expr(function)? {
+            ExprKind::Identifier { name } => {
+                let definition = self.definition_of(name)?;
+                match self.arena.stmt(definition)? {
+                    StmtKind::FunctionDef { decorators, .. } if decorators.is_empty() => Some(Callee {
+                        definition,
+                        bound: false,
+                    }),
+                    StmtKind::ClassDef { name, .. } => {
+                        let (definition, binding) = method(name, "__init__")?;
+                        Some(Callee {
+                            definition,
+                            bound: binding == MethodBinding::Instance,
+                        })
+                    }
+                    _ => None,
+                }
+            }
+            ExprKind::Attribute { object, attribute } => match self.types.get(object)? {
+                Type::Instance { class, arguments } if class == "type" => match arguments.first()? {
+                    Type::Instance { class, .. } => {
+                        let (definition, binding) = method(class, attribute)?;
+                        Some(Callee {
+                            definition,
+                            bound: binding == MethodBinding::Class,
+                        })
+                    }
+                    _ => None,
+                },
+                Type::Instance { class, .. } => {
+                    let (definition, binding) = method(class, attribute)?;
+                    Some(Callee {
+                        definition,
+                        bound: binding != MethodBinding::Static,
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// The builtin or imported function a call to the expression
+    /// `function` runs, when its signature is known
+    fn external_callee(&self, function: NodeId) -> Option<ExternalCallee> {
+        let method = |class: &str, name: &str, on_class: bool| {
+            let MethodSource::Declared(method) = self.find_method(class, name)? else {
+                return None;
+            };
+            let signature = match method.kind {
+                MethodKind::Instance if on_class => method.signature.clone(),
+                MethodKind::Instance | MethodKind::Class => method.signature.without_receiver(),
+                MethodKind::Static => method.signature.clone(),
+                MethodKind::Property => return None,
+            };
+            Some(ExternalCallee {
+                name: format!("{}.{}", class, name),
+                signature,
+            })
+        };
+        match self.arena.expr(function)? {
+            ExprKind::Identifier { name } if self.is_builtin(name) => {
+                let builtins = self.symbol_table.builtins_scope_id()?;
+                let signature = self.symbol_table.get_scope(builtins)?.lookup(name)?.signature.as_deref()?;
+                Some(ExternalCallee {
+                    name: name.clone(),
+                    signature: signature.clone(),
+                })
+            }
+            ExprKind::Identifier { name } if self.is_class(name) => method(name, "__init__", false),
+            ExprKind::Identifier { name } => {
+                let symbol = self.imported_symbol(name)?;
+                match (&symbol.class_info, &symbol.signature) {
+                    (Some(info), _) => method(&info.name, "__init__", false),
+                    (None, Some(signature)) => Some(ExternalCallee {
+                        name: name.clone(),
+                        signature: (**signature).clone(),
+                    }),
+                    (None, None) => None,
+                }
+            }
+            ExprKind::Attribute { object, attribute } => match self.types.get(object)? {
+                Type::Instance { class, arguments } if class == "type" => match arguments.first()? {
+                    Type::Instance { class, .. } => method(class, attribute, true),
+                    _ => None,
+                },
+                Type::Instance { class, .. } => method(class, attribute, false),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Whether a name read at this point is bound by a `class` statement
+    fn is_class(&self, name: &str) -> bool {
+        let definition = self.definition_of(name).and_then(|id| self.arena.stmt(id));
+        matches!(definition, Some(StmtKind::ClassDef { .. }))
+    }
+
+    /// Whether a name refers to a builtin, not shadowed by any binding
+    fn is_builtin(&self, name: &str) -> bool {
+        builtins::lookup(name).is_some()
+            && !self.definitions.contains_key(name)
+            && self.frames.iter().all(|frame| !frame.variables.contains_key(name))
+    }
+
+    /// The type of an attribute of a value
+    fn attribute_type(&mut self, object: &Type, attribute: &str) -> Type {
+        if let Some(class) = self.enum_class(object) {
+            if self.classes[class].enum_members.iter().any(|member| member == attribute) {
+                return Type::instance(class);
+            }
+        }
+        let Type::Instance { class, arguments } = object else {
+            return Type::Any;
+        };
+        if let Some(value) = self.find_member(class, |members| members.enum_value.clone()) {
+            match attribute {
+                "name" => return Type::Str,
+                "value" => return value,
+                _ => {}
+            }
+        }
+        let type_arguments = self.type_arguments(class, arguments);
+        if let Some(found) = self.find_member(class, |members| members.attributes.get(attribute).cloned()) {
+            return found.substitute(&type_arguments);
+        }
+        let method = match self.find_method(class, attribute) {
+            Some(MethodSource::Defined(method)) => method,
+            Some(MethodSource::Declared(method)) => return declared_method_type(&method).substitute(&type_arguments),
+            None => return Type::Any,
+        };
+        let binding = match self.arena.stmt(method) {
+            Some(StmtKind::FunctionDef { decorators, .. }) => method_binding(self.arena, decorators),
+            _ => None,
+        };
+        // A property has the type it returns, and other methods read from
+        // an instance are bound to it, except static ones
+        match (binding, self.function_type(method, true).substitute(&type_arguments)) {
+            (None, _) => Type::Any,
+            (Some(MethodBinding::Property), Type::Callable { returns, .. }) => *returns,
+            (Some(MethodBinding::Static), function) => function,
+            (_, Type::Callable { parameters: Some(mut parameters), returns }) if !parameters.is_empty() => {
+                parameters.remove(0);
+                Type::Callable {
+                    parameters: Some(parameters),
+                    returns,
+                }
+            }
+            (_, other) => other,
+        }
+    }
+}
+
+/// The type of a method of an imported class read from an instance: a
+/// property has the type it returns, and other methods are bound to the
+/// instance
+fn declared_method_type(method: &MethodInfo) -> Type {
+    match method.kind {
+        MethodKind::Property => method.signature.returns.clone().unwrap_or(Type::Any),
+        MethodKind::Static => method.signature.callable_type(),
+        MethodKind::Instance | MethodKind::Class => method.signature.without_receiver().callable_type(),
+    }
+}
+
+/// The symbols bound by imports in any scope of a module, by the position
+/// of the imported name. Names bound more than once are left out.
+fn imported_symbols(symbol_table: &SymbolTable) -> HashMap<SourcePosition, Vec<&Symbol>> {
+    let builtins = symbol_table.builtins_scope_id();
+    let mut imported: HashMap<SourcePosition, Vec<&Symbol>> = HashMap::new();
+    for scope in symbol_table.scopes().values().filter(|scope| Some(scope.id) != builtins) {
+        for symbol in scope.symbols().filter(|symbol| symbol.definitions.len() == 1) {
+            imported.entry(symbol.position).or_default().push(symbol);
+        }
+    }
+    imported
+}
+
+/// Where the nearest definition of a method comes from
+enum MethodSource {
+    /// A `def` in the module
+    Defined(NodeId),
+    /// A method of an imported class, or one `@dataclass` generates
+    Declared(MethodInfo),
+}
+
+/// Whether a call's function is `enum.auto`
+fn is_auto(arena: &AstArena, function: NodeId) -> bool {
+    match arena.expr(function) {
+        Some(ExprKind::Identifier { name }) => name == "auto",
+        Some(ExprKind::Attribute { attribute, .. }) => attribute == "auto",
+        _ => false,
+    }
+}
+
+/// How a method receives the object it is looked up on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MethodBinding {
+    Instance,
+    Class,
+    Static,
+    /// Read as the value the method returns
+    Property,
+}
+
+/// How a method with the given decorators is bound, or `None` if a
+/// decorator may replace it with something else
+fn method_binding(arena: &AstArena, decorators: &[NodeId]) -> Option<MethodBinding> {
+    let mut binding = MethodBinding::Instance;
+    for &decorator in decorators {
+        match arena.expr(decorator) {
+            Some(ExprKind::Identifier { name }) if name == "staticmethod" => binding = MethodBinding::Static,
+            Some(ExprKind::Identifier { name }) if name == "classmethod" => binding = MethodBinding::Class,
+            Some(ExprKind::Identifier { name }) if matches!(name.as_str(), "property" | "cached_property") => {
+                binding = MethodBinding::Property
+            }
+            Some(ExprKind::Attribute { attribute, .. }) if is_property_accessor(attribute) => {
+                binding = MethodBinding::Property
+            }
+            _ => return None,
+        }
+    }
+    Some(binding)
+}
+
+/// Whether a decorator attribute makes a method another accessor of a
+/// property (`@value.setter`), or a cached property
+fn is_property_accessor(attribute: &str) -> bool {
+    matches!(attribute, "getter" | "setter" | "deleter" | "cached_property")
+}
+
+/// The type of a literal
+pub fn literal_type(literal: &Literal) -> Type {
+    match literal {
+        Literal::Integer { .. } => Type::Int,
+        Literal::Float { .. } => Type::Float,
+        Literal::String { .. } => Type::Str,
+        Literal::Boolean { .. } => Type::Bool,
+        Literal::None { .. } => Type::None,
+        Literal::Ellipsis { .. } => Type::instance("ellipsis"),
+    }
+}
+
+/// The type of a class object, `type[C]`, whose call makes a `C`
+fn class_object(instance: Type) -> Type {
+    Type::Instance {
+        class: "type".to_string(),
+        arguments: vec![instance],
+    }
+}
+
+/// The type of a builtin name read as a value
+fn builtin_type(name: &str) -> Type {
+    match builtins::lookup(name).map(|builtin| builtin.kind) {
+        Some(BuiltinKind::Class) | Some(BuiltinKind::Exception) => class_object(match name {
+            "int" => Type::Int,
+            "float" => Type::Float,
+            "str" => Type::Str,
+            "bool" => Type::Bool,
+            "list" => Type::List(Box::new(Type::Any)),
+            "dict" => Type::Dict(Box::new(Type::Any), Box::new(Type::Any)),
+            "set" => Type::Set(Box::new(Type::Any)),
+            "tuple" => Type::VarTuple(Box::new(Type::Any)),
+            _ => Type::instance(name),
+        }),
+        Some(BuiltinKind::Function) => Type::Callable {
+            parameters: None,
+            returns: Box::new(builtin_call(name, &[])),
+        },
+        Some(BuiltinKind::ModuleAttribute) if name == "__name__" || name == "__file__" => Type::Str,
+        _ => Type::Any,
+    }
+}
+
+/// The result of calling a builtin function or class with arguments of
+/// the given types
+fn builtin_call(name: &str, arguments: &[Type]) -> Type {
+    let first = arguments.first().cloned().unwrap_or(Type::Any);
+    let elements = element_type(&first);
+    match name {
+        "len" | "ord" | "hash" | "id" | "int" => Type::Int,
+        "float" => Type::Float,
+        "str" | "repr" | "input" | "chr" | "format" | "ascii" | "hex" | "bin" | "oct" => Type::Str,
+        "bool" | "isinstance" | "issubclass" | "callable" | "hasattr" | "all" | "any" => Type::Bool,
+        "print" | "setattr" | "delattr" => Type::None,
+        "abs" => match first {
+            Type::Bool => Type::Int,
+            other => other,
+        },
+        "round" if arguments.len() > 1 => first,
+        "round" => Type::Int,
+        "sum" => match elements {
+            Type::Bool => Type::Int,
+            other => other,
+        },
+        "min" | "max" if arguments.len() == 1 => elements,
+        "min" | "max" => element_or_any(arguments.to_vec()),
+        "sorted" | "list" => Type::List(Box::new(if arguments.is_empty() { Type::Any } else { elements })),
+        "set" => Type::Set(Box::new(if arguments.is_empty() { Type::Any } else { elements })),
+        "frozenset" => Type::Instance {
+            class: "frozenset".to_string(),
+            arguments: vec![elements],
+        },
+        "tuple" => Type::VarTuple(Box::new(if arguments.is_empty() { Type::Any } else { elements })),
+        "dict" => match elements {
+            Type::Tuple(pair) if pair.len() == 2 => Type::Dict(Box::new(pair[0].clone()), Box::new(pair[1].clone())),
+            _ => match first {
+                Type::Dict(..) => first,
+                _ => Type::Dict(Box::new(Type::Any), Box::new(Type::Any)),
+            },
+        },
+        "range" => Type::instance("range"),
+        "enumerate" => Type::Instance {
+            class: "enumerate".to_string(),
+            arguments: vec![elements],
+        },
+        "reversed" | "iter" | "filter" => Type::Instance {
+            class: if name == "iter" { "Iterator".to_string() } else { name.to_string() },
+            arguments: vec![elements],
+        },
+        "zip" => Type::Instance {
+            class: "zip".to_string(),
+            arguments: vec![Type::Tuple(arguments.iter().map(element_type).collect())],
+        },
+        "next" => elements,
+        "divmod" => {
+            let quotient = arguments
+                .get(1)
+                .and_then(|second| binary_result(BinaryOperator::FloorDivide, &first, second))
+                .unwrap_or(Type::Any);
+            Type::Tuple(vec![quotient.clone(), quotient])
+        }
+        "type" if arguments.len() == 1 => class_object(first),
+        "open" => Type::instance("TextIOWrapper"),
+        _ => match builtins::lookup(name).map(|builtin| builtin.kind) {
+            Some(BuiltinKind::Class) | Some(BuiltinKind::Exception) => Type::instance(name),
+            _ => Type::Any,
+        },
+    }
+}
+
+/// The result of calling a value of type `callee`
+fn call_result(callee: &Type, arguments: &[Type], is_subclass: &dyn Fn(&str, &str) -> Option<bool>) -> Type {
+    match callee {
+        Type::Callable { parameters: Some(parameters), returns } => {
+            instantiate(parameters, returns, arguments, is_subclass)
+        }
+        Type::Callable { returns, .. } => (**returns).clone(),
+        Type::Instance { class, arguments: instance } if class == "type" => match instance.first() {
+            Some(Type::List(_)) | Some(Type::Set(_)) | Some(Type::VarTuple(_)) | Some(Type::Dict(..)) => {
+                let name = match instance[0] {
+                    Type::List(_) => "list",
+                    Type::Set(_) => "set",
+                    Type::VarTuple(_) => "tuple",
+                    _ => "dict",
+                };
+                builtin_call(name, arguments)
+            }
+            Some(instance) => instance.clone(),
+            None => Type::Any,
+        },
+        Type::Union(members) => {
+            Type::union(members.iter().map(|member| call_result(member, arguments, is_subclass)))
+        }
+        _ => Type::Any,
+    }
+}
+
+/// The return type of a call to a callable whose parameters may have type
+/// variables, solved from the types of the arguments. Variables that no
+/// argument solves, or whose solution breaks their bound or constraints,
+/// are `Any`.
+fn instantiate(
+    parameters: &[Type],
+    returns: &Type,
+    arguments: &[Type],
+    is_subclass: &dyn Fn(&str, &str) -> Option<bool>,
+) -> Type {
+    let mut solutions = HashMap::new();
+    for (parameter, argument) in parameters.iter().zip(arguments) {
+        parameter.solve(argument, &mut solutions);
+    }
+    let substitution: HashMap<String, Type> = parameters
+        .iter()
+        .flat_map(Type::type_vars)
+        .map(|variable| {
+            let solved = solutions
+                .get(&variable.name)
+                .and_then(|solved| variable.admit(solved, is_subclass))
+                .unwrap_or(Type::Any);
+            (variable.name.clone(), solved)
+        })
+        .collect();
+    returns.substitute(&substitution)
+}
+
+/// The type of the elements produced by iterating over a value
+pub fn element_type(iterable: &Type) -> Type {
+    match iterable {
+        Type::List(element) | Type::Set(element) | Type::VarTuple(element) => (**element).clone(),
+        Type::Tuple(elements) => element_or_any(elements.clone()),
+        Type::Dict(key, _) => (**key).clone(),
+        Type::Str => Type::Str,
+        Type::Instance { class, arguments } => match (class.as_str(), arguments.first()) {
+            ("range", _) => Type::Int,
+            ("enumerate", Some(element)) => Type::Tuple(vec![Type::Int, element.clone()]),
+            (_, Some(element)) if class != "type" => element.clone(),
+            _ => Type::Any,
+        },
+        Type::Union(members) => Type::union(members.iter().map(element_type)),
+        _ => Type::Any,
+    }
+}
+
+/// The result of indexing a value of type `object`; `literal_index` is
+/// the index if it is an integer literal
+fn subscript_result(object: &Type, literal_index: Option<i64>) -> Type {
+    match object {
+        Type::List(element) | Type::VarTuple(element) => (**element).clone(),
+        Type::Dict(_, value) => (**value).clone(),
+        Type::Str => Type::Str,
+        Type::Tuple(elements) => {
+            let position = literal_index.and_then(|index| {
+                let index = if index < 0 { elements.len() as i64 + index } else { index };
+                usize::try_from(index).ok()
+            });
+            match position.and_then(|position| elements.get(position)) {
+                Some(element) => element.clone(),
+                None => element_or_any(elements.clone()),
+            }
+        }
+        Type::Union(members) => Type::union(members.iter().map(|member| subscript_result(member, literal_index))),
+        _ => Type::Any,
+    }
+}
+
+/// Union of element types, or `Any` for an empty container
+fn element_or_any(types: Vec<Type>) -> Type {
+    if types.is_empty() {
+        Type::Any
+    } else {
+        Type::union(types)
+    }
+}
+
+/// Numeric rank for promotion: `bool` < `int` < `float`
+fn numeric_rank(value: &Type) -> Option<u8> {
+    match value {
+        Type::Bool => Some(0),
+        Type::Int => Some(1),
+        Type::Float => Some(2),
+        _ => None,
+    }
+}
+
+/// The result type of a binary operator, or `None` if Python would raise
+/// a `TypeError` for operands of these types
+pub fn binary_result(op: BinaryOperator, left: &Type, right: &Type) -> Option<Type> {
+    use BinaryOperator::*;
+    match op {
+        Equal | NotEqual | LessThan | LessThanEq | GreaterThan | GreaterThanEq | In | NotIn | Is | IsNot => {
+            return Some(Type::Bool);
+        }
+        And | Or => return Some(Type::union([left.clone(), right.clone()])),
+        _ => {}
+    }
+    match (left, right) {
+        (Type::Any, _) | (_, Type::Any) => return Some(Type::Any),
+        (Type::Union(members), _) => {
+            let results: Option<Vec<Type>> = members.iter().map(|member| binary_result(op, member, right)).collect();
+            return results.map(Type::union);
+        }
+        (_, Type::Union(members)) => {
+            let results: Option<Vec<Type>> = members.iter().map(|member| binary_result(op, left, member)).collect();
+            return results.map(Type::union);
+        }
+        // User classes may overload any operator
+        (Type::Instance { .. }, _) | (_, Type::Instance { .. }) | (Type::TypeVar(_), _) | (_, Type::TypeVar(_)) => {
+            return Some(Type::Any);
+        }
+        _ => {}
+    }
+
+    if let (Some(left_rank), Some(right_rank)) = (numeric_rank(left), numeric_rank(right)) {
+        let promoted = match left_rank.max(right_rank).max(1) {
+            1 => Type::Int,
+            _ => Type::Float,
+        };
+        return match op {
+            Divide => Some(Type::Float),
+            Add | Subtract | Multiply | FloorDivide | Modulo | Power => Some(promoted),
+            BitwiseAnd | BitwiseOr | BitwiseXor if left_rank == 0 && right_rank == 0 => Some(Type::Bool),
+            BitwiseAnd | BitwiseOr | BitwiseXor | LeftShift | RightShift if left_rank < 2 && right_rank < 2 => {
+                Some(Type::Int)
+            }
+            _ => None,
+        };
+    }
+
+    let is_int = |value: &Type| matches!(value, Type::Int | Type::Bool);
+    match (op, left, right) {
+        (Add, Type::Str, Type::Str) => Some(Type::Str),
+        (Multiply, Type::Str, count) | (Multiply, count, Type::Str) if is_int(count) => Some(Type::Str),
+        (Modulo, Type::Str, _) => Some(Type::Str),
+        (Add, Type::List(a), Type::List(b)) => Some(Type::List(Box::new(Type::union([(**a).clone(), (**b).clone()])))),
+        (Multiply, Type::List(_), count) if is_int(count) => Some(left.clone()),
+        (Multiply, count, Type::List(_)) if is_int(count) => Some(right.clone()),
+        (Add, Type::Tuple(a), Type::Tuple(b)) => Some(Type::Tuple(a.iter().chain(b).cloned().collect())),
+        (Add, Type::Tuple(_) | Type::VarTuple(_), Type::Tuple(_) | Type::VarTuple(_)) => {
+            Some(Type::VarTuple(Box::new(Type::union([element_type(left), element_type(right)]))))
+        }
+        (Multiply, Type::Tuple(_) | Type::VarTuple(_), count) if is_int(count) => {
+            Some(Type::VarTuple(Box::new(element_type(left))))
+        }
+        (BitwiseOr | BitwiseXor, Type::Set(a), Type::Set(b)) => {
+            Some(Type::Set(Box::new(Type::union([(**a).clone(), (**b).clone()]))))
+        }
+        (BitwiseAnd | Subtract, Type::Set(_), Type::Set(_)) => Some(left.clone()),
+        (BitwiseOr, Type::Dict(k1, v1), Type::Dict(k2, v2)) => Some(Type::Dict(
+            Box::new(Type::union([(**k1).clone(), (**k2).clone()])),
+            Box::new(Type::union([(**v1).clone(), (**v2).clone()])),
+        )),
+        _ => None,
+    }
+}
+
+/// The result type of a unary operator, or `None` if Python would raise a
+/// `TypeError`
+pub fn unary_result(op: UnaryOperator, operand: &Type) -> Option<Type> {
+    match (op, operand) {
+        (UnaryOperator::Not, _) => Some(Type::Bool),
+        (_, Type::Any) | (_, Type::Instance { .. }) | (_, Type::TypeVar(_)) => Some(Type::Any),
+        (UnaryOperator::Minus | UnaryOperator::Plus | UnaryOperator::BitwiseNot, Type::Bool | Type::Int) => {
+            Some(Type::Int)
+        }
+        (UnaryOperator::Minus | UnaryOperator::Plus, Type::Float) => Some(Type::Float),
+        (_, Type::Union(members)) => {
+            let results: Option<Vec<Type>> = members.iter().map(|member| unary_result(op, member)).collect();
+            results.map(Type::union)
+        }
+        _ => None,
+    }
+}
+
+/// The binary operator an augmented assignment applies
+pub fn augmented_operator(op: AugmentedOperator) -> BinaryOperator {
+    match op {
+        AugmentedOperator::Add => BinaryOperator::Add,
+        AugmentedOperator::Subtract => BinaryOperator::Subtract,
+        AugmentedOperator::Multiply => BinaryOperator::Multiply,
+        AugmentedOperator::Divide => BinaryOperator::Divide,
+        AugmentedOperator::FloorDivide => BinaryOperator::FloorDivide,
+        AugmentedOperator::Modulo => BinaryOperator::Modulo,
+        AugmentedOperator::Power => BinaryOperator::Power,
+        AugmentedOperator::BitwiseAnd => BinaryOperator::BitwiseAnd,
+        AugmentedOperator::BitwiseOr => BinaryOperator::BitwiseOr,
+        AugmentedOperator::BitwiseXor => BinaryOperator::BitwiseXor,
+        AugmentedOperator::LeftShift => BinaryOperator::LeftShift,
+        AugmentedOperator::RightShift => BinaryOperator::RightShift,
+    }
+}
+
+/// Whether two types are unions of the same members, in any order
+fn same_members(left: &Type, right: &Type) -> bool {
+    match (left, right) {
+        (Type::Union(left), Type::Union(right)) => {
+            left.len() == right.len() && left.iter().all(|member| right.contains(member))
+        }
+        _ => left == right,
+    }
+}
+
+/// Whether an expression is the literal `None`
+fn is_none(arena: &AstArena, id: NodeId) -> bool {
+    match arena.expr(id) {
+        Some(ExprKind::Literal(Literal::None { .. })) => true,
+        Some(ExprKind::Parenthesized { expr }) => is_none(arena, *expr),
+        _ => false,
+    }
+}
+
+/// Whether a condition is the literal `True` or `False`
+fn is_constant(arena: &AstArena, id: NodeId, value: bool) -> bool {
+    match arena.expr(id) {
+        Some(ExprKind::Literal(Literal::Boolean { value: literal, .. })) => *literal == value,
+        Some(ExprKind::Parenthesized { expr }) => is_constant(arena, *expr, value),
+        _ => false,
+    }
+}
+
+/// The variable or attribute path (`self.v`) a condition tests, which
+/// narrowing can refine
+fn narrowed_name(arena: &AstArena, id: NodeId) -> Option<String> {
+    match arena.expr(id)? {
+        ExprKind::Identifier { name } => Some(name.clone()),
+        ExprKind::AssignmentExpr { target, .. } => Some(target.clone()),
+        ExprKind::Parenthesized { expr } => narrowed_name(arena, *expr),
+        ExprKind::Attribute { object, attribute } => Some(format!("{}.{}", narrowed_name(arena, *object)?, attribute)),
+        _ => None,
+    }
+}
+
+/// Whether a narrowed name is an attribute path rather than a variable
+fn is_path(name: &str) -> bool {
+    name.contains('.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::semantic::SemanticAnalyzer;
+
+    fn infer(source: &str) -> (AstArena, InferredTypes) {
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let module = Parser::new(tokens).parse().unwrap();
+        let (symbol_table, _) = SemanticAnalyzer::new().diagnose(&module);
+        let arena = AstArena::from_module(&module);
+        let inferred = infer_types(&arena, &symbol_table);
+        (arena, inferred)
+    }
+
+    fn variable(source: &str, name: &str) -> String {
+        let (_, inferred) = infer(source);
+        inferred.module_variables[name].to_string()
+    }
+
+    fn returns(source: &str, function: &str) -> String {
+        let (arena, inferred) = infer(source);
+        let id = arena
+            .ids()
+            .find(|&id| matches!(arena.stmt(id), Some(StmtKind::FunctionDef { name, .. }) if name == function))
+            .unwrap();
+        inferred.return_type(id).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_literals_and_assignment() {
+        let source = "a = 1\nb = 2.5\nc = 'x'\nd = True\ne = None\nf = a\ng, h = 1, 'y'\nfirst, *rest = [1, 2, 3]\n";
+        let cases = [
+            ("a", "int"),
+            ("b", "float"),
+            ("c", "str"),
+            ("d", "bool"),
+            ("e", "None"),
+            ("f", "int"),
+            ("g", "int"),
+            ("h", "str"),
+            ("first", "int"),
+            ("rest", "list[int]"),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(variable(source, name), expected, "type of {}", name);
+        }
+    }
+
+    #[test]
+    fn test_binary_operator_results() {
+        let cases = [
+            ("x = 1 + 2", "int"),
+            ("x = 1 + 2.0", "float"),
+            ("x = 1 / 2", "float"),
+            ("x = 7 // 2", "int"),
+            ("x = True + True", "int"),
+            ("x = 'a' + 'b'", "str"),
+            ("x = 'ab' * 3", "str"),
+            ("x = [1] * 3", "list[int]"),
+            ("x = [1] + ['a']", "list[int | str]"),
+            ("x = (1,) + ('a',)", "tuple[int, str]"),
+            ("x = {1} | {2}", "set[int]"),
+            ("x = 1 < 2", "bool"),
+            ("x = -1.5", "float"),
+            ("x = not 1", "bool"),
+            ("x = 1\nx += 0.5", "float"),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(variable(source, "x"), expected, "{}", source);
+        }
+        assert_eq!(binary_result(BinaryOperator::Add, &Type::Str, &Type::Int), None);
+        assert_eq!(binary_result(BinaryOperator::Subtract, &Type::Str, &Type::Str), None);
+        assert_eq!(unary_result(UnaryOperator::Minus, &Type::Str), None);
+    }
+
+    #[test]
+    fn test_containers_and_comprehensions() {
+        let cases = [
+            ("x = [1, 2.0]", "list[int | float]"),
+            ("x = []", "list[Any]"),
+            ("x = {'a': 1}", "dict[str, int]"),
+            ("x = {1, 2}", "set[int]"),
+            ("x = [str(i) for i in range(3)]", "list[str]"),
+            ("x = {k: len(k) for k in ['a']}", "dict[str, int]"),
+            ("x = {c for c in 'abc'}", "set[str]"),
+            ("x = [n * 2 for n in [1.5] if n]", "list[float]"),
+            ("x = {'a': [1]}['a'][0]", "int"),
+            ("x = (1, 'a')[1]", "str"),
+            ("x = [pair[0] for pair in enumerate(['a'])]", "list[int]"),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(variable(source, "x"), expected, "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_control_flow_merges_types() {
+        let source = "if c:\n    x = 1\nelse:\n    x = 'a'\ny = 0\nfor i in range(3):\n    y = y / 2\n";
+        assert_eq!(variable(source, "x"), "int | str");
+        assert_eq!(variable(source, "y"), "int | float");
+        assert_eq!(variable("x = 1\nif c:\n    x = None\n", "x"), "int | None");
+    }
+
+    #[test]
+    fn test_function_return_types() {
+        assert_eq!(returns("def f():\n    return 1\n", "f"), "int");
+        assert_eq!(returns("def f(x):\n    if x:\n        return 1\n    return 'a'\n", "f"), "int | str");
+        assert_eq!(returns("def f(x):\n    if x:\n        return 1\n", "f"), "int | None");
+        assert_eq!(returns("def f():\n    pass\n", "f"), "None");
+        assert_eq!(returns("def f(x: int):\n    return x * 2.0\n", "f"), "float");
+        assert_eq!(returns("def f() -> float:\n    return 1\n", "f"), "float");
+        assert_eq!(returns("def f(n):\n    return f(n - 1)\n", "f"), "Any");
+        assert_eq!(returns("def f():\n    while True:\n        return 1\n", "f"), "int");
+
+        let source = "def g():\n    return f() + 1\ndef f():\n    return 2.0\nx = g()\n";
+        assert_eq!(variable(source, "x"), "float");
+    }
+
+    #[test]
+    fn test_narrowing() {
+        let optional = |body: &str| returns(&format!("def f(x: int | None):\n{body}"), "f");
+        assert_eq!(optional("    if x is not None:\n        return x\n    return 0\n"), "int");
+        assert_eq!(optional("    if x is None:\n        return 0\n    return x\n"), "int");
+        assert_eq!(optional("    if x is None:\n        raise ValueError()\n    return x\n"), "int");
+        assert_eq!(optional("    if not x:\n        return 0\n    return x\n"), "int");
+        assert_eq!(optional("    assert x is not None\n    return x\n"), "int");
+        assert_eq!(optional("    return x if x is not None else 0\n"), "int");
+        assert_eq!(optional("    return x is not None and x + 1\n"), "bool | int");
+        assert_eq!(optional("    if x is not None and x > 0:\n        return x\n    return 0\n"), "int");
+        assert_eq!(optional("    if x is None or x < 0:\n        return 0\n    return x\n"), "int");
+        assert_eq!(optional("    while x is None:\n        x = 1\n    return x\n"), "int");
+        assert_eq!(optional("    if x is not None:\n        pass\n    return x\n"), "int | None");
+
+        let source = "class A:\n    pass\nclass B(A):\n    pass\ndef f(x: int | str | B):\n    if isinstance(x, (int, A)):\n        return x\n    return 0\ndef g(x: int | str):\n    if isinstance(x, int):\n        return 1.5\n    return x\n";
+        assert_eq!(returns(source, "f"), "int | B");
+        assert_eq!(returns(source, "g"), "float | str");
+        assert_eq!(returns("def f(x):\n    if isinstance(x, str):\n        return x\n    return 0\n", "f"), "str | int");
+
+        // Attribute paths are narrowed until they or their base are assigned
+        let source = "\
+class A:
+    def __init__(self, v: int | None):
+        self.v = v
+    def get(self):
+        if self.v is not None:
+            return self.v
+        return 0
+    def check(self):
+        if self.v is None or self.v < 0:
+            return 0
+        return [self.v for _ in range(2)][0]
+    def reset(self):
+        if self.v is not None:
+            self.v = None
+            return self.v
+        return 0
+    def rebind(self, other: 'A'):
+        if self.v is not None:
+            self = other
+            return self.v
+        return 0
+    def branch(self, flag: bool):
+        if flag:
+            assert self.v is not None
+        return self.v
+";
+        assert_eq!(returns(source, "get"), "int");
+        assert_eq!(returns(source, "check"), "int");
+        assert_eq!(returns(source, "reset"), "int | None");
+        assert_eq!(returns(source, "rebind"), "int | None");
+        assert_eq!(returns(source, "branch"), "int | None");
+
+        // A call may reassign any attribute, so it ends the narrowing of
+        // attribute paths; builtins other than `setattr` and friends do not
+        let source = "\
+class A:
+    def __init__(self, v: int | None):
+        self.v = v
+    def reset(self):
+        self.v = None
+    def after_method(self):
+        if self.v is not None:
+            self.reset()
+            return self.v
+        return 0
+    def after_function(self):
+        if self.v is not None:
+            clear(self)
+            return self.v
+        return 0
+    def after_builtin(self):
+        if self.v is not None:
+            print(self.v)
+            return self.v
+        return 0
+    def after_setattr(self):
+        if self.v is not None:
+            setattr(self, 'v', None)
+            return self.v
+        return 0
+    def in_condition(self):
+        if self.v is not None and self.reset() is None:
+            return self.v
+        return 0
+def clear(a: A):
+    a.v = None
+";
+        assert_eq!(returns(source, "after_method"), "int | None");
+        assert_eq!(returns(source, "after_function"), "int | None");
+        assert_eq!(returns(source, "after_builtin"), "int");
+        assert_eq!(returns(source, "after_setattr"), "int | None");
+        assert_eq!(returns(source, "in_condition"), "int | None");
+
+        // Paths that cannot reach the end of the function return nothing
+        assert_eq!(returns("def f(x):\n    for i in x:\n        if i:\n            return i\n        continue\n    raise ValueError()\n", "f"), "Any");
+        assert_eq!(returns("def f():\n    while True:\n        if g():\n            break\n    return 1\n", "f"), "int");
+    }
+
+    #[test]
+    fn test_generic_calls() {
+        let source = "\
+from typing import Generic, Iterable, Optional, TypeVar
+T = TypeVar('T')
+K = TypeVar('K')
+def first(items: list[T]) -> T:
+    return items[0]
+def pair(left: T, right: K) -> tuple[K, T]:
+    return (right, left)
+def find(items: Iterable[T], default: Optional[T] = None) -> Optional[T]:
+    return default
+def identity[U](value: U) -> U:
+    return value
+class Box(Generic[T]):
+    def __init__(self, item: T):
+        self.item = item
+    def get(self) -> T:
+        return self.item
+class Stack[V]:
+    def __init__(self, items: list[V]):
+        self.items = items
+    def pop(self) -> V:
+        return self.items.pop()
+a = first([1, 2])
+b = pair('x', 2.5)
+c = find({1, 2})
+d = identity(True)
+e = first(unknown)
+box = Box('text')
+f = box.get()
+stack = Stack([1.5])
+g = stack.pop()
+";
+        let cases = [
+            ("a", "int"),
+            ("b", "tuple[float, str]"),
+            ("c", "int | None"),
+            ("d", "bool"),
+            ("e", "Any"),
+            ("box", "Box[str]"),
+            ("f", "str"),
+            ("stack", "Stack[float]"),
+            ("g", "float"),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(variable(source, name), expected, "type of {}", name);
+        }
+
+        // Bound and constrained type variables only take types they admit
+        let source = "\
+from typing import TypeVar
+N = TypeVar('N', bound=float)
+S = TypeVar('S', str, bytes)
+def twice(value: N) -> N:
+    return value
+def same(value: S) -> S:
+    return value
+def bounded[M: int](value: M) -> list[M]:
+    return [value]
+a = twice(1)
+b = twice('x')
+c = same('x')
+d = bounded(True)
+";
+        assert_eq!(variable(source, "a"), "int");
+        assert_eq!(variable(source, "b"), "Any");
+        assert_eq!(variable(source, "c"), "str");
+        assert_eq!(variable(source, "d"), "list[bool]");
+    }
+
+    #[test]
+    fn test_classes_and_methods() {
+        let source = "\
+class Point:
+    def __init__(self, x: int):
+        self.x = x
+    def scaled(self, factor):
+        return self.x * factor
+    def name(self):
+        return 'point'
+p = Point(1)
+x = p.x
+n = p.name()
+s = p.scaled(2)
+";
+        assert_eq!(variable(source, "p"), "Point");
+        assert_eq!(variable(source, "x"), "int");
+        assert_eq!(variable(source, "n"), "str");
+        assert_eq!(variable(source, "s"), "Any");
+    }
+
+    #[test]
+    fn test_properties_and_static_methods() {
+        let source = "\
+class A:
+    def __init__(self):
+        self._p = 0
+    @property
+    def p(self) -> int:
+        return self._p
+    @p.setter
+    def p(self, value: int) -> None:
+        self._p = value
+    @staticmethod
+    def make(name: str) -> str:
+        return name
+a = A()
+total = A().p + 1
+read = a.p
+make = a.make
+";
+        assert_eq!(variable(source, "total"), "int");
+        assert_eq!(variable(source, "read"), "int");
+        assert_eq!(variable(source, "make"), "Callable[[str], str]");
+    }
+
+    #[test]
+    fn test_dataclasses_enums_and_named_tuples() {
+        let source = "\
+from dataclasses import dataclass
+from enum import Enum, StrEnum, auto
+from typing import NamedTuple
+@dataclass
+class Point:
+    x: int
+    y: float = 0.0
+class Color(Enum):
+    RED = 1
+    GREEN = 'green'
+class Mode(StrEnum):
+    FAST = auto()
+class Pair(NamedTuple):
+    left: int
+    right: str
+p = Point(1)
+y = p.y
+red = Color.RED
+value = red.value
+name = red.name
+mode = Mode['FAST'].value
+colors = [color for color in Color]
+right = Pair(1, 'a')._replace(left=2).right
+";
+        let cases = [
+            ("p", "Point"),
+            ("y", "float"),
+            ("red", "Color"),
+            ("value", "int | str"),
+            ("name", "str"),
+            ("mode", "str"),
+            ("colors", "list[Color]"),
+            ("right", "str"),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(variable(source, name), expected, "type of {}", name);
+        }
+    }
+
+    #[test]
+    fn test_annotations_and_builtins() {
+        let source = "\
+from typing import Optional
+a: Optional[int] = None
+b: list[str] = []
+c = len('abc')
+d = sorted([3, 1])
+e = abs(-2.5)
+f = int('1')
+g = input()
+";
+        let cases = [
+            ("a", "int | None"),
+            ("b", "list[str]"),
+            ("c", "int"),
+            ("d", "list[int]"),
+            ("e", "float"),
+            ("f", "int"),
+            ("g", "str"),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(variable(source, name), expected, "type of {}", name);
+        }
+    }
+
+    #[test]
+    fn test_every_expression_has_a_type() {
+        let (arena, inferred) = infer("x = [a + 1 for a in range(3)]\nprint(x[0], {'k': x})\n");
+        for id in arena.ids() {
+            if arena.expr(id).is_some() {
+                assert!(inferred.type_of(id).is_some(), "no type for {:?}", arena.expr(id));
+            }
+        }
+    }
+}
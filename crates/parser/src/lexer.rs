@@ -1,7 +1,7 @@
 //! Lexer implementation for Mamba
 
 use crate::token::{SourcePosition, Token, TokenKind};
-use mamba_error::MambaError;
+use mamba_error::{Diagnostic, ErrorCode, MambaError};
 use std::str::Chars;
 
 type LexResult = Result<Token, MambaError>;
@@ -162,15 +162,30 @@ impl<'a> Lexer<'a> {
                 Ok(Token::new(TokenKind::At, start_pos, "@".to_string()))
             }
             
-            Some(c) => Err(MambaError::SyntaxError(format!(
-                "Unexpected character '{}' at {}",
-                c, start_pos
-            ))),
+            Some(c) => Err(self.error(
+                ErrorCode::UnexpectedCharacter,
+                format!("Unexpected character '{}'", c),
+                start_pos,
+                1,
+            )),
         }
     }
 
     // Helper methods
 
+    /// Create a syntax error spanning `len` characters from `position`
+    fn error(
+        &self,
+        code: ErrorCode,
+        message: impl Into<String>,
+        position: SourcePosition,
+        len: usize,
+    ) -> MambaError {
+        MambaError::SyntaxError(Box::new(
+            Diagnostic::error(code, message).with_span(position.span(len)),
+        ))
+    }
+
     fn advance(&mut self) {
         if let Some(ch) = self.current_char {
             if ch == '\n' {
@@ -261,10 +276,12 @@ impl<'a> Lexer<'a> {
                 }
             }
             
-            return Err(MambaError::SyntaxError(format!(
-                "Unterminated triple-quoted string at {}",
-                start_pos
-            )));
+            return Err(self.error(
+                ErrorCode::UnterminatedString,
+                "Unterminated triple-quoted string",
+                start_pos,
+                1,
+            ));
         }
         
         // Single-quoted string
@@ -301,10 +318,7 @@ impl<'a> Lexer<'a> {
             }
         }
         
-        Err(MambaError::SyntaxError(format!(
-            "Unterminated string at {}",
-            start_pos
-        )))
+        Err(self.error(ErrorCode::UnterminatedString, "Unterminated string", start_pos, 1))
     }
 
     fn tokenize_number(&mut self) -> LexResult {
@@ -361,10 +375,12 @@ impl<'a> Lexer<'a> {
             }
             
             let value = lexeme.parse::<f64>().map_err(|_| {
-                MambaError::SyntaxError(format!(
-                    "Invalid float literal '{}' at {}",
-                    lexeme, start_pos
-                ))
+                self.error(
+                    ErrorCode::InvalidNumber,
+                    format!("Invalid float literal '{}'", lexeme),
+                    start_pos,
+                    lexeme.chars().count(),
+                )
             })?;
             
             return Ok(Token::new(TokenKind::Float(value), start_pos, lexeme));
@@ -372,10 +388,12 @@ impl<'a> Lexer<'a> {
         
         // Integer
         let value = lexeme.parse::<i64>().map_err(|_| {
-            MambaError::SyntaxError(format!(
-                "Invalid integer literal '{}' at {}",
-                lexeme, start_pos
-            ))
+            self.error(
+                ErrorCode::InvalidNumber,
+                format!("Invalid integer literal '{}'", lexeme),
+                start_pos,
+                lexeme.chars().count(),
+            )
         })?;
         
         Ok(Token::new(TokenKind::Integer(value), start_pos, lexeme))
@@ -396,10 +414,12 @@ impl<'a> Lexer<'a> {
                 lexeme.push(first);
                 self.advance();
             } else {
-                return Err(MambaError::SyntaxError(format!(
-                    "Invalid identifier start character '{}' at {}",
-                    first, start_pos
-                )));
+                return Err(self.error(
+                    ErrorCode::InvalidIdentifier,
+                    format!("Invalid identifier start character '{}'", first),
+                    start_pos,
+                    1,
+                ));
             }
         }
         
@@ -554,9 +574,10 @@ impl<'a> Lexer<'a> {
             self.advance();
             Ok(Token::new(TokenKind::NotEqual, start_pos, lexeme))
         } else {
-            Err(MambaError::SyntaxError(format!(
-                "Unexpected character '!' at {} (did you mean '!='?)",
-                start_pos
+            Err(MambaError::SyntaxError(Box::new(
+                Diagnostic::error(ErrorCode::UnexpectedCharacter, "Unexpected character '!'")
+                    .with_span(start_pos.span(1))
+                    .with_help("Did you mean '!='?"),
             )))
         }
     }
@@ -666,17 +687,21 @@ impl<'a> Lexer<'a> {
         }
         
         if !has_digits {
-            return Err(MambaError::SyntaxError(format!(
-                "Invalid hexadecimal literal '{}' at {}",
-                lexeme, start_pos
-            )));
+            return Err(self.error(
+                ErrorCode::InvalidNumber,
+                format!("Invalid hexadecimal literal '{}'", lexeme),
+                start_pos,
+                lexeme.chars().count(),
+            ));
         }
         
         let value = i64::from_str_radix(&lexeme[2..], 16).map_err(|_| {
-            MambaError::SyntaxError(format!(
-                "Invalid hexadecimal literal '{}' at {}",
-                lexeme, start_pos
-            ))
+            self.error(
+                ErrorCode::InvalidNumber,
+                format!("Invalid hexadecimal literal '{}'", lexeme),
+                start_pos,
+                lexeme.chars().count(),
+            )
         })?;
         
         Ok(Token::new(TokenKind::Integer(value), start_pos, lexeme))
@@ -692,27 +717,33 @@ impl<'a> Lexer<'a> {
                 has_digits = true;
             } else if c.is_ascii_digit() {
                 // Invalid octal digit (8 or 9)
-                return Err(MambaError::SyntaxError(format!(
-                    "Invalid octal digit '{}' in '{}' at {}",
-                    c, lexeme, start_pos
-                )));
+                return Err(self.error(
+                    ErrorCode::InvalidNumber,
+                    format!("Invalid octal digit '{}' in '{}'", c, lexeme),
+                    start_pos,
+                    lexeme.chars().count(),
+                ));
             } else {
                 break;
             }
         }
         
         if !has_digits {
-            return Err(MambaError::SyntaxError(format!(
-                "Invalid octal literal '{}' at {}",
-                lexeme, start_pos
-            )));
+            return Err(self.error(
+                ErrorCode::InvalidNumber,
+                format!("Invalid octal literal '{}'", lexeme),
+                start_pos,
+                lexeme.chars().count(),
+            ));
         }
         
         let value = i64::from_str_radix(&lexeme[2..], 8).map_err(|_| {
-            MambaError::SyntaxError(format!(
-                "Invalid octal literal '{}' at {}",
-                lexeme, start_pos
-            ))
+            self.error(
+                ErrorCode::InvalidNumber,
+                format!("Invalid octal literal '{}'", lexeme),
+                start_pos,
+                lexeme.chars().count(),
+            )
         })?;
         
         Ok(Token::new(TokenKind::Integer(value), start_pos, lexeme))
@@ -728,27 +759,33 @@ impl<'a> Lexer<'a> {
                 has_digits = true;
             } else if c.is_ascii_digit() {
                 // Invalid binary digit (2-9)
-                return Err(MambaError::SyntaxError(format!(
-                    "Invalid binary digit '{}' in '{}' at {}",
-                    c, lexeme, start_pos
-                )));
+                return Err(self.error(
+                    ErrorCode::InvalidNumber,
+                    format!("Invalid binary digit '{}' in '{}'", c, lexeme),
+                    start_pos,
+                    lexeme.chars().count(),
+                ));
             } else {
                 break;
             }
         }
         
         if !has_digits {
-            return Err(MambaError::SyntaxError(format!(
-                "Invalid binary literal '{}' at {}",
-                lexeme, start_pos
-            )));
+            return Err(self.error(
+                ErrorCode::InvalidNumber,
+                format!("Invalid binary literal '{}'", lexeme),
+                start_pos,
+                lexeme.chars().count(),
+            ));
         }
         
         let value = i64::from_str_radix(&lexeme[2..], 2).map_err(|_| {
-            MambaError::SyntaxError(format!(
-                "Invalid binary literal '{}' at {}",
-                lexeme, start_pos
-            ))
+            self.error(
+                ErrorCode::InvalidNumber,
+                format!("Invalid binary literal '{}'", lexeme),
+                start_pos,
+                lexeme.chars().count(),
+            )
         })?;
         
         Ok(Token::new(TokenKind::Integer(value), start_pos, lexeme))
@@ -798,10 +835,12 @@ impl<'a> Lexer<'a> {
                 }
             }
             
-            return Err(MambaError::SyntaxError(format!(
-                "Unterminated triple-quoted raw string at {}",
-                start_pos
-            )));
+            return Err(self.error(
+                ErrorCode::UnterminatedString,
+                "Unterminated triple-quoted raw string",
+                start_pos,
+                1,
+            ));
         }
         
         // Single-quoted raw string - no escape sequences processed
@@ -830,10 +869,7 @@ impl<'a> Lexer<'a> {
             }
         }
         
-        Err(MambaError::SyntaxError(format!(
-            "Unterminated raw string at {}",
-            start_pos
-        )))
+        Err(self.error(ErrorCode::UnterminatedString, "Unterminated raw string", start_pos, 1))
     }
 
     fn tokenize_fstring(&mut self) -> LexResult {
@@ -860,10 +896,7 @@ impl<'a> Lexer<'a> {
             self.advance();
         }
         
-        Err(MambaError::SyntaxError(format!(
-            "Unterminated f-string at {}",
-            start_pos
-        )))
+        Err(self.error(ErrorCode::UnterminatedString, "Unterminated f-string", start_pos, 1))
     }
     
     /// Handle indentation at the start of a line
@@ -893,10 +926,12 @@ impl<'a> Lexer<'a> {
         
         // Check for mixed tabs and spaces
         if has_tabs && has_spaces {
-            return Err(MambaError::SyntaxError(format!(
-                "Mixed tabs and spaces in indentation at {}",
-                start_pos
-            )));
+            return Err(self.error(
+                ErrorCode::InvalidIndentation,
+                "Mixed tabs and spaces in indentation",
+                start_pos,
+                1,
+            ));
         }
         
         // Empty lines and comment-only lines don't affect indentation
@@ -926,9 +961,13 @@ impl<'a> Lexer<'a> {
             
             // Check if we found a matching level
             if *self.indent_stack.last().unwrap() != indent_level {
-                return Err(MambaError::SyntaxError(format!(
-                    "Inconsistent indentation at {} (expected one of {:?}, got {})",
-                    start_pos, self.indent_stack, indent_level
+                return Err(MambaError::SyntaxError(Box::new(
+                    Diagnostic::error(ErrorCode::InvalidIndentation, "Inconsistent indentation")
+                        .with_span(start_pos.span(1))
+                        .with_note(format!(
+                            "expected one of {:?}, got {}",
+                            self.indent_stack, indent_level
+                        )),
                 )));
             }
             
@@ -9,3 +9,16 @@ pub mod ast;
 pub mod parser;
 pub mod symbol_table;
 pub mod semantic;
+pub mod builtins;
+pub mod classes;
+pub mod modules;
+pub mod cache;
+pub mod stubs;
+pub mod types;
+pub mod infer;
+pub mod checker;
+pub mod arena;
+pub mod suggest;
+pub mod lint;
+pub mod annotate;
+pub mod flow;
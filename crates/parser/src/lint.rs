@@ -0,0 +1,211 @@
+//! Opt-in lints
+//!
+//! Lints report code that is legal but suspicious. They never produce errors
+//! and none of them run unless requested (e.g. `mamba check --lint rebind-type`).
+
+use std::collections::HashMap;
+
+use crate::ast::{Expression, Literal, Module, Statement};
+use crate::token::SourcePosition;
+use mamba_error::{Diagnostic, ErrorCode};
+
+/// A lint that can be enabled by name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lint {
+    /// A name rebound to a value of a different type (`x = 1` then `x = "a"`)
+    RebindType,
+}
+
+impl Lint {
+    /// All available lints
+    pub const ALL: &'static [Lint] = &[Lint::RebindType];
+
+    /// Name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lint::RebindType => "rebind-type",
+        }
+    }
+
+    /// Look up a lint by its command-line name
+    pub fn from_name(name: &str) -> Option<Lint> {
+        Lint::ALL.iter().copied().find(|lint| lint.name() == name)
+    }
+}
+
+/// Run the given lints over a module and return their warnings
+pub fn run(module: &Module, lints: &[Lint]) -> Vec<Diagnostic> {
+    let mut warnings = Vec::new();
+    for lint in lints {
+        match lint {
+            Lint::RebindType => {
+                let mut checker = RebindChecker::default();
+                checker.visit_block(&module.statements);
+                warnings.extend(checker.warnings);
+            }
+        }
+    }
+    warnings
+}
+
+/// Tracks the last known value type of each name, one map per function/class body
+#[derive(Default)]
+struct RebindChecker {
+    scopes: Vec<HashMap<String, (&'static str, SourcePosition)>>,
+    warnings: Vec<Diagnostic>,
+}
+
+impl RebindChecker {
+    fn visit_block(&mut self, statements: &[Statement]) {
+        self.scopes.push(HashMap::new());
+        for statement in statements {
+            self.visit_statement(statement);
+        }
+        self.scopes.pop();
+    }
+
+    fn visit_statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Assignment { targets, value, position } => {
+                let kind = value_kind(value);
+                for target in targets {
+                    match target {
+                        Expression::Identifier { name, .. } => self.bind(name, kind, position),
+                        other => self.forget_names(other),
+                    }
+                }
+            }
+            Statement::AnnAssignment { target, annotation, value, position } => {
+                let kind = annotation_kind(annotation).or_else(|| value.as_ref().and_then(value_kind));
+                self.bind(target, kind, position);
+            }
+            Statement::For { target, body, else_block, .. } => {
+                self.forget_names(target);
+                self.visit_statements(body);
+                if let Some(block) = else_block {
+                    self.visit_statements(block);
+                }
+            }
+            Statement::If { then_block, elif_blocks, else_block, .. } => {
+                self.visit_statements(then_block);
+                for (_, block) in elif_blocks {
+                    self.visit_statements(block);
+                }
+                if let Some(block) = else_block {
+                    self.visit_statements(block);
+                }
+            }
+            Statement::While { body, else_block, .. } => {
+                self.visit_statements(body);
+                if let Some(block) = else_block {
+                    self.visit_statements(block);
+                }
+            }
+            Statement::FunctionDef { name, body, position, .. } => {
+                self.bind(name, Some("function"), position);
+                self.visit_block(body);
+            }
+            Statement::ClassDef { name, body, position, .. } => {
+                self.bind(name, Some("class"), position);
+                self.visit_block(body);
+            }
+            _ => {}
+        }
+    }
+
+    /// Visit a nested block that shares the enclosing scope (if/while/for bodies)
+    fn visit_statements(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            self.visit_statement(statement);
+        }
+    }
+
+    /// Record a binding, warning if it changes the known type of the name
+    fn bind(&mut self, name: &str, kind: Option<&'static str>, position: &SourcePosition) {
+        let Some(scope) = self.scopes.last_mut() else {
+            return;
+        };
+        let Some(kind) = kind else {
+            // Unknown type: stop comparing against the previous binding
+            scope.remove(name);
+            return;
+        };
+
+        if let Some((previous, previous_position)) = scope.get(name) {
+            if !compatible(previous, kind) {
+                let len = name.chars().count();
+                self.warnings.push(
+                    Diagnostic::warning(
+                        ErrorCode::TypeChangingRebinding,
+                        format!("'{}' is rebound from {} to {}", name, previous, kind),
+                    )
+                    .with_span(position.span(len))
+                    .with_label(previous_position.span(len), format!("previously bound to {} here", previous))
+                    .with_help("use a separate name for the new value"),
+                );
+            }
+        }
+        scope.insert(name.to_string(), (kind, *position));
+    }
+
+    /// Forget every name bound by an unpacking target
+    fn forget_names(&mut self, target: &Expression) {
+        match target {
+            Expression::Identifier { name, .. } => {
+                if let Some(scope) = self.scopes.last_mut() {
+                    scope.remove(name);
+                }
+            }
+            Expression::Tuple { elements, .. } | Expression::List { elements, .. } => {
+                for element in elements {
+                    self.forget_names(element);
+                }
+            }
+            Expression::Starred { value, .. } | Expression::Parenthesized { expr: value, .. } => {
+                self.forget_names(value)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Whether rebinding from `previous` to `next` is unremarkable
+fn compatible(previous: &str, next: &str) -> bool {
+    previous == next
+        || previous == "None"
+        || next == "None"
+        || matches!((previous, next), ("int", "float") | ("float", "int"))
+}
+
+/// Builtin type names that can appear as simple annotations or constructor calls
+const BUILTIN_TYPES: &[&str] = &["int", "float", "str", "bool", "list", "dict", "set", "tuple"];
+
+/// Type of a value when it is obvious from the expression alone
+fn value_kind(expr: &Expression) -> Option<&'static str> {
+    match expr {
+        Expression::Literal(literal) => match literal {
+            Literal::Integer { .. } => Some("int"),
+            Literal::Float { .. } => Some("float"),
+            Literal::String { .. } => Some("str"),
+            Literal::Boolean { .. } => Some("bool"),
+            Literal::None { .. } => Some("None"),
+            Literal::Ellipsis { .. } => None,
+        },
+        Expression::List { .. } | Expression::ListComp { .. } => Some("list"),
+        Expression::Tuple { .. } => Some("tuple"),
+        Expression::Dict { .. } | Expression::DictComp { .. } => Some("dict"),
+        Expression::Set { .. } | Expression::SetComp { .. } => Some("set"),
+        Expression::Lambda { .. } => Some("function"),
+        Expression::Parenthesized { expr, .. } => value_kind(expr),
+        Expression::Call { function, .. } => annotation_kind(function),
+        _ => None,
+    }
+}
+
+/// Type named by a simple builtin annotation such as `int`
+fn annotation_kind(expr: &Expression) -> Option<&'static str> {
+    match expr {
+        Expression::Identifier { name, .. } => BUILTIN_TYPES.iter().copied().find(|builtin| builtin == name),
+        _ => None,
+    }
+}
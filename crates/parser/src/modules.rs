@@ -0,0 +1,655 @@
+//! Module Resolution
+//!
+//! This module maps dotted module names to `.mmb` files on a search path and
+//! analyzes a program one module at a time. Each module is parsed and
+//! analyzed once, after the modules it imports, so that `from m import name`
+//! can be checked against the names `m` defines.
+//!
+//! A module `a.b` is found as `a/b.mmb` or `a/b/__init__.mmb` in the first
+//! search path directory that has either. A [stub](crate::stubs) `a/b.mmbi`
+//! or `a/b/__init__.mmbi` next to them is used in their place, and declares
+//! the module of a native or standard library module too. Other modules of
+//! the Python standard library are accepted without being checked.
+//!
+//! With an [analysis cache](crate::cache), a module whose source and imports
+//! are unchanged since it was last analyzed is only parsed, and takes its
+//! symbols and diagnostics from the cache.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::ast::{AugmentedOperator, Expression, Module, Statement};
+use crate::cache::{self, AnalysisCache, CachedAnalysis};
+use crate::checker::CheckMode;
+use crate::classes::string_elements;
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use crate::semantic::SemanticAnalyzer;
+use crate::symbol_table::{Symbol, SymbolTable};
+use mamba_error::{Diagnostic, MambaError};
+
+/// File extension of Mamba source files
+pub const EXTENSION: &str = "mmb";
+
+/// File extension of Mamba stub files
+pub const STUB_EXTENSION: &str = "mmbi";
+
+/// What an imported module name resolves to
+#[derive(Debug, Clone)]
+pub enum Import {
+    /// A module on the search path, analyzed before its importer
+    Loaded(Rc<ModuleInterface>),
+    /// A module whose names are not known: part of the Python standard
+    /// library, or a file that does not parse
+    Opaque,
+    /// Neither on the search path nor in the standard library
+    Missing,
+    /// A module still being loaded further up the import chain. Holds the
+    /// chain of module names from it back to itself.
+    Cycle(Vec<String>),
+}
+
+/// The top-level names of an analyzed module
+#[derive(Debug, Clone)]
+pub struct ModuleInterface {
+    pub name: String,
+    pub path: PathBuf,
+    /// Symbols bound in the module scope
+    pub symbols: HashMap<String, Symbol>,
+    /// The names listed in `__all__`, if the module declares it as a list or
+    /// tuple of string literals
+    pub all: Option<Vec<String>>,
+}
+
+impl ModuleInterface {
+    /// Summarize a module from its AST and the symbol table of its analysis
+    pub fn new(name: &str, path: &Path, module: &Module, symbol_table: &SymbolTable) -> Self {
+        let symbols = symbol_table
+            .module_scope_id()
+            .and_then(|scope| symbol_table.get_scope(scope))
+            .map(|scope| scope.symbols().map(|symbol| (symbol.name.clone(), symbol.clone())).collect())
+            .unwrap_or_default();
+
+        Self {
+            name: name.to_string(),
+            path: path.to_path_buf(),
+            symbols,
+            all: declared_all(&module.statements),
+        }
+    }
+
+    /// Look up a top-level name
+    pub fn lookup(&self, name: &str) -> Option<&Symbol> {
+        self.symbols.get(name)
+    }
+
+    /// The symbols bound by `from module import *`: those named in `__all__`,
+    /// or every name not starting with an underscore. Sorted by name.
+    pub fn star_exports(&self) -> Vec<&Symbol> {
+        let mut exports: Vec<&Symbol> = match &self.all {
+            Some(names) => names.iter().filter_map(|name| self.symbols.get(name)).collect(),
+            None => self.symbols.values().filter(|symbol| !symbol.name.starts_with('_')).collect(),
+        };
+        exports.sort_by(|a, b| a.name.cmp(&b.name));
+        exports.dedup_by(|a, b| a.name == b.name);
+        exports
+    }
+}
+
+/// A module analyzed by the loader
+#[derive(Debug)]
+pub struct CheckedModule {
+    pub name: String,
+    pub path: PathBuf,
+    pub source: String,
+    /// The parsed module, if it parsed
+    pub module: Option<Module>,
+    /// Lexer, parser and semantic diagnostics, errors and warnings alike
+    pub diagnostics: Vec<Diagnostic>,
+    /// Whether the symbols and diagnostics came from the analysis cache
+    pub cached: bool,
+}
+
+impl CheckedModule {
+    /// Whether the module was loaded from a stub, which declares names
+    /// without implementing them
+    pub fn is_stub(&self) -> bool {
+        is_stub(&self.path)
+    }
+}
+
+/// Finds, parses and analyzes the modules of a program
+///
+/// # Example
+///
+/// ```no_run
+/// use mamba_parser::modules::ModuleLoader;
+/// use std::path::{Path, PathBuf};
+///
+/// let mut loader = ModuleLoader::new(vec![PathBuf::from("src")]);
+/// loader.check_file(Path::new("src/main.mmb")).unwrap();
+/// for module in loader.modules() {
+///     println!("{}: {} diagnostic(s)", module.path.display(), module.diagnostics.len());
+/// }
+/// ```
+pub struct ModuleLoader {
+    search_path: Vec<PathBuf>,
+    /// Resolution of every module name imported so far, except cycles
+    resolved: HashMap<String, Import>,
+    /// Names of the modules being loaded, outermost first
+    loading: Vec<String>,
+    /// Every analyzed module, in the order analysis finished
+    modules: Vec<CheckedModule>,
+    /// Whether and how strictly to type check each module
+    type_checking: Option<CheckMode>,
+    /// Where analyses are cached, if anywhere
+    cache: Option<AnalysisCache>,
+    /// Cache key of every module analyzed, by path
+    keys: HashMap<PathBuf, u64>,
+}
+
+impl ModuleLoader {
+    /// Create a loader that looks for modules in `search_path`, in order
+    pub fn new(search_path: Vec<PathBuf>) -> Self {
+        Self {
+            search_path,
+            resolved: HashMap::new(),
+            loading: Vec::new(),
+            modules: Vec::new(),
+            type_checking: None,
+            cache: None,
+            keys: HashMap::new(),
+        }
+    }
+
+    /// Type check every module after analyzing it
+    pub fn with_type_checking(mut self, mode: CheckMode) -> Self {
+        self.type_checking = Some(mode);
+        self
+    }
+
+    /// Reuse the analyses in `cache` of modules whose inputs are unchanged,
+    /// and store the analyses of the others there
+    pub fn with_cache(mut self, cache: AnalysisCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// The search path for checking `file`: its directory, then each
+    /// directory listed in the `MAMBAPATH` environment variable
+    pub fn search_path_for(file: &Path) -> Vec<PathBuf> {
+        // The parent of a bare file name is empty, which joins as the
+        // working directory
+        let mut search_path = vec![file.parent().map(Path::to_path_buf).unwrap_or_default()];
+        if let Some(paths) = std::env::var_os("MAMBAPATH") {
+            search_path.extend(std::env::split_paths(&paths).filter(|path| !path.as_os_str().is_empty()));
+        }
+        search_path
+    }
+
+    /// Find the file of a dotted module name on the search path, preferring
+    /// a stub to a source file in the same directory
+    pub fn find(&self, name: &str) -> Option<PathBuf> {
+        let relative: PathBuf = name.split('.').collect();
+        self.search_path.iter().find_map(|directory| {
+            let module = directory.join(&relative);
+            let package = directory.join(&relative).join("__init__");
+            [module, package]
+                .into_iter()
+                .flat_map(|path| [path.with_extension(STUB_EXTENSION), path.with_extension(EXTENSION)])
+                .find(|path| path.is_file())
+        })
+    }
+
+    /// Find the directory of a namespace package on the search path
+    fn find_directory(&self, name: &str) -> Option<PathBuf> {
+        let relative: PathBuf = name.split('.').collect();
+        self.search_path.iter().map(|directory| directory.join(&relative)).find(|path| path.is_dir())
+    }
+
+    /// Analyze a program starting from `file`, and every module it imports
+    /// that is on the search path. The file is named after its stem.
+    pub fn check_file(&mut self, file: &Path) -> io::Result<()> {
+        let source = fs::read_to_string(file)?;
+        let name = file.file_stem().map_or_else(String::new, |stem| stem.to_string_lossy().into_owned());
+        self.load(&name, file.to_path_buf(), source);
+        Ok(())
+    }
+
+    /// Resolve an imported module name, loading the module if it is on the
+    /// search path and has not been loaded yet
+    pub fn import(&mut self, name: &str) -> Import {
+        if let Some(import) = self.resolved.get(name) {
+            return import.clone();
+        }
+        if let Some(start) = self.loading.iter().position(|loading| loading == name) {
+            let mut cycle = self.loading[start..].to_vec();
+            cycle.push(name.to_string());
+            return Import::Cycle(cycle);
+        }
+
+        let import = match self.find(name) {
+            Some(path) => match fs::read_to_string(&path) {
+                Ok(source) => self.load(name, path, source),
+                Err(_) => Import::Missing,
+            },
+            None if is_stdlib_module(name) => Import::Opaque,
+            // A directory without `__init__.mmb` is a namespace package,
+            // which defines nothing but its submodules
+            None => match self.find_directory(name) {
+                Some(path) => Import::Loaded(Rc::new(ModuleInterface {
+                    name: name.to_string(),
+                    path,
+                    symbols: HashMap::new(),
+                    all: None,
+                })),
+                None => Import::Missing,
+            },
+        };
+        self.resolved.insert(name.to_string(), import.clone());
+        import
+    }
+
+    /// The analyzed modules, each after the modules it imports
+    pub fn modules(&self) -> &[CheckedModule] {
+        &self.modules
+    }
+
+    /// Take the analyzed modules out of the loader
+    pub fn into_modules(self) -> Vec<CheckedModule> {
+        self.modules
+    }
+
+    /// Parse and analyze one module, loading its imports first
+    fn load(&mut self, name: &str, path: PathBuf, source: String) -> Import {
+        self.loading.push(name.to_string());
+        let mut cached = false;
+        let (module, diagnostics, import) = match parse(&source) {
+            Ok(module) => {
+                let imports = self.resolve_imports(&module.statements);
+                let key = self.cache.as_ref().map(|_| {
+                    cache::analysis_key(name, &path, &source, self.type_checking, &imports, &self.keys)
+                });
+                let hit = self.cache.as_ref().zip(key).and_then(|(cache, key)| cache.get(&path, key));
+                cached = hit.is_some();
+                let (interface, diagnostics) = match hit {
+                    Some(CachedAnalysis { symbols, all, diagnostics, .. }) => {
+                        let interface = ModuleInterface {
+                            name: name.to_string(),
+                            path: path.clone(),
+                            symbols,
+                            all,
+                        };
+                        (interface, diagnostics)
+                    }
+                    None => {
+                        let mut analyzer = SemanticAnalyzer::new().with_imports(imports);
+                        if let Some(mode) = self.type_checking {
+                            analyzer = analyzer.with_type_checking(mode);
+                        }
+                        let (symbol_table, problems) = analyzer.diagnose(&module);
+                        let interface = ModuleInterface::new(name, &path, &module, &symbol_table);
+                        let diagnostics: Vec<_> = problems.iter().map(|problem| problem.to_diagnostic()).collect();
+                        if let Some((cache, key)) = self.cache.as_ref().zip(key) {
+                            let analysis = CachedAnalysis {
+                                key,
+                                symbols: interface.symbols.clone(),
+                                all: interface.all.clone(),
+                                diagnostics: diagnostics.clone(),
+                            };
+                            if let Err(error) = cache.put(&path, &analysis) {
+                                log::warn!("cannot cache the analysis of {}: {}", path.display(), error);
+                            }
+                        }
+                        (interface, diagnostics)
+                    }
+                };
+                if let Some(key) = key {
+                    self.keys.insert(path.clone(), key);
+                }
+                (Some(module), diagnostics, Import::Loaded(Rc::new(interface)))
+            }
+            Err(errors) => {
+                let diagnostics = errors.iter().filter_map(MambaError::diagnostic).cloned().collect();
+                (None, diagnostics, Import::Opaque)
+            }
+        };
+        self.loading.pop();
+        self.resolved.insert(name.to_string(), import.clone());
+
+        self.modules.push(CheckedModule {
+            name: name.to_string(),
+            path,
+            source,
+            module,
+            diagnostics,
+            cached,
+        });
+        import
+    }
+
+    /// Resolve every module imported anywhere in a body, including function
+    /// and class bodies. `from m import name` also resolves the submodule
+    /// `m.name` when `m` does not define `name` and the submodule exists.
+    fn resolve_imports(&mut self, body: &[Statement]) -> HashMap<String, Import> {
+        let mut imports = HashMap::new();
+        for statement in body {
+            match statement {
+                Statement::Import { items, .. } => {
+                    for item in items {
+                        let import = self.import(&item.module);
+                        imports.insert(item.module.clone(), import);
+                    }
+                }
+                Statement::FromImport { module, items, .. } => {
+                    let import = self.import(module);
+                    if let Import::Loaded(interface) = &import {
+                        for item in items.iter().filter(|item| item.name != "*") {
+                            let submodule = format!("{}.{}", module, item.name);
+                            if interface.lookup(&item.name).is_none() && self.find(&submodule).is_some() {
+                                let import = self.import(&submodule);
+                                imports.insert(submodule, import);
+                            }
+                        }
+                    }
+                    imports.insert(module.clone(), import);
+                }
+                Statement::If { then_block, elif_blocks, else_block, .. } => {
+                    imports.extend(self.resolve_imports(then_block));
+                    for (_, block) in elif_blocks {
+                        imports.extend(self.resolve_imports(block));
+                    }
+                    if let Some(block) = else_block {
+                        imports.extend(self.resolve_imports(block));
+                    }
+                }
+                Statement::While { body, else_block, .. } | Statement::For { body, else_block, .. } => {
+                    imports.extend(self.resolve_imports(body));
+                    if let Some(block) = else_block {
+                        imports.extend(self.resolve_imports(block));
+                    }
+                }
+                Statement::FunctionDef { body, .. } | Statement::ClassDef { body, .. } => {
+                    imports.extend(self.resolve_imports(body));
+                }
+                _ => {}
+            }
+        }
+        imports
+    }
+}
+
+/// Whether a path names a stub file
+pub fn is_stub(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == STUB_EXTENSION)
+}
+
+/// Lex and parse a source file
+fn parse(source: &str) -> Result<Module, Vec<MambaError>> {
+    let tokens = Lexer::new(source).tokenize().map_err(|error| vec![error])?;
+    Parser::new(tokens).parse()
+}
+
+/// The literal `__all__` of a module, following top-level assignments and
+/// `+=` extensions. Any other binding of `__all__` makes it unknown.
+fn declared_all(body: &[Statement]) -> Option<Vec<String>> {
+    let names = |value: &Expression| {
+        string_elements(value).map(|elements| elements.into_iter().map(|(name, _)| name).collect::<Vec<_>>())
+    };
+    let is_all = |target: &Expression| matches!(target, Expression::Identifier { name, .. } if name == "__all__");
+
+    let mut all: Option<Vec<String>> = None;
+    for statement in body {
+        match statement {
+            Statement::Assignment { targets, value, .. } if targets.iter().any(is_all) => all = names(value),
+            Statement::AnnAssignment { target, value, .. } if target == "__all__" => {
+                all = value.as_ref().and_then(names);
+            }
+            Statement::AugmentedAssignment { target, op: AugmentedOperator::Add, value, .. } if is_all(target) => {
+                all = all.zip(names(value)).map(|(mut all, more)| {
+                    all.extend(more);
+                    all
+                });
+            }
+            _ => {}
+        }
+    }
+    all
+}
+
+/// Whether a dotted module name belongs to the Python standard library
+pub fn is_stdlib_module(name: &str) -> bool {
+    let top_level = name.split('.').next().unwrap_or(name);
+    STDLIB_MODULES.binary_search(&top_level).is_ok()
+}
+
+/// Top-level modules of the Python 3.12 standard library, sorted
+const STDLIB_MODULES: &[&str] = &[
+    "__future__", "_thread", "abc", "argparse", "array", "ast", "asyncio", "atexit", "base64",
+    "bdb", "binascii", "bisect", "builtins", "bz2", "cProfile", "calendar", "cmath", "cmd", "code", "codecs",
+    "codeop", "collections", "colorsys", "compileall", "concurrent", "configparser", "contextlib",
+    "contextvars", "copy", "copyreg", "csv", "ctypes", "curses", "dataclasses",
+    "datetime", "dbm", "decimal", "difflib", "dis", "doctest", "email", "encodings", "ensurepip",
+    "enum", "errno", "faulthandler", "fcntl", "filecmp", "fileinput", "fnmatch", "fractions",
+    "ftplib", "functools", "gc", "getopt", "getpass", "gettext", "glob", "graphlib", "grp",
+    "gzip", "hashlib", "heapq", "hmac", "html", "http", "idlelib", "imaplib", "importlib",
+    "inspect", "io", "ipaddress", "itertools", "json", "keyword", "linecache", "locale",
+    "logging", "lzma", "mailbox", "marshal", "math", "mimetypes", "mmap", "modulefinder",
+    "multiprocessing", "netrc", "numbers", "operator", "optparse", "os", "pathlib", "pdb",
+    "pickle", "pickletools", "pkgutil", "platform", "plistlib", "poplib", "posix", "pprint",
+    "profile", "pstats", "pty", "pwd", "py_compile", "pyclbr", "pydoc", "queue", "quopri",
+    "random", "re", "readline", "reprlib", "resource", "rlcompleter", "runpy", "sched",
+    "secrets", "select", "selectors", "shelve", "shlex", "shutil", "signal", "site", "smtplib",
+    "socket", "socketserver", "sqlite3", "ssl", "stat", "statistics", "string", "stringprep",
+    "struct", "subprocess", "symtable", "sys", "sysconfig", "syslog", "tabnanny", "tarfile",
+    "tempfile", "termios", "textwrap", "threading", "time", "timeit", "tkinter", "token",
+    "tokenize", "tomllib", "trace", "traceback", "tracemalloc", "tty", "turtle", "types",
+    "typing", "unicodedata", "unittest", "urllib", "uuid", "venv", "warnings", "wave",
+    "weakref", "webbrowser", "winreg", "wsgiref", "xml", "xmlrpc", "zipapp", "zipfile",
+    "zipimport", "zlib", "zoneinfo",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write `files` into a fresh directory and check and type check
+    /// `main.mmb`, returning the codes and messages of each module's
+    /// diagnostics by module name
+    fn check(test: &str, files: &[(&str, &str)]) -> HashMap<String, Vec<(String, String)>> {
+        let root = std::env::temp_dir().join(format!("mamba-modules-{}-{}", std::process::id(), test));
+        let _ = fs::remove_dir_all(&root);
+        for (path, source) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, source).unwrap();
+        }
+
+        let mut loader = ModuleLoader::new(vec![root.clone()]).with_type_checking(CheckMode::Lenient);
+        loader.check_file(&root.join("main.mmb")).unwrap();
+        let results = loader
+            .into_modules()
+            .into_iter()
+            .map(|module| {
+                let diagnostics =
+                    module.diagnostics.iter().map(|d| (d.code.to_string(), d.message.clone())).collect();
+                (module.name, diagnostics)
+            })
+            .collect();
+        fs::remove_dir_all(&root).unwrap();
+        results
+    }
+
+    #[test]
+    fn test_stdlib_modules_sorted() {
+        assert!(STDLIB_MODULES.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(is_stdlib_module("os.path"));
+        assert!(!is_stdlib_module("helpers"));
+    }
+
+    #[test]
+    fn test_from_import_checked_against_module() {
+        let results = check(
+            "from-import",
+            &[
+                ("main.mmb", "from shapes import area, perimeter\nprint(area(1, 2, 3))\n"),
+                ("shapes.mmb", "def area(width, height):\n    return width * height\n"),
+            ],
+        );
+        assert!(results["shapes"].is_empty());
+        let codes: Vec<&str> = results["main"].iter().map(|(code, _)| code.as_str()).collect();
+        assert_eq!(codes, vec!["E0220", "E0211"]);
+        assert_eq!(results["main"][0].1, "cannot import name 'perimeter' from 'shapes'");
+    }
+
+    #[test]
+    fn test_unknown_module() {
+        let results = check("unknown", &[("main.mmb", "import os.path\nimport helpres\nfrom json import loads\n")]);
+        assert_eq!(results["main"], vec![("E0219".to_string(), "no module named 'helpres'".to_string())]);
+    }
+
+    #[test]
+    fn test_packages_and_submodules() {
+        let results = check(
+            "packages",
+            &[
+                ("main.mmb", "import geometry.shapes\nfrom geometry import shapes, VERSION\nfrom util import text\n"),
+                ("geometry/__init__.mmb", "VERSION = 1\n"),
+                ("geometry/shapes.mmb", "SIDES = 4\n"),
+                ("util/text.mmb", "WIDTH = 80\n"),
+            ],
+        );
+        assert!(results["main"].is_empty(), "{:?}", results["main"]);
+        assert_eq!(results.len(), 4);
+    }
+
+    #[test]
+    fn test_star_import_honors_all() {
+        let results = check(
+            "star",
+            &[
+                ("main.mmb", "from shapes import *\nprint(square(2))\nprint(cube(2))\nprint(_helper)\n"),
+                (
+                    "shapes.mmb",
+                    "__all__ = ['square']\ndef square(x):\n    return x * x\ndef cube(x):\n    return x * x * x\n_helper = 1\n",
+                ),
+            ],
+        );
+        let messages: Vec<&str> = results["main"].iter().map(|(_, message)| message.as_str()).collect();
+        assert_eq!(messages, vec!["Undefined variable: 'cube'", "Undefined variable: '_helper'"]);
+    }
+
+    #[test]
+    fn test_import_cycle_reported_once() {
+        let results = check(
+            "cycle",
+            &[
+                ("main.mmb", "import a\n"),
+                ("a.mmb", "from b import y\nz = 1\n"),
+                ("b.mmb", "from a import z\ny = 2\ndef later():\n    import main\n"),
+            ],
+        );
+        assert!(results["main"].is_empty());
+        assert!(results["a"].is_empty());
+        assert_eq!(results["b"], vec![("E0221".to_string(), "import cycle: a -> b -> a".to_string())]);
+    }
+
+    #[test]
+    fn test_module_with_parse_error_is_opaque() {
+        let results = check(
+            "opaque",
+            &[("main.mmb", "from broken import anything\n"), ("broken.mmb", "def (:\n")],
+        );
+        assert!(results["main"].is_empty());
+        assert!(!results["broken"].is_empty());
+    }
+
+    #[test]
+    fn test_stub_preferred_to_source() {
+        let stub = "PI: float\n\ndef hypot(x: float, y: float) -> float:\n    ...\n\n\
+                    class Point:\n    x: float\n\n    def __init__(self, x: float) -> None:\n        ...\n\n    \
+                    def scaled(self, factor: float) -> Point:\n        ...\n";
+        let results = check(
+            "stub",
+            &[
+                (
+                    "main.mmb",
+                    "from geometry import PI, Point, hypot\nd = hypot(PI, 'far')\nlabel: str = Point(1.0).scaled(2).x\n",
+                ),
+                ("geometry.mmbi", stub),
+                ("geometry.mmb", "def (:\n"),
+            ],
+        );
+        assert!(results["geometry"].is_empty(), "{:?}", results["geometry"]);
+        assert_eq!(
+            results["main"],
+            vec![
+                ("E0224".to_string(), "argument 'y' of 'hypot' has type 'str', expected 'float'".to_string()),
+                ("E0226".to_string(), "'label' is declared as 'str' but assigned a value of type 'float'".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unchanged_modules_reuse_cached_analysis() {
+        let root = std::env::temp_dir().join(format!("mamba-modules-{}-cache", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let write = |path: &str, source: &str| fs::write(root.join(path), source).unwrap();
+        write("main.mmb", "from shapes import Square, area\nside: str = area(Square(2))\n");
+        write("shapes.mmb", "class Square:\n    def __init__(self, side: int):\n        self.side = side\n\n\n\
+                              def area(shape: Square) -> int:\n    return shape.side * shape.side\n");
+        write("other.mmb", "import shapes\n");
+
+        let run = |file: &str| {
+            let cache = AnalysisCache::new(root.join(".mamba_cache"));
+            let mut loader = ModuleLoader::new(vec![root.clone()]).with_type_checking(CheckMode::Lenient);
+            loader = loader.with_cache(cache);
+            loader.check_file(&root.join(file)).unwrap();
+            loader
+                .into_modules()
+                .into_iter()
+                .map(|module| (module.name, (module.cached, module.diagnostics.len())))
+                .collect::<HashMap<_, _>>()
+        };
+        let first = run("main.mmb");
+        assert_eq!(first["main"], (false, 1));
+        assert_eq!(first["shapes"], (false, 0));
+        // The cached interface of `shapes` still types `area`
+        let second = run("main.mmb");
+        assert_eq!(second["main"], (true, 1));
+        assert_eq!(second["shapes"], (true, 0));
+        assert_eq!(run("other.mmb")["shapes"], (true, 0));
+
+        // A change to an imported module invalidates its importers
+        write("shapes.mmb", "class Square:\n    def __init__(self, side: int):\n        self.side = side\n\n\n\
+                              def area(shape: Square) -> str:\n    return str(shape.side)\n");
+        let third = run("main.mmb");
+        assert_eq!(third["main"], (false, 0));
+        assert_eq!(third["shapes"], (false, 0));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_stub_declares_stdlib_module() {
+        let results = check(
+            "stub-stdlib",
+            &[
+                ("main.mmb", "from math import sqrt, tau\nroot: int = sqrt(2)\n"),
+                ("math.mmbi", "def sqrt(x: float, /) -> float:\n    ...\n"),
+            ],
+        );
+        let messages: Vec<&str> = results["main"].iter().map(|(_, message)| message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "cannot import name 'tau' from 'math'",
+                "'root' is declared as 'int' but assigned a value of type 'float'",
+            ]
+        );
+        assert!(results.iter().all(|(name, _)| name == "main" || name == "math"));
+    }
+}
@@ -3,8 +3,9 @@
 //! Converts a stream of tokens into an Abstract Syntax Tree (AST).
 
 use crate::ast::*;
+use crate::suggest;
 use crate::token::{SourcePosition, Token, TokenKind};
-use mamba_error::MambaError;
+use mamba_error::{Diagnostic, ErrorCode, MambaError, Span};
 use std::iter::Peekable;
 use std::vec::IntoIter;
 
@@ -21,9 +22,11 @@ pub struct Parser {
 
 impl Parser {
     /// Create a new parser from a vector of tokens
+    ///
+    /// Comment tokens are dropped; they carry no meaning for the grammar.
     pub fn new(tokens: Vec<Token>) -> Self {
         let mut parser = Parser {
-            tokens: tokens.into_iter().peekable(),
+            tokens: Self::strip_comments(tokens).into_iter().peekable(),
             current_token: None,
             previous_position: SourcePosition::new(0, 0, 0),
             errors: Vec::new(),
@@ -33,6 +36,26 @@ impl Parser {
         parser
     }
 
+    /// Remove comments; a comment on a line of its own is removed together
+    /// with its newline so it behaves like a blank line
+    fn strip_comments(tokens: Vec<Token>) -> Vec<Token> {
+        let mut stripped: Vec<Token> = Vec::with_capacity(tokens.len());
+        let mut skip_newline = false;
+        for token in tokens {
+            match token.kind {
+                TokenKind::Comment(_) => {
+                    skip_newline = matches!(stripped.last().map(|t| &t.kind), None | Some(TokenKind::Newline));
+                }
+                TokenKind::Newline if skip_newline => skip_newline = false,
+                _ => {
+                    skip_newline = false;
+                    stripped.push(token);
+                }
+            }
+        }
+        stripped
+    }
+
     /// Parse a complete module (list of statements)
     /// Returns the parsed module and any errors encountered during parsing
     pub fn parse(&mut self) -> Result<Module, Vec<MambaError>> {
@@ -47,6 +70,7 @@ impl Parser {
             }
             
             // Try to parse statement, recover on error
+            let statement_start = self.current_position();
             match self.parse_statement() {
                 Ok(stmt) => {
                     statements.push(stmt);
@@ -58,6 +82,12 @@ impl Parser {
                         self.errors.push(e);
                         self.panic_mode = true;
                     }
+                    // A statement that failed on its first token (e.g. an unsupported
+                    // keyword that is also a recovery point) must be skipped, or
+                    // synchronize() would stop on it again and never make progress
+                    if self.current_position() == statement_start {
+                        self.advance();
+                    }
                     // Try to recover to next statement
                     self.synchronize();
                 }
@@ -115,21 +145,15 @@ impl Parser {
                         if self.match_token(&TokenKind::Def) {
                             self.parse_function_def(true, pos, decorators)
                         } else {
-                            return Err(MambaError::ParseError(
-                                format!("Expected 'def' after 'async' at {}:{}", 
-                                    self.current_position().line, 
-                                    self.current_position().column)
-                            ));
+                            return Err(self.error("Expected 'def' after 'async'"));
                         }
                     }
                     Some(TokenKind::Class) => {
                         self.parse_class_def(decorators)
                     }
                     _ => {
-                        return Err(MambaError::ParseError(
-                            format!("Expected function or class definition after decorator at {}:{}", 
-                                self.current_position().line, 
-                                self.current_position().column)
+                        return Err(self.error(
+                            "Expected function or class definition after decorator",
                         ));
                     }
                 }
@@ -146,11 +170,7 @@ impl Parser {
                 if self.match_token(&TokenKind::Def) {
                     self.parse_function_def(true, pos, Vec::new())
                 } else {
-                    return Err(MambaError::ParseError(
-                        format!("Expected 'def' after 'async' at {}:{}", 
-                            self.current_position().line, 
-                            self.current_position().column)
-                    ));
+                    return Err(self.error("Expected 'def' after 'async'"));
                 }
             }
             Some(TokenKind::Class) => self.parse_class_def(Vec::new()),
@@ -269,9 +289,11 @@ impl Parser {
                     if let Some(suggestion) = self.suggest_keyword_fix(name) {
                         // Check if this looks like a statement keyword context (followed by identifier/colon)
                         if matches!(self.current_kind(), Some(TokenKind::Identifier(_)) | Some(TokenKind::Colon)) {
-                            return Err(MambaError::ParseError(format!(
-                                "Unexpected identifier '{}' at {}:{}. {}",
-                                name, position.line, position.column, suggestion
+                            let message = format!("Unexpected identifier '{}'", name);
+                            return Err(MambaError::ParseError(Box::new(
+                                Diagnostic::error(ErrorCode::InvalidSyntax, message)
+                                    .with_span(position.span(name.chars().count()))
+                                    .with_help(suggestion),
                             )));
                         }
                     }
@@ -411,19 +433,14 @@ impl Parser {
                 _ => {
                     // Provide more specific error message if no names were parsed yet
                     if names.is_empty() {
-                        return Err(MambaError::ParseError(
-                            format!("Expected at least one identifier after '{}' at {}:{}", 
+                        return Err(self.error(
+                            format!(
+                                "Expected at least one identifier after '{}'",
                                 keyword,
-                                self.current_position().line, 
-                                self.current_position().column)
+                            ),
                         ));
                     } else {
-                        return Err(MambaError::ParseError(
-                            format!("Expected identifier after '{}' at {}:{}", 
-                                keyword,
-                                self.current_position().line, 
-                                self.current_position().column)
-                        ));
+                        return Err(self.error(format!("Expected identifier after '{}'", keyword)));
                     }
                 }
             }
@@ -485,11 +502,7 @@ impl Parser {
                         Some(alias_name)
                     }
                     _ => {
-                        return Err(MambaError::ParseError(
-                            format!("Expected identifier after 'as' at {}:{}", 
-                                self.current_position().line, 
-                                self.current_position().column)
-                        ));
+                        return Err(self.error("Expected identifier after 'as'"));
                     }
                 }
             } else {
@@ -533,12 +546,7 @@ impl Parser {
                 self.advance();
             }
             _ => {
-                return Err(MambaError::ParseError(
-                    format!("Expected module name after '{}' at {}:{}", 
-                        context,
-                        self.current_position().line, 
-                        self.current_position().column)
-                ));
+                return Err(self.error(format!("Expected module name after '{}'", context)));
             }
         }
         
@@ -550,11 +558,7 @@ impl Parser {
                     self.advance();
                 }
                 _ => {
-                    return Err(MambaError::ParseError(
-                        format!("Expected identifier after '.' in module name at {}:{}", 
-                            self.current_position().line, 
-                            self.current_position().column)
-                    ));
+                    return Err(self.error("Expected identifier after '.' in module name"));
                 }
             }
         }
@@ -572,10 +576,8 @@ impl Parser {
         
         // Expect 'import' keyword
         if !self.match_token(&TokenKind::Import) {
-            return Err(MambaError::ParseError(
-                format!("Expected 'import' after module name in from...import statement at {}:{}", 
-                    self.current_position().line, 
-                    self.current_position().column)
+            return Err(self.error(
+                "Expected 'import' after module name in from...import statement",
             ));
         }
         
@@ -588,20 +590,12 @@ impl Parser {
             
             // Wildcard can't have an alias
             if self.check(&TokenKind::As) {
-                return Err(MambaError::ParseError(
-                    format!("Wildcard import cannot have an alias at {}:{}", 
-                        self.current_position().line, 
-                        self.current_position().column)
-                ));
+                return Err(self.error("Wildcard import cannot have an alias"));
             }
             
             // Wildcard must be alone (no comma-separated names)
             if self.check(&TokenKind::Comma) {
-                return Err(MambaError::ParseError(
-                    format!("Wildcard import cannot be combined with other imports at {}:{}", 
-                        self.current_position().line, 
-                        self.current_position().column)
-                ));
+                return Err(self.error("Wildcard import cannot be combined with other imports"));
             }
             
             items.push(FromImportItem {
@@ -622,11 +616,7 @@ impl Parser {
                         name_str
                     }
                     _ => {
-                        return Err(MambaError::ParseError(
-                            format!("Expected identifier after 'import' at {}:{}", 
-                                self.current_position().line, 
-                                self.current_position().column)
-                        ));
+                        return Err(self.error("Expected identifier after 'import'"));
                     }
                 };
                 
@@ -639,11 +629,7 @@ impl Parser {
                             Some(alias_name)
                         }
                         _ => {
-                            return Err(MambaError::ParseError(
-                                format!("Expected identifier after 'as' at {}:{}", 
-                                    self.current_position().line, 
-                                    self.current_position().column)
-                            ));
+                            return Err(self.error("Expected identifier after 'as'"));
                         }
                     }
                 } else {
@@ -780,11 +766,7 @@ impl Parser {
         
         // Expect 'in' keyword
         if !self.match_token(&TokenKind::In) {
-            return Err(MambaError::ParseError(
-                format!("Expected 'in' after for target at {}:{}", 
-                    self.current_position().line, 
-                    self.current_position().column)
-            ));
+            return Err(self.error("Expected 'in' after for target"));
         }
         
         // Parse iterable expression
@@ -832,11 +814,7 @@ impl Parser {
                 id
             }
             _ => {
-                return Err(MambaError::ParseError(
-                    format!("Expected identifier in for target at {}:{}", 
-                        self.current_position().line, 
-                        self.current_position().column)
-                ));
+                return Err(self.error("Expected identifier in for target"));
             }
         };
         
@@ -860,11 +838,7 @@ impl Parser {
                         self.advance();
                     }
                     _ => {
-                        return Err(MambaError::ParseError(
-                            format!("Expected identifier in for target at {}:{}", 
-                                self.current_position().line, 
-                                self.current_position().column)
-                        ));
+                        return Err(self.error("Expected identifier in for target"));
                     }
                 }
                 
@@ -893,11 +867,7 @@ impl Parser {
             
             // Expect newline after decorator
             if !self.match_token(&TokenKind::Newline) {
-                return Err(MambaError::ParseError(
-                    format!("Expected newline after decorator at {}:{}", 
-                        self.current_position().line, 
-                        self.current_position().column)
-                ));
+                return Err(self.error("Expected newline after decorator"));
             }
         }
         
@@ -920,6 +890,8 @@ impl Parser {
             }
         };
         
+        let type_params = self.parse_type_params()?;
+
         // Expect opening parenthesis
         if !self.match_token(&TokenKind::LeftParen) {
             return Err(self.expected_after("'('", "function name"));
@@ -930,11 +902,7 @@ impl Parser {
         
         // Expect closing parenthesis
         if !self.match_token(&TokenKind::RightParen) {
-            return Err(MambaError::ParseError(
-                format!("Expected ')' after parameters at {}:{}", 
-                    self.current_position().line, 
-                    self.current_position().column)
-            ));
+            return Err(self.error("Expected ')' after parameters"));
         }
         
         // Parse optional return type annotation (-> type)
@@ -954,6 +922,7 @@ impl Parser {
         
         Ok(Statement::FunctionDef {
             name,
+            type_params,
             parameters,
             body,
             is_async,
@@ -976,14 +945,12 @@ impl Parser {
                 class_name
             }
             _ => {
-                return Err(MambaError::ParseError(
-                    format!("Expected class name after 'class' at {}:{}", 
-                        self.current_position().line, 
-                        self.current_position().column)
-                ));
+                return Err(self.error("Expected class name after 'class'"));
             }
         };
         
+        let type_params = self.parse_type_params()?;
+
         // Parse optional base classes (inheritance) and metaclass
         let mut bases = Vec::new();
         let mut metaclass = None;
@@ -1003,20 +970,14 @@ impl Parser {
                                 self.advance(); // consume identifier
                                 
                                 if id_name != "metaclass" {
-                                    return Err(MambaError::ParseError(
-                                        format!("Invalid keyword argument '{}' in class definition. Only 'metaclass' is allowed at {}:{}", 
-                                            id_name,
-                                            self.current_position().line, 
-                                            self.current_position().column)
+                                    return Err(self.error_with_suggestion(
+                                        format!("Invalid keyword argument '{}' in class definition", id_name),
+                                        Some("Only 'metaclass' is allowed".to_string()),
                                     ));
                                 }
                                 
                                 if metaclass.is_some() {
-                                    return Err(MambaError::ParseError(
-                                        format!("Duplicate metaclass specification at {}:{}", 
-                                            self.current_position().line, 
-                                            self.current_position().column)
-                                    ));
+                                    return Err(self.error("Duplicate metaclass specification"));
                                 }
                                 
                                 self.advance(); // consume '='
@@ -1042,10 +1003,8 @@ impl Parser {
                     
                     // Not a keyword argument - parse as base class
                     if metaclass.is_some() {
-                        return Err(MambaError::ParseError(
-                            format!("Base classes must come before metaclass specification at {}:{}", 
-                                self.current_position().line, 
-                                self.current_position().column)
+                        return Err(self.error(
+                            "Base classes must come before metaclass specification",
                         ));
                     }
                     
@@ -1067,11 +1026,7 @@ impl Parser {
             
             // Expect closing parenthesis
             if !self.match_token(&TokenKind::RightParen) {
-                return Err(MambaError::ParseError(
-                    format!("Expected ')' after base classes at {}:{}", 
-                        self.current_position().line, 
-                        self.current_position().column)
-                ));
+                return Err(self.error("Expected ')' after base classes"));
             }
         }
         
@@ -1085,6 +1040,7 @@ impl Parser {
         
         Ok(Statement::ClassDef {
             name,
+            type_params,
             bases,
             body,
             decorators,
@@ -1093,6 +1049,36 @@ impl Parser {
         })
     }
 
+    /// Parse an optional type parameter list after a function or class name
+    /// (`[T, U: int, V: (int, str)]`)
+    fn parse_type_params(&mut self) -> ParseResult<Vec<TypeParam>> {
+        let mut type_params = Vec::new();
+        if !self.match_token(&TokenKind::LeftBracket) {
+            return Ok(type_params);
+        }
+        loop {
+            let position = self.current_position();
+            let name = match self.current_kind() {
+                Some(TokenKind::Identifier(name)) => name.clone(),
+                _ => return Err(self.error("Expected type parameter name")),
+            };
+            self.advance();
+            let bound = if self.match_token(&TokenKind::Colon) {
+                Some(self.parse_expression()?)
+            } else {
+                None
+            };
+            type_params.push(TypeParam { name, bound, position });
+            if !self.match_token(&TokenKind::Comma) || self.check(&TokenKind::RightBracket) {
+                break;
+            }
+        }
+        if !self.match_token(&TokenKind::RightBracket) {
+            return Err(self.error("Expected ']' after type parameters"));
+        }
+        Ok(type_params)
+    }
+
     /// Parse parameter list inside function definition
     fn parse_parameter_list(&mut self) -> ParseResult<Vec<Parameter>> {
         let mut parameters = Vec::new();
@@ -1113,21 +1099,24 @@ impl Parser {
             // Check for / (positional-only marker)
             if self.match_token(&TokenKind::Slash) {
                 if seen_slash {
-                    return Err(MambaError::ParseError(
-                        format!("Duplicate '/' parameter at {}:{}", 
-                            param_pos.line, param_pos.column)
+                    return Err(self.error_at(
+                        ErrorCode::InvalidParameters,
+                        "Duplicate '/' parameter",
+                        &param_pos,
                     ));
                 }
                 if seen_varargs_or_bare_star {
-                    return Err(MambaError::ParseError(
-                        format!("'/' must come before '*' or '*args' at {}:{}", 
-                            param_pos.line, param_pos.column)
+                    return Err(self.error_at(
+                        ErrorCode::InvalidParameters,
+                        "'/' must come before '*' or '*args'",
+                        &param_pos,
                     ));
                 }
                 if seen_varkwargs {
-                    return Err(MambaError::ParseError(
-                        format!("'/' must come before '**kwargs' at {}:{}", 
-                            param_pos.line, param_pos.column)
+                    return Err(self.error_at(
+                        ErrorCode::InvalidParameters,
+                        "'/' must come before '**kwargs'",
+                        &param_pos,
                     ));
                 }
                 
@@ -1144,9 +1133,10 @@ impl Parser {
             // Check for **kwargs
             else if self.match_token(&TokenKind::DoubleStar) {
                 if seen_varkwargs {
-                    return Err(MambaError::ParseError(
-                        format!("Duplicate **kwargs parameter at {}:{}", 
-                            param_pos.line, param_pos.column)
+                    return Err(self.error_at(
+                        ErrorCode::InvalidParameters,
+                        "Duplicate **kwargs parameter",
+                        &param_pos,
                     ));
                 }
                 
@@ -1157,11 +1147,7 @@ impl Parser {
                         name
                     }
                     _ => {
-                        return Err(MambaError::ParseError(
-                            format!("Expected parameter name after '**' at {}:{}", 
-                                self.current_position().line, 
-                                self.current_position().column)
-                        ));
+                        return Err(self.error("Expected parameter name after '**'"));
                     }
                 };
                 
@@ -1185,15 +1171,17 @@ impl Parser {
             // Check for * (either *args or bare * for keyword-only)
             else if self.match_token(&TokenKind::Star) {
                 if seen_varargs_or_bare_star {
-                    return Err(MambaError::ParseError(
-                        format!("Duplicate * or *args parameter at {}:{}", 
-                            param_pos.line, param_pos.column)
+                    return Err(self.error_at(
+                        ErrorCode::InvalidParameters,
+                        "Duplicate * or *args parameter",
+                        &param_pos,
                     ));
                 }
                 if seen_varkwargs {
-                    return Err(MambaError::ParseError(
-                        format!("* or *args must come before **kwargs at {}:{}", 
-                            param_pos.line, param_pos.column)
+                    return Err(self.error_at(
+                        ErrorCode::InvalidParameters,
+                        "* or *args must come before **kwargs",
+                        &param_pos,
                     ));
                 }
                 
@@ -1211,11 +1199,7 @@ impl Parser {
                             name
                         }
                         _ => {
-                            return Err(MambaError::ParseError(
-                                format!("Expected parameter name after '*' at {}:{}", 
-                                    self.current_position().line, 
-                                    self.current_position().column)
-                            ));
+                            return Err(self.error("Expected parameter name after '*'"));
                         }
                     };
                     
@@ -1241,9 +1225,10 @@ impl Parser {
             // Regular, positional-only, or keyword-only parameter
             else {
                 if seen_varkwargs {
-                    return Err(MambaError::ParseError(
-                        format!("Parameter cannot appear after **kwargs at {}:{}", 
-                            param_pos.line, param_pos.column)
+                    return Err(self.error_at(
+                        ErrorCode::InvalidParameters,
+                        "Parameter cannot appear after **kwargs",
+                        &param_pos,
                     ));
                 }
                 
@@ -1254,11 +1239,7 @@ impl Parser {
                         name
                     }
                     _ => {
-                        return Err(MambaError::ParseError(
-                            format!("Expected parameter name at {}:{}", 
-                                self.current_position().line, 
-                                self.current_position().column)
-                        ));
+                        return Err(self.error("Expected parameter name"));
                     }
                 };
                 
@@ -1285,9 +1266,10 @@ impl Parser {
                     if default.is_some() {
                         seen_default = true;
                     } else if seen_default {
-                        return Err(MambaError::ParseError(
-                            format!("Parameter without default cannot follow parameter with default at {}:{}", 
-                                param_pos.line, param_pos.column)
+                        return Err(self.error_at(
+                            ErrorCode::InvalidParameters,
+                            "Parameter without default cannot follow parameter with default",
+                            &param_pos,
                         ));
                     }
                     ParameterKind::Regular
@@ -1320,20 +1302,12 @@ impl Parser {
     fn parse_block(&mut self) -> ParseResult<Vec<Statement>> {
         // Consume newline after colon
         if !self.match_token(&TokenKind::Newline) {
-            return Err(MambaError::ParseError(
-                format!("Expected newline after ':' at {}:{}", 
-                    self.current_position().line, 
-                    self.current_position().column)
-            ));
+            return Err(self.error("Expected newline after ':'"));
         }
         
         // Expect INDENT token
         if !self.match_token(&TokenKind::Indent) {
-            return Err(MambaError::ParseError(
-                format!("Expected indented block at {}:{}", 
-                    self.current_position().line, 
-                    self.current_position().column)
-            ));
+            return Err(self.error("Expected indented block"));
         }
         
         // Parse statements until DEDENT
@@ -1354,19 +1328,11 @@ impl Parser {
         
         // Expect DEDENT token
         if !self.match_token(&TokenKind::Dedent) {
-            return Err(MambaError::ParseError(
-                format!("Expected dedent after block at {}:{}", 
-                    self.current_position().line, 
-                    self.current_position().column)
-            ));
+            return Err(self.error("Expected dedent after block"));
         }
         
         if statements.is_empty() {
-            return Err(MambaError::ParseError(
-                format!("Block cannot be empty (use 'pass' for empty blocks) at {}:{}", 
-                    self.current_position().line, 
-                    self.current_position().column)
-            ));
+            return Err(self.error("Block cannot be empty (use 'pass' for empty blocks)"));
         }
         
         Ok(statements)
@@ -1377,8 +1343,10 @@ impl Parser {
         let starred_count = self.count_starred_expressions(targets);
         
         if starred_count > 1 {
-            return Err(MambaError::ParseError(
-                format!("Multiple starred expressions in assignment (only one allowed)")
+            return Err(self.error_at(
+                ErrorCode::InvalidAssignmentTarget,
+                "Multiple starred expressions in assignment (only one allowed)",
+                targets[0].position(),
             ));
         }
         
@@ -1422,31 +1390,40 @@ impl Parser {
                     | Literal::None { position }
                     | Literal::Ellipsis { position } => position,
                 };
-                Err(MambaError::ParseError(
-                    format!("Cannot assign to literal at {}:{}", position.line, position.column)
+                Err(self.error_at(
+                    ErrorCode::InvalidAssignmentTarget,
+                    "Cannot assign to literal",
+                    position,
                 ))
             }
             Expression::BinaryOp { position, .. }
             | Expression::UnaryOp { position, .. } => {
-                Err(MambaError::ParseError(
-                    format!("Cannot assign to operator at {}:{}", position.line, position.column)
+                Err(self.error_at(
+                    ErrorCode::InvalidAssignmentTarget,
+                    "Cannot assign to operator",
+                    position,
                 ))
             }
             Expression::Call { position, .. } => {
-                Err(MambaError::ParseError(
-                    format!("Cannot assign to function call at {}:{}", position.line, position.column)
+                Err(self.error_at(
+                    ErrorCode::InvalidAssignmentTarget,
+                    "Cannot assign to function call",
+                    position,
                 ))
             }
             Expression::Lambda { position, .. } => {
-                Err(MambaError::ParseError(
-                    format!("Cannot assign to lambda at {}:{}", position.line, position.column)
+                Err(self.error_at(
+                    ErrorCode::InvalidAssignmentTarget,
+                    "Cannot assign to lambda",
+                    position,
                 ))
             }
             _ => {
                 // For other expression types, reject as invalid assignment target
-                Err(MambaError::ParseError(
-                    format!("Invalid assignment target at {}:{}", 
-                        target.position().line, target.position().column)
+                Err(self.error_at(
+                    ErrorCode::InvalidAssignmentTarget,
+                    "Invalid assignment target",
+                    target.position(),
                 ))
             }
         }
@@ -1586,10 +1563,11 @@ impl Parser {
                     }
                     _ => {
                         let curr_pos = self.current_position();
-                        return Err(MambaError::ParseError(format!(
-                            "Expected parameter name at {}:{}",
-                            curr_pos.line, curr_pos.column
-                        )));
+                        return Err(self.error_at(
+                            ErrorCode::InvalidSyntax,
+                            "Expected parameter name",
+                            &curr_pos,
+                        ));
                     }
                 }
                 
@@ -1834,12 +1812,13 @@ impl Parser {
                     // Function call: func(args)
                     self.advance(); // consume '('
                     let mut arguments = Vec::new();
+                    let mut keywords = Vec::new();
                     let call_pos = expr.position().clone();
 
                     // Parse arguments if not empty
                     if !self.check(&TokenKind::RightParen) {
                         loop {
-                            arguments.push(self.parse_expression()?);
+                            self.parse_call_argument(&mut arguments, &mut keywords)?;
                             
                             if !self.match_token(&TokenKind::Comma) {
                                 break;
@@ -1857,6 +1836,7 @@ impl Parser {
                     expr = Expression::Call {
                         function: Box::new(expr),
                         arguments,
+                        keywords,
                         position: call_pos,
                     };
                 }
@@ -1916,9 +1896,7 @@ impl Parser {
                             };
                         }
                         _ => {
-                            return Err(MambaError::ParseError(
-                                format!("Expected identifier after '.' at {}", self.current_position())
-                            ));
+                            return Err(self.error("Expected identifier after '.'"));
                         }
                     }
                 }
@@ -2367,15 +2345,20 @@ impl Parser {
     // Error Message Helpers
     // ========================================
 
-    /// Create a formatted error with current position
+    /// Create a syntax error at the current token
     fn error(&self, message: impl Into<String>) -> MambaError {
-        let pos = self.current_position();
-        MambaError::ParseError(format!("{} at {}:{}", message.into(), pos.line, pos.column))
+        self.error_with_suggestion(message, None)
+    }
+
+    /// Create an error with a specific code at the given position
+    fn error_at(&self, code: ErrorCode, message: impl Into<String>, position: &SourcePosition) -> MambaError {
+        MambaError::ParseError(Box::new(
+            Diagnostic::error(code, message).with_span(position.span(1)),
+        ))
     }
 
     /// Create "Expected X, found Y" error message
     fn expected(&self, expected: &str) -> MambaError {
-        let pos = self.current_position();
         let found = self.current_token_string();
         
         // Check if the found token is a common keyword typo
@@ -2385,26 +2368,13 @@ impl Parser {
             None
         };
         
-        let base_msg = format!(
-            "Expected {}, found {} at {}:{}",
-            expected, found, pos.line, pos.column
-        );
-        
-        if let Some(suggestion_text) = suggestion {
-            MambaError::ParseError(format!("{}. {}", base_msg, suggestion_text))
-        } else {
-            MambaError::ParseError(base_msg)
-        }
+        self.error_with_suggestion(format!("Expected {}, found {}", expected, found), suggestion)
     }
 
     /// Create "Expected X after Y" error message
     fn expected_after(&self, expected: &str, after: &str) -> MambaError {
-        let pos = self.current_position();
         let found = self.current_token_string();
-        MambaError::ParseError(format!(
-            "Expected {} after {}, found {} at {}:{}",
-            expected, after, found, pos.line, pos.column
-        ))
+        self.error(format!("Expected {} after {}, found {}", expected, after, found))
     }
 
     /// Get a human-readable string for the current token
@@ -2511,11 +2481,18 @@ impl Parser {
 
     /// Create error with optional suggestion
     fn error_with_suggestion(&self, message: impl Into<String>, suggestion: Option<String>) -> MambaError {
-        let pos = self.current_position();
-        let msg = message.into();
-        match suggestion {
-            Some(hint) => MambaError::ParseError(format!("{} at {}:{}. {}", msg, pos.line, pos.column, hint)),
-            None => MambaError::ParseError(format!("{} at {}:{}", msg, pos.line, pos.column)),
+        MambaError::ParseError(Box::new(
+            Diagnostic::error(ErrorCode::InvalidSyntax, message)
+                .with_span(self.current_span())
+                .with_optional_help(suggestion),
+        ))
+    }
+
+    /// Span covering the current token
+    fn current_span(&self) -> Span {
+        match &self.current_token {
+            Some(token) => token.position.span(token.lexeme.chars().count()),
+            None => self.current_position().span(1),
         }
     }
 
@@ -2533,6 +2510,11 @@ impl Parser {
             "foreach" => Some("Did you mean 'for'?".to_string()),
             "until" => Some("Mamba uses 'while not' instead of 'until'".to_string()),
             "unless" => Some("Mamba uses 'if not' instead of 'unless'".to_string()),
+            // Very short identifiers are too close to too many keywords to guess
+            _ if identifier.chars().count() >= 3 => {
+                suggest::best_match(identifier, TokenKind::KEYWORDS.iter().copied())
+                    .map(|keyword| format!("Did you mean '{}'?", keyword))
+            }
             _ => None,
         }
     }
@@ -2622,6 +2604,60 @@ impl Parser {
         )
     }
 
+    /// Parse one call argument: `expr`, `*expr`, `name=expr` or `**expr`
+    ///
+    /// Positional arguments may not follow keyword arguments, and `*expr`
+    /// may not follow `**expr`, matching Python's call grammar.
+    fn parse_call_argument(
+        &mut self,
+        arguments: &mut Vec<Expression>,
+        keywords: &mut Vec<Keyword>,
+    ) -> ParseResult<()> {
+        if self.match_token(&TokenKind::DoubleStar) {
+            let position = self.previous_position();
+            let value = self.parse_expression()?;
+            keywords.push(Keyword { name: None, value, position });
+            return Ok(());
+        }
+
+        if self.match_token(&TokenKind::Star) {
+            let position = self.previous_position();
+            if keywords.iter().any(|k| k.name.is_none()) {
+                return Err(self.error_at(
+                    ErrorCode::InvalidSyntax,
+                    "Iterable argument unpacking follows keyword argument unpacking",
+                    &position,
+                ));
+            }
+            let value = Box::new(self.parse_expression()?);
+            arguments.push(Expression::Starred { value, position });
+            return Ok(());
+        }
+
+        let value = self.parse_expression()?;
+        if let Expression::Identifier { name, position } = &value {
+            if self.match_token(&TokenKind::Assign) {
+                let keyword = Keyword {
+                    name: Some(name.clone()),
+                    value: self.parse_expression()?,
+                    position: *position,
+                };
+                keywords.push(keyword);
+                return Ok(());
+            }
+        }
+
+        if !keywords.is_empty() {
+            return Err(self.error_at(
+                ErrorCode::InvalidSyntax,
+                "Positional argument follows keyword argument",
+                value.position(),
+            ));
+        }
+        arguments.push(value);
+        Ok(())
+    }
+
     /// Parse comprehension generators: for target in iter [if cond] [for ...]
     fn parse_comprehension_generators(&mut self) -> ParseResult<Vec<Comprehension>> {
         let mut generators = Vec::new();
@@ -2642,11 +2678,7 @@ impl Parser {
                     name
                 }
                 _ => {
-                    return Err(MambaError::ParseError(format!(
-                        "Expected identifier after 'for' at {}:{}",
-                        self.current_position().line,
-                        self.current_position().column
-                    )))
+                    return Err(self.error("Expected identifier after 'for'"))
                 }
             };
 
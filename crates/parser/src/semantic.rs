@@ -3,9 +3,23 @@
 //! This module performs semantic analysis on the AST, building a symbol table
 //! and detecting semantic errors such as undefined variables, redeclarations, etc.
 
-use crate::ast::{Expression, Module, Statement};
-use crate::symbol_table::{ScopeKind, SymbolKind, SymbolTable};
-use crate::token::SourcePosition;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::arena::AstArena;
+use crate::ast::{
+    Comprehension, Expression, FromImportItem, Keyword, Literal, Module, ParameterKind, Statement, TypeParam,
+};
+use crate::flow::{is_constant_false, Assigned, ControlFlowGraph, DefiniteAssignment};
+use crate::builtins;
+use crate::checker::{self, CheckMode};
+use crate::classes::{self, dotted_name, Base, ClassInfo, Field, MethodInfo, MethodKind, Synthesized};
+use crate::stubs;
+use crate::suggest;
+use crate::modules::{Import, ModuleInterface};
+use crate::types::{self, AnnotationResolver, Type, TypeName, TypeVariable};
+use crate::symbol_table::{ScopeId, ScopeKind, Signature, Symbol, SymbolKind, SymbolTable};
+use crate::token::{SourcePosition, TokenKind};
+use mamba_error::{Diagnostic, ErrorCode, MambaError};
 
 /// Semantic error types
 #[derive(Debug, Clone, PartialEq)]
@@ -14,6 +28,8 @@ pub enum SemanticError {
     UndefinedVariable {
         name: String,
         position: SourcePosition,
+        /// Closest visible name, shown as "Did you mean ...?"
+        suggestion: Option<String>,
     },
     /// Variable or function declared multiple times in same scope
     Redeclaration {
@@ -41,6 +57,222 @@ pub enum SemanticError {
         name: String,
         position: SourcePosition,
     },
+    /// Local variable read before its first assignment in the function
+    UnboundLocal {
+        name: String,
+        position: SourcePosition,
+        /// The assignment that makes the name local to the function
+        binding: SourcePosition,
+    },
+    /// Name read where it is bound on some control flow paths but not all
+    /// (a warning)
+    PossiblyUnbound {
+        name: String,
+        position: SourcePosition,
+    },
+    /// `break` outside of a loop body
+    BreakOutsideLoop {
+        position: SourcePosition,
+    },
+    /// `continue` outside of a loop body
+    ContinueOutsideLoop {
+        position: SourcePosition,
+    },
+    /// `return` at module level or directly in a class body
+    ReturnOutsideFunction {
+        position: SourcePosition,
+    },
+    /// `nonlocal` in a class body that is not nested in a function
+    NonlocalInClassBody {
+        name: String,
+        position: SourcePosition,
+    },
+    /// Statement that can never run (a warning)
+    UnreachableCode {
+        position: SourcePosition,
+        /// Position and length of the always-false literal condition
+        /// guarding it, for dead branches
+        dead_condition: Option<(SourcePosition, usize)>,
+    },
+    /// Function with a return annotation that can end without returning
+    /// a value (a warning)
+    MissingReturn {
+        name: String,
+        position: SourcePosition,
+    },
+    /// Call with more positional arguments than the function takes
+    TooManyArguments {
+        function: String,
+        /// The first extra argument
+        position: SourcePosition,
+        expected: usize,
+        given: usize,
+        definition: SourcePosition,
+    },
+    /// Call that leaves required parameters without a value
+    MissingArguments {
+        function: String,
+        names: Vec<String>,
+        /// Whether the missing parameters are keyword-only
+        keyword_only: bool,
+        position: SourcePosition,
+        definition: SourcePosition,
+    },
+    /// Keyword argument that matches no parameter
+    UnexpectedKeyword {
+        function: String,
+        name: String,
+        position: SourcePosition,
+        /// Closest accepted keyword, shown as "Did you mean ...?"
+        suggestion: Option<String>,
+    },
+    /// Parameter given a value twice in one call
+    DuplicateArgument {
+        function: String,
+        name: String,
+        position: SourcePosition,
+    },
+    /// Positional-only parameter passed by keyword
+    PositionalOnlyAsKeyword {
+        function: String,
+        name: String,
+        position: SourcePosition,
+    },
+    /// Attribute read on `self` that the class and its bases never define
+    UnknownAttribute {
+        class: String,
+        attribute: String,
+        position: SourcePosition,
+        /// Closest known member, shown as "Did you mean ...?"
+        suggestion: Option<String>,
+    },
+    /// Class whose bases admit no consistent method resolution order
+    InconsistentMro {
+        class: String,
+        bases: Vec<String>,
+        position: SourcePosition,
+    },
+    /// Instance or class method without a parameter for `self` / `cls`
+    MissingSelf {
+        method: String,
+        class: String,
+        /// Whether the method is a class method (expects `cls`)
+        classmethod: bool,
+        position: SourcePosition,
+    },
+    /// Import of a module found neither on the search path nor in the
+    /// standard library
+    UnknownModule {
+        module: String,
+        /// The module name, or the `from` keyword of a from-import
+        position: SourcePosition,
+        from_import: bool,
+    },
+    /// `from m import name` where `m` defines no `name`
+    UnknownImportName {
+        module: String,
+        name: String,
+        position: SourcePosition,
+        /// Closest top-level name of the module, shown as "Did you mean ...?"
+        suggestion: Option<String>,
+    },
+    /// Module-level import of a module that is still being imported
+    ImportCycle {
+        /// Module names from the imported module back to itself
+        cycle: Vec<String>,
+        /// The module name, or the `from` keyword of a from-import
+        position: SourcePosition,
+        from_import: bool,
+    },
+    /// Annotation naming a type that is not defined
+    UnknownType {
+        name: String,
+        position: SourcePosition,
+    },
+    /// Annotation that is not a valid type
+    InvalidAnnotation {
+        message: String,
+        position: SourcePosition,
+    },
+    /// Argument whose type does not match the parameter's annotation
+    ArgumentTypeMismatch {
+        function: String,
+        parameter: String,
+        expected: Type,
+        found: Type,
+        position: SourcePosition,
+    },
+    /// Returned value whose type does not match the return annotation
+    ReturnTypeMismatch {
+        function: String,
+        expected: Type,
+        found: Type,
+        position: SourcePosition,
+    },
+    /// Annotated variable assigned a value of another type
+    AssignmentTypeMismatch {
+        name: String,
+        expected: Type,
+        found: Type,
+        position: SourcePosition,
+    },
+    /// Attribute read from a value whose type does not have it
+    UnknownMember {
+        /// The type the attribute is missing from
+        owner: Type,
+        attribute: String,
+        position: SourcePosition,
+        /// Closest attribute of the type, shown as "Did you mean ...?"
+        suggestion: Option<String>,
+    },
+    /// Operator applied to operands of types it does not support; one
+    /// operand for unary operators
+    UnsupportedOperand {
+        operator: String,
+        operands: Vec<Type>,
+        position: SourcePosition,
+    },
+    /// Value that may be `None` used where `None` is not supported
+    PossiblyNone {
+        /// What is done with the value ("read attribute 'x'", "call it")
+        operation: String,
+        found: Type,
+        position: SourcePosition,
+    },
+    /// Function parameter or return without an annotation, in strict mode
+    MissingAnnotation {
+        function: String,
+        /// The parameter, or `None` for the return type
+        parameter: Option<String>,
+        position: SourcePosition,
+    },
+    /// Type variable of a generic function solved by the arguments of a
+    /// call to a type outside its bound or constraints
+    TypeVarMismatch {
+        function: String,
+        variable: TypeVariable,
+        found: Type,
+        position: SourcePosition,
+    },
+    /// Dataclass or named tuple field without a default after a field with
+    /// one, which the generated `__init__` cannot take
+    FieldOrder {
+        class: String,
+        field: String,
+        /// The earlier field with a default
+        previous: String,
+        position: SourcePosition,
+        previous_position: SourcePosition,
+    },
+    /// Assignment to a field of a frozen dataclass or named tuple instance,
+    /// or to a member of an enum
+    FrozenAssignment {
+        class: String,
+        attribute: String,
+        /// "frozen dataclass", "named tuple" or "enum"
+        kind: &'static str,
+        position: SourcePosition,
+    },
 }
 
 impl SemanticError {
@@ -53,6 +285,37 @@ impl SemanticError {
             SemanticError::NonlocalAtModuleLevel { position, .. } => position,
             SemanticError::NonlocalNotFound { position, .. } => position,
             SemanticError::GlobalAtModuleLevel { position, .. } => position,
+            SemanticError::UnboundLocal { position, .. } => position,
+            SemanticError::PossiblyUnbound { position, .. } => position,
+            SemanticError::BreakOutsideLoop { position } => position,
+            SemanticError::ContinueOutsideLoop { position } => position,
+            SemanticError::ReturnOutsideFunction { position } => position,
+            SemanticError::NonlocalInClassBody { position, .. } => position,
+            SemanticError::UnreachableCode { position, .. } => position,
+            SemanticError::MissingReturn { position, .. } => position,
+            SemanticError::TooManyArguments { position, .. } => position,
+            SemanticError::MissingArguments { position, .. } => position,
+            SemanticError::UnexpectedKeyword { position, .. } => position,
+            SemanticError::DuplicateArgument { position, .. } => position,
+            SemanticError::PositionalOnlyAsKeyword { position, .. } => position,
+            SemanticError::UnknownAttribute { position, .. } => position,
+            SemanticError::InconsistentMro { position, .. } => position,
+            SemanticError::MissingSelf { position, .. } => position,
+            SemanticError::UnknownModule { position, .. } => position,
+            SemanticError::UnknownImportName { position, .. } => position,
+            SemanticError::ImportCycle { position, .. } => position,
+            SemanticError::UnknownType { position, .. } => position,
+            SemanticError::InvalidAnnotation { position, .. } => position,
+            SemanticError::ArgumentTypeMismatch { position, .. } => position,
+            SemanticError::TypeVarMismatch { position, .. } => position,
+            SemanticError::ReturnTypeMismatch { position, .. } => position,
+            SemanticError::AssignmentTypeMismatch { position, .. } => position,
+            SemanticError::UnknownMember { position, .. } => position,
+            SemanticError::UnsupportedOperand { position, .. } => position,
+            SemanticError::PossiblyNone { position, .. } => position,
+            SemanticError::MissingAnnotation { position, .. } => position,
+            SemanticError::FieldOrder { position, .. } => position,
+            SemanticError::FrozenAssignment { position, .. } => position,
         }
     }
 
@@ -75,62 +338,664 @@ impl SemanticError {
             SemanticError::GlobalAtModuleLevel { name, .. } => {
                 format!("name '{}' is used prior to global declaration", name)
             }
+            SemanticError::UnboundLocal { name, .. } => {
+                format!("local variable '{}' referenced before assignment", name)
+            }
+            SemanticError::PossiblyUnbound { name, .. } => {
+                format!("'{}' is possibly unbound", name)
+            }
+            SemanticError::BreakOutsideLoop { .. } => "'break' outside loop".to_string(),
+            SemanticError::ContinueOutsideLoop { .. } => "'continue' not properly in loop".to_string(),
+            SemanticError::ReturnOutsideFunction { .. } => "'return' outside function".to_string(),
+            SemanticError::NonlocalInClassBody { name, .. } => {
+                format!("nonlocal declaration in a class body outside any function: '{}'", name)
+            }
+            SemanticError::UnreachableCode { .. } => "unreachable code".to_string(),
+            SemanticError::MissingReturn { name, .. } => {
+                format!("function '{}' can reach the end without returning a value", name)
+            }
+            SemanticError::TooManyArguments { function, expected, given, .. } => format!(
+                "'{}' takes {} positional argument{} but {} {} given",
+                function,
+                expected,
+                if *expected == 1 { "" } else { "s" },
+                given,
+                if *given == 1 { "was" } else { "were" }
+            ),
+            SemanticError::MissingArguments { function, names, keyword_only, .. } => format!(
+                "'{}' is missing {} required {} argument{}: {}",
+                function,
+                names.len(),
+                if *keyword_only { "keyword-only" } else { "positional" },
+                if names.len() == 1 { "" } else { "s" },
+                quoted_list(names)
+            ),
+            SemanticError::UnexpectedKeyword { function, name, .. } => {
+                format!("'{}' got an unexpected keyword argument '{}'", function, name)
+            }
+            SemanticError::DuplicateArgument { function, name, .. } => {
+                format!("'{}' got multiple values for argument '{}'", function, name)
+            }
+            SemanticError::PositionalOnlyAsKeyword { function, name, .. } => {
+                format!("positional-only argument '{}' of '{}' passed by keyword", name, function)
+            }
+            SemanticError::UnknownAttribute { class, attribute, .. } => {
+                format!("'{}' object has no attribute '{}'", class, attribute)
+            }
+            SemanticError::InconsistentMro { bases, .. } => format!(
+                "cannot create a consistent method resolution order (MRO) for bases {}",
+                bases.join(", ")
+            ),
+            SemanticError::MissingSelf { method, class, classmethod, .. } => format!(
+                "method '{}' of class '{}' has no '{}' parameter",
+                method,
+                class,
+                if *classmethod { "cls" } else { "self" }
+            ),
+            SemanticError::UnknownModule { module, .. } => format!("no module named '{}'", module),
+            SemanticError::UnknownImportName { module, name, .. } => {
+                format!("cannot import name '{}' from '{}'", name, module)
+            }
+            SemanticError::ImportCycle { cycle, .. } => format!("import cycle: {}", cycle.join(" -> ")),
+            SemanticError::UnknownType { name, .. } => format!("unknown type '{}'", name),
+            SemanticError::InvalidAnnotation { message, .. } => message.clone(),
+            SemanticError::ArgumentTypeMismatch { function, parameter, expected, found, .. } => format!(
+                "argument '{}' of '{}' has type '{}', expected '{}'",
+                parameter, function, found, expected
+            ),
+            SemanticError::ReturnTypeMismatch { function, expected, found, .. } => {
+                format!("'{}' returns a value of type '{}', expected '{}'", function, found, expected)
+            }
+            SemanticError::AssignmentTypeMismatch { name, expected, found, .. } => {
+                format!("'{}' is declared as '{}' but assigned a value of type '{}'", name, expected, found)
+            }
+            SemanticError::UnknownMember { owner, attribute, .. } => {
+                // A class object, typed `type[C]`, is named as the class
+                let class_object = match owner {
+                    Type::Instance { class, arguments } if class == "type" => {
+                        arguments.first().and_then(Type::class_name)
+                    }
+                    _ => None,
+                };
+                match class_object {
+                    Some(class) => format!("class '{}' has no attribute '{}'", class, attribute),
+                    None => format!("'{}' object has no attribute '{}'", owner, attribute),
+                }
+            }
+            SemanticError::UnsupportedOperand { operator, operands, .. } => match operands.as_slice() {
+                [operand] => format!("bad operand type for unary {}: '{}'", operator, operand),
+                _ => format!(
+                    "unsupported operand types for {}: {}",
+                    operator,
+                    operands.iter().map(|operand| format!("'{}'", operand)).collect::<Vec<_>>().join(" and ")
+                ),
+            },
+            SemanticError::PossiblyNone { operation, found, .. } => {
+                format!("cannot {}: value of type '{}' may be None", operation, found)
+            }
+            SemanticError::MissingAnnotation { function, parameter: Some(parameter), .. } => {
+                format!("parameter '{}' of '{}' has no type annotation", parameter, function)
+            }
+            SemanticError::MissingAnnotation { function, parameter: None, .. } => {
+                format!("'{}' has no return type annotation", function)
+            }
+            SemanticError::TypeVarMismatch { function, variable, found, .. } => match &variable.bound {
+                Some(bound) => format!(
+                    "type variable '{}' of '{}' cannot be '{}': it must be a subtype of '{}'",
+                    variable.name, function, found, bound
+                ),
+                None => format!(
+                    "type variable '{}' of '{}' cannot be '{}': it must be one of {}",
+                    variable.name,
+                    function,
+                    found,
+                    variable.constraints.iter().map(|constraint| format!("'{}'", constraint)).collect::<Vec<_>>().join(", ")
+                ),
+            },
+            SemanticError::FieldOrder { class, field, previous, .. } => format!(
+                "field '{}' of '{}' has no default but follows '{}', which has one",
+                field, class, previous
+            ),
+            SemanticError::FrozenAssignment { class, attribute, kind: "enum", .. } => {
+                format!("cannot assign member '{}' of enum '{}'", attribute, class)
+            }
+            SemanticError::FrozenAssignment { class, attribute, kind, .. } => {
+                format!("cannot assign field '{}' of {} '{}'", attribute, kind, class)
+            }
+        }
+    }
+
+    /// Get the stable error code for this error
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            SemanticError::UndefinedVariable { .. } => ErrorCode::UndefinedName,
+            SemanticError::Redeclaration { .. } => ErrorCode::Redeclaration,
+            SemanticError::InvalidScope { .. } => ErrorCode::InvalidScope,
+            SemanticError::NonlocalAtModuleLevel { .. } => ErrorCode::NonlocalAtModuleLevel,
+            SemanticError::NonlocalNotFound { .. } => ErrorCode::NonlocalNotFound,
+            SemanticError::GlobalAtModuleLevel { .. } => ErrorCode::GlobalAtModuleLevel,
+            SemanticError::UnboundLocal { .. } => ErrorCode::UnboundLocal,
+            SemanticError::PossiblyUnbound { .. } => ErrorCode::PossiblyUnbound,
+            SemanticError::BreakOutsideLoop { .. } => ErrorCode::BreakOutsideLoop,
+            SemanticError::ContinueOutsideLoop { .. } => ErrorCode::ContinueOutsideLoop,
+            SemanticError::ReturnOutsideFunction { .. } => ErrorCode::ReturnOutsideFunction,
+            SemanticError::NonlocalInClassBody { .. } => ErrorCode::NonlocalInClassBody,
+            SemanticError::UnreachableCode { .. } => ErrorCode::UnreachableCode,
+            SemanticError::MissingReturn { .. } => ErrorCode::MissingReturn,
+            SemanticError::TooManyArguments { .. } => ErrorCode::TooManyArguments,
+            SemanticError::MissingArguments { .. } => ErrorCode::MissingArguments,
+            SemanticError::UnexpectedKeyword { .. } => ErrorCode::UnexpectedKeyword,
+            SemanticError::DuplicateArgument { .. } => ErrorCode::DuplicateArgument,
+            SemanticError::PositionalOnlyAsKeyword { .. } => ErrorCode::PositionalOnlyAsKeyword,
+            SemanticError::UnknownAttribute { .. } => ErrorCode::UnknownAttribute,
+            SemanticError::InconsistentMro { .. } => ErrorCode::InconsistentMro,
+            SemanticError::MissingSelf { .. } => ErrorCode::MissingSelf,
+            SemanticError::UnknownModule { .. } => ErrorCode::UnknownModule,
+            SemanticError::UnknownImportName { .. } => ErrorCode::UnknownImportName,
+            SemanticError::ImportCycle { .. } => ErrorCode::ImportCycle,
+            SemanticError::UnknownType { .. } => ErrorCode::UnknownType,
+            SemanticError::InvalidAnnotation { .. } => ErrorCode::InvalidAnnotation,
+            SemanticError::ArgumentTypeMismatch { .. } => ErrorCode::ArgumentType,
+            SemanticError::TypeVarMismatch { .. } => ErrorCode::TypeVarMismatch,
+            SemanticError::ReturnTypeMismatch { .. } => ErrorCode::ReturnType,
+            SemanticError::AssignmentTypeMismatch { .. } => ErrorCode::AssignmentType,
+            SemanticError::UnknownMember { .. } => ErrorCode::UnknownMember,
+            SemanticError::UnsupportedOperand { .. } => ErrorCode::UnsupportedOperand,
+            SemanticError::PossiblyNone { .. } => ErrorCode::PossiblyNone,
+            SemanticError::MissingAnnotation { .. } => ErrorCode::MissingAnnotation,
+            SemanticError::FieldOrder { .. } => ErrorCode::FieldOrder,
+            SemanticError::FrozenAssignment { .. } => ErrorCode::FrozenAssignment,
+        }
+    }
+
+    /// Whether this is a warning rather than an error
+    pub fn is_warning(&self) -> bool {
+        matches!(
+            self,
+            SemanticError::PossiblyUnbound { .. }
+                | SemanticError::UnreachableCode { .. }
+                | SemanticError::MissingReturn { .. }
+        )
+    }
+
+    /// Convert into a structured diagnostic
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        // Undefined and redeclared names are reported at the name itself
+        let len = match self {
+            SemanticError::UndefinedVariable { name, .. }
+            | SemanticError::Redeclaration { name, .. }
+            | SemanticError::UnboundLocal { name, .. }
+            | SemanticError::PossiblyUnbound { name, .. } => name.chars().count(),
+            // Call problems are reported at the callee or the offending keyword
+            SemanticError::MissingArguments { function: name, .. }
+            | SemanticError::UnexpectedKeyword { name, .. }
+            | SemanticError::DuplicateArgument { name, .. }
+            | SemanticError::PositionalOnlyAsKeyword { name, .. } => name.chars().count(),
+            // Misplaced statements are reported at their keyword
+            SemanticError::BreakOutsideLoop { .. } => "break".len(),
+            SemanticError::ContinueOutsideLoop { .. } => "continue".len(),
+            SemanticError::ReturnOutsideFunction { .. } => "return".len(),
+            SemanticError::NonlocalInClassBody { .. } => "nonlocal".len(),
+            SemanticError::MissingReturn { .. } | SemanticError::MissingSelf { .. } => "def".len(),
+            SemanticError::InconsistentMro { .. } => "class".len(),
+            SemanticError::UnknownAttribute { attribute, .. } => "self.".len() + attribute.chars().count(),
+            // Import problems are reported at the module name, or at `from`
+            SemanticError::UnknownModule { from_import: true, .. }
+            | SemanticError::ImportCycle { from_import: true, .. } => "from".len(),
+            SemanticError::UnknownModule { module, .. } => module.chars().count(),
+            SemanticError::ImportCycle { cycle, .. } => cycle.last().map_or(1, |module| module.chars().count()),
+            SemanticError::UnknownImportName { name, .. } => name.chars().count(),
+            SemanticError::AssignmentTypeMismatch { name, .. } => name.chars().count(),
+            SemanticError::MissingAnnotation { parameter: Some(parameter), .. } => parameter.chars().count(),
+            SemanticError::MissingAnnotation { parameter: None, .. } => "def".len(),
+            SemanticError::FieldOrder { field, .. } => field.chars().count(),
+            _ => 1,
+        };
+        let diagnostic = if self.is_warning() {
+            Diagnostic::warning(self.code(), self.message())
+        } else {
+            Diagnostic::error(self.code(), self.message())
+        }
+        .with_span(self.position().span(len));
+
+        match self {
+            SemanticError::UndefinedVariable { suggestion: Some(suggestion), .. } => {
+                diagnostic.with_help(format!("Did you mean '{}'?", suggestion))
+            }
+            SemanticError::Redeclaration { first_position, .. } => {
+                diagnostic.with_label(first_position.span(len), "first defined here")
+            }
+            SemanticError::NonlocalAtModuleLevel { .. } => {
+                diagnostic.with_help("use 'global' to refer to module-level names")
+            }
+            SemanticError::UnboundLocal { name, binding, .. } => diagnostic
+                .with_label(binding.span(len), format!("'{}' is assigned here, which makes it local", name))
+                .with_help(format!(
+                    "assign '{}' before reading it, or declare it 'global {}' to use the module-level variable",
+                    name, name
+                )),
+            SemanticError::PossiblyUnbound { name, .. } => {
+                diagnostic.with_help(format!("assign '{}' on every path that reaches this use", name))
+            }
+            SemanticError::NonlocalInClassBody { .. } => {
+                diagnostic.with_help("use 'global' to refer to module-level names")
+            }
+            SemanticError::UnreachableCode { dead_condition: Some((condition, len)), .. } => {
+                diagnostic.with_label(condition.span(*len), "this condition is always false")
+            }
+            SemanticError::UnreachableCode { dead_condition: None, .. } => diagnostic
+                .with_note("it follows a 'return', 'raise', 'break', 'continue' or a loop that never ends"),
+            SemanticError::MissingReturn { .. } => {
+                diagnostic.with_help("return a value on every path, or include None in the return annotation")
+            }
+            SemanticError::TooManyArguments { function, definition, .. }
+            | SemanticError::MissingArguments { function, definition, .. } => {
+                diagnostic.with_label(definition.span("def".len()), format!("'{}' is defined here", function))
+            }
+            SemanticError::UnexpectedKeyword { suggestion: Some(suggestion), .. } => {
+                diagnostic.with_help(format!("Did you mean '{}'?", suggestion))
+            }
+            SemanticError::PositionalOnlyAsKeyword { .. } => {
+                diagnostic.with_help("pass it by position; parameters before '/' cannot be named")
+            }
+            SemanticError::UnknownAttribute { suggestion: Some(suggestion), .. } => {
+                diagnostic.with_help(format!("Did you mean 'self.{}'?", suggestion))
+            }
+            SemanticError::UnknownAttribute { attribute, .. } => {
+                diagnostic.with_help(format!("assign 'self.{}' in a method such as '__init__'", attribute))
+            }
+            SemanticError::InconsistentMro { .. } => {
+                diagnostic.with_note("a base must come before its own bases, and bases keep the order they are listed in")
+            }
+            SemanticError::MissingSelf { classmethod: false, .. } => {
+                diagnostic.with_help("add 'self' as the first parameter, or decorate the method with '@staticmethod'")
+            }
+            SemanticError::MissingSelf { classmethod: true, .. } => {
+                diagnostic.with_help("add 'cls' as the first parameter")
+            }
+            SemanticError::UnknownModule { .. } => diagnostic
+                .with_note("modules are looked up next to the checked file, then in the directories of MAMBAPATH"),
+            SemanticError::UnknownImportName { suggestion: Some(suggestion), .. } => {
+                diagnostic.with_help(format!("Did you mean '{}'?", suggestion))
+            }
+            SemanticError::ImportCycle { .. } => diagnostic
+                .with_help("move the shared names into a separate module, or import inside the function that needs them"),
+            SemanticError::ArgumentTypeMismatch { found, expected, .. }
+            | SemanticError::ReturnTypeMismatch { found, expected, .. }
+            | SemanticError::AssignmentTypeMismatch { found, expected, .. }
+                if found.is_optional() && !expected.is_optional() =>
+            {
+                diagnostic.with_help("the value may be None; check 'is not None' first")
+            }
+            SemanticError::UnknownMember { suggestion: Some(suggestion), .. } => {
+                diagnostic.with_help(format!("Did you mean '{}'?", suggestion))
+            }
+            SemanticError::PossiblyNone { .. } => {
+                diagnostic.with_help("check 'is not None' first, or return early when it is None")
+            }
+            SemanticError::MissingAnnotation { .. } => {
+                diagnostic.with_note("strict mode requires annotations on every function signature")
+            }
+            SemanticError::FieldOrder { field, previous, previous_position, .. } => diagnostic
+                .with_label(previous_position.span(previous.chars().count()), format!("'{}' has a default", previous))
+                .with_help(format!("give '{}' a default, or move it before '{}'", field, previous)),
+            SemanticError::FrozenAssignment { kind: "frozen dataclass", attribute, .. } => {
+                diagnostic.with_help(format!("build a new instance with 'dataclasses.replace(..., {}=...)'", attribute))
+            }
+            SemanticError::FrozenAssignment { kind: "named tuple", attribute, .. } => {
+                diagnostic.with_help(format!("build a new instance with '._replace({}=...)'", attribute))
+            }
+            _ => diagnostic,
         }
     }
 }
 
+impl From<SemanticError> for MambaError {
+    fn from(error: SemanticError) -> Self {
+        MambaError::SemanticError(Box::new(error.to_diagnostic()))
+    }
+}
+
 /// The semantic analyzer traverses the AST and builds a symbol table
 pub struct SemanticAnalyzer {
     /// Symbol table tracking all declarations and scopes
     symbol_table: SymbolTable,
-    /// Collected semantic errors
+    /// Collected semantic errors and warnings
     errors: Vec<SemanticError>,
+    /// Summaries of the classes being analyzed (innermost last)
+    classes: Vec<ClassInfo>,
+    /// The `self` parameter of the method being analyzed, if its body is
+    /// directly that of an instance method or property
+    receiver: Option<String>,
+    /// The parameter receiving the instance or class of the method whose
+    /// body, or a function nested in it, is being analyzed, with the kind of
+    /// the method. Undefined names are suggested as its members.
+    method_receiver: Option<(String, MethodKind)>,
+    /// Every name bound anywhere in a module or function body, with its first
+    /// binding site. Python decides which names are local before running the body.
+    local_bindings: HashMap<ScopeId, HashMap<String, SourcePosition>>,
+    /// Definite-assignment state of the reads in each module or function body
+    flow: HashMap<ScopeId, DefiniteAssignment>,
+    /// Locals already reported as unbound, so each is reported once per scope
+    reported_unbound: HashSet<(ScopeId, String)>,
+    /// Number of loop bodies enclosing the current statement within the
+    /// current function or class body (`break`/`continue` need at least one)
+    loop_depth: usize,
+    /// Calls to plain names, checked once every binding is known
+    calls: Vec<PendingCall>,
+    /// Bindings through `global` or `nonlocal`, with the scope they occur
+    /// in, recorded on the outer symbol once every scope is analyzed
+    outer_bindings: Vec<(ScopeId, String, SourcePosition)>,
+    /// Resolution of every imported module, when analyzing through a
+    /// [`ModuleLoader`](crate::modules::ModuleLoader); imports are not checked otherwise
+    imports: Option<HashMap<String, Import>>,
+    /// Whether a star import of a module with unknown names was seen, after
+    /// which undefined names are not reported
+    opaque_star_import: bool,
+    /// Annotations, resolved once every binding is known
+    annotations: Vec<PendingAnnotation>,
+    /// Names bound to members of the typing modules, by member name
+    typing_names: HashMap<String, String>,
+    /// Names bound to the typing modules themselves (`typing`, `t` in
+    /// `import typing as t`)
+    typing_modules: HashSet<String>,
+    /// Names bound to members of the `dataclasses` and `enum` modules, by
+    /// qualified name (`dataclass` is `dataclasses.dataclass`)
+    stdlib_names: HashMap<String, String>,
+    /// Names bound to the `dataclasses` and `enum` modules themselves, by
+    /// module
+    stdlib_modules: HashMap<String, String>,
+    /// Names assigned a `TypeVar(...)`, with their bound and constraints
+    /// once resolved
+    type_vars: HashMap<String, TypeVariable>,
+    /// Type parameters of each `def f[T]` and `class C[T]`, by the
+    /// definition's position
+    type_params: HashMap<SourcePosition, Vec<TypeVariable>>,
+    /// Positions of the generic definitions enclosing the current point,
+    /// innermost last
+    generics: Vec<SourcePosition>,
+    /// Bounds and constraints of type variables, resolved before the
+    /// annotations that use them
+    type_var_bounds: Vec<PendingTypeVar>,
+    /// Every class defined in the module, given the types of its members
+    /// once annotations are resolved
+    class_bodies: Vec<ClassBody>,
+    /// Whether and how strictly to type check the module after analysis
+    type_checking: Option<CheckMode>,
+}
+
+/// A call to a plain name, checked against the callee's signature after the
+/// whole module has been visited, so functions defined later are known
+struct PendingCall {
+    /// Scope the call appears in
+    scope: ScopeId,
+    function: String,
+    position: SourcePosition,
+    /// Position of each positional argument
+    arguments: Vec<SourcePosition>,
+    /// Name and position of each keyword argument
+    keywords: Vec<(String, SourcePosition)>,
+}
+
+/// An annotation, resolved after the whole module has been visited so that
+/// forward references to later classes resolve
+struct PendingAnnotation {
+    /// Scope the annotation is evaluated in
+    scope: ScopeId,
+    /// Generic definitions whose type parameters are visible, innermost last
+    generics: Vec<SourcePosition>,
+    annotation: Expression,
+    target: AnnotatedTarget,
+}
+
+/// The bound or constraints of a type variable, written as
+/// `TypeVar('T', bound=B)`, `TypeVar('T', A, B)`, `T: B` or `T: (A, B)`
+struct PendingTypeVar {
+    /// Scope the bound is evaluated in
+    scope: ScopeId,
+    variable: TypeVarSite,
+    bound: Option<Expression>,
+    constraints: Vec<Expression>,
+}
+
+/// A class definition and the scope of its body
+struct ClassBody {
+    /// Scope the class is bound in
+    scope: ScopeId,
+    name: String,
+    position: SourcePosition,
+    body: ScopeId,
+}
+
+/// Where a type variable is declared
+enum TypeVarSite {
+    /// Assigned a `TypeVar(...)`
+    Assigned(String),
+    /// The type parameter at an index of a generic definition
+    Parameter(SourcePosition, usize),
+}
+
+/// What an annotation gives a type to
+enum AnnotatedTarget {
+    /// A variable bound in a scope
+    Name { scope: ScopeId, name: String },
+    /// A parameter of a function bound in the annotation's scope, and the
+    /// parameter's symbol in the function body
+    Parameter {
+        function: String,
+        index: usize,
+        kind: ParameterKind,
+        body: ScopeId,
+        name: String,
+    },
+    /// The return type of a function bound in the annotation's scope
+    Return { function: String },
+}
+
+/// Result of resolving a name read at the current point of analysis
+enum Resolution {
+    /// Bound in the current scope or an enclosing one
+    Bound,
+    /// Local to the current function but not assigned on any path yet
+    Unbound(SourcePosition),
+    /// Local to the current scope and assigned on some paths only
+    PossiblyUnbound,
+    /// Not bound anywhere visible
+    Undefined,
 }
 
 impl SemanticAnalyzer {
     /// Create a new semantic analyzer
     pub fn new() -> Self {
-        let mut symbol_table = SymbolTable::new();
-        
-        // Declare built-in functions in the module scope
-        let builtin_pos = SourcePosition::start();
-        let _ = symbol_table.declare("print".to_string(), SymbolKind::Function, builtin_pos.clone());
-        let _ = symbol_table.declare("range".to_string(), SymbolKind::Function, builtin_pos.clone());
-        let _ = symbol_table.declare("len".to_string(), SymbolKind::Function, builtin_pos.clone());
-        let _ = symbol_table.declare("str".to_string(), SymbolKind::Function, builtin_pos.clone());
-        let _ = symbol_table.declare("int".to_string(), SymbolKind::Function, builtin_pos.clone());
-        let _ = symbol_table.declare("float".to_string(), SymbolKind::Function, builtin_pos.clone());
-        let _ = symbol_table.declare("bool".to_string(), SymbolKind::Function, builtin_pos.clone());
-        let _ = symbol_table.declare("list".to_string(), SymbolKind::Function, builtin_pos.clone());
-        let _ = symbol_table.declare("dict".to_string(), SymbolKind::Function, builtin_pos.clone());
-        let _ = symbol_table.declare("set".to_string(), SymbolKind::Function, builtin_pos.clone());
-        let _ = symbol_table.declare("tuple".to_string(), SymbolKind::Function, builtin_pos.clone());
-        
-        // Declare built-in constants
-        let _ = symbol_table.declare("True".to_string(), SymbolKind::Variable, builtin_pos.clone());
-        let _ = symbol_table.declare("False".to_string(), SymbolKind::Variable, builtin_pos.clone());
-        let _ = symbol_table.declare("None".to_string(), SymbolKind::Variable, builtin_pos);
-        
+        let mut analyzer = Self::with_untyped_builtins();
+        // The bundled stub gives the builtin functions annotated signatures
+        if let Some(builtins_scope) = analyzer.symbol_table.builtins_scope_id() {
+            for (name, signature) in stubs::builtin_signatures() {
+                analyzer.symbol_table.set_signature_in(builtins_scope, name, signature.clone());
+            }
+        }
+        analyzer
+    }
+
+    /// Create an analyzer whose builtin functions have no signatures yet.
+    /// Used to analyze the bundled stub that declares them.
+    pub(crate) fn with_untyped_builtins() -> Self {
+        let mut symbol_table = SymbolTable::with_builtins_scope();
+
+        // Declare the builtin namespace in the scope enclosing the module
+        let builtins_scope = symbol_table.builtins_scope_id().expect("builtins scope");
+        for builtin in builtins::BUILTINS {
+            let _ = symbol_table.declare_in(
+                builtins_scope,
+                builtin.name.to_string(),
+                builtin.symbol_kind(),
+                SourcePosition::start(),
+            );
+        }
+
         Self {
             symbol_table,
             errors: Vec::new(),
+            classes: Vec::new(),
+            receiver: None,
+            method_receiver: None,
+            local_bindings: HashMap::new(),
+            flow: HashMap::new(),
+            reported_unbound: HashSet::new(),
+            loop_depth: 0,
+            calls: Vec::new(),
+            outer_bindings: Vec::new(),
+            imports: None,
+            opaque_star_import: false,
+            annotations: Vec::new(),
+            typing_names: HashMap::new(),
+            typing_modules: HashSet::new(),
+            stdlib_names: HashMap::new(),
+            stdlib_modules: HashMap::new(),
+            type_vars: HashMap::new(),
+            type_params: HashMap::new(),
+            generics: Vec::new(),
+            type_var_bounds: Vec::new(),
+            class_bodies: Vec::new(),
+            type_checking: None,
         }
     }
 
+    /// Check imports against the modules they resolve to, keyed by dotted
+    /// module name. Modules missing from the map are not checked.
+    pub fn with_imports(mut self, imports: HashMap<String, Import>) -> Self {
+        self.imports = Some(imports);
+        self
+    }
+
+    /// Type check the module once names and annotations are resolved
+    pub fn with_type_checking(mut self, mode: CheckMode) -> Self {
+        self.type_checking = Some(mode);
+        self
+    }
+
     /// Analyze a module and return the symbol table or errors
     ///
-    /// Returns Ok(symbol_table) if no errors, Err(errors) if errors found
-    pub fn analyze(mut self, module: &Module) -> Result<SymbolTable, Vec<SemanticError>> {
+    /// Returns Ok(symbol_table) if no errors, Err(errors) if errors found.
+    /// Warnings are dropped; use `diagnose` to get them too.
+    pub fn analyze(self, module: &Module) -> Result<SymbolTable, Vec<SemanticError>> {
+        let (symbol_table, problems) = self.diagnose(module);
+        let errors: Vec<SemanticError> = problems.into_iter().filter(|problem| !problem.is_warning()).collect();
+
+        // Return symbol table if no errors, otherwise return errors
+        if errors.is_empty() {
+            Ok(symbol_table)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Analyze a module and return the symbol table with every error and
+    /// warning found, in source order of discovery
+    pub fn diagnose(mut self, module: &Module) -> (SymbolTable, Vec<SemanticError>) {
+        let module_scope = self.symbol_table.current_scope_id();
+        self.local_bindings.insert(module_scope, collect_bindings(&module.statements));
+        let builtins = self
+            .symbol_table
+            .builtins_scope_id()
+            .and_then(|scope| self.symbol_table.get_scope(scope))
+            .map(|scope| scope.symbols().map(|symbol| symbol.name.clone()).collect())
+            .unwrap_or_default();
+        let cfg = ControlFlowGraph::build_module(&module.statements);
+        self.analyze_flow(module_scope, &module.statements, cfg, &builtins);
+
         // Visit all statements in the module
         for statement in &module.statements {
             self.visit_statement(statement);
         }
+        self.record_outer_bindings();
+        self.check_calls();
+        self.resolve_annotations();
+        self.record_member_types();
+        if let Some(mode) = self.type_checking {
+            let arena = AstArena::from_module(module);
+            let problems = checker::check_types(&arena, &self.symbol_table, mode);
+            self.errors.extend(problems);
+        }
 
-        // Return symbol table if no errors, otherwise return errors
-        if self.errors.is_empty() {
-            Ok(self.symbol_table)
-        } else {
-            Err(self.errors)
+        (self.symbol_table, self.errors)
+    }
+
+    /// Run definite-assignment and reachability analysis over a module or
+    /// function body
+    fn analyze_flow(
+        &mut self,
+        scope: ScopeId,
+        body: &[Statement],
+        cfg: ControlFlowGraph,
+        bound_on_entry: &HashSet<String>,
+    ) -> ControlFlowGraph {
+        self.flow.insert(scope, DefiniteAssignment::analyze(&cfg, bound_on_entry));
+
+        let unreachable: HashSet<(usize, usize)> = cfg
+            .unreachable_statements()
+            .iter()
+            .map(|position| (position.line, position.column))
+            .collect();
+        self.report_unreachable(body, &unreachable);
+        cfg
+    }
+
+    /// Warn about the first statement of every unreachable run of statements,
+    /// including the bodies of `if False:` and `while False:`. Nested function
+    /// and class bodies are checked with their own graph.
+    fn report_unreachable(&mut self, body: &[Statement], unreachable: &HashSet<(usize, usize)>) {
+        for statement in body {
+            let position = statement.position();
+            if unreachable.contains(&(position.line, position.column)) {
+                self.add_error(SemanticError::UnreachableCode { position: *position, dead_condition: None });
+                return;
+            }
+
+            match statement {
+                Statement::If { condition, then_block, elif_blocks, else_block, .. } => {
+                    let branches = std::iter::once((condition, then_block))
+                        .chain(elif_blocks.iter().map(|(condition, block)| (condition, block)));
+                    for (condition, block) in branches {
+                        self.report_dead_branch(condition, block, unreachable);
+                    }
+                    if let Some(block) = else_block {
+                        self.report_unreachable(block, unreachable);
+                    }
+                }
+                Statement::While { condition, body, else_block, .. } => {
+                    self.report_dead_branch(condition, body, unreachable);
+                    if let Some(block) = else_block {
+                        self.report_unreachable(block, unreachable);
+                    }
+                }
+                Statement::For { body, else_block, .. } => {
+                    self.report_unreachable(body, unreachable);
+                    if let Some(block) = else_block {
+                        self.report_unreachable(block, unreachable);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Report a block guarded by an always-false literal such as `False`,
+    /// `0`, `""` or `None` as unreachable, otherwise look inside it
+    fn report_dead_branch(
+        &mut self,
+        condition: &Expression,
+        block: &[Statement],
+        unreachable: &HashSet<(usize, usize)>,
+    ) {
+        match block.first() {
+            Some(first) if is_constant_false(condition) => {
+                self.add_error(SemanticError::UnreachableCode {
+                    position: *first.position(),
+                    dead_condition: Some((*condition.position(), constant_len(condition))),
+                });
+            }
+            _ => self.report_unreachable(block, unreachable),
         }
     }
 
@@ -141,6 +1006,20 @@ impl SemanticAnalyzer {
             Statement::Assignment { targets, value, position } => {
                 // Visit the value expression first
                 self.visit_expression(value);
+                if let ([Expression::Identifier { name, .. }], Expression::Call { function, arguments, keywords, .. }) =
+                    (targets.as_slice(), value)
+                {
+                    if dotted_name(function).and_then(|callee| self.typing_member(&callee)).as_deref() == Some("TypeVar") {
+                        self.type_vars.insert(name.clone(), TypeVariable::new(name));
+                        let bound = keywords.iter().find(|keyword| keyword.name.as_deref() == Some("bound"));
+                        self.type_var_bounds.push(PendingTypeVar {
+                            scope: self.symbol_table.current_scope_id(),
+                            variable: TypeVarSite::Assigned(name.clone()),
+                            bound: bound.map(|keyword| keyword.value.clone()),
+                            constraints: arguments.iter().skip(1).cloned().collect(),
+                        });
+                    }
+                }
                 
                 // Extract and declare all target variables
                 for target in targets {
@@ -149,24 +1028,21 @@ impl SemanticAnalyzer {
             }
 
             // AnnAssignment - track typed variable declarations
-            Statement::AnnAssignment { target, value, position, .. } => {
+            Statement::AnnAssignment { target, annotation, value, position } => {
                 // Visit the value expression if present
                 if let Some(val) = value {
                     self.visit_expression(val);
                 }
                 
-                // Declare the variable
-                if let Err(existing) = self.symbol_table.declare(
-                    target.clone(),
-                    SymbolKind::Variable,
-                    position.clone()
-                ) {
-                    self.add_error(SemanticError::Redeclaration {
-                        name: target.clone(),
-                        first_position: existing.position.clone(),
-                        second_position: position.clone(),
-                    });
-                }
+                // Bind the variable (annotating an existing name rebinds it)
+                self.bind_name(target, SymbolKind::Variable, position);
+                let scope = self.symbol_table.binding_scope_id();
+                self.annotations.push(PendingAnnotation {
+                    scope: self.symbol_table.current_scope_id(),
+                    generics: self.generics.clone(),
+                    annotation: annotation.clone(),
+                    target: AnnotatedTarget::Name { scope, name: target.clone() },
+                });
             }
 
             // AugmentedAssignment - check variable exists before augmenting
@@ -174,14 +1050,10 @@ impl SemanticAnalyzer {
                 // Visit the value expression
                 self.visit_expression(value);
                 
-                // Check if target exists (for identifiers)
+                // Check if target exists (for identifiers), then rebind it
                 if let Expression::Identifier { name, .. } = target {
-                    if self.symbol_table.lookup(name).is_none() {
-                        self.add_error(SemanticError::UndefinedVariable {
-                            name: name.clone(),
-                            position: position.clone(),
-                        });
-                    }
+                    self.check_name(name, position);
+                    self.bind_name(name, SymbolKind::Variable, position);
                 } else {
                     // For complex targets (like attributes, subscripts), just visit them
                     self.visit_expression(target);
@@ -189,22 +1061,79 @@ impl SemanticAnalyzer {
             }
 
             // FunctionDef - track function declarations
-            Statement::FunctionDef { name, parameters, body, position, .. } => {
-                // Declare function in current scope
-                if let Err(existing) = self.symbol_table.declare(
-                    name.clone(),
-                    SymbolKind::Function,
-                    position.clone()
-                ) {
-                    self.add_error(SemanticError::Redeclaration {
-                        name: name.clone(),
-                        first_position: existing.position.clone(),
-                        second_position: position.clone(),
+            Statement::FunctionDef { name, type_params, parameters, body, return_type, decorators, position, .. } => {
+                // Bind function name in current scope (redefinition rebinds it).
+                // A decorator may replace the function, so only plain defs
+                // keep a signature to check calls against.
+                self.bind_name(name, SymbolKind::Function, position);
+                if decorators.is_empty() {
+                    let scope = self.symbol_table.binding_scope_id();
+                    self.symbol_table.set_signature_in(scope, name, Signature::from_parameters(parameters));
+                }
+                let defining_scope = self.symbol_table.current_scope_id();
+                let generic = self.declare_type_params(type_params, position);
+
+                // A method of the class being analyzed checks attributes read on `self`
+                let receiver = match self.classes.last() {
+                    Some(class) if self.symbol_table.current_scope_kind() == ScopeKind::Class => class
+                        .methods
+                        .iter()
+                        .find(|method| method.position == *position)
+                        .filter(|method| matches!(method.kind, MethodKind::Instance | MethodKind::Property))
+                        .and_then(|method| method.receiver())
+                        .map(str::to_string),
+                    _ => None,
+                };
+                let enclosing_receiver = std::mem::replace(&mut self.receiver, receiver);
+                // A function nested in a method still sees its receiver
+                let method_receiver = match self.classes.last() {
+                    Some(class) if self.symbol_table.current_scope_kind() == ScopeKind::Class => class
+                        .methods
+                        .iter()
+                        .find(|method| method.position == *position)
+                        .and_then(|method| Some((method.receiver()?.to_string(), method.kind))),
+                    _ => self.method_receiver.clone(),
+                };
+                let enclosing_method_receiver = std::mem::replace(&mut self.method_receiver, method_receiver);
+
+                // Enter new function scope; everything bound in the body is local to it
+                let scope = self.symbol_table.enter_scope(ScopeKind::Function);
+                self.local_bindings.insert(scope, collect_bindings(body));
+                let parameter_names = parameters.iter().map(|param| param.name.clone()).collect();
+                let cfg = self.analyze_flow(scope, body, ControlFlowGraph::build(body), &parameter_names);
+                if return_type.as_ref().is_some_and(|annotation| !is_none_annotation(annotation))
+                    && cfg.falls_off_end()
+                    && !is_stub_body(body)
+                {
+                    self.add_error(SemanticError::MissingReturn { name: name.clone(), position: *position });
+                }
+                let enclosing_loops = std::mem::take(&mut self.loop_depth);
+
+                // Annotations are evaluated in the scope defining the function
+                if let Some(annotation) = return_type {
+                    self.annotations.push(PendingAnnotation {
+                        scope: defining_scope,
+                        generics: self.generics.clone(),
+                        annotation: annotation.clone(),
+                        target: AnnotatedTarget::Return { function: name.clone() },
                     });
                 }
-
-                // Enter new function scope
-                self.symbol_table.enter_scope(ScopeKind::Function);
+                for (index, param) in parameters.iter().enumerate() {
+                    if let Some(annotation) = &param.type_annotation {
+                        self.annotations.push(PendingAnnotation {
+                            scope: defining_scope,
+                            generics: self.generics.clone(),
+                            annotation: annotation.clone(),
+                            target: AnnotatedTarget::Parameter {
+                                function: name.clone(),
+                                index,
+                                kind: param.kind.clone(),
+                                body: scope,
+                                name: param.name.clone(),
+                            },
+                        });
+                    }
+                }
 
                 // Declare parameters in function scope
                 for param in parameters {
@@ -227,26 +1156,49 @@ impl SemanticAnalyzer {
                 }
 
                 // Exit function scope
+                self.loop_depth = enclosing_loops;
+                self.receiver = enclosing_receiver;
+                self.method_receiver = enclosing_method_receiver;
                 self.symbol_table.exit_scope();
+                if generic {
+                    self.generics.pop();
+                }
             }
 
             // ClassDef - track class declarations
-            Statement::ClassDef { name, body, position, .. } => {
-                // Declare class in current scope
-                if let Err(existing) = self.symbol_table.declare(
-                    name.clone(),
-                    SymbolKind::Class,
-                    position.clone()
-                ) {
-                    self.add_error(SemanticError::Redeclaration {
-                        name: name.clone(),
-                        first_position: existing.position.clone(),
-                        second_position: position.clone(),
-                    });
+            Statement::ClassDef { name, type_params, bases, body, decorators, metaclass, position } => {
+                // Bases and metaclass are evaluated before the class is bound
+                let generic = self.declare_type_params(type_params, position);
+                for base in bases {
+                    match base {
+                        // The type arguments of a generic base may be the
+                        // class's own type parameters
+                        Expression::Subscript { object, .. } if generic => self.visit_expression(object),
+                        _ => self.visit_expression(base),
+                    }
                 }
+                if let Some(metaclass) = metaclass {
+                    self.visit_expression(metaclass);
+                }
+                let info = self.class_info(name, bases, decorators, metaclass, body, position);
+
+                // Bind class name in current scope (redefinition rebinds it)
+                self.bind_name(name, SymbolKind::Class, position);
+                let scope = self.symbol_table.binding_scope_id();
+                self.symbol_table.set_class_info_in(scope, name, info.clone());
 
                 // Enter new class scope
-                self.symbol_table.enter_scope(ScopeKind::Class);
+                let body_scope = self.symbol_table.enter_scope(ScopeKind::Class);
+                self.class_bodies.push(ClassBody {
+                    scope,
+                    name: name.clone(),
+                    position: *position,
+                    body: body_scope,
+                });
+                self.classes.push(info);
+                let enclosing_loops = std::mem::take(&mut self.loop_depth);
+                let enclosing_receiver = self.receiver.take();
+                let enclosing_method_receiver = self.method_receiver.take();
 
                 // Analyze class body
                 for statement in body {
@@ -254,7 +1206,14 @@ impl SemanticAnalyzer {
                 }
 
                 // Exit class scope
+                self.loop_depth = enclosing_loops;
+                self.receiver = enclosing_receiver;
+                self.method_receiver = enclosing_method_receiver;
+                self.classes.pop();
                 self.symbol_table.exit_scope();
+                if generic {
+                    self.generics.pop();
+                }
             }
 
             // If - no new scope in Python, just visit all parts
@@ -289,11 +1248,13 @@ impl SemanticAnalyzer {
                 self.visit_expression(condition);
                 
                 // Visit body
+                self.loop_depth += 1;
                 for statement in body {
                     self.visit_statement(statement);
                 }
+                self.loop_depth -= 1;
                 
-                // Visit else block if present
+                // Visit else block if present (not part of the loop for break/continue)
                 if let Some(else_body) = else_block {
                     for statement in else_body {
                         self.visit_statement(statement);
@@ -310,11 +1271,13 @@ impl SemanticAnalyzer {
                 self.extract_and_declare_names(target, position);
                 
                 // Visit body
+                self.loop_depth += 1;
                 for statement in body {
                     self.visit_statement(statement);
                 }
+                self.loop_depth -= 1;
                 
-                // Visit else block if present
+                // Visit else block if present (not part of the loop for break/continue)
                 if let Some(else_body) = else_block {
                     for statement in else_body {
                         self.visit_statement(statement);
@@ -327,12 +1290,57 @@ impl SemanticAnalyzer {
                 self.visit_expression(expr);
             }
 
-            // TODO: Import/ImportFrom - track imported names
-            Statement::Import { .. } => {
-                // TODO: Declare imported module names
+            // Import/ImportFrom - check the modules and bind the imported names
+            Statement::Import { items, .. } => {
+                for item in items {
+                    self.check_module(&item.module, &item.position, false);
+                    self.bind_name(import_binding(&item.module, &item.alias), SymbolKind::Variable, &item.position);
+                    if types::TYPING_MODULES.contains(&item.module.as_str()) {
+                        self.typing_modules.insert(item.alias.clone().unwrap_or_else(|| item.module.clone()));
+                    }
+                    if classes::SYNTHESIZING_MODULES.iter().any(|(module, _)| *module == item.module) {
+                        let binding = import_binding(&item.module, &item.alias).to_string();
+                        self.stdlib_modules.insert(binding, item.module.clone());
+                    }
+                }
             }
-            Statement::FromImport { .. } => {
-                // TODO: Declare imported names
+            Statement::FromImport { module, items, position } => {
+                let interface = match self.check_module(module, position, true) {
+                    Some(Import::Loaded(interface)) => Some(interface),
+                    Some(Import::Opaque) if items.iter().any(|item| item.name == "*") => {
+                        self.opaque_star_import = true;
+                        None
+                    }
+                    _ => None,
+                };
+                for item in items {
+                    self.bind_from_import(module, interface.as_deref(), item);
+                }
+                if types::TYPING_MODULES.contains(&module.as_str()) {
+                    for item in items {
+                        if item.name == "*" {
+                            let members = types::SPECIAL_FORMS.iter().chain(&["TypeVar", "Generic", "Protocol"]);
+                            self.typing_names.extend(members.map(|member| (member.to_string(), member.to_string())));
+                        } else {
+                            let binding = import_binding(&item.name, &item.alias).to_string();
+                            self.typing_names.insert(binding, item.name.clone());
+                        }
+                    }
+                }
+                if let Some((_, exported)) = classes::SYNTHESIZING_MODULES.iter().find(|(name, _)| name == module) {
+                    for item in items {
+                        if item.name == "*" {
+                            let members = exported.iter().map(|member| member.to_string());
+                            self.stdlib_names.extend(members.map(|member| {
+                                let qualified = format!("{}.{}", module, member);
+                                (member, qualified)
+                            }));
+                        } else {
+                            let binding = import_binding(&item.name, &item.alias).to_string();
+                            self.stdlib_names.insert(binding, format!("{}.{}", module, item.name));
+                        }
+                    }
+                }
             }
 
             // Global - mark variables as global
@@ -380,6 +1388,22 @@ impl SemanticAnalyzer {
                     }
                     return;
                 }
+
+                // A class body only has enclosing function scopes if the class is
+                // defined inside a function
+                let in_function = self
+                    .symbol_table
+                    .enclosing_scopes()
+                    .any(|scope| scope.kind == ScopeKind::Function);
+                if self.symbol_table.current_scope_kind() == ScopeKind::Class && !in_function {
+                    for name in names {
+                        self.add_error(SemanticError::NonlocalInClassBody {
+                            name: name.clone(),
+                            position: *position,
+                        });
+                    }
+                    return;
+                }
                 
                 // For each name, find it in an enclosing scope (not global)
                 for name in names {
@@ -394,7 +1418,9 @@ impl SemanticAnalyzer {
                     }
                     
                     // Look for the variable in enclosing scopes (excluding module/global)
-                    if self.symbol_table.lookup_in_enclosing_function_scopes(name).is_some() {
+                    if self.symbol_table.lookup_in_enclosing_function_scopes(name).is_some()
+                        || self.enclosing_function_binds(name)
+                    {
                         // Declare the nonlocal reference in current scope
                         if self.symbol_table.declare(
                             name.clone(),
@@ -413,7 +1439,10 @@ impl SemanticAnalyzer {
             }
 
             // Statements with expressions that need semantic analysis
-            Statement::Return { value, .. } => {
+            Statement::Return { value, position } => {
+                if self.symbol_table.current_scope_kind() != ScopeKind::Function {
+                    self.add_error(SemanticError::ReturnOutsideFunction { position: *position });
+                }
                 if let Some(expr) = value {
                     self.visit_expression(expr);
                 }
@@ -439,9 +1468,19 @@ impl SemanticAnalyzer {
             }
 
             // Statements with no expressions to visit
-            Statement::Pass(_)
-            | Statement::Break(_)
-            | Statement::Continue(_) => {
+            // Loop control - must be inside a loop body of the same function
+            Statement::Break(position) => {
+                if self.loop_depth == 0 {
+                    self.add_error(SemanticError::BreakOutsideLoop { position: *position });
+                }
+            }
+            Statement::Continue(position) => {
+                if self.loop_depth == 0 {
+                    self.add_error(SemanticError::ContinueOutsideLoop { position: *position });
+                }
+            }
+
+            Statement::Pass(_) => {
                 // No child expressions
             }
         }
@@ -452,12 +1491,7 @@ impl SemanticAnalyzer {
         match expression {
             // Identifier - check if variable is defined
             Expression::Identifier { name, position } => {
-                if self.symbol_table.lookup(name).is_none() {
-                    self.add_error(SemanticError::UndefinedVariable {
-                        name: name.clone(),
-                        position: position.clone(),
-                    });
-                }
+                self.check_name(name, position);
             }
 
             // Binary operation - visit both operands
@@ -477,16 +1511,21 @@ impl SemanticAnalyzer {
             }
 
             // Function call - visit function and all arguments
-            Expression::Call { function, arguments, .. } => {
+            Expression::Call { function, arguments, keywords, position } => {
                 self.visit_expression(function);
                 for arg in arguments {
                     self.visit_expression(arg);
                 }
+                for keyword in keywords {
+                    self.visit_expression(&keyword.value);
+                }
+                self.record_call(function, arguments, keywords, position);
             }
 
             // Attribute access - visit object
-            Expression::Attribute { object, .. } => {
+            Expression::Attribute { object, attribute, position } => {
                 self.visit_expression(object);
+                self.check_receiver_attribute(object, attribute, position);
             }
 
             // Subscript - visit both object and index
@@ -535,16 +1574,7 @@ impl SemanticAnalyzer {
             Expression::AssignmentExpr { target, value, position } => {
                 self.visit_expression(value);
                 // In Python, walrus operator can both introduce new variables and reassign existing ones.
-                // Check if variable exists in current scope - if not, declare it; if yes, it's a reassignment.
-                if self.symbol_table.lookup_current_scope(target).is_none() {
-                    // Variable doesn't exist in current scope, declare it
-                    let _ = self.symbol_table.declare(
-                        target.clone(),
-                        SymbolKind::Variable,
-                        position.clone()
-                    );
-                }
-                // If it already exists, it's a reassignment (no action needed)
+                self.bind_name(target, SymbolKind::Variable, position);
             }
 
             // Starred expression - visit the value
@@ -552,23 +1582,30 @@ impl SemanticAnalyzer {
                 self.visit_expression(value);
             }
 
-            // TODO: Lambda - track lambda parameters
-            Expression::Lambda { .. } => {
-                // TODO: Enter new scope
-                // TODO: Declare parameters
-                // TODO: Visit body
-                // TODO: Exit scope
+            // Lambda - parameters live in their own scope
+            Expression::Lambda { parameters, body, position } => {
+                self.symbol_table.enter_scope(ScopeKind::Lambda);
+                for param in parameters {
+                    if let Err(existing) = self.symbol_table.declare(param.clone(), SymbolKind::Parameter, *position) {
+                        self.add_error(SemanticError::Redeclaration {
+                            name: param.clone(),
+                            first_position: existing.position,
+                            second_position: *position,
+                        });
+                    }
+                }
+                self.visit_expression(body);
+                self.symbol_table.exit_scope();
             }
 
-            // TODO: ListComp/SetComp/DictComp/GeneratorExpr - handle comprehension scopes
-            Expression::ListComp { .. }
-            | Expression::SetComp { .. }
-            | Expression::DictComp { .. }
-            | Expression::GeneratorExpr { .. } => {
-                // TODO: Enter new scope
-                // TODO: Visit generators (declare loop variables)
-                // TODO: Visit element/key/value
-                // TODO: Exit scope
+            // Comprehensions - loop targets live in their own scope
+            Expression::ListComp { element, generators, .. }
+            | Expression::SetComp { element, generators, .. }
+            | Expression::GeneratorExpr { element, generators, .. } => {
+                self.visit_comprehension(generators, &[element]);
+            }
+            Expression::DictComp { key, value, generators, .. } => {
+                self.visit_comprehension(generators, &[key, value]);
             }
 
             // Literals - no semantic analysis needed
@@ -576,12 +1613,890 @@ impl SemanticAnalyzer {
         }
     }
 
-    /// Add a semantic error to the error list
-    fn add_error(&mut self, error: SemanticError) {
-        self.errors.push(error);
-    }
-
-    /// Extract identifier names from an expression and declare them as variables
+    /// Visit the generators and result expressions of a comprehension
+    ///
+    /// As in Python, the first iterable is evaluated in the enclosing scope;
+    /// everything else runs in the comprehension's own scope, where the loop
+    /// targets are bound.
+    fn visit_comprehension(&mut self, generators: &[Comprehension], results: &[&Expression]) {
+        if let Some(first) = generators.first() {
+            self.visit_expression(&first.iter);
+        }
+
+        self.symbol_table.enter_scope(ScopeKind::Comprehension);
+        for (index, generator) in generators.iter().enumerate() {
+            if index > 0 {
+                self.visit_expression(&generator.iter);
+            }
+            if self.symbol_table.lookup_current_scope(&generator.target).is_some() {
+                self.symbol_table.add_definition(&generator.target, generator.position);
+            } else {
+                let _ = self.symbol_table.declare(generator.target.clone(), SymbolKind::Variable, generator.position);
+            }
+            for condition in &generator.conditions {
+                self.visit_expression(condition);
+            }
+        }
+        for result in results {
+            self.visit_expression(result);
+        }
+        self.symbol_table.exit_scope();
+    }
+
+    /// Add a semantic error to the error list
+    fn add_error(&mut self, error: SemanticError) {
+        self.errors.push(error);
+    }
+
+    /// Report a read of `name` that does not resolve to a binding
+    fn check_name(&mut self, name: &str, position: &SourcePosition) {
+        let key = (self.symbol_table.binding_scope_id(), name.to_string());
+        match self.resolve(name, position) {
+            Resolution::Bound => {}
+            Resolution::Unbound(binding) => {
+                if self.reported_unbound.insert(key) {
+                    self.add_error(SemanticError::UnboundLocal {
+                        name: name.to_string(),
+                        position: *position,
+                        binding,
+                    });
+                }
+            }
+            Resolution::PossiblyUnbound => {
+                if self.reported_unbound.insert(key) {
+                    self.add_error(SemanticError::PossiblyUnbound {
+                        name: name.to_string(),
+                        position: *position,
+                    });
+                }
+            }
+            Resolution::Undefined if self.opaque_star_import => {}
+            Resolution::Undefined => self.add_error(SemanticError::UndefinedVariable {
+                name: name.to_string(),
+                position: *position,
+                suggestion: self.suggest_name(name),
+            }),
+        }
+    }
+
+    /// Resolve a name read at `position`, following Python's LEGB rule
+    ///
+    /// A name bound anywhere in the current function is local throughout it,
+    /// so whether it is bound at the read is decided by the control flow of
+    /// the body, even if an outer scope defines it. Enclosing class bodies are
+    /// skipped. Names bound later in an enclosing function or the module are
+    /// fine from inside a function, which only runs once called.
+    fn resolve(&self, name: &str, position: &SourcePosition) -> Resolution {
+        // Comprehensions run inline, so past their own targets the rules of
+        // the containing scope apply
+        let mut scopes = self.symbol_table.enclosing_scopes().peekable();
+        while let Some(scope) = scopes.next_if(|scope| scope.kind == ScopeKind::Comprehension) {
+            if scope.contains(name) {
+                return Resolution::Bound;
+            }
+        }
+        let Some(current) = scopes.next() else {
+            return Resolution::Undefined;
+        };
+        let local_binding = self.local_bindings.get(&current.id).and_then(|names| names.get(name));
+        let assigned = self.flow.get(&current.id).and_then(|flow| flow.at(position));
+        match (local_binding, assigned) {
+            (Some(_), Some(Assigned::Possibly)) => return Resolution::PossiblyUnbound,
+            (Some(binding), Some(Assigned::Never)) => {
+                return match current.kind {
+                    ScopeKind::Function => Resolution::Unbound(*binding),
+                    _ => Resolution::Undefined,
+                };
+            }
+            _ => {}
+        }
+        if current.contains(name) {
+            return Resolution::Bound;
+        }
+        if current.kind == ScopeKind::Function {
+            if let Some(binding) = local_binding {
+                return Resolution::Unbound(*binding);
+            }
+        }
+
+        let mut deferred = matches!(current.kind, ScopeKind::Function | ScopeKind::Lambda);
+        for scope in scopes {
+            let bound_later = self.local_bindings.get(&scope.id).is_some_and(|names| names.contains_key(name));
+            if scope.contains(name) || (deferred && bound_later) {
+                return Resolution::Bound;
+            }
+            deferred |= matches!(scope.kind, ScopeKind::Function | ScopeKind::Lambda);
+        }
+        Resolution::Undefined
+    }
+
+    /// Summarize a class definition and report an inconsistent MRO, methods
+    /// missing their `self` / `cls` parameter, or dataclass fields out of order
+    fn class_info(
+        &mut self,
+        name: &str,
+        bases: &[Expression],
+        decorators: &[Expression],
+        metaclass: &Option<Expression>,
+        body: &[Statement],
+        position: &SourcePosition,
+    ) -> ClassInfo {
+        // `Generic[...]` and `Protocol` declare type parameters and
+        // structural typing; they add no members
+        let mut protocol = false;
+        let mut named_tuple = false;
+        let mut declared_params = None;
+        let mut implicit_params = Vec::new();
+        let mut class_bases = Vec::new();
+        for base in bases {
+            let (object, arguments) = match base {
+                Expression::Subscript { object, index, .. } => (&**object, Some(&**index)),
+                other => (other, None),
+            };
+            let params = arguments.map(|arguments| self.type_var_names(arguments)).unwrap_or_default();
+            match dotted_name(object).and_then(|name| self.typing_member(&name)).as_deref() {
+                Some("Generic") => declared_params = Some(params),
+                Some("Protocol") => {
+                    protocol = true;
+                    if arguments.is_some() {
+                        declared_params = Some(params);
+                    }
+                }
+                // A named tuple is a tuple with the annotated fields
+                Some("NamedTuple") => named_tuple = true,
+                _ => {
+                    for param in params {
+                        if !implicit_params.contains(&param) {
+                            implicit_params.push(param);
+                        }
+                    }
+                    class_bases.push(object);
+                }
+            }
+        }
+
+        let builtins_scope = self.symbol_table.builtins_scope_id();
+        let mut resolved: Vec<Base> = class_bases
+            .iter()
+            .map(|base| {
+                let Expression::Identifier { name, .. } = base else {
+                    return Base::Unknown("?");
+                };
+                match self.symbol_table.lookup(name) {
+                    Some(symbol) if Some(symbol.scope_id) == builtins_scope && symbol.kind == SymbolKind::Class => {
+                        builtins::lookup(name).map_or(Base::Unknown(name), |builtin| Base::Builtin(builtin.name))
+                    }
+                    Some(symbol) if symbol.definitions.len() == 1 => match &symbol.class_info {
+                        Some(info) => Base::Class(info),
+                        None => Base::Unknown(name),
+                    },
+                    _ => Base::Unknown(name),
+                }
+            })
+            .collect();
+        let synthesized = self.synthesized(decorators, &class_bases, &resolved, named_tuple, body);
+        if named_tuple {
+            resolved.insert(0, Base::Builtin("tuple"));
+        }
+        let metaclass = metaclass.as_ref().and_then(dotted_name);
+        let mut info = ClassInfo::build(name, *position, &resolved, metaclass, body);
+        info.bases = class_bases.iter().map(|base| dotted_name(base).unwrap_or_else(|| "?".to_string())).collect();
+        if named_tuple {
+            info.bases.insert(0, "tuple".to_string());
+        }
+        info.protocol = protocol;
+        info.type_params = match self.type_params.get(position) {
+            Some(variables) => variables.iter().map(|variable| variable.name.clone()).collect(),
+            None => declared_params.unwrap_or(implicit_params),
+        };
+        let bases_known = !resolved.iter().any(|base| matches!(base, Base::Unknown(_)));
+        if let Some(synthesized) = synthesized {
+            self.check_field_order(name, synthesized.fields());
+            info.synthesize(synthesized);
+        }
+
+        if info.mro.is_none() && bases_known {
+            self.add_error(SemanticError::InconsistentMro {
+                class: name.to_string(),
+                bases: info.bases.clone(),
+                position: *position,
+            });
+        }
+        for method in &info.methods {
+            let needs_receiver = matches!(method.kind, MethodKind::Instance | MethodKind::Class | MethodKind::Property);
+            let takes_var_args = method.signature.has_var_args();
+            if needs_receiver && method.known_decorators && !takes_var_args && method.receiver().is_none() {
+                self.errors.push(SemanticError::MissingSelf {
+                    method: method.name.clone(),
+                    class: name.to_string(),
+                    classmethod: method.kind == MethodKind::Class,
+                    position: method.position,
+                });
+            }
+        }
+        info
+    }
+
+    /// Report `self.attr` in a method when the class and its bases never
+    /// define `attr`
+    ///
+    /// Only applies while `self` is still the method's parameter, and never to
+    /// open classes (unknown bases, `__getattr__`, `setattr`) or dunders.
+    fn check_receiver_attribute(&mut self, object: &Expression, attribute: &str, position: &SourcePosition) {
+        let (Some(receiver), Some(class)) = (&self.receiver, self.classes.last()) else {
+            return;
+        };
+        let Expression::Identifier { name, .. } = object else {
+            return;
+        };
+        if name != receiver || class.open || class.has_member(attribute) || classes::is_dunder(attribute) {
+            return;
+        }
+        let still_parameter = self.symbol_table.lookup(name).is_some_and(|symbol| {
+            symbol.kind == SymbolKind::Parameter
+                && self.symbol_table.get_scope(symbol.scope_id).is_some_and(|scope| scope.kind == ScopeKind::Function)
+                && !self.local_bindings.get(&symbol.scope_id).is_some_and(|names| names.contains_key(name))
+        });
+        if !still_parameter {
+            return;
+        }
+        let error = SemanticError::UnknownAttribute {
+            class: class.name.clone(),
+            attribute: attribute.to_string(),
+            position: *position,
+            suggestion: suggest::best_match(attribute, class.members()).map(str::to_string),
+        };
+        self.add_error(error);
+    }
+
+    /// Remember a call to a plain, bound name for `check_calls`
+    ///
+    /// Calls that unpack `*args` or `**kwargs` are skipped, since the number
+    /// and names of the arguments are not known statically.
+    fn record_call(
+        &mut self,
+        function: &Expression,
+        arguments: &[Expression],
+        keywords: &[Keyword],
+        position: &SourcePosition,
+    ) {
+        let Expression::Identifier { name, .. } = function else {
+            return;
+        };
+        let unpacks = arguments.iter().any(|arg| matches!(arg, Expression::Starred { .. }))
+            || keywords.iter().any(|keyword| keyword.name.is_none());
+        if unpacks || !matches!(self.resolve(name, position), Resolution::Bound) {
+            return;
+        }
+        self.calls.push(PendingCall {
+            scope: self.symbol_table.current_scope_id(),
+            function: name.clone(),
+            position: *position,
+            arguments: arguments.iter().map(|arg| *arg.position()).collect(),
+            keywords: keywords
+                .iter()
+                .filter_map(|keyword| Some((keyword.name.clone()?, keyword.position)))
+                .collect(),
+        });
+    }
+
+    /// Resolve every recorded annotation, reporting unknown or malformed
+    /// types and recording the types on the annotated symbols and signatures
+    fn resolve_annotations(&mut self) {
+        self.resolve_type_var_bounds();
+        for pending in std::mem::take(&mut self.annotations) {
+            let (resolved, errors) = {
+                let lookup = |name: &str| match self.type_param(&pending.generics, name) {
+                    Some(variable) => TypeName::TypeVar(variable.clone()),
+                    None => self.type_name(pending.scope, name),
+                };
+                let mut resolver = AnnotationResolver::new(&lookup);
+                let resolved = resolver.resolve(&pending.annotation);
+                (resolved, resolver.into_errors())
+            };
+            self.errors.extend(errors);
+            self.symbol_table.set_annotation_type(*pending.annotation.position(), resolved.clone());
+
+            match pending.target {
+                AnnotatedTarget::Name { scope, name } => {
+                    self.symbol_table.set_declared_type_in(scope, &name, resolved);
+                }
+                AnnotatedTarget::Parameter { function, index, kind, body, name } => {
+                    // `*args: T` collects a tuple and `**kwargs: T` a dict
+                    let declared_type = match kind {
+                        ParameterKind::VarArgs => Type::VarTuple(Box::new(resolved.clone())),
+                        ParameterKind::VarKwargs => Type::Dict(Box::new(Type::Str), Box::new(resolved.clone())),
+                        _ => resolved.clone(),
+                    };
+                    self.symbol_table.set_declared_type_in(body, &name, declared_type);
+                    if let Some(mut signature) = self.defined_signature(pending.scope, &function) {
+                        signature.parameters[index].annotation = Some(resolved);
+                        self.symbol_table.set_signature_in(pending.scope, &function, signature);
+                    }
+                }
+                AnnotatedTarget::Return { function } => {
+                    if let Some(mut signature) = self.defined_signature(pending.scope, &function) {
+                        signature.returns = Some(resolved);
+                        self.symbol_table.set_signature_in(pending.scope, &function, signature);
+                    }
+                }
+            }
+        }
+    }
+
+    /// What the standard library generates for a class: the fields of a
+    /// `@dataclass` or `NamedTuple` subclass, or the members of an `Enum`
+    /// subclass
+    fn synthesized(
+        &self,
+        decorators: &[Expression],
+        bases: &[&Expression],
+        resolved: &[Base],
+        named_tuple: bool,
+        body: &[Statement],
+    ) -> Option<Synthesized> {
+        if named_tuple {
+            return Some(Synthesized::NamedTuple {
+                fields: self.fields(body, false),
+            });
+        }
+        let is_enum = bases.iter().zip(resolved).any(|(base, resolved)| match resolved {
+            Base::Class(info) => matches!(info.synthesized, Some(Synthesized::Enum { .. })),
+            _ => dotted_name(base)
+                .and_then(|name| self.stdlib_member(&name))
+                .is_some_and(|member| classes::ENUM_BASES.contains(&member.as_str())),
+        });
+        if is_enum {
+            let mut members = Vec::new();
+            for statement in body {
+                if let Statement::Assignment { targets, .. } = statement {
+                    for target in targets {
+                        if let Expression::Identifier { name, .. } = target {
+                            if classes::is_enum_member(name) && !members.contains(name) {
+                                members.push(name.clone());
+                            }
+                        }
+                    }
+                }
+            }
+            return Some(Synthesized::Enum { members });
+        }
+
+        let keywords = decorators.iter().find_map(|decorator| {
+            let (function, keywords) = match decorator {
+                Expression::Call { function, keywords, .. } => (&**function, keywords.as_slice()),
+                other => (other, [].as_slice()),
+            };
+            let member = dotted_name(function).and_then(|name| self.stdlib_member(&name));
+            (member.as_deref() == Some("dataclasses.dataclass")).then_some(keywords)
+        })?;
+        let option = |name: &str, default: bool| bool_keyword(keywords, name).unwrap_or(default);
+
+        // Fields of dataclass bases come first, in reverse method resolution
+        // order; a field defined again keeps its place
+        let mut fields: Vec<Field> = Vec::new();
+        let inherited = resolved.iter().rev().filter_map(|base| match base {
+            Base::Class(ClassInfo { synthesized: Some(Synthesized::Dataclass { fields, .. }), .. }) => Some(fields),
+            _ => None,
+        });
+        for field in inherited.flatten().cloned().chain(self.fields(body, option("kw_only", false))) {
+            match fields.iter_mut().find(|existing| existing.name == field.name) {
+                Some(existing) => *existing = field,
+                None => fields.push(field),
+            }
+        }
+        Some(Synthesized::Dataclass {
+            fields,
+            init: option("init", true),
+            eq: option("eq", true),
+            order: option("order", false),
+            frozen: option("frozen", false),
+        })
+    }
+
+    /// The fields a class body annotates, in order. `ClassVar` annotations
+    /// are not fields, and the fields after a `KW_ONLY` annotation are
+    /// keyword-only.
+    fn fields(&self, body: &[Statement], mut kw_only: bool) -> Vec<Field> {
+        let mut fields = Vec::new();
+        for statement in body {
+            let Statement::AnnAssignment { target, annotation, value, position } = statement else {
+                continue;
+            };
+            let annotation = match annotation {
+                Expression::Subscript { object, .. } => dotted_name(object),
+                other => dotted_name(other),
+            };
+            let annotation = annotation.as_deref();
+            if annotation.and_then(|name| self.typing_member(name)).as_deref() == Some("ClassVar") {
+                continue;
+            }
+            if annotation.and_then(|name| self.stdlib_member(name)).as_deref() == Some("dataclasses.KW_ONLY") {
+                kw_only = true;
+                continue;
+            }
+            let mut field = Field {
+                name: target.clone(),
+                position: *position,
+                has_default: value.is_some(),
+                init: true,
+                kw_only,
+            };
+            // `field(...)` describes the field rather than giving its default
+            if let Some(Expression::Call { function, keywords, .. }) = value {
+                let member = dotted_name(function).and_then(|name| self.stdlib_member(&name));
+                if member.as_deref() == Some("dataclasses.field") {
+                    let mut names = keywords.iter().filter_map(|keyword| keyword.name.as_deref());
+                    field.has_default = names.any(|name| name == "default" || name == "default_factory");
+                    field.init = bool_keyword(keywords, "init").unwrap_or(true);
+                    field.kw_only = bool_keyword(keywords, "kw_only").unwrap_or(kw_only);
+                }
+            }
+            fields.push(field);
+        }
+        fields
+    }
+
+    /// Report the first field `__init__` takes by position without a
+    /// default after one with a default
+    fn check_field_order(&mut self, class: &str, fields: &[Field]) {
+        let mut with_default: Option<&Field> = None;
+        for field in fields.iter().filter(|field| field.init && !field.kw_only) {
+            match with_default {
+                None if field.has_default => with_default = Some(field),
+                Some(previous) if !field.has_default => {
+                    self.add_error(SemanticError::FieldOrder {
+                        class: class.to_string(),
+                        field: field.name.clone(),
+                        previous: previous.name.clone(),
+                        position: field.position,
+                        previous_position: previous.position,
+                    });
+                    return;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Give the summary of every class the types of the names annotated in
+    /// its body and the annotated signatures of its methods
+    fn record_member_types(&mut self) {
+        for class in std::mem::take(&mut self.class_bodies) {
+            let Some(body) = self.symbol_table.get_scope(class.body) else {
+                continue;
+            };
+            let attribute_types: BTreeMap<String, Type> = body
+                .symbols()
+                .filter(|symbol| symbol.kind == SymbolKind::Variable)
+                .filter_map(|symbol| Some((symbol.name.clone(), (**symbol.declared_type.as_ref()?).clone())))
+                .collect();
+            let signatures: HashMap<String, Signature> = body
+                .symbols()
+                .filter(|symbol| symbol.definitions.len() == 1)
+                .filter_map(|symbol| Some((symbol.name.clone(), (**symbol.signature.as_ref()?).clone())))
+                .collect();
+
+            // A later class of the same name replaces this one's summary
+            let Some(mut info) = self
+                .symbol_table
+                .get_scope(class.scope)
+                .and_then(|scope| scope.lookup(&class.name))
+                .and_then(|symbol| symbol.class_info.as_deref())
+                .filter(|info| info.position == class.position)
+                .cloned()
+            else {
+                continue;
+            };
+            // A generated `__init__` takes each field with its annotated
+            // type, which an inherited field has in its own class
+            let field_type = |field: &str| {
+                attribute_types.get(field).cloned().or_else(|| {
+                    info.mro.iter().flatten().skip(1).find_map(|base| {
+                        let mut scopes = self.symbol_table.enclosing_scopes_of(class.scope);
+                        let base = scopes.find_map(|scope| scope.lookup(base))?;
+                        base.class_info.as_ref()?.attribute_types.get(field).cloned()
+                    })
+                })
+            };
+            let field_types: HashMap<String, Type> = info
+                .synthesized
+                .iter()
+                .flat_map(|synthesized| synthesized.fields())
+                .filter_map(|field| Some((field.name.clone(), field_type(&field.name)?)))
+                .collect();
+            info.attribute_types = attribute_types;
+            for index in 0..info.methods.len() {
+                if info.is_synthesized(&info.methods[index]) {
+                    if info.methods[index].name == "__init__" {
+                        for param in info.methods[index].signature.parameters.iter_mut().skip(1) {
+                            param.annotation = field_types.get(&param.name).cloned();
+                        }
+                    }
+                } else if let Some(signature) = signatures.get(&info.methods[index].name) {
+                    info.methods[index].signature = signature.clone();
+                }
+            }
+            self.symbol_table.set_class_info_in(class.scope, &class.name, info);
+        }
+    }
+
+    /// The signature of a function defined exactly once in `scope`
+    fn defined_signature(&self, scope: ScopeId, function: &str) -> Option<Signature> {
+        self.symbol_table
+            .get_scope(scope)
+            .and_then(|scope| scope.lookup(function))
+            .filter(|symbol| symbol.definitions.len() == 1)
+            .and_then(|symbol| symbol.signature.as_deref().cloned())
+    }
+
+    /// What a (possibly dotted) name in an annotation evaluated in `scope`
+    /// refers to
+    fn type_name(&self, scope: ScopeId, name: &str) -> TypeName {
+        if let Some(member) = self.typing_member(name) {
+            return match member.as_str() {
+                "TypeVar" => TypeName::NotAType("the TypeVar constructor"),
+                member if types::is_special_form(member) => TypeName::Special(member.to_string()),
+                member => TypeName::Class(member.to_string()),
+            };
+        }
+        if let Some(variable) = self.type_vars.get(name) {
+            return TypeName::TypeVar(variable.clone());
+        }
+        if name.contains('.') {
+            return TypeName::Opaque;
+        }
+
+        let symbol = self.symbol_table.enclosing_scopes_of(scope).find_map(|scope| scope.lookup(name));
+        match symbol.map(|symbol| symbol.kind) {
+            Some(SymbolKind::Class) => TypeName::Class(name.to_string()),
+            Some(SymbolKind::Function) => TypeName::NotAType("a function"),
+            Some(_) => TypeName::Opaque,
+            None if self.opaque_star_import => TypeName::Opaque,
+            None => TypeName::Undefined,
+        }
+    }
+
+    /// Record the type parameters of a generic `def` or `class` at
+    /// `position`, which annotations inside it can use. Returns whether
+    /// there were any, in which case the definition is entered into
+    /// `generics` until the caller leaves it.
+    fn declare_type_params(&mut self, type_params: &[TypeParam], position: &SourcePosition) -> bool {
+        if type_params.is_empty() {
+            return false;
+        }
+        let scope = self.symbol_table.current_scope_id();
+        for (index, param) in type_params.iter().enumerate() {
+            let (bound, constraints) = match &param.bound {
+                Some(Expression::Tuple { elements, .. }) => (None, elements.clone()),
+                bound => (bound.clone(), Vec::new()),
+            };
+            self.type_var_bounds.push(PendingTypeVar {
+                scope,
+                variable: TypeVarSite::Parameter(*position, index),
+                bound,
+                constraints,
+            });
+        }
+        let variables = type_params.iter().map(|param| TypeVariable::new(&param.name)).collect();
+        self.type_params.insert(*position, variables);
+        self.generics.push(*position);
+        true
+    }
+
+    /// Names of the type variables used in the type arguments of a base
+    /// class, in order of first use
+    fn type_var_names(&self, arguments: &Expression) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        let mut pending = vec![arguments];
+        while let Some(expression) = pending.pop() {
+            match expression {
+                Expression::Identifier { name, .. } => {
+                    let is_type_var = self.type_vars.contains_key(name) || self.type_param(&self.generics, name).is_some();
+                    if is_type_var && !names.contains(name) {
+                        names.push(name.clone());
+                    }
+                }
+                Expression::Tuple { elements, .. } | Expression::List { elements, .. } => {
+                    pending.extend(elements.iter().rev());
+                }
+                Expression::Subscript { index, .. } => pending.push(index),
+                Expression::Parenthesized { expr, .. } => pending.push(expr),
+                _ => {}
+            }
+        }
+        names
+    }
+
+    /// The type parameter `name` of the innermost of `generics` declaring it
+    fn type_param(&self, generics: &[SourcePosition], name: &str) -> Option<&TypeVariable> {
+        generics
+            .iter()
+            .rev()
+            .filter_map(|position| self.type_params.get(position))
+            .find_map(|variables| variables.iter().find(|variable| variable.name == name))
+    }
+
+    /// Resolve the bounds and constraints of every type variable
+    fn resolve_type_var_bounds(&mut self) {
+        for pending in std::mem::take(&mut self.type_var_bounds) {
+            let (bound, constraints, errors) = {
+                let lookup = |name: &str| self.type_name(pending.scope, name);
+                let mut resolver = AnnotationResolver::new(&lookup);
+                let bound = pending.bound.as_ref().map(|bound| resolver.resolve(bound));
+                let constraints: Vec<Type> =
+                    pending.constraints.iter().map(|constraint| resolver.resolve(constraint)).collect();
+                (bound, constraints, resolver.into_errors())
+            };
+            self.errors.extend(errors);
+            let variable = match &pending.variable {
+                TypeVarSite::Assigned(name) => self.type_vars.get_mut(name),
+                TypeVarSite::Parameter(position, index) => {
+                    self.type_params.get_mut(position).and_then(|variables| variables.get_mut(*index))
+                }
+            };
+            if let Some(variable) = variable {
+                variable.bound = bound.map(Box::new);
+                variable.constraints = constraints;
+            }
+        }
+    }
+
+    /// The member of a typing module a dotted name refers to (`Optional`
+    /// after `from typing import Optional`, `t.List` after `import typing as t`)
+    fn typing_member(&self, name: &str) -> Option<String> {
+        if let Some(member) = self.typing_names.get(name) {
+            return Some(member.clone());
+        }
+        let (module, member) = name.rsplit_once('.')?;
+        self.typing_modules.contains(module).then(|| member.to_string())
+    }
+
+    /// The member of `dataclasses` or `enum` a dotted name refers to, qualified
+    /// by its module (`dataclasses.field` after `from dataclasses import field`)
+    fn stdlib_member(&self, name: &str) -> Option<String> {
+        if let Some(member) = self.stdlib_names.get(name) {
+            return Some(member.clone());
+        }
+        let (module, member) = name.rsplit_once('.')?;
+        self.stdlib_modules.get(module).map(|module| format!("{}.{}", module, member))
+    }
+
+    /// Check every recorded call whose callee is a function defined exactly
+    /// once by a plain `def`
+    fn check_calls(&mut self) {
+        for call in std::mem::take(&mut self.calls) {
+            if let Some((signature, definition)) = self.callee_signature(call.scope, &call.function) {
+                let PendingCall { function, position, arguments, keywords, .. } = &call;
+                let problems = check_arguments(function, *position, arguments, keywords, &signature, definition);
+                self.errors.extend(problems);
+            }
+        }
+    }
+
+    /// The signature and definition site of the function `name` refers to
+    /// from `scope`, if it is statically known. A class is called with the
+    /// parameters of its `__init__`.
+    fn callee_signature(&self, scope: ScopeId, name: &str) -> Option<(Signature, SourcePosition)> {
+        let symbol = self
+            .symbol_table
+            .enclosing_scopes_of(scope)
+            .find_map(|scope| scope.lookup(name).filter(|symbol| !symbol.is_global && !symbol.is_nonlocal))?;
+        match (&symbol.kind, &symbol.signature, &symbol.class_info) {
+            (SymbolKind::Function, Some(signature), _) if symbol.definitions.len() == 1 => {
+                Some(((**signature).clone(), symbol.position))
+            }
+            (SymbolKind::Class, _, Some(info)) if symbol.definitions.len() == 1 => {
+                match self.constructor(scope, info)? {
+                    Some(init) => Some((init.signature.without_receiver(), init.position)),
+                    None => Some((Signature { parameters: Vec::new(), returns: None }, symbol.position)),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// The `__init__` calling a class runs: the one `@dataclass` or
+    /// `NamedTuple` generates, or the nearest one along the class's bases,
+    /// which is `None` when only `object` takes the arguments. Unknown when
+    /// a base is not a class of the module, or has a metaclass or `__new__`
+    /// that may take other arguments.
+    fn constructor<'s>(&'s self, scope: ScopeId, info: &'s ClassInfo) -> Option<Option<&'s MethodInfo>> {
+        if let Some(init) = info.method("__init__").filter(|init| info.is_synthesized(init)) {
+            return Some(Some(init));
+        }
+        let mut init = None;
+        for (index, ancestor) in info.mro.as_ref()?.iter().enumerate() {
+            let ancestor = match index {
+                0 => info,
+                _ if ancestor == "object" => continue,
+                _ => {
+                    let symbol = self.symbol_table.enclosing_scopes_of(scope).find_map(|scope| scope.lookup(ancestor))?;
+                    symbol.class_info.as_deref().filter(|_| symbol.definitions.len() == 1)?
+                }
+            };
+            if ancestor.metaclass.is_some() || ancestor.class_attributes.contains_key("__new__") {
+                return None;
+            }
+            if init.is_none() && ancestor.class_attributes.contains_key("__init__") {
+                // Assigned rather than defined by a plain `def`
+                let method = ancestor.method("__init__").filter(|init| {
+                    init.known_decorators && init.kind == MethodKind::Instance && !ancestor.is_synthesized(init)
+                })?;
+                init = Some(method);
+            }
+        }
+        Some(init)
+    }
+
+    /// Whether an enclosing function (not the module) binds `name` anywhere in
+    /// its body, for `nonlocal` declarations that precede the outer assignment
+    fn enclosing_function_binds(&self, name: &str) -> bool {
+        self.symbol_table
+            .enclosing_scopes()
+            .skip(1)
+            .take_while(|scope| scope.kind != ScopeKind::Module)
+            .any(|scope| self.local_bindings.get(&scope.id).is_some_and(|names| names.contains_key(name)))
+    }
+
+    /// Bind a name in the current scope, Python style
+    ///
+    /// The first binding declares the symbol; later bindings (reassignment,
+    /// redefinition of a function or class) are recorded as additional
+    /// definition sites. Names declared `global` or `nonlocal` bind in the
+    /// outer scope, where the binding is recorded once the analysis is done. Inside a comprehension
+    /// (walrus targets) the name binds in the containing scope.
+    fn bind_name(&mut self, name: &str, kind: SymbolKind, position: &SourcePosition) {
+        let scope_id = self.symbol_table.binding_scope_id();
+        match self.symbol_table.get_scope(scope_id).and_then(|scope| scope.lookup(name)) {
+            Some(existing) if existing.is_global || existing.is_nonlocal => {
+                self.outer_bindings.push((scope_id, name.to_string(), *position));
+            }
+            Some(_) => {
+                self.symbol_table.add_definition_in(scope_id, name, *position);
+            }
+            None => {
+                let _ = self.symbol_table.declare_in(scope_id, name.to_string(), kind, *position);
+            }
+        }
+    }
+
+    /// Record the bindings through `global` and `nonlocal` as definitions of
+    /// the outer names. An assignment through `global` declares a module
+    /// name that is bound nowhere else.
+    fn record_outer_bindings(&mut self) {
+        for (scope, name, position) in std::mem::take(&mut self.outer_bindings) {
+            let Some(local) = self.symbol_table.get_scope(scope).and_then(|scope| scope.lookup(&name)) else {
+                continue;
+            };
+            let outer = if local.is_global {
+                self.symbol_table.module_scope_id()
+            } else {
+                // The nearest enclosing function that binds the name itself
+                let parent = self.symbol_table.get_scope(scope).and_then(|scope| scope.parent);
+                parent.and_then(|parent| {
+                    self.symbol_table
+                        .enclosing_scopes_of(parent)
+                        .filter(|scope| scope.kind == ScopeKind::Function)
+                        .find(|scope| {
+                            scope.lookup(&name).is_some_and(|symbol| !symbol.is_nonlocal && !symbol.is_global)
+                        })
+                        .map(|scope| scope.id)
+                })
+            };
+            let Some(outer) = outer else {
+                continue;
+            };
+            if !self.symbol_table.add_definition_in(outer, &name, position) {
+                let _ = self.symbol_table.declare_in(outer, name, SymbolKind::Variable, position);
+            }
+        }
+    }
+
+    /// Report an import of a missing module, or a module-level import that
+    /// closes a cycle, and return what the module resolved to
+    fn check_module(&mut self, module: &str, position: &SourcePosition, from_import: bool) -> Option<Import> {
+        let import = self.imports.as_ref()?.get(module)?.clone();
+        match &import {
+            Import::Missing => self.add_error(SemanticError::UnknownModule {
+                module: module.to_string(),
+                position: *position,
+                from_import,
+            }),
+            // An import inside a function runs after every module has loaded
+            Import::Cycle(cycle) if self.symbol_table.current_scope_kind() == ScopeKind::Module => {
+                self.add_error(SemanticError::ImportCycle {
+                    cycle: cycle.clone(),
+                    position: *position,
+                    from_import,
+                })
+            }
+            _ => {}
+        }
+        Some(import)
+    }
+
+    /// Bind one item of `from module import ...`, checking it against the
+    /// module's top-level names when they are known. Imported functions and
+    /// classes keep their signature and members.
+    fn bind_from_import(&mut self, module: &str, interface: Option<&ModuleInterface>, item: &FromImportItem) {
+        let Some(interface) = interface else {
+            if item.name != "*" {
+                self.bind_name(import_binding(&item.name, &item.alias), SymbolKind::Variable, &item.position);
+            }
+            return;
+        };
+        if item.name == "*" {
+            for symbol in interface.star_exports() {
+                self.bind_imported(&symbol.name, symbol, &item.position);
+            }
+            return;
+        }
+
+        let binding = import_binding(&item.name, &item.alias);
+        match interface.lookup(&item.name) {
+            Some(symbol) => self.bind_imported(binding, symbol, &item.position),
+            None => {
+                let submodule = format!("{}.{}", module, item.name);
+                let is_submodule = self
+                    .imports
+                    .as_ref()
+                    .and_then(|imports| imports.get(&submodule))
+                    .is_some_and(|import| !matches!(import, Import::Missing));
+                if !is_submodule {
+                    self.add_error(SemanticError::UnknownImportName {
+                        module: module.to_string(),
+                        name: item.name.clone(),
+                        position: item.position,
+                        suggestion: suggest::best_match(&item.name, interface.symbols.keys().map(String::as_str))
+                            .map(str::to_string),
+                    });
+                }
+                self.bind_name(binding, SymbolKind::Variable, &item.position);
+            }
+        }
+    }
+
+    /// Bind a name to a symbol imported from another module
+    fn bind_imported(&mut self, name: &str, symbol: &Symbol, position: &SourcePosition) {
+        let scope_id = self.symbol_table.binding_scope_id();
+        let is_new = self.symbol_table.get_scope(scope_id).is_some_and(|scope| !scope.contains(name));
+        self.bind_name(name, symbol.kind, position);
+        // A signature is only trusted for a single `def` in both modules
+        if is_new && symbol.definitions.len() == 1 {
+            if let Some(signature) = &symbol.signature {
+                self.symbol_table.set_signature_in(scope_id, name, (**signature).clone());
+            }
+            if let Some(info) = &symbol.class_info {
+                self.symbol_table.set_class_info_in(scope_id, name, (**info).clone());
+            }
+            if let Some(declared_type) = &symbol.declared_type {
+                self.symbol_table.set_declared_type_in(scope_id, name, (**declared_type).clone());
+            }
+        }
+    }
+
+    /// Extract identifier names from an expression and declare them as variables
     ///
     /// Handles:
     /// - Identifier: x
@@ -591,33 +2506,7 @@ impl SemanticAnalyzer {
     fn extract_and_declare_names(&mut self, expr: &Expression, position: &SourcePosition) {
         match expr {
             Expression::Identifier { name, .. } => {
-                // Check if already declared as global or nonlocal in current scope
-                if let Some(existing) = self.symbol_table.lookup_current_scope(name) {
-                    // If it's a global or nonlocal declaration, don't redeclare
-                    if existing.is_global || existing.is_nonlocal {
-                        return; // Skip declaration, it's a reference to outer scope
-                    }
-                    // Otherwise it's a redeclaration error
-                    self.add_error(SemanticError::Redeclaration {
-                        name: name.clone(),
-                        first_position: existing.position.clone(),
-                        second_position: position.clone(),
-                    });
-                    return;
-                }
-                
-                // Declare new variable
-                if let Err(existing) = self.symbol_table.declare(
-                    name.clone(),
-                    SymbolKind::Variable,
-                    position.clone()
-                ) {
-                    self.add_error(SemanticError::Redeclaration {
-                        name: name.clone(),
-                        first_position: existing.position.clone(),
-                        second_position: position.clone(),
-                    });
-                }
+                self.bind_name(name, SymbolKind::Variable, position);
             }
             Expression::Tuple { elements, .. } | Expression::List { elements, .. } => {
                 // Tuple/list unpacking: (a, b, c) = ...
@@ -636,6 +2525,37 @@ impl SemanticAnalyzer {
         }
     }
 
+    /// Suggest a replacement for an undefined name.
+    ///
+    /// Inside a method, an exact member of its class wins (`count` ->
+    /// `self.count`), then the closest visible name or keyword, then the
+    /// closest class member. Members are named through the method's own
+    /// receiver, and a class method's are those of the class.
+    fn suggest_name(&self, name: &str) -> Option<String> {
+        let (receiver, members) = match (&self.method_receiver, self.classes.last()) {
+            (Some((receiver, kind)), Some(class)) => {
+                let members = class.members().filter(|member| {
+                    *kind != MethodKind::Class
+                        || class.class_attributes.contains_key(*member)
+                        || !class.instance_attributes.contains_key(*member)
+                });
+                (receiver.as_str(), members.collect())
+            }
+            _ => ("self", Vec::new()),
+        };
+        if members.contains(&name) {
+            return Some(format!("{}.{}", receiver, name));
+        }
+
+        let visible = self.symbol_table.visible_names();
+        let candidates = visible.into_iter().chain(TokenKind::KEYWORDS.iter().copied());
+        if let Some(found) = suggest::best_match(name, candidates) {
+            return Some(found.to_string());
+        }
+
+        suggest::best_match(name, members).map(|member| format!("{}.{}", receiver, member))
+    }
+
     /// Get a reference to the symbol table (for testing)
     #[cfg(test)]
     pub fn symbol_table(&self) -> &SymbolTable {
@@ -643,44 +2563,380 @@ impl SemanticAnalyzer {
     }
 }
 
-impl Default for SemanticAnalyzer {
-    fn default() -> Self {
-        Self::new()
+/// Bind the arguments of a call to `function` at `position` to a signature
+/// the way Python does, returning every mismatch. `arguments` are the
+/// positions of the positional arguments and `keywords` the names and
+/// positions of the keyword arguments.
+pub(crate) fn check_arguments(
+    function: &str,
+    position: SourcePosition,
+    arguments: &[SourcePosition],
+    keywords: &[(String, SourcePosition)],
+    signature: &Signature,
+    definition: SourcePosition,
+) -> Vec<SemanticError> {
+    let mut problems = Vec::new();
+    let function = function.to_string();
+    let positional: Vec<_> = signature.positional().collect();
+    if arguments.len() > positional.len() && !signature.has_var_args() {
+        problems.push(SemanticError::TooManyArguments {
+            function: function.clone(),
+            position: arguments[positional.len()],
+            expected: positional.len(),
+            given: arguments.len(),
+            definition,
+        });
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::lexer::Lexer;
-    use crate::parser::Parser;
 
-    /// Helper to parse code and create an analyzer
-    fn parse(code: &str) -> Module {
-        let mut lexer = Lexer::new(code);
-        let tokens = lexer.tokenize().expect("Tokenize should succeed");
-        let mut parser = Parser::new(tokens);
-        parser.parse().expect("Parse should succeed")
+    let mut filled: HashSet<&str> = positional.iter().take(arguments.len()).map(|param| param.name.as_str()).collect();
+    let mut seen = HashSet::new();
+    for (name, position) in keywords {
+        if !seen.insert(name.as_str()) {
+            problems.push(SemanticError::DuplicateArgument {
+                function: function.clone(),
+                name: name.clone(),
+                position: *position,
+            });
+            continue;
+        }
+        let parameter = signature.parameters.iter().find(|param| {
+            param.name == *name && !matches!(param.kind, ParameterKind::VarArgs | ParameterKind::VarKwargs)
+        });
+        match parameter {
+            // With **kwargs the keyword lands there and the parameter stays unfilled
+            Some(param) if param.kind == ParameterKind::PositionalOnly && signature.has_var_kwargs() => {}
+            Some(param) if param.kind == ParameterKind::PositionalOnly => {
+                filled.insert(param.name.as_str());
+                problems.push(SemanticError::PositionalOnlyAsKeyword {
+                    function: function.clone(),
+                    name: name.clone(),
+                    position: *position,
+                });
+            }
+            Some(param) => {
+                if !filled.insert(param.name.as_str()) {
+                    problems.push(SemanticError::DuplicateArgument {
+                        function: function.clone(),
+                        name: name.clone(),
+                        position: *position,
+                    });
+                }
+            }
+            None if signature.has_var_kwargs() => {}
+            None => {
+                let accepted = signature.parameters.iter().filter(|param| {
+                    matches!(param.kind, ParameterKind::Regular | ParameterKind::KwOnly)
+                });
+                problems.push(SemanticError::UnexpectedKeyword {
+                    function: function.clone(),
+                    name: name.clone(),
+                    position: *position,
+                    suggestion: suggest::best_match(name, accepted.map(|param| param.name.as_str()))
+                        .map(str::to_string),
+                });
+            }
+        }
     }
 
-    #[test]
-    fn test_analyzer_creation() {
-        let analyzer = SemanticAnalyzer::new();
-        assert_eq!(analyzer.errors.len(), 0);
-        assert_eq!(analyzer.symbol_table.current_scope_id(), 0);
+    let missing = |keyword_only: bool| -> Vec<String> {
+        signature
+            .parameters
+            .iter()
+            .filter(|param| (param.kind == ParameterKind::KwOnly) == keyword_only)
+            .filter(|param| !matches!(param.kind, ParameterKind::VarArgs | ParameterKind::VarKwargs))
+            .filter(|param| !param.has_default && !filled.contains(param.name.as_str()))
+            .map(|param| param.name.clone())
+            .collect()
+    };
+    for keyword_only in [false, true] {
+        let names = missing(keyword_only);
+        if !names.is_empty() {
+            problems.push(SemanticError::MissingArguments {
+                function: function.clone(),
+                names,
+                keyword_only,
+                position,
+                definition,
+            });
+        }
     }
+    problems
+}
 
-    #[test]
-    fn test_analyze_empty_module() {
-        let module = parse("");
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Empty module should analyze without errors");
+/// Format names as `'a'`, `'a' and 'b'` or `'a', 'b' and 'c'`
+fn quoted_list(names: &[String]) -> String {
+    let quoted: Vec<String> = names.iter().map(|name| format!("'{}'", name)).collect();
+    match quoted.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} and {}", rest.join(", "), last),
+        _ => quoted.concat(),
     }
+}
 
-    #[test]
-    fn test_analyze_literal_expression() {
-        let module = parse("42");
+/// Whether a return annotation is `None`
+fn is_none_annotation(annotation: &Expression) -> bool {
+    matches!(annotation, Expression::Literal(Literal::None { .. }))
+}
+
+/// Whether a function body is only a placeholder (`...`, `pass` or a
+/// docstring), as in stubs and abstract methods
+fn is_stub_body(body: &[Statement]) -> bool {
+    body.iter().all(|statement| {
+        matches!(
+            statement,
+            Statement::Pass(_)
+                | Statement::Expression(Expression::Literal(Literal::Ellipsis { .. } | Literal::String { .. }))
+        )
+    })
+}
+
+/// Length of an always-false literal condition as usually written: `False`,
+/// `0`, `0.0`, `""` or `None`, in any parentheses
+fn constant_len(condition: &Expression) -> usize {
+    match condition {
+        Expression::Literal(Literal::Boolean { .. }) => "False".len(),
+        Expression::Literal(Literal::Integer { .. }) => "0".len(),
+        Expression::Literal(Literal::Float { .. }) => "0.0".len(),
+        Expression::Literal(Literal::String { .. }) => "\"\"".len(),
+        Expression::Literal(Literal::None { .. }) => "None".len(),
+        Expression::Parenthesized { expr, .. } => constant_len(expr) + "()".len(),
+        _ => 1,
+    }
+}
+
+/// Name bound by an import: the alias, or the first component of the module
+/// path (`import os.path` binds `os`)
+/// The value of a keyword argument given as `True` or `False`
+fn bool_keyword(keywords: &[Keyword], name: &str) -> Option<bool> {
+    keywords.iter().find(|keyword| keyword.name.as_deref() == Some(name)).and_then(|keyword| match &keyword.value {
+        Expression::Literal(Literal::Boolean { value, .. }) => Some(*value),
+        _ => None,
+    })
+}
+
+pub(crate) fn import_binding<'a>(name: &'a str, alias: &'a Option<String>) -> &'a str {
+    match alias {
+        Some(alias) => alias,
+        None => name.split('.').next().unwrap_or(name),
+    }
+}
+
+/// Names bound anywhere in a module or function body, with their first
+/// binding site
+///
+/// Covers assignment, loop and `del` targets, walrus targets, imports and
+/// nested `def`/`class` names. Nested function and class bodies are not
+/// entered, and names declared `global` or `nonlocal` are left out since they
+/// bind in another scope.
+fn collect_bindings(body: &[Statement]) -> HashMap<String, SourcePosition> {
+    fn bind(bindings: &mut HashMap<String, SourcePosition>, name: &str, position: &SourcePosition) {
+        bindings.entry(name.to_string()).or_insert(*position);
+    }
+
+    fn collect_target(target: &Expression, bindings: &mut HashMap<String, SourcePosition>) {
+        match target {
+            Expression::Identifier { name, position } => bind(bindings, name, position),
+            Expression::Tuple { elements, .. } | Expression::List { elements, .. } => {
+                for element in elements {
+                    collect_target(element, bindings);
+                }
+            }
+            Expression::Starred { value, .. } | Expression::Parenthesized { expr: value, .. } => {
+                collect_target(value, bindings)
+            }
+            other => collect_walrus(other, bindings),
+        }
+    }
+
+    // Walrus targets bind in the enclosing function, even inside comprehensions
+    fn collect_walrus(expr: &Expression, bindings: &mut HashMap<String, SourcePosition>) {
+        let mut visit = |expr: &Expression| collect_walrus(expr, bindings);
+        match expr {
+            Expression::AssignmentExpr { target, value, position } => {
+                collect_walrus(value, bindings);
+                bind(bindings, target, position);
+            }
+            Expression::BinaryOp { left, right, .. } => {
+                visit(left);
+                visit(right);
+            }
+            Expression::UnaryOp { operand: inner, .. }
+            | Expression::Parenthesized { expr: inner, .. }
+            | Expression::Attribute { object: inner, .. }
+            | Expression::Starred { value: inner, .. } => visit(inner),
+            Expression::Call { function, arguments, keywords, .. } => {
+                visit(function);
+                arguments.iter().for_each(&mut visit);
+                keywords.iter().for_each(|keyword| visit(&keyword.value));
+            }
+            Expression::Subscript { object, index, .. } => {
+                visit(object);
+                visit(index);
+            }
+            Expression::List { elements, .. }
+            | Expression::Tuple { elements, .. }
+            | Expression::Set { elements, .. } => elements.iter().for_each(visit),
+            Expression::Dict { pairs, .. } => {
+                for (key, value) in pairs {
+                    visit(key);
+                    visit(value);
+                }
+            }
+            Expression::Conditional { condition, true_expr, false_expr, .. } => {
+                visit(condition);
+                visit(true_expr);
+                visit(false_expr);
+            }
+            Expression::ListComp { element, generators, .. }
+            | Expression::SetComp { element, generators, .. }
+            | Expression::GeneratorExpr { element, generators, .. } => {
+                visit(element);
+                for generator in generators {
+                    visit(&generator.iter);
+                    generator.conditions.iter().for_each(&mut visit);
+                }
+            }
+            Expression::DictComp { key, value, generators, .. } => {
+                visit(key);
+                visit(value);
+                for generator in generators {
+                    visit(&generator.iter);
+                    generator.conditions.iter().for_each(&mut visit);
+                }
+            }
+            // A lambda body is its own scope
+            Expression::Lambda { .. } | Expression::Identifier { .. } | Expression::Literal(_) => {}
+        }
+    }
+
+    fn collect_block(
+        body: &[Statement],
+        bindings: &mut HashMap<String, SourcePosition>,
+        declared: &mut Vec<String>,
+    ) {
+        for statement in body {
+            match statement {
+                Statement::Assignment { targets, value, .. } => {
+                    collect_walrus(value, bindings);
+                    for target in targets {
+                        collect_target(target, bindings);
+                    }
+                }
+                Statement::AnnAssignment { target, value, position, .. } => {
+                    if let Some(value) = value {
+                        collect_walrus(value, bindings);
+                    }
+                    bind(bindings, target, position);
+                }
+                Statement::AugmentedAssignment { target, value, .. } => {
+                    collect_walrus(value, bindings);
+                    collect_target(target, bindings);
+                }
+                Statement::Del { targets, .. } => {
+                    for target in targets {
+                        collect_target(target, bindings);
+                    }
+                }
+                Statement::For { target, iter, body, else_block, .. } => {
+                    collect_walrus(iter, bindings);
+                    collect_target(target, bindings);
+                    collect_block(body, bindings, declared);
+                    if let Some(block) = else_block {
+                        collect_block(block, bindings, declared);
+                    }
+                }
+                Statement::While { condition, body, else_block, .. } => {
+                    collect_walrus(condition, bindings);
+                    collect_block(body, bindings, declared);
+                    if let Some(block) = else_block {
+                        collect_block(block, bindings, declared);
+                    }
+                }
+                Statement::If { condition, then_block, elif_blocks, else_block, .. } => {
+                    collect_walrus(condition, bindings);
+                    collect_block(then_block, bindings, declared);
+                    for (condition, block) in elif_blocks {
+                        collect_walrus(condition, bindings);
+                        collect_block(block, bindings, declared);
+                    }
+                    if let Some(block) = else_block {
+                        collect_block(block, bindings, declared);
+                    }
+                }
+                Statement::FunctionDef { name, position, .. } | Statement::ClassDef { name, position, .. } => {
+                    bind(bindings, name, position);
+                }
+                Statement::Import { items, .. } => {
+                    for item in items {
+                        bind(bindings, import_binding(&item.module, &item.alias), &item.position);
+                    }
+                }
+                Statement::FromImport { items, .. } => {
+                    for item in items.iter().filter(|item| item.name != "*") {
+                        bind(bindings, import_binding(&item.name, &item.alias), &item.position);
+                    }
+                }
+                Statement::Global { names, .. } | Statement::Nonlocal { names, .. } => {
+                    declared.extend(names.iter().cloned());
+                }
+                Statement::Expression(expr) => collect_walrus(expr, bindings),
+                Statement::Return { value: Some(expr), .. } | Statement::Raise { exception: Some(expr), .. } => {
+                    collect_walrus(expr, bindings)
+                }
+                Statement::Assert { condition, .. } => collect_walrus(condition, bindings),
+                _ => {}
+            }
+        }
+    }
+
+    let mut bindings = HashMap::new();
+    let mut declared = Vec::new();
+    collect_block(body, &mut bindings, &mut declared);
+    for name in declared {
+        bindings.remove(&name);
+    }
+    bindings
+}
+
+impl Default for SemanticAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    /// Helper to parse code and create an analyzer
+    fn parse(code: &str) -> Module {
+        let mut lexer = Lexer::new(code);
+        let tokens = lexer.tokenize().expect("Tokenize should succeed");
+        let mut parser = Parser::new(tokens);
+        parser.parse().expect("Parse should succeed")
+    }
+
+    #[test]
+    fn test_analyzer_creation() {
+        let analyzer = SemanticAnalyzer::new();
+        assert_eq!(analyzer.errors.len(), 0);
+        let module = analyzer.symbol_table.current_scope();
+        assert_eq!(module.kind, ScopeKind::Module);
+        assert_eq!(module.parent, analyzer.symbol_table.builtins_scope_id());
+    }
+
+    #[test]
+    fn test_analyze_empty_module() {
+        let module = parse("");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Empty module should analyze without errors");
+    }
+
+    #[test]
+    fn test_analyze_literal_expression() {
+        let module = parse("42");
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
         assert!(result.is_ok(), "Literal expression should analyze without errors");
@@ -700,6 +2956,7 @@ mod tests {
         let error = SemanticError::UndefinedVariable {
             name: "x".to_string(),
             position: pos.clone(),
+            suggestion: None,
         };
         assert_eq!(error.position(), &pos);
         assert_eq!(error.message(), "Undefined variable: 'x'");
@@ -729,1163 +2986,2135 @@ mod tests {
         assert_eq!(error.message(), "Cannot exit root scope");
     }
 
-    // Variable Declaration Tests
-
     #[test]
-    fn test_simple_assignment() {
-        let module = parse("x = 5");
+    fn test_redeclaration_diagnostic() {
+        let error = SemanticError::Redeclaration {
+            name: "x".to_string(),
+            first_position: SourcePosition { line: 1, column: 7, offset: 6 },
+            second_position: SourcePosition { line: 1, column: 10, offset: 9 },
+        };
+        let diagnostic = error.to_diagnostic();
+        assert_eq!(diagnostic.code, ErrorCode::Redeclaration);
+        assert_eq!(diagnostic.span.unwrap().column, 10);
+        assert_eq!(diagnostic.labels.len(), 1);
+        assert_eq!(diagnostic.labels[0].span.column, 7);
+        assert_eq!(diagnostic.labels[0].message, "first defined here");
+    }
+
+    #[test]
+    fn test_semantic_error_into_mamba_error() {
+        let error = SemanticError::UndefinedVariable {
+            name: "y".to_string(),
+            position: SourcePosition { line: 3, column: 1, offset: 20 },
+            suggestion: None,
+        };
+        let error: MambaError = error.into();
+        let diagnostic = error.diagnostic().unwrap();
+        assert_eq!(diagnostic.code, ErrorCode::UndefinedName);
+        assert_eq!(diagnostic.code.as_str(), "E0200");
+    }
+
+    // Variable Declaration Tests
+
+    #[test]
+    fn test_simple_assignment() {
+        let module = parse("x = 5");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Simple assignment should succeed");
+        
+        let table = result.unwrap();
+        let symbol = table.lookup("x");
+        assert!(symbol.is_some(), "Variable x should be declared");
+        assert_eq!(symbol.unwrap().kind, SymbolKind::Variable);
+    }
+
+    #[test]
+    fn test_multiple_assignment() {
+        let module = parse("x = y = 10");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Multiple assignment should succeed");
+        
+        let table = result.unwrap();
+        assert!(table.lookup("x").is_some(), "Variable x should be declared");
+        assert!(table.lookup("y").is_some(), "Variable y should be declared");
+    }
+
+    #[test]
+    fn test_tuple_unpacking() {
+        let module = parse("a, b = 1, 2");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Tuple unpacking should succeed");
+        
+        let table = result.unwrap();
+        assert!(table.lookup("a").is_some(), "Variable a should be declared");
+        assert!(table.lookup("b").is_some(), "Variable b should be declared");
+    }
+
+    #[test]
+    fn test_list_unpacking() {
+        let module = parse("[x, y, z] = [1, 2, 3]");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "List unpacking should succeed");
+        
+        let table = result.unwrap();
+        assert!(table.lookup("x").is_some(), "Variable x should be declared");
+        assert!(table.lookup("y").is_some(), "Variable y should be declared");
+        assert!(table.lookup("z").is_some(), "Variable z should be declared");
+    }
+
+    #[test]
+    fn test_nested_unpacking() {
+        let module = parse("(a, (b, c)) = (1, (2, 3))");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Nested unpacking should succeed");
+        
+        let table = result.unwrap();
+        assert!(table.lookup("a").is_some(), "Variable a should be declared");
+        assert!(table.lookup("b").is_some(), "Variable b should be declared");
+        assert!(table.lookup("c").is_some(), "Variable c should be declared");
+    }
+
+    #[test]
+    fn test_starred_unpacking() {
+        let module = parse("a, *rest = [1, 2, 3, 4]");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Starred unpacking should succeed");
+        
+        let table = result.unwrap();
+        assert!(table.lookup("a").is_some(), "Variable a should be declared");
+        assert!(table.lookup("rest").is_some(), "Variable rest should be declared");
+    }
+
+    #[test]
+    fn test_annotated_assignment() {
+        let module = parse("x: int = 5");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Annotated assignment should succeed");
+        
+        let table = result.unwrap();
+        let symbol = table.lookup("x");
+        assert!(symbol.is_some(), "Variable x should be declared");
+        assert_eq!(symbol.unwrap().kind, SymbolKind::Variable);
+    }
+
+    #[test]
+    fn test_annotated_assignment_no_value() {
+        let module = parse("x: int");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Annotated assignment without value should succeed");
+        
+        let table = result.unwrap();
+        assert!(table.lookup("x").is_some(), "Variable x should be declared");
+    }
+
+    #[test]
+    fn test_augmented_assignment_defined() {
+        let module = parse("x = 5\nx += 1");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Augmented assignment to defined variable should succeed");
+    }
+
+    #[test]
+    fn test_augmented_assignment_undefined() {
+        let module = parse("x += 1");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_err(), "Augmented assignment to undefined variable should fail");
+        
+        let errors = result.unwrap_err();
+        assert_eq!(errors.len(), 1);
+        match &errors[0] {
+            SemanticError::UndefinedVariable { name, .. } => {
+                assert_eq!(name, "x");
+            }
+            _ => panic!("Expected UndefinedVariable error"),
+        }
+    }
+
+    #[test]
+    fn test_variable_rebinding_same_scope() {
+        // Python rebinding: the second assignment records another definition site
+        let module = parse("x = 1\nx = 2");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Reassignment in same scope should succeed");
+
+        let table = result.unwrap();
+        let symbol = table.lookup("x").unwrap();
+        assert_eq!(symbol.position.line, 1);
+        assert_eq!(symbol.definitions.len(), 2);
+        assert_eq!(symbol.definitions[1].line, 2);
+    }
+
+    #[test]
+    fn test_multiple_variables_declaration() {
+        let module = parse("a = 1\nb = 2\nc = 3");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Multiple variable declarations should succeed");
+        
+        let table = result.unwrap();
+        assert!(table.lookup("a").is_some());
+        assert!(table.lookup("b").is_some());
+        assert!(table.lookup("c").is_some());
+    }
+
+    #[test]
+    fn test_chained_assignment() {
+        let module = parse("x = y = z = 42");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Chained assignment should succeed");
+        
+        let table = result.unwrap();
+        assert!(table.lookup("x").is_some());
+        assert!(table.lookup("y").is_some());
+        assert!(table.lookup("z").is_some());
+    }
+
+    #[test]
+    fn test_assignment_with_expression() {
+        let module = parse("result = 10 + 20 * 30");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Assignment with expression should succeed");
+        
+        let table = result.unwrap();
+        assert!(table.lookup("result").is_some());
+    }
+
+    #[test]
+    fn test_all_augmented_operators() {
+        let code = "x = 10\nx += 5\nx -= 2\nx *= 3\nx /= 2";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "All augmented operators should work with defined variable");
+    }
+
+    #[test]
+    fn test_augmented_assignment_complex_target() {
+        // Augmented assignment to attribute or subscript should not error
+        // (we don't track whether those exist, only identifier variables)
+        let module = parse("x = [1, 2, 3]\nx[0] += 10");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Augmented assignment to subscript should succeed");
+    }
+
+    #[test]
+    fn test_tuple_with_mixed_targets() {
+        let module = parse("x, y = 1, 2\na, b, c = 3, 4, 5");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Multiple tuple unpacking should succeed");
+        
+        let table = result.unwrap();
+        assert!(table.lookup("x").is_some());
+        assert!(table.lookup("y").is_some());
+        assert!(table.lookup("a").is_some());
+        assert!(table.lookup("b").is_some());
+        assert!(table.lookup("c").is_some());
+    }
+
+    #[test]
+    fn test_annotation_with_complex_type() {
+        let module = parse("items: list = []");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Annotated assignment with complex type should succeed");
+        
+        let table = result.unwrap();
+        assert!(table.lookup("items").is_some());
+    }
+
+    #[test]
+    fn test_rebinding_with_annotation() {
+        let module = parse("x = 1\nx: int = 2");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Annotated reassignment should succeed");
+        assert_eq!(result.unwrap().lookup("x").unwrap().definitions.len(), 2);
+    }
+
+    // Function Definition Tests
+
+    #[test]
+    fn test_simple_function() {
+        let module = parse("def foo():\n    pass\n");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Simple function definition should succeed");
+        
+        let table = result.unwrap();
+        let symbol = table.lookup("foo");
+        assert!(symbol.is_some(), "Function foo should be declared");
+        assert_eq!(symbol.unwrap().kind, SymbolKind::Function);
+    }
+
+    #[test]
+    fn test_function_with_parameters() {
+        let module = parse("def greet(name, age):\n    pass\n");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Function with parameters should succeed");
+        
+        let table = result.unwrap();
+        assert!(table.lookup("greet").is_some(), "Function greet should be declared");
+        // Note: parameters are in function scope, not module scope
+    }
+
+    #[test]
+    fn test_function_with_body() {
+        let code = "def compute():\n    x = 10\n    y = 20\n    return x + y\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Function with body should succeed");
+        
+        let table = result.unwrap();
+        assert!(table.lookup("compute").is_some(), "Function compute should be declared");
+        // Variables x and y are in function scope, not module scope
+        assert!(table.lookup("x").is_none(), "Variable x should not be in module scope");
+    }
+
+    #[test]
+    fn test_nested_functions() {
+        let code = "def outer():\n    def inner():\n        pass\n    pass\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Nested functions should succeed");
+        
+        let table = result.unwrap();
+        assert!(table.lookup("outer").is_some(), "Function outer should be declared");
+        // inner is in outer's scope, not module scope
+        assert!(table.lookup("inner").is_none(), "Function inner should not be in module scope");
+    }
+
+    #[test]
+    fn test_function_redefinition() {
+        let code = "def foo():\n    pass\ndef foo():\n    pass\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Function redefinition should succeed");
+
+        let table = result.unwrap();
+        let symbol = table.lookup("foo").unwrap();
+        assert_eq!(symbol.kind, SymbolKind::Function);
+        assert_eq!(symbol.definitions.len(), 2);
+    }
+
+    #[test]
+    fn test_parameter_redeclaration() {
+        let code = "def func(x, x):\n    pass\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_err(), "Duplicate parameter should fail");
+        
+        let errors = result.unwrap_err();
+        assert_eq!(errors.len(), 1);
+        match &errors[0] {
+            SemanticError::Redeclaration { name, .. } => {
+                assert_eq!(name, "x");
+            }
+            _ => panic!("Expected Redeclaration error"),
+        }
+    }
+
+    #[test]
+    fn test_multiple_functions() {
+        let code = "def foo():\n    pass\ndef bar():\n    pass\ndef baz():\n    pass\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Multiple functions should succeed");
+        
+        let table = result.unwrap();
+        assert!(table.lookup("foo").is_some());
+        assert!(table.lookup("bar").is_some());
+        assert!(table.lookup("baz").is_some());
+    }
+
+    #[test]
+    fn test_function_with_return_type() {
+        let code = "def get_number() -> int:\n    return 42\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Function with return type should succeed");
+        
+        let table = result.unwrap();
+        assert!(table.lookup("get_number").is_some());
+    }
+
+    #[test]
+    fn test_function_with_type_annotations() {
+        let code = "def add(a: int, b: int) -> int:\n    return a + b\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Function with type annotations should succeed");
+        
+        let table = result.unwrap();
+        assert!(table.lookup("add").is_some());
+    }
+
+    #[test]
+    fn test_async_function() {
+        let code = "async def fetch_data():\n    pass\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Async function should succeed");
+        
+        let table = result.unwrap();
+        assert!(table.lookup("fetch_data").is_some());
+    }
+
+    #[test]
+    fn test_function_variable_scoping() {
+        let code = "x = 10\ndef foo():\n    x = 20\n    pass\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Variable shadowing in function should succeed");
+        
+        let table = result.unwrap();
+        let module_x = table.lookup("x");
+        assert!(module_x.is_some(), "Module-level x should exist");
+        // Function's x is in a different scope
+    }
+
+    #[test]
+    fn test_function_and_variable_different_names() {
+        let code = "x = 10\ndef foo():\n    pass\ny = 20\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Functions and variables with different names should succeed");
+        
+        let table = result.unwrap();
+        assert!(table.lookup("x").is_some());
+        assert!(table.lookup("foo").is_some());
+        assert!(table.lookup("y").is_some());
+    }
+
+    #[test]
+    fn test_function_with_default_parameters() {
+        let code = "def greet(name, greeting=\"Hello\"):\n    pass\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Function with default parameters should succeed");
+        
+        let table = result.unwrap();
+        assert!(table.lookup("greet").is_some());
+    }
+
+    #[test]
+    fn test_function_with_decorators() {
+        let code = "@decorator\ndef foo():\n    pass\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Function with decorator should succeed");
+        
+        let table = result.unwrap();
+        assert!(table.lookup("foo").is_some());
+    }
+
+    #[test]
+    fn test_empty_function() {
+        let code = "def empty():\n    pass\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Empty function should succeed");
+        
+        let table = result.unwrap();
+        assert!(table.lookup("empty").is_some());
+    }
+
+    // Variable Usage Detection Tests
+
+    #[test]
+    fn test_undefined_variable_in_expression() {
+        let module = parse("y = x + 1");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_err(), "Using undefined variable should fail");
+        
+        let errors = result.unwrap_err();
+        assert_eq!(errors.len(), 1);
+        match &errors[0] {
+            SemanticError::UndefinedVariable { name, .. } => {
+                assert_eq!(name, "x");
+            }
+            _ => panic!("Expected UndefinedVariable error"),
+        }
+    }
+
+    #[test]
+    fn test_variable_used_after_definition() {
+        let module = parse("x = 10\ny = x + 5");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Using defined variable should succeed");
+    }
+
+    #[test]
+    fn test_multiple_undefined_variables() {
+        let module = parse("result = a + b + c");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_err(), "Multiple undefined variables should fail");
+        
+        let errors = result.unwrap_err();
+        assert_eq!(errors.len(), 3);
+    }
+
+    #[test]
+    fn test_undefined_in_function_call() {
+        let module = parse("result = foo(x, y)");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_err(), "Undefined variables in call should fail");
+        
+        let errors = result.unwrap_err();
+        assert!(errors.len() >= 2); // foo, x, y all undefined
+    }
+
+    #[test]
+    fn test_undefined_in_subscript() {
+        let module = parse("value = arr[idx]");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_err(), "Undefined variables in subscript should fail");
+        
+        let errors = result.unwrap_err();
+        assert_eq!(errors.len(), 2); // arr and idx
+    }
+
+    #[test]
+    fn test_undefined_in_attribute_access() {
+        let module = parse("value = obj.attr");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_err(), "Undefined variable in attribute access should fail");
+        
+        let errors = result.unwrap_err();
+        assert_eq!(errors.len(), 1);
+        match &errors[0] {
+            SemanticError::UndefinedVariable { name, .. } => {
+                assert_eq!(name, "obj");
+            }
+            _ => panic!("Expected UndefinedVariable error"),
+        }
+    }
+
+    #[test]
+    fn test_undefined_in_list_literal() {
+        let module = parse("items = [a, b, c]");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_err(), "Undefined variables in list should fail");
+        
+        let errors = result.unwrap_err();
+        assert_eq!(errors.len(), 3);
+    }
+
+    #[test]
+    fn test_undefined_in_dict_literal() {
+        let module = parse("data = {k: v}");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_err(), "Undefined variables in dict should fail");
+        
+        let errors = result.unwrap_err();
+        assert_eq!(errors.len(), 2); // k and v
+    }
+
+    #[test]
+    fn test_undefined_in_conditional_expression() {
+        let module = parse("result = x if condition else y");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_err(), "Undefined variables in conditional should fail");
+        
+        let errors = result.unwrap_err();
+        assert_eq!(errors.len(), 3); // x, condition, y
+    }
+
+    #[test]
+    fn test_variable_in_function_scope() {
+        let code = "def foo():\n    x = 10\n    y = x + 5\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Variable defined in function scope should work");
+    }
+
+    #[test]
+    fn test_undefined_in_function_body() {
+        let code = "def foo():\n    y = x + 1\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_err(), "Undefined variable in function should fail");
+        
+        let errors = result.unwrap_err();
+        assert_eq!(errors.len(), 1);
+        match &errors[0] {
+            SemanticError::UndefinedVariable { name, .. } => {
+                assert_eq!(name, "x");
+            }
+            _ => panic!("Expected UndefinedVariable error"),
+        }
+    }
+
+    #[test]
+    fn test_parameter_usage_in_function() {
+        let code = "def add(a, b):\n    return a + b\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Using parameters should succeed");
+    }
+
+    #[test]
+    fn test_nested_scope_variable_access() {
+        let code = "x = 10\ndef foo():\n    y = x + 5\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Accessing outer scope variable should succeed");
+    }
+
+    #[test]
+    fn test_walrus_operator_declaration() {
+        let code = "if (n := len([1, 2, 3])) > 0:\n    pass\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Walrus operator should declare variable");
+    }
+
+    #[test]
+    fn test_complex_expression_chain() {
+        let code = "x = 1\ny = 2\nz = (x + y) * (x - y) / (x * y)\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Complex expression with defined variables should succeed");
+    }
+
+    #[test]
+    fn test_undefined_in_nested_expression() {
+        let module = parse("result = ((a + b) * c) / d");
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_err(), "Nested undefined variables should fail");
+        
+        let errors = result.unwrap_err();
+        assert_eq!(errors.len(), 4); // a, b, c, d
+    }
+
+    // Redeclaration and Shadowing Tests
+
+    #[test]
+    fn test_shadowing_in_nested_function() {
+        let code = "x = 1\ndef outer():\n    x = 2\n    def inner():\n        x = 3\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Shadowing across nested scopes should succeed");
+    }
+
+    #[test]
+    fn test_parameter_shadows_outer_variable() {
+        let code = "x = 10\ndef foo(x):\n    return x * 2\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Parameter shadowing outer variable should succeed");
+    }
+
+    #[test]
+    fn test_parameter_rebinding_in_body() {
+        let code = "def foo(x):\n    x = 20\n    return x\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Reassigning a parameter in the function body should succeed");
+    }
+
+    #[test]
+    fn test_nested_function_shadows_parameter() {
+        let code = "def outer(x):\n    def inner():\n        x = 5\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Nested function can shadow outer parameter");
+    }
+
+    #[test]
+    fn test_multiple_rebindings_in_scope() {
+        let code = "x = 1\nx = 2\nx = 3\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Multiple reassignments should succeed");
+
+        let table = result.unwrap();
+        let lines: Vec<usize> = table.lookup("x").unwrap().definitions.iter().map(|p| p.line).collect();
+        assert_eq!(lines, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_function_rebinds_variable() {
+        let code = "x = 10\ndef x():\n    pass\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Function with same name as variable rebinds it");
+        assert_eq!(result.unwrap().lookup("x").unwrap().definitions.len(), 2);
+    }
+
+    #[test]
+    fn test_variable_after_function_same_name() {
+        let code = "def foo():\n    pass\nfoo = 10\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Variable with same name as function rebinds it");
+    }
+
+    #[test]
+    fn test_shadowing_with_annotation() {
+        let code = "x = 10\ndef foo():\n    x: int = 20\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Shadowing with annotation should succeed");
+    }
+
+    #[test]
+    fn test_no_conflict_different_scopes() {
+        let code = "def foo():\n    x = 1\ndef bar():\n    x = 2\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Same variable name in different function scopes should succeed");
+    }
+
+    #[test]
+    fn test_rebinding_mixed_types() {
+        // Type-changing rebinding is legal Python; the opt-in rebind-type lint flags it
+        let code = "x = 10\nx: str = 'hello'\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "Rebinding with a different type should succeed");
+    }
+
+    #[test]
+    fn test_walrus_redeclaration() {
+        // Walrus operator allows reassignment of existing variables
+        let code = "x = 10\ny = (x := 20)\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        // Walrus operator on existing variable - in Python this is allowed (rebinds the variable)
+        assert!(result.is_ok(), "Walrus operator should allow reassignment");
+    }
+
+    // ==================== Nested Scope Support Tests ====================
+
+    #[test]
+    fn test_if_statement_no_new_scope() {
+        // Variables declared in if blocks should be accessible outside
+        let code = "if True:\n    x = 10\nprint(x)\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "If statement should not create new scope");
+    }
+
+    #[test]
+    fn test_while_statement_no_new_scope() {
+        // Variables declared in while blocks should be accessible outside
+        let code = "while False:\n    y = 20\nprint(y)\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "While statement should not create new scope");
+    }
+
+    #[test]
+    fn test_for_loop_variable_accessible() {
+        // For loop variable should be accessible after loop
+        let code = "for i in range(10):\n    pass\nprint(i)\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "For loop variable should persist after loop");
+    }
+
+    #[test]
+    fn test_for_loop_with_unpacking() {
+        // For loop with tuple unpacking
+        let code = "for x, y in [(1, 2), (3, 4)]:\n    print(x, y)\nprint(x, y)\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "For loop unpacked variables should persist");
+    }
+
+    #[test]
+    fn test_nested_if_while_for() {
+        // Variables in deeply nested control flow should all be in same scope
+        let code = "if True:\n    a = 1\n    while True:\n        b = 2\n        for i in range(10):\n            c = 3\nprint(a)\nprint(b)\nprint(c)\nprint(i)\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_ok(), "All variables in nested control flow should be accessible");
+    }
+
+    #[test]
+    fn test_class_basic_scope() {
+        // Class should create its own scope
+        let code = "class MyClass:\n    x = 10\nprint(x)\n";
+        let module = parse(code);
+        let analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&module);
+        assert!(result.is_err(), "Class scope should be isolated");
+        let errors = result.unwrap_err();
+        assert!(errors.iter().any(|e| matches!(e, SemanticError::UndefinedVariable { name, .. } if name == "x")));
+    }
+
+    #[test]
+    fn test_class_declaration() {
+        // Class name should be accessible after declaration
+        let code = "class MyClass:\n    pass\nx = MyClass\n";
+        let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Simple assignment should succeed");
-        
-        let table = result.unwrap();
-        let symbol = table.lookup("x");
-        assert!(symbol.is_some(), "Variable x should be declared");
-        assert_eq!(symbol.unwrap().kind, SymbolKind::Variable);
+        assert!(result.is_ok(), "Class name should be accessible");
     }
 
     #[test]
-    fn test_multiple_assignment() {
-        let module = parse("x = y = 10");
+    fn test_class_redefinition() {
+        // Redefining a class rebinds the name
+        let code = "class MyClass:\n    pass\nclass MyClass:\n    pass\n";
+        let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Multiple assignment should succeed");
-        
-        let table = result.unwrap();
-        assert!(table.lookup("x").is_some(), "Variable x should be declared");
-        assert!(table.lookup("y").is_some(), "Variable y should be declared");
+        assert!(result.is_ok(), "Class redefinition should succeed");
+        assert_eq!(result.unwrap().lookup("MyClass").unwrap().definitions.len(), 2);
     }
 
     #[test]
-    fn test_tuple_unpacking() {
-        let module = parse("a, b = 1, 2");
+    fn test_nested_class_in_function() {
+        // Class inside function should have function as parent scope
+        let code = "def outer():\n    x = 10\n    class Inner:\n        y = x\n    return Inner\n";
+        let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Tuple unpacking should succeed");
-        
-        let table = result.unwrap();
-        assert!(table.lookup("a").is_some(), "Variable a should be declared");
-        assert!(table.lookup("b").is_some(), "Variable b should be declared");
+        assert!(result.is_ok(), "Nested class should access function scope");
     }
 
     #[test]
-    fn test_list_unpacking() {
-        let module = parse("[x, y, z] = [1, 2, 3]");
+    fn test_function_in_class() {
+        // Function inside class should create nested scopes
+        let code = "class MyClass:\n    def method(self):\n        x = 10\n        return x\n";
+        let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "List unpacking should succeed");
-        
-        let table = result.unwrap();
-        assert!(table.lookup("x").is_some(), "Variable x should be declared");
-        assert!(table.lookup("y").is_some(), "Variable y should be declared");
-        assert!(table.lookup("z").is_some(), "Variable z should be declared");
+        assert!(result.is_ok(), "Method in class should work correctly");
     }
 
     #[test]
-    fn test_nested_unpacking() {
-        let module = parse("(a, (b, c)) = (1, (2, 3))");
+    fn test_deeply_nested_scopes() {
+        // Test deep nesting: module -> function -> class -> function -> if/for
+        // Note: This test validates scope isolation, not closure behavior (Task 9)
+        let code = "def outer_func():\n    a = 1\n    class InnerClass:\n        b = 2\n        def inner_method(self):\n            c = 3\n            if True:\n                d = 4\n                for i in range(10):\n                    e = 5\n                    print(c)\n                    print(d)\n                    print(e)\n                    print(i)\n    return InnerClass\n";
+        let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Nested unpacking should succeed");
-        
-        let table = result.unwrap();
-        assert!(table.lookup("a").is_some(), "Variable a should be declared");
-        assert!(table.lookup("b").is_some(), "Variable b should be declared");
-        assert!(table.lookup("c").is_some(), "Variable c should be declared");
+        // Variables c, d, e, i should all be accessible (if/for don't create scopes)
+        // Note: We removed reference to 'a' since closure analysis is Task 9
+        assert!(result.is_ok(), "Variables in nested control flow should be accessible");
     }
 
     #[test]
-    fn test_starred_unpacking() {
-        let module = parse("a, *rest = [1, 2, 3, 4]");
+    fn test_if_elif_else_blocks() {
+        // All branches of if/elif/else should be in same scope
+        let code = "x = 1\nif x == 1:\n    y = 2\nelif x == 2:\n    z = 3\nelse:\n    w = 4\n";
+        let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Starred unpacking should succeed");
-        
-        let table = result.unwrap();
-        assert!(table.lookup("a").is_some(), "Variable a should be declared");
-        assert!(table.lookup("rest").is_some(), "Variable rest should be declared");
+        assert!(result.is_ok(), "If/elif/else should not create scopes");
     }
 
     #[test]
-    fn test_annotated_assignment() {
-        let module = parse("x: int = 5");
+    fn test_while_with_else() {
+        // While with else block
+        let code = "while False:\n    a = 1\nelse:\n    b = 2\nprint(a)\nprint(b)\n";
+        let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Annotated assignment should succeed");
-        
-        let table = result.unwrap();
-        let symbol = table.lookup("x");
-        assert!(symbol.is_some(), "Variable x should be declared");
-        assert_eq!(symbol.unwrap().kind, SymbolKind::Variable);
+        assert!(result.is_ok(), "While-else should not create scopes");
     }
 
     #[test]
-    fn test_annotated_assignment_no_value() {
-        let module = parse("x: int");
+    fn test_for_with_else() {
+        // For with else block
+        let code = "for i in []:\n    a = 1\nelse:\n    b = 2\nprint(i)\nprint(a)\nprint(b)\n";
+        let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Annotated assignment without value should succeed");
-        
-        let table = result.unwrap();
-        assert!(table.lookup("x").is_some(), "Variable x should be declared");
+        assert!(result.is_ok(), "For-else should not create scopes");
     }
 
+    // ==================== Closure Tracking & Global/Nonlocal Tests ====================
+
     #[test]
-    fn test_augmented_assignment_defined() {
-        let module = parse("x = 5\nx += 1");
+    fn test_global_at_module_level() {
+        // global at module level is allowed (though redundant)
+        let code = "global x\nx = 10\n";
+        let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Augmented assignment to defined variable should succeed");
+        assert!(result.is_ok(), "global at module level should be allowed");
     }
 
     #[test]
-    fn test_augmented_assignment_undefined() {
-        let module = parse("x += 1");
+    fn test_global_in_function() {
+        // global in function allows modifying module-level variable
+        let code = "x = 10\ndef func():\n    global x\n    x = 20\n";
+        let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "Augmented assignment to undefined variable should fail");
-        
-        let errors = result.unwrap_err();
-        assert_eq!(errors.len(), 1);
-        match &errors[0] {
-            SemanticError::UndefinedVariable { name, .. } => {
-                assert_eq!(name, "x");
-            }
-            _ => panic!("Expected UndefinedVariable error"),
-        }
+        assert!(result.is_ok(), "global declaration in function should work");
     }
 
     #[test]
-    fn test_variable_redeclaration_same_scope() {
-        let module = parse("x = 1\nx = 2");
+    fn test_global_multiple_names() {
+        // global can declare multiple names
+        let code = "def func():\n    global x, y, z\n    x = 1\n    y = 2\n    z = 3\n";
+        let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "Redeclaration in same scope should fail");
-        
-        let errors = result.unwrap_err();
-        assert_eq!(errors.len(), 1);
-        match &errors[0] {
-            SemanticError::Redeclaration { name, .. } => {
-                assert_eq!(name, "x");
-            }
-            _ => panic!("Expected Redeclaration error"),
-        }
+        assert!(result.is_ok(), "global with multiple names should work");
     }
 
     #[test]
-    fn test_multiple_variables_declaration() {
-        let module = parse("a = 1\nb = 2\nc = 3");
+    fn test_global_after_local_declaration() {
+        // Cannot use global after local declaration
+        let code = "def func():\n    x = 10\n    global x\n";
+        let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Multiple variable declarations should succeed");
-        
-        let table = result.unwrap();
-        assert!(table.lookup("a").is_some());
-        assert!(table.lookup("b").is_some());
-        assert!(table.lookup("c").is_some());
+        assert!(result.is_err(), "global after local declaration should fail");
+        let errors = result.unwrap_err();
+        assert!(errors.iter().any(|e| matches!(e, SemanticError::Redeclaration { name, .. } if name == "x")));
     }
 
     #[test]
-    fn test_chained_assignment() {
-        let module = parse("x = y = z = 42");
+    fn test_nonlocal_at_module_level() {
+        // nonlocal at module level is an error
+        let code = "nonlocal x\n";
+        let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Chained assignment should succeed");
-        
-        let table = result.unwrap();
-        assert!(table.lookup("x").is_some());
-        assert!(table.lookup("y").is_some());
-        assert!(table.lookup("z").is_some());
+        assert!(result.is_err(), "nonlocal at module level should fail");
+        let errors = result.unwrap_err();
+        assert!(errors.iter().any(|e| matches!(e, SemanticError::NonlocalAtModuleLevel { name, .. } if name == "x")));
     }
 
     #[test]
-    fn test_assignment_with_expression() {
-        let module = parse("result = 10 + 20 * 30");
+    fn test_nonlocal_in_nested_function() {
+        // nonlocal in nested function accesses outer function variable
+        let code = "def outer():\n    x = 10\n    def inner():\n        nonlocal x\n        x = 20\n    return inner\n";
+        let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Assignment with expression should succeed");
-        
-        let table = result.unwrap();
-        assert!(table.lookup("result").is_some());
+        assert!(result.is_ok(), "nonlocal in nested function should work");
     }
 
     #[test]
-    fn test_all_augmented_operators() {
-        let code = "x = 10\nx += 5\nx -= 2\nx *= 3\nx /= 2";
+    fn test_outer_bindings_recorded() {
+        let code = "\
+def inc():
+    global count, fresh
+    count = 'a'
+    fresh = 1
+count = 0
+def outer():
+    x = 10
+    def inner():
+        nonlocal x
+        x = 20
+    return inner
+";
+        let table = SemanticAnalyzer::new().analyze(&parse(code)).unwrap();
+        let lines = |symbol: &Symbol| symbol.definitions.iter().map(|position| position.line).collect::<Vec<_>>();
+        let module = table.get_scope(table.module_scope_id().unwrap()).unwrap();
+        // The first binding stays first; the others follow in source order
+        assert_eq!(lines(module.lookup("count").unwrap()), vec![5, 3]);
+        assert_eq!(lines(module.lookup("fresh").unwrap()), vec![4]);
+        let outer = table
+            .scopes()
+            .values()
+            .filter(|scope| scope.kind == ScopeKind::Function)
+            .find_map(|scope| scope.lookup("x").filter(|symbol| !symbol.is_nonlocal))
+            .unwrap();
+        assert_eq!(lines(outer), vec![7, 10]);
+    }
+
+    #[test]
+    fn test_nonlocal_not_found() {
+        // nonlocal variable must exist in enclosing scope
+        let code = "def outer():\n    def inner():\n        nonlocal x\n        x = 10\n";
         let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "All augmented operators should work with defined variable");
+        assert!(result.is_err(), "nonlocal without enclosing binding should fail");
+        let errors = result.unwrap_err();
+        assert!(errors.iter().any(|e| matches!(e, SemanticError::NonlocalNotFound { name, .. } if name == "x")));
     }
 
     #[test]
-    fn test_augmented_assignment_complex_target() {
-        // Augmented assignment to attribute or subscript should not error
-        // (we don't track whether those exist, only identifier variables)
-        let module = parse("x = [1, 2, 3]\nx[0] += 10");
+    fn test_nonlocal_skips_module_scope() {
+        // nonlocal should not find variables in module scope
+        let code = "x = 10\ndef func():\n    nonlocal x\n";
+        let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Augmented assignment to subscript should succeed");
+        assert!(result.is_err(), "nonlocal should not find module-level variables");
+        let errors = result.unwrap_err();
+        assert!(errors.iter().any(|e| matches!(e, SemanticError::NonlocalNotFound { name, .. } if name == "x")));
     }
 
     #[test]
-    fn test_tuple_with_mixed_targets() {
-        let module = parse("x, y = 1, 2\na, b, c = 3, 4, 5");
+    fn test_nonlocal_multiple_names() {
+        // nonlocal can declare multiple names
+        let code = "def outer():\n    x = 1\n    y = 2\n    def inner():\n        nonlocal x, y\n        x = 10\n        y = 20\n";
+        let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Multiple tuple unpacking should succeed");
-        
-        let table = result.unwrap();
-        assert!(table.lookup("x").is_some());
-        assert!(table.lookup("y").is_some());
-        assert!(table.lookup("a").is_some());
-        assert!(table.lookup("b").is_some());
-        assert!(table.lookup("c").is_some());
+        assert!(result.is_ok(), "nonlocal with multiple names should work");
     }
 
     #[test]
-    fn test_annotation_with_complex_type() {
-        let module = parse("items: list = []");
+    fn test_nonlocal_after_local_declaration() {
+        // Cannot use nonlocal after local declaration
+        let code = "def outer():\n    x = 10\n    def inner():\n        x = 5\n        nonlocal x\n";
+        let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Annotated assignment with complex type should succeed");
-        
-        let table = result.unwrap();
-        assert!(table.lookup("items").is_some());
+        assert!(result.is_err(), "nonlocal after local declaration should fail");
+        let errors = result.unwrap_err();
+        assert!(errors.iter().any(|e| matches!(e, SemanticError::Redeclaration { name, .. } if name == "x")));
     }
 
     #[test]
-    fn test_redeclaration_with_annotation() {
-        let module = parse("x = 1\nx: int = 2");
+    fn test_closure_basic() {
+        // Basic closure - inner function references outer variable
+        let code = "def outer():\n    x = 10\n    def inner():\n        print(x)\n    return inner\n";
+        let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "Redeclaration with annotation should fail");
-        
-        let errors = result.unwrap_err();
-        assert_eq!(errors.len(), 1);
-        match &errors[0] {
-            SemanticError::Redeclaration { name, .. } => {
-                assert_eq!(name, "x");
-            }
-            _ => panic!("Expected Redeclaration error"),
-        }
+        assert!(result.is_ok(), "Basic closure should work");
     }
 
-    // Function Definition Tests
-
     #[test]
-    fn test_simple_function() {
-        let module = parse("def foo():\n    pass\n");
+    fn test_closure_multiple_levels() {
+        // Multi-level closure
+        let code = "def level1():\n    x = 1\n    def level2():\n        y = 2\n        def level3():\n            print(x, y)\n        return level3\n    return level2\n";
+        let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Simple function definition should succeed");
-        
-        let table = result.unwrap();
-        let symbol = table.lookup("foo");
-        assert!(symbol.is_some(), "Function foo should be declared");
-        assert_eq!(symbol.unwrap().kind, SymbolKind::Function);
+        assert!(result.is_ok(), "Multi-level closure should work");
     }
 
     #[test]
-    fn test_function_with_parameters() {
-        let module = parse("def greet(name, age):\n    pass\n");
+    fn test_global_and_nonlocal_different_vars() {
+        // Can use global and nonlocal for different variables
+        let code = "x = 1\ndef outer():\n    y = 2\n    def inner():\n        global x\n        nonlocal y\n        x = 10\n        y = 20\n";
+        let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Function with parameters should succeed");
-        
-        let table = result.unwrap();
-        assert!(table.lookup("greet").is_some(), "Function greet should be declared");
-        // Note: parameters are in function scope, not module scope
+        assert!(result.is_ok(), "global and nonlocal for different vars should work");
     }
 
     #[test]
-    fn test_function_with_body() {
-        let code = "def compute():\n    x = 10\n    y = 20\n    return x + y\n";
+    fn test_nonlocal_finds_nearest_enclosing() {
+        // nonlocal should find variable in nearest enclosing function scope
+        let code = "def outer():\n    x = 1\n    def middle():\n        x = 2\n        def inner():\n            nonlocal x\n            x = 3\n        return inner\n    return middle\n";
         let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Function with body should succeed");
-        
-        let table = result.unwrap();
-        assert!(table.lookup("compute").is_some(), "Function compute should be declared");
-        // Variables x and y are in function scope, not module scope
-        assert!(table.lookup("x").is_none(), "Variable x should not be in module scope");
+        assert!(result.is_ok(), "nonlocal should find nearest enclosing scope");
     }
 
     #[test]
-    fn test_nested_functions() {
-        let code = "def outer():\n    def inner():\n        pass\n    pass\n";
+    fn test_nonlocal_in_class() {
+        // nonlocal in class method
+        let code = "def outer():\n    x = 10\n    class Inner:\n        def method(self):\n            nonlocal x\n            x = 20\n    return Inner\n";
         let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Nested functions should succeed");
-        
-        let table = result.unwrap();
-        assert!(table.lookup("outer").is_some(), "Function outer should be declared");
-        // inner is in outer's scope, not module scope
-        assert!(table.lookup("inner").is_none(), "Function inner should not be in module scope");
+        assert!(result.is_ok(), "nonlocal in class method should work");
     }
 
     #[test]
-    fn test_function_redeclaration() {
-        let code = "def foo():\n    pass\ndef foo():\n    pass\n";
+    fn test_undefined_in_return() {
+        // Undefined variable in return statement
+        let code = "def func():\n    return undefined_var\n";
         let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "Function redeclaration should fail");
-        
+        assert!(result.is_err(), "undefined variable in return should fail");
         let errors = result.unwrap_err();
         assert_eq!(errors.len(), 1);
         match &errors[0] {
-            SemanticError::Redeclaration { name, .. } => {
-                assert_eq!(name, "foo");
+            SemanticError::UndefinedVariable { name, .. } => {
+                assert_eq!(name, "undefined_var");
             }
-            _ => panic!("Expected Redeclaration error"),
+            _ => panic!("Expected UndefinedVariable error"),
         }
     }
 
     #[test]
-    fn test_parameter_redeclaration() {
-        let code = "def func(x, x):\n    pass\n";
+    fn test_undefined_in_assert() {
+        // Undefined variable in assert condition
+        let code = "assert undefined_condition\n";
         let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "Duplicate parameter should fail");
-        
+        assert!(result.is_err(), "undefined variable in assert should fail");
         let errors = result.unwrap_err();
         assert_eq!(errors.len(), 1);
         match &errors[0] {
-            SemanticError::Redeclaration { name, .. } => {
-                assert_eq!(name, "x");
+            SemanticError::UndefinedVariable { name, .. } => {
+                assert_eq!(name, "undefined_condition");
             }
-            _ => panic!("Expected Redeclaration error"),
+            _ => panic!("Expected UndefinedVariable error"),
         }
     }
 
     #[test]
-    fn test_multiple_functions() {
-        let code = "def foo():\n    pass\ndef bar():\n    pass\ndef baz():\n    pass\n";
+    fn test_undefined_in_assert_message() {
+        // Undefined variable in assert message
+        let code = "assert True, undefined_message\n";
         let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Multiple functions should succeed");
-        
-        let table = result.unwrap();
-        assert!(table.lookup("foo").is_some());
-        assert!(table.lookup("bar").is_some());
-        assert!(table.lookup("baz").is_some());
+        assert!(result.is_err(), "undefined variable in assert message should fail");
+        let errors = result.unwrap_err();
+        assert_eq!(errors.len(), 1);
+        match &errors[0] {
+            SemanticError::UndefinedVariable { name, .. } => {
+                assert_eq!(name, "undefined_message");
+            }
+            _ => panic!("Expected UndefinedVariable error"),
+        }
     }
 
     #[test]
-    fn test_function_with_return_type() {
-        let code = "def get_number() -> int:\n    return 42\n";
+    fn test_undefined_in_del() {
+        // Undefined variable in del statement
+        let code = "del undefined_var\n";
         let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Function with return type should succeed");
-        
-        let table = result.unwrap();
-        assert!(table.lookup("get_number").is_some());
+        assert!(result.is_err(), "undefined variable in del should fail");
+        let errors = result.unwrap_err();
+        assert_eq!(errors.len(), 1);
+        match &errors[0] {
+            SemanticError::UndefinedVariable { name, .. } => {
+                assert_eq!(name, "undefined_var");
+            }
+            _ => panic!("Expected UndefinedVariable error"),
+        }
     }
 
     #[test]
-    fn test_function_with_type_annotations() {
-        let code = "def add(a: int, b: int) -> int:\n    return a + b\n";
+    fn test_undefined_in_raise() {
+        // Undefined variable in raise statement
+        let code = "raise undefined_exception\n";
         let module = parse(code);
         let analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Function with type annotations should succeed");
-        
-        let table = result.unwrap();
-        assert!(table.lookup("add").is_some());
+        assert!(result.is_err(), "undefined variable in raise should fail");
+        let errors = result.unwrap_err();
+        assert_eq!(errors.len(), 1);
+        match &errors[0] {
+            SemanticError::UndefinedVariable { name, .. } => {
+                assert_eq!(name, "undefined_exception");
+            }
+            _ => panic!("Expected UndefinedVariable error"),
+        }
     }
 
-    #[test]
-    fn test_async_function() {
-        let code = "async def fetch_data():\n    pass\n";
+    // Suggestion Tests
+
+    /// Helper to get the suggestion attached to the first undefined-name error
+    fn first_suggestion(code: &str) -> Option<String> {
         let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Async function should succeed");
-        
-        let table = result.unwrap();
-        assert!(table.lookup("fetch_data").is_some());
+        let errors = SemanticAnalyzer::new().analyze(&module).unwrap_err();
+        match &errors[0] {
+            SemanticError::UndefinedVariable { suggestion, .. } => suggestion.clone(),
+            other => panic!("Expected UndefinedVariable error, got {:?}", other),
+        }
     }
 
     #[test]
-    fn test_function_variable_scoping() {
-        let code = "x = 10\ndef foo():\n    x = 20\n    pass\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Variable shadowing in function should succeed");
-        
-        let table = result.unwrap();
-        let module_x = table.lookup("x");
-        assert!(module_x.is_some(), "Module-level x should exist");
-        // Function's x is in a different scope
+    fn test_suggest_local_variable() {
+        let code = "counter = 0\nprint(countr)\n";
+        assert_eq!(first_suggestion(code), Some("counter".to_string()));
     }
 
     #[test]
-    fn test_function_and_variable_different_names() {
-        let code = "x = 10\ndef foo():\n    pass\ny = 20\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Functions and variables with different names should succeed");
-        
-        let table = result.unwrap();
-        assert!(table.lookup("x").is_some());
-        assert!(table.lookup("foo").is_some());
-        assert!(table.lookup("y").is_some());
+    fn test_suggest_builtin() {
+        assert_eq!(first_suggestion("pritn(1)\n"), Some("print".to_string()));
     }
 
     #[test]
-    fn test_function_with_default_parameters() {
-        let code = "def greet(name, greeting=\"Hello\"):\n    pass\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Function with default parameters should succeed");
-        
-        let table = result.unwrap();
-        assert!(table.lookup("greet").is_some());
+    fn test_suggest_keyword_case() {
+        assert_eq!(first_suggestion("x = true\n"), Some("True".to_string()));
     }
 
     #[test]
-    fn test_function_with_decorators() {
-        let code = "@decorator\ndef foo():\n    pass\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Function with decorator should succeed");
-        
-        let table = result.unwrap();
-        assert!(table.lookup("foo").is_some());
+    fn test_suggest_from_enclosing_function() {
+        let code = "def outer():\n    total = 0\n    def inner():\n        return totl\n    return inner\n";
+        assert_eq!(first_suggestion(code), Some("total".to_string()));
     }
 
     #[test]
-    fn test_empty_function() {
-        let code = "def empty():\n    pass\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Empty function should succeed");
-        
-        let table = result.unwrap();
-        assert!(table.lookup("empty").is_some());
+    fn test_suggest_self_attribute() {
+        let code = "class Counter:\n    def __init__(self):\n        self.count = 0\n    def get(self):\n        return count\n";
+        assert_eq!(first_suggestion(code), Some("self.count".to_string()));
     }
 
-    // Variable Usage Detection Tests
+    #[test]
+    fn test_suggest_member_through_receiver() {
+        // Outside a method there is no receiver to name a member through
+        let code = "class A:\n    n = 2\n    ys = [x * n for x in range(3)]\n";
+        assert_eq!(first_suggestion(code), None);
+        let code = "class A:\n    n = 2\n    @staticmethod\n    def f():\n        return n\n";
+        assert_eq!(first_suggestion(code), None);
+        // The receiver's own name, also from a nested function
+        let code = "class A:\n    n = 2\n    def f(this):\n        def g():\n            return n\n        return g\n";
+        assert_eq!(first_suggestion(code), Some("this.n".to_string()));
+        let code = "class A:\n    n = 2\n    @classmethod\n    def f(cls):\n        return n\n";
+        assert_eq!(first_suggestion(code), Some("cls.n".to_string()));
+        // A class method reaches class attributes, not instance ones
+        let code = "class A:\n    def __init__(self):\n        self.n = 2\n    @classmethod\n    def f(cls):\n        return n\n";
+        assert_eq!(first_suggestion(code), None);
+    }
 
     #[test]
-    fn test_undefined_variable_in_expression() {
-        let module = parse("y = x + 1");
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "Using undefined variable should fail");
-        
-        let errors = result.unwrap_err();
+    fn test_suggest_misspelled_method() {
+        let code = "class A:\n    def reset(self):\n        pass\n    def run(self):\n        rest()\n";
+        assert_eq!(first_suggestion(code), Some("self.reset".to_string()));
+    }
+
+    #[test]
+    fn test_no_suggestion_for_unrelated_name() {
+        assert_eq!(first_suggestion("print(zzzzzz)\n"), None);
+    }
+
+    #[test]
+    fn test_suggestion_in_diagnostic_help() {
+        let module = parse("pritn(1)\n");
+        let errors = SemanticAnalyzer::new().analyze(&module).unwrap_err();
+        let diagnostic = errors[0].to_diagnostic();
+        assert_eq!(diagnostic.help.as_deref(), Some("Did you mean 'print'?"));
+    }
+
+    // LEGB Scoping Tests
+
+    #[test]
+    fn test_function_calls_later_module_function() {
+        let code = "def main():\n    return helper()\ndef helper():\n    return 1\n";
+        assert!(SemanticAnalyzer::new().analyze(&parse(code)).is_ok());
+    }
+
+    #[test]
+    fn test_if_branch_binding_visible_after() {
+        let code = "def f(flag):\n    if flag:\n        value = 1\n    else:\n        value = 2\n    return value\n";
+        assert!(SemanticAnalyzer::new().analyze(&parse(code)).is_ok());
+    }
+
+    #[test]
+    fn test_class_variable_not_visible_in_method() {
+        let code = "class A:\n    limit = 10\n    def check(self):\n        return limit\n";
+        let errors = SemanticAnalyzer::new().analyze(&parse(code)).unwrap_err();
         assert_eq!(errors.len(), 1);
         match &errors[0] {
-            SemanticError::UndefinedVariable { name, .. } => {
-                assert_eq!(name, "x");
+            SemanticError::UndefinedVariable { name, suggestion, .. } => {
+                assert_eq!(name, "limit");
+                assert_eq!(suggestion.as_deref(), Some("self.limit"));
             }
-            _ => panic!("Expected UndefinedVariable error"),
+            other => panic!("Expected UndefinedVariable error, got {:?}", other),
         }
     }
 
     #[test]
-    fn test_variable_used_after_definition() {
-        let module = parse("x = 10\ny = x + 5");
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Using defined variable should succeed");
+    fn test_local_read_before_assignment() {
+        let code = "count = 0\ndef bump():\n    print(count)\n    count = count + 1\n";
+        let errors = SemanticAnalyzer::new().analyze(&parse(code)).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        match &errors[0] {
+            SemanticError::UnboundLocal { name, position, binding } => {
+                assert_eq!(name, "count");
+                assert_eq!((position.line, position.column), (3, 11));
+                assert_eq!((binding.line, binding.column), (4, 5));
+            }
+            other => panic!("Expected UnboundLocal error, got {:?}", other),
+        }
+        assert_eq!(errors[0].code(), ErrorCode::UnboundLocal);
+        assert_eq!(errors[0].to_diagnostic().labels.len(), 1);
     }
 
     #[test]
-    fn test_multiple_undefined_variables() {
-        let module = parse("result = a + b + c");
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "Multiple undefined variables should fail");
-        
-        let errors = result.unwrap_err();
-        assert_eq!(errors.len(), 3);
+    fn test_augmented_assignment_makes_name_local() {
+        let code = "total = 0\ndef add(n):\n    total += n\n";
+        let errors = SemanticAnalyzer::new().analyze(&parse(code)).unwrap_err();
+        assert!(matches!(&errors[..], [SemanticError::UnboundLocal { name, .. }] if name == "total"));
+    }
+
+    #[test]
+    fn test_global_read_when_not_assigned_locally() {
+        let code = "count = 0\ndef show():\n    print(count)\ndef bump():\n    global count\n    print(count)\n    count = count + 1\n";
+        assert!(SemanticAnalyzer::new().analyze(&parse(code)).is_ok());
+    }
+
+    #[test]
+    fn test_loop_may_assign_before_read() {
+        // An earlier iteration may have assigned `last`: a warning, not an error
+        let code = "def f(items):\n    for item in items:\n        if item:\n            print(last)\n        last = item\n";
+        assert!(SemanticAnalyzer::new().analyze(&parse(code)).is_ok());
+        let (_, problems) = SemanticAnalyzer::new().diagnose(&parse(code));
+        assert!(matches!(&problems[..], [SemanticError::PossiblyUnbound { name, .. }] if name == "last"));
+    }
+
+    #[test]
+    fn test_nonlocal_before_outer_assignment() {
+        let code = "def outer():\n    def inner():\n        nonlocal x\n        x = 2\n    x = 1\n    return inner\n";
+        assert!(SemanticAnalyzer::new().analyze(&parse(code)).is_ok());
+    }
+
+    #[test]
+    fn test_module_level_read_before_assignment_is_undefined() {
+        let code = "print(x)\nx = 1\n";
+        let errors = SemanticAnalyzer::new().analyze(&parse(code)).unwrap_err();
+        assert!(matches!(&errors[..], [SemanticError::UndefinedVariable { name, .. }] if name == "x"));
+    }
+
+    #[test]
+    fn test_import_binds_name() {
+        let code = "import os.path\nfrom math import sqrt as root\ndef f():\n    return [os, root]\n";
+        assert!(SemanticAnalyzer::new().analyze(&parse(code)).is_ok());
+    }
+
+    // Lambda and Comprehension Scope Tests
+
+    /// Helper to get the names reported as undefined
+    fn undefined_names(code: &str) -> Vec<String> {
+        match SemanticAnalyzer::new().analyze(&parse(code)) {
+            Ok(_) => Vec::new(),
+            Err(errors) => errors
+                .into_iter()
+                .filter_map(|error| match error {
+                    SemanticError::UndefinedVariable { name, .. } => Some(name),
+                    _ => None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_lambda_parameters_in_scope() {
+        assert!(undefined_names("f = lambda x, y: x + y\n").is_empty());
+        assert_eq!(undefined_names("f = lambda x: x + z\n"), vec!["z"]);
+    }
+
+    #[test]
+    fn test_lambda_parameters_do_not_leak() {
+        assert_eq!(undefined_names("f = lambda x: x\nprint(x)\n"), vec!["x"]);
     }
 
     #[test]
-    fn test_undefined_in_function_call() {
-        let module = parse("result = foo(x, y)");
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "Undefined variables in call should fail");
-        
-        let errors = result.unwrap_err();
-        assert!(errors.len() >= 2); // foo, x, y all undefined
+    fn test_lambda_duplicate_parameter() {
+        let errors = SemanticAnalyzer::new().analyze(&parse("f = lambda a, a: a\n")).unwrap_err();
+        assert!(matches!(&errors[..], [SemanticError::Redeclaration { name, .. }] if name == "a"));
     }
 
     #[test]
-    fn test_undefined_in_subscript() {
-        let module = parse("value = arr[idx]");
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "Undefined variables in subscript should fail");
-        
-        let errors = result.unwrap_err();
-        assert_eq!(errors.len(), 2); // arr and idx
+    fn test_lambda_sees_later_module_binding() {
+        assert!(undefined_names("f = lambda: g()\ndef g():\n    return 1\n").is_empty());
     }
 
     #[test]
-    fn test_undefined_in_attribute_access() {
-        let module = parse("value = obj.attr");
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "Undefined variable in attribute access should fail");
-        
-        let errors = result.unwrap_err();
-        assert_eq!(errors.len(), 1);
-        match &errors[0] {
-            SemanticError::UndefinedVariable { name, .. } => {
-                assert_eq!(name, "obj");
-            }
-            _ => panic!("Expected UndefinedVariable error"),
-        }
+    fn test_comprehension_body_checked() {
+        assert_eq!(undefined_names("xs = [1, 2]\nys = [f(y) for x in xs]\n"), vec!["f", "y"]);
     }
 
     #[test]
-    fn test_undefined_in_list_literal() {
-        let module = parse("items = [a, b, c]");
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "Undefined variables in list should fail");
-        
-        let errors = result.unwrap_err();
-        assert_eq!(errors.len(), 3);
+    fn test_comprehension_target_does_not_leak() {
+        assert_eq!(undefined_names("ys = [x for x in range(3)]\nprint(x)\n"), vec!["x"]);
     }
 
     #[test]
-    fn test_undefined_in_dict_literal() {
-        let module = parse("data = {k: v}");
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "Undefined variables in dict should fail");
-        
-        let errors = result.unwrap_err();
-        assert_eq!(errors.len(), 2); // k and v
+    fn test_all_comprehension_kinds() {
+        let code = "xs = [1, 2]\na = {x for x in xs if x}\nb = {x: x * 2 for x in xs}\nc = (x for x in xs)\nd = [y for x in xs for y in range(x)]\n";
+        assert!(undefined_names(code).is_empty());
     }
 
     #[test]
-    fn test_undefined_in_conditional_expression() {
-        let module = parse("result = x if condition else y");
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "Undefined variables in conditional should fail");
-        
-        let errors = result.unwrap_err();
-        assert_eq!(errors.len(), 3); // x, condition, y
+    fn test_comprehension_variables_in_symbol_table() {
+        let table = SemanticAnalyzer::new().analyze(&parse("ys = [x for x in range(3)]\n")).unwrap();
+        assert!(table.lookup("x").is_none());
+        let comprehension = table
+            .scopes()
+            .values()
+            .find(|scope| scope.kind == ScopeKind::Comprehension)
+            .expect("comprehension scope");
+        assert!(comprehension.contains("x"));
+        let parent = comprehension.parent.and_then(|parent| table.get_scope(parent)).unwrap();
+        assert_eq!(parent.kind, ScopeKind::Module);
     }
 
     #[test]
-    fn test_variable_in_function_scope() {
-        let code = "def foo():\n    x = 10\n    y = x + 5\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Variable defined in function scope should work");
+    fn test_first_iterator_evaluated_in_enclosing_scope() {
+        // Only the first iterable can see class-level names
+        let code = "class A:\n    xs = [1, 2]\n    ys = [x for x in xs]\n    zs = [x + len(xs) for x in xs]\n";
+        assert_eq!(undefined_names(code), vec!["xs"]);
     }
 
     #[test]
-    fn test_undefined_in_function_body() {
-        let code = "def foo():\n    y = x + 1\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "Undefined variable in function should fail");
-        
-        let errors = result.unwrap_err();
-        assert_eq!(errors.len(), 1);
-        match &errors[0] {
-            SemanticError::UndefinedVariable { name, .. } => {
-                assert_eq!(name, "x");
+    fn test_walrus_in_comprehension_binds_function_scope() {
+        let code = "def f(xs):\n    ys = [(last := x) for x in xs]\n    return last\n";
+        let table = SemanticAnalyzer::new().analyze(&parse(code)).unwrap();
+        let function = table
+            .scopes()
+            .values()
+            .find(|scope| scope.kind == ScopeKind::Function)
+            .unwrap();
+        assert!(function.contains("last"));
+    }
+
+    // Definite Assignment Tests
+
+    /// Helper to get every error and warning
+    fn problems(code: &str) -> Vec<SemanticError> {
+        SemanticAnalyzer::new().diagnose(&parse(code)).1
+    }
+
+    #[test]
+    fn test_possibly_unbound_after_if() {
+        let found = problems("def f(c):\n    if c:\n        y = 1\n    print(y)\n");
+        match &found[..] {
+            [warning @ SemanticError::PossiblyUnbound { name, position }] => {
+                assert_eq!(name, "y");
+                assert_eq!((position.line, position.column), (4, 11));
+                assert!(warning.is_warning());
+                let diagnostic = warning.to_diagnostic();
+                assert_eq!(diagnostic.severity, mamba_error::Severity::Warning);
+                assert_eq!(diagnostic.code, ErrorCode::PossiblyUnbound);
             }
-            _ => panic!("Expected UndefinedVariable error"),
+            other => panic!("Expected one PossiblyUnbound warning, got {:?}", other),
         }
     }
 
     #[test]
-    fn test_parameter_usage_in_function() {
-        let code = "def add(a, b):\n    return a + b\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Using parameters should succeed");
+    fn test_possibly_unbound_at_module_level() {
+        let found = problems("import sys\nif sys:\n    y = 1\nprint(y)\n");
+        assert!(matches!(&found[..], [SemanticError::PossiblyUnbound { name, .. }] if name == "y"));
     }
 
     #[test]
-    fn test_nested_scope_variable_access() {
-        let code = "x = 10\ndef foo():\n    y = x + 5\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Accessing outer scope variable should succeed");
+    fn test_bound_on_all_branches() {
+        assert!(problems("def f(c):\n    if c:\n        y = 1\n    elif c > 1:\n        y = 2\n    else:\n        y = 3\n    return y\n").is_empty());
     }
 
     #[test]
-    fn test_walrus_operator_declaration() {
-        let code = "if (n := len([1, 2, 3])) > 0:\n    pass\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Walrus operator should declare variable");
+    fn test_branch_that_returns_does_not_leave_unbound() {
+        assert!(problems("def f(c):\n    if c:\n        y = 1\n    else:\n        return 0\n    return y\n").is_empty());
     }
 
     #[test]
-    fn test_complex_expression_chain() {
-        let code = "x = 1\ny = 2\nz = (x + y) * (x - y) / (x * y)\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Complex expression with defined variables should succeed");
+    fn test_while_true_with_break_binds() {
+        assert!(problems("def f():\n    while True:\n        y = 1\n        if y:\n            break\n    return y\n").is_empty());
     }
 
     #[test]
-    fn test_undefined_in_nested_expression() {
-        let module = parse("result = ((a + b) * c) / d");
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "Nested undefined variables should fail");
-        
-        let errors = result.unwrap_err();
-        assert_eq!(errors.len(), 4); // a, b, c, d
+    fn test_for_loop_body_possibly_skipped() {
+        let found = problems("def f(xs):\n    for x in xs:\n        last = x\n    return last\n");
+        assert!(matches!(&found[..], [SemanticError::PossiblyUnbound { name, .. }] if name == "last"));
     }
 
-    // Redeclaration and Shadowing Tests
+    #[test]
+    fn test_for_else_without_break() {
+        // The else clause runs whenever the loop is not broken out of
+        assert!(problems("def f(xs):\n    for x in xs:\n        pass\n    else:\n        found = False\n    return found\n").is_empty());
+    }
 
     #[test]
-    fn test_shadowing_in_nested_function() {
-        let code = "x = 1\ndef outer():\n    x = 2\n    def inner():\n        x = 3\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Shadowing across nested scopes should succeed");
+    fn test_read_after_del_is_unbound() {
+        let found = problems("def f():\n    x = 1\n    del x\n    return x\n");
+        assert!(matches!(&found[..], [SemanticError::UnboundLocal { name, .. }] if name == "x"));
     }
 
     #[test]
-    fn test_parameter_shadows_outer_variable() {
-        let code = "x = 10\ndef foo(x):\n    return x * 2\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Parameter shadowing outer variable should succeed");
+    fn test_possibly_unbound_reported_once() {
+        let found = problems("def f(c):\n    if c:\n        y = 1\n    print(y)\n    print(y)\n");
+        assert_eq!(found.len(), 1);
     }
 
     #[test]
-    fn test_parameter_redeclaration_in_body() {
-        let code = "def foo(x):\n    x = 20\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "Redeclaring parameter in function body should fail");
-        
-        let errors = result.unwrap_err();
-        assert_eq!(errors.len(), 1);
-        match &errors[0] {
-            SemanticError::Redeclaration { name, .. } => {
-                assert_eq!(name, "x");
+    fn test_rebound_parameter_is_bound_on_entry() {
+        assert!(problems("def f(x, c):\n    if c:\n        x = 2\n    return x\n").is_empty());
+    }
+
+    // Statement Placement Tests
+
+    #[test]
+    fn test_break_outside_loop() {
+        let found = problems("x = 1\nbreak\n");
+        match &found[..] {
+            [error @ SemanticError::BreakOutsideLoop { position }] => {
+                assert_eq!((position.line, position.column), (2, 1));
+                assert_eq!(error.code(), ErrorCode::BreakOutsideLoop);
+                assert_eq!(error.to_diagnostic().span.unwrap().len, 5);
             }
-            _ => panic!("Expected Redeclaration error"),
+            other => panic!("Expected BreakOutsideLoop, got {:?}", other),
         }
     }
 
     #[test]
-    fn test_nested_function_shadows_parameter() {
-        let code = "def outer(x):\n    def inner():\n        x = 5\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Nested function can shadow outer parameter");
+    fn test_continue_outside_loop() {
+        let found = problems("def f():\n    continue\n");
+        assert!(matches!(&found[..], [SemanticError::ContinueOutsideLoop { .. }]));
     }
 
     #[test]
-    fn test_multiple_redeclarations_in_scope() {
-        let code = "x = 1\nx = 2\nx = 3\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "Multiple redeclarations should fail");
-        
-        let errors = result.unwrap_err();
-        assert_eq!(errors.len(), 2); // Two redeclaration errors
+    fn test_break_and_continue_in_loops() {
+        let code = "while True:\n    break\nfor i in range(3):\n    if i:\n        continue\n    while i:\n        break\n";
+        assert!(problems(code).is_empty());
     }
 
     #[test]
-    fn test_function_and_variable_name_conflict() {
-        let code = "x = 10\ndef x():\n    pass\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "Function with same name as variable should fail");
-        
-        let errors = result.unwrap_err();
-        assert_eq!(errors.len(), 1);
-        match &errors[0] {
-            SemanticError::Redeclaration { name, .. } => {
-                assert_eq!(name, "x");
-            }
-            _ => panic!("Expected Redeclaration error"),
-        }
+    fn test_break_in_loop_else_needs_outer_loop() {
+        assert!(matches!(
+            &problems("for i in range(3):\n    pass\nelse:\n    break\n")[..],
+            [SemanticError::BreakOutsideLoop { .. }]
+        ));
+        assert!(problems("while True:\n    for i in range(3):\n        pass\n    else:\n        break\n").is_empty());
     }
 
     #[test]
-    fn test_variable_after_function_same_name() {
-        let code = "def foo():\n    pass\nfoo = 10\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "Variable with same name as function should fail");
-        
-        let errors = result.unwrap_err();
-        assert_eq!(errors.len(), 1);
+    fn test_break_in_function_inside_loop() {
+        let found = problems("for i in range(3):\n    def f():\n        break\n");
+        assert!(matches!(&found[..], [SemanticError::BreakOutsideLoop { .. }]));
     }
 
     #[test]
-    fn test_shadowing_with_annotation() {
-        let code = "x = 10\ndef foo():\n    x: int = 20\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Shadowing with annotation should succeed");
+    fn test_return_outside_function() {
+        assert!(matches!(&problems("return 1\n")[..], [SemanticError::ReturnOutsideFunction { .. }]));
+        assert!(matches!(
+            &problems("class A:\n    return\n")[..],
+            [SemanticError::ReturnOutsideFunction { .. }]
+        ));
+        assert!(problems("class A:\n    def f(self):\n        return 1\n").is_empty());
     }
 
     #[test]
-    fn test_no_conflict_different_scopes() {
-        let code = "def foo():\n    x = 1\ndef bar():\n    x = 2\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Same variable name in different function scopes should succeed");
+    fn test_nonlocal_in_class_body() {
+        let found = problems("class A:\n    nonlocal x\n");
+        assert!(matches!(&found[..], [SemanticError::NonlocalInClassBody { name, .. }] if name == "x"));
+        // Inside a function the class body can refer to the function's locals
+        assert!(problems("def f():\n    x = 1\n    class A:\n        nonlocal x\n    return A\n").is_empty());
     }
 
-    #[test]
-    fn test_redeclaration_mixed_types() {
-        let code = "x = 10\nx: str = 'hello'\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "Redeclaration with different type should fail");
+    // Reachability Tests
+
+    /// Helper to get the (line, column) of every unreachable-code warning
+    fn unreachable_at(code: &str) -> Vec<(usize, usize)> {
+        problems(code)
+            .into_iter()
+            .filter_map(|problem| match problem {
+                SemanticError::UnreachableCode { position, .. } => Some((position.line, position.column)),
+                _ => None,
+            })
+            .collect()
     }
 
     #[test]
-    fn test_walrus_redeclaration() {
-        // Walrus operator allows reassignment of existing variables
-        let code = "x = 10\ny = (x := 20)\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        // Walrus operator on existing variable - in Python this is allowed (rebinds the variable)
-        assert!(result.is_ok(), "Walrus operator should allow reassignment");
+    fn test_unreachable_after_return() {
+        let code = "def f():\n    return 1\n    x = 2\n    print(x)\n";
+        assert_eq!(unreachable_at(code), vec![(3, 5)]);
+        let error = problems(code).remove(0);
+        assert!(error.is_warning());
+        assert_eq!(error.code(), ErrorCode::UnreachableCode);
     }
 
-    // ==================== Nested Scope Support Tests ====================
+    #[test]
+    fn test_unreachable_after_break_and_continue() {
+        let code = "for i in range(3):\n    if i:\n        break\n        print(i)\n    continue\n    print(i)\n";
+        assert_eq!(unreachable_at(code), vec![(4, 9), (6, 5)]);
+    }
 
     #[test]
-    fn test_if_statement_no_new_scope() {
-        // Variables declared in if blocks should be accessible outside
-        let code = "if True:\n    x = 10\nprint(x)\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "If statement should not create new scope");
+    fn test_unreachable_after_infinite_loop() {
+        assert_eq!(unreachable_at("while True:\n    pass\nprint(1)\n"), vec![(3, 1)]);
+        assert!(unreachable_at("while True:\n    break\nprint(1)\n").is_empty());
     }
 
     #[test]
-    fn test_while_statement_no_new_scope() {
-        // Variables declared in while blocks should be accessible outside
-        let code = "while False:\n    y = 20\nprint(y)\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "While statement should not create new scope");
+    fn test_dead_branch() {
+        let found = problems("if False:\n    print(1)\nwhile False:\n    print(2)\n");
+        let positions: Vec<_> = found
+            .iter()
+            .filter_map(|problem| match problem {
+                SemanticError::UnreachableCode { position, dead_condition: Some((condition, _)) } => {
+                    Some(((position.line, position.column), (condition.line, condition.column)))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(positions, vec![((2, 5), (1, 4)), ((4, 5), (3, 7))]);
     }
 
     #[test]
-    fn test_for_loop_variable_accessible() {
-        // For loop variable should be accessible after loop
-        let code = "for i in range(10):\n    pass\nprint(i)\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "For loop variable should persist after loop");
+    fn test_dead_branch_under_falsy_literals() {
+        let code = "if 0:\n    print(1)\nif '':\n    print(2)\nwhile None:\n    print(3)\nif (0.0):\n    print(4)\n";
+        let diagnostics: Vec<_> = problems(code).iter().map(SemanticError::to_diagnostic).collect();
+        let labels: Vec<_> = diagnostics
+            .iter()
+            .flat_map(|diagnostic| &diagnostic.labels)
+            .map(|label| (label.span.line, label.span.column, label.span.len))
+            .collect();
+        assert_eq!(labels, vec![(1, 4, 1), (3, 4, 2), (5, 7, 4), (7, 4, 5)]);
+        assert!(problems("if 1:\n    print(1)\nif 'a':\n    print(2)\n").is_empty());
     }
 
     #[test]
-    fn test_for_loop_with_unpacking() {
-        // For loop with tuple unpacking
-        let code = "for x, y in [(1, 2), (3, 4)]:\n    print(x, y)\nprint(x, y)\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "For loop unpacked variables should persist");
+    fn test_module_level_return_cuts_nothing_off() {
+        let found = problems("return\nx = 1\nprint(x)\n");
+        assert!(matches!(&found[..], [SemanticError::ReturnOutsideFunction { .. }]));
+        assert_eq!(unreachable_at("def f():\n    return\n    x = 1\n"), vec![(3, 5)]);
     }
 
     #[test]
-    fn test_nested_if_while_for() {
-        // Variables in deeply nested control flow should all be in same scope
-        let code = "if True:\n    a = 1\n    while True:\n        b = 2\n        for i in range(10):\n            c = 3\nprint(a)\nprint(b)\nprint(c)\nprint(i)\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "All variables in nested control flow should be accessible");
+    fn test_else_of_if_true_unreachable() {
+        assert_eq!(unreachable_at("if True:\n    x = 1\nelse:\n    x = 2\n"), vec![(4, 5)]);
     }
 
     #[test]
-    fn test_class_basic_scope() {
-        // Class should create its own scope
-        let code = "class MyClass:\n    x = 10\nprint(x)\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "Class scope should be isolated");
-        let errors = result.unwrap_err();
-        assert!(errors.iter().any(|e| matches!(e, SemanticError::UndefinedVariable { name, .. } if name == "x")));
+    fn test_missing_return() {
+        let code = "def sign(n: int) -> int:\n    if n > 0:\n        return 1\n    elif n < 0:\n        return -1\n";
+        let found = problems(code);
+        assert!(matches!(&found[..], [SemanticError::MissingReturn { name, .. }] if name == "sign"));
+        assert!(found[0].is_warning());
     }
 
     #[test]
-    fn test_class_declaration() {
-        // Class name should be accessible after declaration
-        let code = "class MyClass:\n    pass\nx = MyClass\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Class name should be accessible");
+    fn test_no_missing_return() {
+        // Every path returns or raises
+        assert!(problems("def f(n: int) -> int:\n    if n:\n        return 1\n    raise n\n").is_empty());
+        // None annotation, no annotation, and stub bodies
+        // Loops on a condition that is always true never fall through
+        assert!(problems("def f() -> int:\n    while 1:\n        pass\n").is_empty());
+        assert!(problems("def f() -> int:\n    while 'forever':\n        pass\n").is_empty());
+        assert!(problems("def f() -> None:\n    pass\n").is_empty());
+        assert!(problems("def f():\n    pass\n").is_empty());
+        assert!(problems("def f() -> int:\n    ...\n").is_empty());
+        // Infinite loop never falls off the end
+        assert!(problems("def f() -> int:\n    while True:\n        pass\n").is_empty());
+    }
+
+    fn call_problems(code: &str) -> Vec<String> {
+        problems(code).iter().map(|problem| problem.message()).collect()
     }
 
     #[test]
-    fn test_class_redeclaration() {
-        // Cannot redeclare class in same scope
-        let code = "class MyClass:\n    pass\nclass MyClass:\n    pass\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "Class redeclaration should fail");
-        let errors = result.unwrap_err();
-        assert!(errors.iter().any(|e| matches!(e, SemanticError::Redeclaration { name, .. } if name == "MyClass")));
+    fn test_call_arity() {
+        let def = "def area(width, height=1):\n    return width * height\n";
+        assert!(call_problems(&format!("{}area(2)\narea(2, 3)\narea(2, height=3)\n", def)).is_empty());
+        assert_eq!(
+            call_problems(&format!("{}area(2, 3, 4)\n", def)),
+            vec!["'area' takes 2 positional arguments but 3 were given"]
+        );
+        assert_eq!(
+            call_problems(&format!("{}area()\n", def)),
+            vec!["'area' is missing 1 required positional argument: 'width'"]
+        );
     }
 
     #[test]
-    fn test_nested_class_in_function() {
-        // Class inside function should have function as parent scope
-        let code = "def outer():\n    x = 10\n    class Inner:\n        y = x\n    return Inner\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Nested class should access function scope");
+    fn test_call_too_many_points_at_first_extra_argument() {
+        let found = problems("def f(a):\n    pass\nf(1, 2, 3)\n");
+        match &found[..] {
+            [error @ SemanticError::TooManyArguments { position, definition, .. }] => {
+                assert_eq!((position.line, position.column), (3, 6));
+                assert_eq!((definition.line, definition.column), (1, 1));
+                assert_eq!(error.code(), ErrorCode::TooManyArguments);
+            }
+            other => panic!("Expected one TooManyArguments, got {:?}", other),
+        }
     }
 
     #[test]
-    fn test_function_in_class() {
-        // Function inside class should create nested scopes
-        let code = "class MyClass:\n    def method(self):\n        x = 10\n        return x\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Method in class should work correctly");
+    fn test_call_keywords() {
+        let def = "def connect(host, port=80):\n    pass\n";
+        assert_eq!(
+            call_problems(&format!("{}connect('a', prot=1)\n", def)),
+            vec!["'connect' got an unexpected keyword argument 'prot'"]
+        );
+        assert_eq!(
+            call_problems(&format!("{}connect('a', host='b')\n", def)),
+            vec!["'connect' got multiple values for argument 'host'"]
+        );
+        assert_eq!(
+            call_problems(&format!("{}connect(host='a', host='b')\n", def)),
+            vec!["'connect' got multiple values for argument 'host'"]
+        );
+        let found = problems(&format!("{}connect('a', prot=1)\n", def));
+        assert!(matches!(&found[..], [SemanticError::UnexpectedKeyword { suggestion: Some(s), .. }] if s == "port"));
     }
 
     #[test]
-    fn test_deeply_nested_scopes() {
-        // Test deep nesting: module -> function -> class -> function -> if/for
-        // Note: This test validates scope isolation, not closure behavior (Task 9)
-        let code = "def outer_func():\n    a = 1\n    class InnerClass:\n        b = 2\n        def inner_method():\n            c = 3\n            if True:\n                d = 4\n                for i in range(10):\n                    e = 5\n                    print(c)\n                    print(d)\n                    print(e)\n                    print(i)\n    return InnerClass\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        // Variables c, d, e, i should all be accessible (if/for don't create scopes)
-        // Note: We removed reference to 'a' since closure analysis is Task 9
-        assert!(result.is_ok(), "Variables in nested control flow should be accessible");
+    fn test_call_keyword_only_and_positional_only() {
+        let def = "def clamp(value, /, low, *, high):\n    pass\n";
+        assert!(call_problems(&format!("{}clamp(5, 0, high=10)\nclamp(5, low=0, high=10)\n", def)).is_empty());
+        assert_eq!(
+            call_problems(&format!("{}clamp(5, 0)\n", def)),
+            vec!["'clamp' is missing 1 required keyword-only argument: 'high'"]
+        );
+        assert_eq!(
+            call_problems(&format!("{}clamp(value=5, low=0, high=10)\n", def)),
+            vec!["positional-only argument 'value' of 'clamp' passed by keyword"]
+        );
+        assert_eq!(
+            call_problems(&format!("{}clamp(5, 0, 10)\n", def)),
+            vec![
+                "'clamp' takes 2 positional arguments but 3 were given",
+                "'clamp' is missing 1 required keyword-only argument: 'high'",
+            ]
+        );
     }
 
     #[test]
-    fn test_if_elif_else_blocks() {
-        // All branches of if/elif/else should be in same scope
-        let code = "x = 1\nif x == 1:\n    y = 2\nelif x == 2:\n    z = 3\nelse:\n    w = 4\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "If/elif/else should not create scopes");
+    fn test_call_var_args_and_kwargs() {
+        let def = "def log(message, *args, **fields):\n    pass\n";
+        assert!(call_problems(&format!("{}log('m', 1, 2, level=3, message2=4)\n", def)).is_empty());
+        // A positional-only name passed by keyword lands in **kwargs
+        assert_eq!(
+            call_problems("def f(a, /, **kw):\n    pass\nf(a=1)\n"),
+            vec!["'f' is missing 1 required positional argument: 'a'"]
+        );
+        assert_eq!(
+            call_problems("def f(a, b, c):\n    pass\nf()\n"),
+            vec!["'f' is missing 3 required positional arguments: 'a', 'b' and 'c'"]
+        );
     }
 
     #[test]
-    fn test_while_with_else() {
-        // While with else block
-        let code = "while False:\n    a = 1\nelse:\n    b = 2\nprint(a)\nprint(b)\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "While-else should not create scopes");
+    fn test_call_checks_functions_defined_later() {
+        let code = "def main():\n    helper(1)\n\ndef helper(a, b):\n    pass\n";
+        assert_eq!(call_problems(code), vec!["'helper' is missing 1 required positional argument: 'b'"]);
     }
 
     #[test]
-    fn test_for_with_else() {
-        // For with else block
-        let code = "for i in []:\n    a = 1\nelse:\n    b = 2\nprint(i)\nprint(a)\nprint(b)\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "For-else should not create scopes");
+    fn test_call_checks_class_constructors() {
+        let classes = "\
+class Point:
+    def __init__(self, x, y=0):
+        self.x = x
+class Labeled(Point):
+    pass
+class Empty:
+    pass
+";
+        assert!(call_problems(&format!("{}Point(1)\nLabeled(1, y=2)\nEmpty()\n", classes)).is_empty());
+        assert_eq!(
+            call_problems(&format!("{}Point()\nLabeled(1, 2, 3)\nEmpty(1)\n", classes)),
+            vec![
+                "'Point' is missing 1 required positional argument: 'x'",
+                "'Labeled' takes 2 positional arguments but 3 were given",
+                "'Empty' takes 0 positional arguments but 1 was given",
+            ]
+        );
+        // Bases, metaclasses and `__new__` that may take other arguments
+        let unknown = "\
+class Error(Exception):
+    pass
+class Meta(type):
+    pass
+class Configured(metaclass=Meta):
+    def __init__(self):
+        pass
+class Cached:
+    def __new__(cls, *args):
+        return object.__new__(cls)
+    def __init__(self):
+        pass
+Error('message')
+Configured(1)
+Cached(1)
+";
+        assert!(problems(unknown).is_empty());
     }
 
-    // ==================== Closure Tracking & Global/Nonlocal Tests ====================
-
     #[test]
-    fn test_global_at_module_level() {
-        // global at module level is allowed (though redundant)
-        let code = "global x\nx = 10\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "global at module level should be allowed");
+    fn test_call_not_checked_when_not_static() {
+        // Unpacked arguments
+        assert!(problems("def f(a):\n    pass\nf(*[1, 2])\nf(**{})\n").is_empty());
+        // Rebound, redefined or decorated functions
+        assert!(problems("def f(a):\n    pass\nf = print\nf(1, 2)\n").is_empty());
+        assert!(problems("def f(a):\n    pass\ndef f(a, b):\n    pass\nf(1, 2)\n").is_empty());
+        assert!(problems("def deco(fn):\n    return fn\n@deco\ndef f(a):\n    pass\nf(1, 2)\n").is_empty());
+        assert!(problems("def f(a):\n    pass\ndef g():\n    global f\n    f = print\nf(1, 2)\n").is_empty());
+        // A local of the same name shadows the outer function
+        assert!(problems("def f(a):\n    pass\ndef g(f):\n    f(1, 2)\n").is_empty());
+        // Builtins with overloads have no recorded signature
+        assert!(problems("max(1, 2, key=abs)\nmax([1, 2])\n").is_empty());
     }
 
+    // Builtin Namespace Tests
+
     #[test]
-    fn test_global_in_function() {
-        // global in function allows modifying module-level variable
-        let code = "x = 10\ndef func():\n    global x\n    x = 20\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "global declaration in function should work");
+    fn test_builtins_defined() {
+        let code = "for i, pair in enumerate(zip([1], [2])):\n    print(isinstance(pair, tuple), sorted(pair, reverse=True))\n\
+                    if __name__ == '__main__':\n    raise ValueError('bad')\nerrors = (KeyError, Exception, NotImplemented)\n";
+        assert!(problems(code).is_empty());
     }
 
     #[test]
-    fn test_global_multiple_names() {
-        // global can declare multiple names
-        let code = "def func():\n    global x, y, z\n    x = 1\n    y = 2\n    z = 3\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "global with multiple names should work");
+    fn test_builtins_can_be_shadowed() {
+        let code = "def len(x, y):\n    return 0\nprint = 3\nclass ValueError:\n    pass\nlen(1, 2)\n";
+        assert!(problems(code).is_empty());
+        let table = SemanticAnalyzer::new().analyze(&parse(code)).unwrap();
+        assert_eq!(table.lookup("print").unwrap().kind, SymbolKind::Variable);
+        assert_eq!(table.lookup("ValueError").unwrap().scope_id, table.current_scope_id());
     }
 
     #[test]
-    fn test_global_after_local_declaration() {
-        // Cannot use global after local declaration
-        let code = "def func():\n    x = 10\n    global x\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "global after local declaration should fail");
-        let errors = result.unwrap_err();
-        assert!(errors.iter().any(|e| matches!(e, SemanticError::Redeclaration { name, .. } if name == "x")));
+    fn test_builtin_read_before_module_shadowing() {
+        // Until the module binds the name, reads fall through to the builtin
+        assert!(problems("print(1)\nprint = 3\n").is_empty());
+        // A function-local binding makes the name local throughout the function
+        assert!(matches!(
+            &problems("def f(x):\n    n = len(x)\n    len = 2\n")[..],
+            [SemanticError::UnboundLocal { name, .. }] if name == "len"
+        ));
     }
 
     #[test]
-    fn test_nonlocal_at_module_level() {
-        // nonlocal at module level is an error
-        let code = "nonlocal x\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "nonlocal at module level should fail");
-        let errors = result.unwrap_err();
-        assert!(errors.iter().any(|e| matches!(e, SemanticError::NonlocalAtModuleLevel { name, .. } if name == "x")));
+    fn test_builtin_calls_checked() {
+        assert_eq!(call_problems("len([1], [2])\n"), vec!["'len' takes 1 positional argument but 2 were given"]);
+        assert_eq!(
+            call_problems("sorted([1], True)\n"),
+            vec!["'sorted' takes 1 positional argument but 2 were given"]
+        );
+        assert_eq!(
+            call_problems("isinstance(obj=1, class_or_tuple=int)\n"),
+            vec![
+                "positional-only argument 'obj' of 'isinstance' passed by keyword",
+                "positional-only argument 'class_or_tuple' of 'isinstance' passed by keyword",
+            ]
+        );
+        assert!(call_problems("print(1, 2, sep='')\nrange(3)\nrange(1, 10, 2)\nenumerate([1], start=1)\n").is_empty());
     }
 
+    // Class Member Model Tests
+
     #[test]
-    fn test_nonlocal_in_nested_function() {
-        // nonlocal in nested function accesses outer function variable
-        let code = "def outer():\n    x = 10\n    def inner():\n        nonlocal x\n        x = 20\n    return inner\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "nonlocal in nested function should work");
+    fn test_unknown_self_attribute() {
+        let code = "class Point:\n    def __init__(self):\n        self.x = 0\n    def norm(self):\n        return self.x + self.xx + self.y\n";
+        let found = problems(code);
+        let attributes: Vec<_> = found
+            .iter()
+            .filter_map(|problem| match problem {
+                SemanticError::UnknownAttribute { attribute, position, suggestion, .. } => {
+                    Some((attribute.as_str(), (position.line, position.column), suggestion.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(attributes, vec![("xx", (5, 25), Some("x".to_string())), ("y", (5, 35), None)]);
+        assert_eq!(found[0].message(), "'Point' object has no attribute 'xx'");
     }
 
     #[test]
-    fn test_nonlocal_not_found() {
-        // nonlocal variable must exist in enclosing scope
-        let code = "def outer():\n    def inner():\n        nonlocal x\n        x = 10\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "nonlocal without enclosing binding should fail");
-        let errors = result.unwrap_err();
-        assert!(errors.iter().any(|e| matches!(e, SemanticError::NonlocalNotFound { name, .. } if name == "x")));
+    fn test_known_self_attributes() {
+        // Class-level names, methods, attributes assigned in any method, slots,
+        // inherited members and dunders
+        let code = "class Base:\n    kind = 'base'\n    def describe(self):\n        return self.kind\n\
+                    class Child(Base):\n    __slots__ = ('extra',)\n    def run(self):\n        self.later()\n        return self.describe() + self.name + self.extra + self.__class__.__name__\n\
+                    \x20   def later(self):\n        self.name, self.size = 'c', 1\n";
+        assert!(problems(code).is_empty(), "{:?}", problems(code));
+        let code = "class E(Exception):\n    def show(self):\n        return self.args\n";
+        assert!(problems(code).is_empty());
     }
 
     #[test]
-    fn test_nonlocal_skips_module_scope() {
-        // nonlocal should not find variables in module scope
-        let code = "x = 10\ndef func():\n    nonlocal x\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "nonlocal should not find module-level variables");
-        let errors = result.unwrap_err();
-        assert!(errors.iter().any(|e| matches!(e, SemanticError::NonlocalNotFound { name, .. } if name == "x")));
+    fn test_self_attributes_not_checked_for_open_classes() {
+        let open = [
+            "import mod\nclass A(mod.Base):\n    def f(self):\n        return self.anything\n",
+            "class A(dict):\n    def f(self):\n        return self.anything\n",
+            "class A:\n    def __getattr__(self, name):\n        return 1\n    def f(self):\n        return self.anything\n",
+            "class A:\n    def __init__(self):\n        setattr(self, 'anything', 1)\n    def f(self):\n        return self.anything\n",
+            "class B:\n    def __getattr__(self, name):\n        return 1\nclass A(B):\n    def f(self):\n        return self.anything\n",
+        ];
+        for code in open {
+            assert!(problems(code).is_empty(), "{}", code);
+        }
+        // Not a method's `self`: static methods, rebound `self`, nested functions
+        let not_self = [
+            "class A:\n    @staticmethod\n    def f(self):\n        return self.anything\n",
+            "class A:\n    def f(self, other):\n        self = other\n        return self.anything\n",
+            "class A:\n    def f(self):\n        def g(self):\n            return self.anything\n        return g\n",
+        ];
+        for code in not_self {
+            assert!(problems(code).is_empty(), "{}", code);
+        }
     }
 
     #[test]
-    fn test_nonlocal_multiple_names() {
-        // nonlocal can declare multiple names
-        let code = "def outer():\n    x = 1\n    y = 2\n    def inner():\n        nonlocal x, y\n        x = 10\n        y = 20\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "nonlocal with multiple names should work");
+    fn test_inconsistent_mro() {
+        let code = "class A:\n    pass\nclass B(A):\n    pass\nclass C(A, B):\n    pass\n";
+        let found = problems(code);
+        assert!(matches!(&found[..], [SemanticError::InconsistentMro { class, bases, .. }] if class == "C" && bases == &["A", "B"]));
+        assert_eq!(found[0].code(), ErrorCode::InconsistentMro);
+        // Diamonds and unknown bases are fine
+        let diamond = "class A:\n    pass\nclass B(A):\n    pass\nclass C(A):\n    pass\nclass D(B, C):\n    pass\n";
+        assert!(problems(diamond).is_empty());
+        assert!(problems("import m\nclass A:\n    pass\nclass C(A, m.B):\n    pass\n").is_empty());
     }
 
     #[test]
-    fn test_nonlocal_after_local_declaration() {
-        // Cannot use nonlocal after local declaration
-        let code = "def outer():\n    x = 10\n    def inner():\n        x = 5\n        nonlocal x\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "nonlocal after local declaration should fail");
-        let errors = result.unwrap_err();
-        assert!(errors.iter().any(|e| matches!(e, SemanticError::Redeclaration { name, .. } if name == "x")));
+    fn test_class_info_on_symbol() {
+        let code = "class Meta(type):\n    pass\nclass A:\n    pass\nclass B(A, metaclass=Meta):\n    @property\n    def size(self):\n        return 1\n    @classmethod\n    def make(cls):\n        return cls()\n    @staticmethod\n    def helper():\n        return 2\n";
+        let table = SemanticAnalyzer::new().analyze(&parse(code)).unwrap();
+        let info = table.lookup("B").and_then(|symbol| symbol.class_info.as_ref()).unwrap();
+        assert_eq!(info.mro.as_deref().unwrap(), ["B", "A", "object"]);
+        assert_eq!(info.metaclass.as_deref(), Some("Meta"));
+        assert_eq!(info.method("size").unwrap().kind, MethodKind::Property);
+        assert_eq!(info.method("make").unwrap().kind, MethodKind::Class);
+        assert_eq!(info.method("helper").unwrap().kind, MethodKind::Static);
+        let meta = table.lookup("Meta").and_then(|symbol| symbol.class_info.as_ref()).unwrap();
+        assert_eq!(meta.mro.as_deref().unwrap(), ["Meta", "type", "object"]);
     }
 
     #[test]
-    fn test_closure_basic() {
-        // Basic closure - inner function references outer variable
-        let code = "def outer():\n    x = 10\n    def inner():\n        print(x)\n    return inner\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Basic closure should work");
+    fn test_missing_self() {
+        let code = "class Counter:\n    def reset():\n        pass\n    @classmethod\n    def make():\n        pass\n";
+        let messages: Vec<String> = problems(code).iter().map(|problem| problem.message()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "method 'reset' of class 'Counter' has no 'self' parameter",
+                "method 'make' of class 'Counter' has no 'cls' parameter",
+            ]
+        );
+        // Static methods, *args, and methods behind unknown decorators are fine
+        let fine = "def deco(fn):\n    return fn\nclass A:\n    @staticmethod\n    def s():\n        pass\n    def v(*args):\n        pass\n    @deco\n    def d():\n        pass\n";
+        assert!(problems(fine).is_empty());
+        assert!(matches!(
+            &problems("class A:\n    def f(*, key):\n        pass\n")[..],
+            [SemanticError::MissingSelf { .. }]
+        ));
     }
 
     #[test]
-    fn test_closure_multiple_levels() {
-        // Multi-level closure
-        let code = "def level1():\n    x = 1\n    def level2():\n        y = 2\n        def level3():\n            print(x, y)\n        return level3\n    return level2\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "Multi-level closure should work");
+    fn test_class_bases_are_resolved() {
+        assert_eq!(undefined_names("class A(Missing):\n    pass\n"), vec!["Missing"]);
+        assert_eq!(undefined_names("class A(metaclass=Meta):\n    pass\n"), vec!["Meta"]);
+    }
+
+    // Annotation Tests
+
+    fn declared_types(code: &str) -> (SymbolTable, Vec<String>) {
+        let (table, problems) = SemanticAnalyzer::new().diagnose(&parse(code));
+        (table, problems.iter().map(|problem| problem.message()).collect())
+    }
+
+    fn declared_type(table: &SymbolTable, name: &str) -> String {
+        table.lookup(name).and_then(|symbol| symbol.declared_type.as_ref()).unwrap().to_string()
     }
 
     #[test]
-    fn test_global_and_nonlocal_different_vars() {
-        // Can use global and nonlocal for different variables
-        let code = "x = 1\ndef outer():\n    y = 2\n    def inner():\n        global x\n        nonlocal y\n        x = 10\n        y = 20\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "global and nonlocal for different vars should work");
+    fn test_annotations_resolved_on_symbols_and_signatures() {
+        let code = "from typing import Optional, TypeVar\nT = TypeVar('T')\n\
+                    scores: dict[str, list[int]] = {}\nbest: Optional['Player'] = None\n\
+                    def pick(items: list[T], *rest: int, **named: str) -> T | None:\n    return None\n\
+                    class Player:\n    pass\n";
+        let (table, problems) = declared_types(code);
+        assert!(problems.is_empty(), "{:?}", problems);
+        assert_eq!(declared_type(&table, "scores"), "dict[str, list[int]]");
+        assert_eq!(declared_type(&table, "best"), "Player | None");
+
+        let signature = table.lookup("pick").and_then(|symbol| symbol.signature.as_ref()).unwrap();
+        let annotations: Vec<String> =
+            signature.parameters.iter().map(|param| param.annotation.as_ref().unwrap().to_string()).collect();
+        assert_eq!(annotations, vec!["list[T]", "int", "str"]);
+        assert_eq!(signature.returns.as_ref().unwrap().to_string(), "T | None");
     }
 
     #[test]
-    fn test_nonlocal_finds_nearest_enclosing() {
-        // nonlocal should find variable in nearest enclosing function scope
-        let code = "def outer():\n    x = 1\n    def middle():\n        x = 2\n        def inner():\n            nonlocal x\n            x = 3\n        return inner\n    return middle\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "nonlocal should find nearest enclosing scope");
+    fn test_type_vars_and_typing_module() {
+        let code = "import typing as t\nfrom typing import TypeVar\nT = TypeVar('T')\n\
+                    def first(items: t.List[T]) -> t.Optional[T]:\n    return None\n";
+        let (table, problems) = declared_types(code);
+        assert!(problems.is_empty(), "{:?}", problems);
+        let signature = table.lookup("first").and_then(|symbol| symbol.signature.as_ref()).unwrap();
+        assert_eq!(signature.parameters[0].annotation.as_ref().unwrap().to_string(), "list[T]");
+        assert_eq!(signature.returns.as_ref().unwrap().to_string(), "T | None");
     }
 
     #[test]
-    fn test_nonlocal_in_class() {
-        // nonlocal in class method
-        let code = "def outer():\n    x = 10\n    class Inner:\n        def method(self):\n            nonlocal x\n            x = 20\n    return Inner\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_ok(), "nonlocal in class method should work");
+    fn test_generic_definitions() {
+        let code = "from typing import Generic, Protocol, TypeVar\nT = TypeVar('T', bound='Missing')\n\
+                    class Box(Generic[T]):\n    pass\nclass Pair[K, V: int](dict[K, V]):\n    def get(self, key: K) -> V:\n        pass\n\
+                    class Sized(Protocol):\n    def size(self) -> int:\n        ...\n\
+                    def first[U](items: list[U]) -> U:\n    pass\n";
+        let (table, problems) = declared_types(code);
+        assert_eq!(problems, vec!["unknown type 'Missing'"]);
+        let class = |name| table.lookup(name).and_then(|symbol| symbol.class_info.as_deref()).unwrap();
+        assert_eq!(class("Box").type_params, ["T"]);
+        assert_eq!(class("Box").bases, Vec::<String>::new());
+        assert!(!class("Box").open);
+        assert_eq!(class("Pair").type_params, ["K", "V"]);
+        assert!(class("Sized").protocol);
+
+        let signature = table.lookup("first").and_then(|symbol| symbol.signature.as_ref()).unwrap();
+        assert_eq!(signature.returns.as_ref().unwrap().to_string(), "U");
+        let Some(Type::TypeVar(variable)) = table
+            .scopes()
+            .values()
+            .find_map(|scope| scope.lookup("get"))
+            .and_then(|symbol| symbol.signature.as_ref())
+            .and_then(|signature| signature.returns.clone())
+        else {
+            panic!("expected a type variable");
+        };
+        assert_eq!(variable.bound.map(|bound| bound.to_string()).as_deref(), Some("int"));
     }
 
     #[test]
-    fn test_undefined_in_return() {
-        // Undefined variable in return statement
-        let code = "def func():\n    return undefined_var\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "undefined variable in return should fail");
-        let errors = result.unwrap_err();
-        assert_eq!(errors.len(), 1);
-        match &errors[0] {
-            SemanticError::UndefinedVariable { name, .. } => {
-                assert_eq!(name, "undefined_var");
-            }
-            _ => panic!("Expected UndefinedVariable error"),
-        }
+    fn test_parameter_declared_types() {
+        let code = "def f(*args: int, **kwargs: float):\n    pass\n";
+        let (table, _) = declared_types(code);
+        let body = table.scopes().values().find(|scope| scope.kind == ScopeKind::Function).unwrap();
+        let declared = |name| body.lookup(name).unwrap().declared_type.as_ref().unwrap().to_string();
+        assert_eq!(declared("args"), "tuple[int, ...]");
+        assert_eq!(declared("kwargs"), "dict[str, float]");
     }
 
     #[test]
-    fn test_undefined_in_assert() {
-        // Undefined variable in assert condition
-        let code = "assert undefined_condition\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "undefined variable in assert should fail");
-        let errors = result.unwrap_err();
-        assert_eq!(errors.len(), 1);
-        match &errors[0] {
-            SemanticError::UndefinedVariable { name, .. } => {
-                assert_eq!(name, "undefined_condition");
-            }
-            _ => panic!("Expected UndefinedVariable error"),
-        }
+    fn test_invalid_annotations() {
+        let (_, problems) = declared_types(
+            "def helper():\n    pass\npairs: list[int, str] = []\ndef f(x: helper, y: 'Missing') -> Strr:\n    pass\n",
+        );
+        assert_eq!(
+            problems,
+            vec![
+                "'list' expects 1 type argument but 2 were given",
+                "unknown type 'Strr'",
+                "'helper' is a function, not a type",
+                "unknown type 'Missing'",
+            ]
+        );
     }
 
     #[test]
-    fn test_undefined_in_assert_message() {
-        // Undefined variable in assert message
-        let code = "assert True, undefined_message\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "undefined variable in assert message should fail");
-        let errors = result.unwrap_err();
-        assert_eq!(errors.len(), 1);
-        match &errors[0] {
-            SemanticError::UndefinedVariable { name, .. } => {
-                assert_eq!(name, "undefined_message");
-            }
-            _ => panic!("Expected UndefinedVariable error"),
-        }
+    fn test_dataclass_fields() {
+        let code = "import dataclasses\nfrom dataclasses import dataclass, field, KW_ONLY\n\
+                    from typing import ClassVar\n\
+                    @dataclass\nclass Point:\n    x: int\n    y: int = 0\n    origin: ClassVar['Point']\n\
+                    @dataclasses.dataclass(frozen=True, order=True)\nclass Labeled(Point):\n    \
+                    tags: list[str] = field(default_factory=list)\n    hidden: int = field(init=False)\n    \
+                    _: KW_ONLY\n    label: str\n\
+                    Labeled(1, 2, [], label='a')\nPoint(1, 2, 3)\nPoint(y=1)\n";
+        let (table, problems) = declared_types(code);
+        assert_eq!(
+            problems,
+            vec![
+                "'Point' takes 2 positional arguments but 3 were given",
+                "'Point' is missing 1 required positional argument: 'x'",
+            ]
+        );
+        let class = |name| table.lookup(name).and_then(|symbol| symbol.class_info.as_deref()).unwrap();
+        let labeled = class("Labeled");
+        let Some(Synthesized::Dataclass { fields, frozen: true, order: true, .. }) = &labeled.synthesized else {
+            panic!("Labeled is a frozen, ordered dataclass");
+        };
+        let names: Vec<&str> = fields.iter().map(|field| field.name.as_str()).collect();
+        assert_eq!(names, ["x", "y", "tags", "hidden", "label"]);
+
+        let init = class("Labeled").method("__init__").unwrap();
+        assert!(class("Labeled").is_synthesized(init));
+        let parameters: Vec<String> = init
+            .signature
+            .parameters
+            .iter()
+            .skip(1)
+            .map(|param| format!("{:?} {}: {}", param.kind, param.name, param.annotation.as_ref().unwrap()))
+            .collect();
+        assert_eq!(parameters, ["Regular x: int", "Regular y: int", "Regular tags: list[str]", "KwOnly label: str"]);
+        assert!(class("Labeled").has_member("__lt__"));
+        assert!(!class("Point").has_member("__lt__"));
     }
 
     #[test]
-    fn test_undefined_in_del() {
-        // Undefined variable in del statement
-        let code = "del undefined_var\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "undefined variable in del should fail");
-        let errors = result.unwrap_err();
-        assert_eq!(errors.len(), 1);
-        match &errors[0] {
-            SemanticError::UndefinedVariable { name, .. } => {
-                assert_eq!(name, "undefined_var");
-            }
-            _ => panic!("Expected UndefinedVariable error"),
-        }
+    fn test_dataclass_field_order() {
+        let code = "from dataclasses import dataclass, field\n\
+                    @dataclass\nclass Item:\n    price: float = 0.0\n    name: str\n\
+                    @dataclass(kw_only=True)\nclass Options:\n    verbose: bool = False\n    path: str\n\
+                    @dataclass\nclass Row:\n    index: int = 0\n    cells: list[str] = field(kw_only=True)\n\
+                    class Plain:\n    first: int = 0\n    second: int\n";
+        let errors = problems(code);
+        assert_eq!(
+            errors.iter().map(|problem| problem.message()).collect::<Vec<_>>(),
+            vec!["field 'name' of 'Item' has no default but follows 'price', which has one"]
+        );
+        assert_eq!(errors[0].code(), ErrorCode::FieldOrder);
     }
 
     #[test]
-    fn test_undefined_in_raise() {
-        // Undefined variable in raise statement
-        let code = "raise undefined_exception\n";
-        let module = parse(code);
-        let analyzer = SemanticAnalyzer::new();
-        let result = analyzer.analyze(&module);
-        assert!(result.is_err(), "undefined variable in raise should fail");
-        let errors = result.unwrap_err();
-        assert_eq!(errors.len(), 1);
-        match &errors[0] {
-            SemanticError::UndefinedVariable { name, .. } => {
-                assert_eq!(name, "undefined_exception");
-            }
-            _ => panic!("Expected UndefinedVariable error"),
-        }
+    fn test_enum_and_named_tuple_members() {
+        let code = "import enum\nfrom typing import NamedTuple\n\
+                    class Color(enum.Enum):\n    RED = 1\n    GREEN = enum.auto()\n    _ignore_ = []\n    \
+                    def describe(self):\n        return self.name\n\
+                    class Pair(NamedTuple):\n    left: int\n    right: str = ''\n\
+                    Pair(1, 'a', 2)\n";
+        let (table, problems) = declared_types(code);
+        assert_eq!(problems, vec!["'Pair' takes 2 positional arguments but 3 were given"]);
+        let class = |name| table.lookup(name).and_then(|symbol| symbol.class_info.as_deref()).unwrap();
+        assert_eq!(class("Color").synthesized, Some(Synthesized::Enum { members: vec!["RED".into(), "GREEN".into()] }));
+
+        let pair = class("Pair");
+        assert_eq!(pair.bases, ["tuple"]);
+        assert_eq!(pair.mro.as_deref().unwrap(), ["Pair", "tuple", "object"]);
+        assert!(!pair.open);
+        assert!(pair.has_member("_replace") && pair.has_member("count") && pair.has_member("left"));
+        assert!(pair.synthesized.as_ref().is_some_and(Synthesized::is_frozen));
     }
 }
-
@@ -0,0 +1,98 @@
+//! Type Stubs
+//!
+//! A stub (a `.mmbi` file) declares the names of a module and their types
+//! without implementing them, for modules that are native, written in
+//! Python, or otherwise not analyzed from source. Stubs are ordinary Mamba
+//! source: functions have `...` bodies and classes declare their attributes
+//! as annotations.
+//!
+//! ```text
+//! def hypot(x: float, y: float) -> float:
+//!     ...
+//!
+//! class Point:
+//!     x: float
+//!     y: float
+//!
+//!     def __init__(self, x: float, y: float) -> None:
+//!         ...
+//! ```
+//!
+//! The [`ModuleLoader`](crate::modules::ModuleLoader) prefers the stub of a
+//! module over its source, and names imported from a stub have the types it
+//! declares. A stub of the builtin functions is bundled with the parser and
+//! gives calls to builtins annotated signatures.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use crate::semantic::SemanticAnalyzer;
+use crate::symbol_table::{Signature, SymbolKind};
+
+/// The bundled stub of the builtin functions
+pub const BUILTINS: &str = include_str!("../stubs/builtins.mmbi");
+
+/// Annotated signatures of the builtin functions declared by the bundled
+/// stub, by name
+pub fn builtin_signatures() -> &'static HashMap<String, Signature> {
+    static SIGNATURES: OnceLock<HashMap<String, Signature>> = OnceLock::new();
+    SIGNATURES.get_or_init(|| {
+        let tokens = Lexer::new(BUILTINS).tokenize().expect("bundled stub lexes");
+        let module = Parser::new(tokens).parse().expect("bundled stub parses");
+        let (symbol_table, _) = SemanticAnalyzer::with_untyped_builtins().diagnose(&module);
+        symbol_table
+            .module_scope_id()
+            .and_then(|scope| symbol_table.get_scope(scope))
+            .map(|scope| {
+                scope
+                    .symbols()
+                    .filter(|symbol| symbol.kind == SymbolKind::Function)
+                    .filter_map(|symbol| Some((symbol.name.clone(), (**symbol.signature.as_ref()?).clone())))
+                    .collect()
+            })
+            .unwrap_or_default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builtins::{self, BuiltinKind};
+    use crate::types::Type;
+
+    #[test]
+    fn test_bundled_stub_is_clean() {
+        let tokens = Lexer::new(BUILTINS).tokenize().unwrap();
+        let module = Parser::new(tokens).parse().unwrap();
+        let (_, problems) = SemanticAnalyzer::with_untyped_builtins().diagnose(&module);
+        assert!(problems.is_empty(), "{:?}", problems);
+    }
+
+    #[test]
+    fn test_stub_matches_builtin_namespace() {
+        let signatures = builtin_signatures();
+        let functions: Vec<&str> = builtins::BUILTINS
+            .iter()
+            .filter(|builtin| builtin.kind == BuiltinKind::Function)
+            .map(|builtin| builtin.name)
+            .collect();
+        for (name, signature) in signatures {
+            assert!(functions.contains(&name.as_str()), "{} is not a builtin function", name);
+            assert!(signature.returns.is_some(), "return type of {}", name);
+        }
+        // Overloaded functions have no single signature
+        let unsigned: Vec<&str> = functions.into_iter().filter(|name| !signatures.contains_key(*name)).collect();
+        assert_eq!(unsigned, ["anext", "max", "min"]);
+    }
+
+    #[test]
+    fn test_builtin_signatures_annotated() {
+        let signatures = builtin_signatures();
+        assert_eq!(signatures["chr"].parameters[0].annotation, Some(Type::Int));
+        assert_eq!(signatures["chr"].returns, Some(Type::Str));
+        assert_eq!(signatures["sorted"].returns.as_ref().unwrap().to_string(), "list[T]");
+        assert_eq!(signatures["print"].returns, Some(Type::None));
+    }
+}
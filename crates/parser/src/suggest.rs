@@ -0,0 +1,132 @@
+//! "Did you mean" suggestions based on edit distance
+//!
+//! Used by the parser for misspelled keywords and by the semantic analyzer for
+//! undefined names (matched against the scope chain, builtins, keywords and
+//! members of the enclosing class).
+
+/// Optimal string alignment distance (Damerau-Levenshtein without
+/// repeated edits of the same substring), counted in characters.
+///
+/// Insertions, deletions, substitutions and transpositions of two adjacent
+/// characters each cost 1, so `edit_distance("pritn", "print") == 1`.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // Three rolling rows: two rows back (for transpositions), previous, current
+    let mut before_prev: Vec<usize> = vec![0; b.len() + 1];
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut current: Vec<usize> = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (prev[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before_prev[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut before_prev, &mut prev);
+        std::mem::swap(&mut prev, &mut current);
+    }
+
+    prev[b.len()]
+}
+
+/// Largest edit distance still considered a typo for a name of this length.
+/// Never the full length, so `y` is not "corrected" to `x`.
+pub fn max_distance(name: &str) -> usize {
+    let len = name.chars().count();
+    (len / 3).max(1).min(len.saturating_sub(1))
+}
+
+/// Find the candidate closest to `name`.
+///
+/// A case-insensitive exact match wins outright (`true` -> `True`); otherwise
+/// the candidate with the smallest edit distance within `max_distance(name)` is
+/// returned. Ties are broken alphabetically so results don't depend on the
+/// iteration order of the candidates. `name` itself is never suggested.
+pub fn best_match<'a, I>(name: &str, candidates: I) -> Option<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let limit = max_distance(name);
+    let lowered = name.to_lowercase();
+
+    let mut best: Option<(usize, &'a str)> = None;
+    for candidate in candidates {
+        if candidate == name {
+            continue;
+        }
+        let distance = if candidate.to_lowercase() == lowered {
+            0
+        } else {
+            edit_distance(name, candidate)
+        };
+        if distance > limit {
+            continue;
+        }
+        best = match best {
+            Some((best_distance, best_name))
+                if (best_distance, best_name) <= (distance, candidate) =>
+            {
+                Some((best_distance, best_name))
+            }
+            _ => Some((distance, candidate)),
+        };
+    }
+
+    best.map(|(_, candidate)| candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("abc", ""), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("count", "cont"), 1);
+        assert_eq!(edit_distance("pritn", "print"), 1);
+        assert_eq!(edit_distance("whiel", "while"), 1);
+        assert_eq!(edit_distance("naïve", "naive"), 1);
+    }
+
+    #[test]
+    fn test_best_match() {
+        let names = ["print", "range", "counter", "count"];
+        assert_eq!(best_match("pritn", names), Some("print"));
+        assert_eq!(best_match("coutn", names), Some("count"));
+        assert_eq!(best_match("xyz", names), None);
+    }
+
+    #[test]
+    fn test_best_match_prefers_case_insensitive() {
+        assert_eq!(best_match("true", ["True", "tree"]), Some("True"));
+    }
+
+    #[test]
+    fn test_best_match_is_order_independent() {
+        assert_eq!(best_match("ab", ["ac", "ad"]), Some("ac"));
+        assert_eq!(best_match("ab", ["ad", "ac"]), Some("ac"));
+    }
+
+    #[test]
+    fn test_best_match_skips_identical_name() {
+        assert_eq!(best_match("count", ["count"]), None);
+    }
+
+    #[test]
+    fn test_max_distance() {
+        assert_eq!(max_distance("x"), 0);
+        assert_eq!(max_distance("ab"), 1);
+        assert_eq!(max_distance("counter"), 2);
+        assert_eq!(best_match("y", ["x"]), None);
+        assert_eq!(best_match("X", ["x"]), Some("x"));
+    }
+}
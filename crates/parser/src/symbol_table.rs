@@ -3,14 +3,18 @@
 //! This module implements the symbol table used for tracking declarations,
 //! managing scopes, and performing semantic analysis on Mamba code.
 
+use crate::ast::{Parameter, ParameterKind};
+use crate::classes::ClassInfo;
+use crate::types::Type;
 use crate::token::SourcePosition;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Unique identifier for a scope
 pub type ScopeId = usize;
 
 /// The kind of symbol (what the identifier represents)
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SymbolKind {
     /// A variable (includes loop variables)
     Variable,
@@ -23,14 +27,18 @@ pub enum SymbolKind {
 }
 
 /// A symbol represents a declared identifier in the code
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Symbol {
     /// The identifier name
     pub name: String,
     /// What kind of symbol this is
     pub kind: SymbolKind,
-    /// Where it was declared
+    /// Where it was declared (first binding)
     pub position: SourcePosition,
+    /// Every binding site, starting with `position` and then in source
+    /// order. Assignments through `global` or `nonlocal` in other scopes
+    /// count as bindings of the outer name.
+    pub definitions: Vec<SourcePosition>,
     /// Which scope it belongs to
     pub scope_id: ScopeId,
     /// Whether this variable is captured by a nested function (for closures)
@@ -39,6 +47,87 @@ pub struct Symbol {
     pub is_global: bool,
     /// Whether this variable was declared with `nonlocal` keyword
     pub is_nonlocal: bool,
+    /// Parameters of the `def` that bound this symbol, if it is a function
+    pub signature: Option<Box<Signature>>,
+    /// Members and ancestry of the `class` that bound this symbol
+    pub class_info: Option<Box<ClassInfo>>,
+    /// Type from the annotation of a variable or parameter
+    pub declared_type: Option<Box<Type>>,
+}
+
+/// The parameter list of a function definition, used to check calls
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Signature {
+    pub parameters: Vec<SignatureParameter>,
+    /// Type from the return annotation
+    pub returns: Option<Type>,
+}
+
+/// One parameter of a [`Signature`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignatureParameter {
+    pub name: String,
+    pub kind: ParameterKind,
+    pub has_default: bool,
+    /// Type from the parameter's annotation
+    pub annotation: Option<Type>,
+}
+
+impl Signature {
+    /// Build the signature of a `def` from its parameters
+    pub fn from_parameters(parameters: &[Parameter]) -> Self {
+        Self {
+            parameters: parameters
+                .iter()
+                .map(|param| SignatureParameter {
+                    name: param.name.clone(),
+                    kind: param.kind.clone(),
+                    has_default: param.default.is_some(),
+                    annotation: None,
+                })
+                .collect(),
+            returns: None,
+        }
+    }
+
+    /// Parameters that can be filled by position, in order
+    pub fn positional(&self) -> impl Iterator<Item = &SignatureParameter> {
+        self.parameters
+            .iter()
+            .filter(|param| matches!(param.kind, ParameterKind::PositionalOnly | ParameterKind::Regular))
+    }
+
+    /// Whether the function takes `*args`
+    pub fn has_var_args(&self) -> bool {
+        self.parameters.iter().any(|param| param.kind == ParameterKind::VarArgs)
+    }
+
+    /// Whether the function takes `**kwargs`
+    pub fn has_var_kwargs(&self) -> bool {
+        self.parameters.iter().any(|param| param.kind == ParameterKind::VarKwargs)
+    }
+
+    /// The signature of the function bound to an object: without the first
+    /// positional parameter, which receives the object
+    pub fn without_receiver(&self) -> Self {
+        let mut signature = self.clone();
+        if let Some(index) = signature.parameters.iter().position(|param| {
+            matches!(param.kind, ParameterKind::PositionalOnly | ParameterKind::Regular)
+        }) {
+            signature.parameters.remove(index);
+        }
+        signature
+    }
+
+    /// The type of the function as a value, `Any` where unannotated
+    pub fn callable_type(&self) -> Type {
+        Type::Callable {
+            parameters: Some(
+                self.positional().map(|param| param.annotation.clone().unwrap_or(Type::Any)).collect(),
+            ),
+            returns: Box::new(self.returns.clone().unwrap_or(Type::Any)),
+        }
+    }
 }
 
 impl Symbol {
@@ -48,13 +137,24 @@ impl Symbol {
             name,
             kind,
             position,
+            definitions: vec![position],
             scope_id,
             is_captured: false,
             is_global: false,
             is_nonlocal: false,
+            signature: None,
+            class_info: None,
+            declared_type: None,
         }
     }
     
+    /// Record a later binding of this name (e.g. `x = 2` after `x = 1`)
+    pub fn add_definition(&mut self, position: SourcePosition) {
+        let later = self.definitions.iter().skip(1).take_while(|definition| definition.offset <= position.offset);
+        let index = 1 + later.count();
+        self.definitions.insert(index, position);
+    }
+
     /// Mark this symbol as captured by a nested function
     pub fn mark_captured(&mut self) {
         self.is_captured = true;
@@ -74,14 +174,18 @@ impl Symbol {
 /// The kind of scope
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ScopeKind {
+    /// Builtin names, enclosing the module scope
+    Builtins,
     /// Module/file-level scope
     Module,
     /// Function scope
     Function,
     /// Class scope
     Class,
-    /// Block scope (if/while/for)
-    Block,
+    /// Lambda expression scope (its parameters)
+    Lambda,
+    /// List/set/dict comprehension or generator expression scope (its loop targets)
+    Comprehension,
 }
 
 /// A scope represents a lexical scope where symbols can be declared
@@ -152,6 +256,8 @@ pub struct SymbolTable {
     current_scope: ScopeId,
     /// Counter for generating unique scope IDs
     next_scope_id: ScopeId,
+    /// Resolved type of every annotation, keyed by the annotation's position
+    annotations: HashMap<SourcePosition, Type>,
 }
 
 impl SymbolTable {
@@ -165,9 +271,37 @@ impl SymbolTable {
             scopes,
             current_scope: 0,
             next_scope_id: 1,
+            annotations: HashMap::new(),
         }
     }
 
+    /// Create a symbol table whose module scope is nested in a builtins scope
+    ///
+    /// The module scope is current; the builtins scope is its parent, so a
+    /// module-level binding shadows a builtin instead of redeclaring it.
+    pub fn with_builtins_scope() -> Self {
+        let mut scopes = HashMap::new();
+        scopes.insert(0, Scope::new(0, ScopeKind::Builtins, None));
+        let mut table = Self {
+            scopes,
+            current_scope: 0,
+            next_scope_id: 1,
+            annotations: HashMap::new(),
+        };
+        table.enter_scope(ScopeKind::Module);
+        table
+    }
+
+    /// The builtins scope, if the table has one
+    pub fn builtins_scope_id(&self) -> Option<ScopeId> {
+        self.scopes.get(&0).filter(|scope| scope.kind == ScopeKind::Builtins).map(|scope| scope.id)
+    }
+
+    /// The module scope, whose symbols are the module's top-level names
+    pub fn module_scope_id(&self) -> Option<ScopeId> {
+        self.scopes.values().find(|scope| scope.kind == ScopeKind::Module).map(|scope| scope.id)
+    }
+
     /// Get the current scope ID
     pub fn current_scope_id(&self) -> ScopeId {
         self.current_scope
@@ -223,7 +357,20 @@ impl SymbolTable {
         kind: SymbolKind,
         position: SourcePosition,
     ) -> Result<(), Symbol> {
-        let scope_id = self.current_scope;
+        self.declare_in(self.current_scope, name, kind, position)
+    }
+
+    /// Declare a new symbol in the given scope
+    ///
+    /// Used where Python binds a name outside the current scope, such as a
+    /// walrus target inside a comprehension.
+    pub fn declare_in(
+        &mut self,
+        scope_id: ScopeId,
+        name: String,
+        kind: SymbolKind,
+        position: SourcePosition,
+    ) -> Result<(), Symbol> {
         let symbol = Symbol::new(name.clone(), kind, position, scope_id);
 
         if let Some(scope) = self.scopes.get_mut(&scope_id) {
@@ -234,34 +381,46 @@ impl SymbolTable {
                 Err(scope.lookup(&name).unwrap().clone())
             }
         } else {
-            // This should never happen - current_scope should always be valid
+            // This should never happen - scope ids are only handed out by enter_scope
             Ok(())
         }
     }
 
     /// Look up a symbol in current scope and all parent scopes
     ///
+    /// Follows Python's LEGB rule: enclosing class bodies are skipped, so a
+    /// method does not see class-level names without `self.` or the class name.
+    ///
     /// Returns the symbol if found, None otherwise
     pub fn lookup(&self, name: &str) -> Option<&Symbol> {
-        let mut current_id = self.current_scope;
+        self.enclosing_scopes().find_map(|scope| scope.lookup(name))
+    }
 
-        loop {
-            if let Some(scope) = self.scopes.get(&current_id) {
-                if let Some(symbol) = scope.lookup(name) {
-                    return Some(symbol);
-                }
+    /// Names visible from the current scope (current scope and all parents,
+    /// skipping enclosing class bodies)
+    pub fn visible_names(&self) -> Vec<&str> {
+        self.enclosing_scopes()
+            .flat_map(|scope| scope.symbols().map(|symbol| symbol.name.as_str()))
+            .collect()
+    }
 
-                // Try parent scope
-                if let Some(parent_id) = scope.parent {
-                    current_id = parent_id;
-                } else {
-                    // Reached root, symbol not found
-                    return None;
-                }
-            } else {
-                return None;
-            }
-        }
+    /// The current scope followed by every parent scope whose names are visible
+    /// from it, innermost first. Class scopes other than the current one are
+    /// skipped.
+    pub fn enclosing_scopes(&self) -> impl Iterator<Item = &Scope> {
+        self.enclosing_scopes_of(self.current_scope)
+    }
+
+    /// Like [`SymbolTable::enclosing_scopes`], starting from any scope
+    pub fn enclosing_scopes_of(&self, scope_id: ScopeId) -> impl Iterator<Item = &Scope> {
+        let mut current = self.scopes.get(&scope_id);
+        let current_id = scope_id;
+        std::iter::from_fn(move || {
+            let scope = current?;
+            current = scope.parent.and_then(|parent| self.scopes.get(&parent));
+            Some(scope)
+        })
+        .filter(move |scope| scope.id == current_id || scope.kind != ScopeKind::Class)
     }
 
     /// Look up a symbol in the current scope only (not parent scopes)
@@ -269,6 +428,75 @@ impl SymbolTable {
         self.scopes.get(&self.current_scope)?.lookup(name)
     }
     
+    /// Record an additional binding site for a symbol in the current scope
+    ///
+    /// Returns false if the name is not declared in the current scope
+    pub fn add_definition(&mut self, name: &str, position: SourcePosition) -> bool {
+        self.add_definition_in(self.current_scope, name, position)
+    }
+
+    /// Record an additional binding site for a symbol in the given scope
+    ///
+    /// Returns false if the name is not declared in that scope
+    pub fn add_definition_in(&mut self, scope_id: ScopeId, name: &str, position: SourcePosition) -> bool {
+        if let Some(scope) = self.scopes.get_mut(&scope_id) {
+            if let Some(symbol) = scope.symbols.get_mut(name) {
+                symbol.add_definition(position);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Attach a function signature to a symbol in the given scope
+    ///
+    /// Returns false if the name is not declared in that scope
+    pub fn set_signature_in(&mut self, scope_id: ScopeId, name: &str, signature: Signature) -> bool {
+        if let Some(scope) = self.scopes.get_mut(&scope_id) {
+            if let Some(symbol) = scope.symbols.get_mut(name) {
+                symbol.signature = Some(Box::new(signature));
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Attach a class summary to a symbol in the given scope
+    ///
+    /// Returns false if the name is not declared in that scope
+    pub fn set_class_info_in(&mut self, scope_id: ScopeId, name: &str, info: ClassInfo) -> bool {
+        if let Some(scope) = self.scopes.get_mut(&scope_id) {
+            if let Some(symbol) = scope.symbols.get_mut(name) {
+                symbol.class_info = Some(Box::new(info));
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Record the annotated type of a symbol in the given scope
+    ///
+    /// Returns false if the name is not declared in that scope
+    pub fn set_declared_type_in(&mut self, scope_id: ScopeId, name: &str, declared_type: Type) -> bool {
+        if let Some(scope) = self.scopes.get_mut(&scope_id) {
+            if let Some(symbol) = scope.symbols.get_mut(name) {
+                symbol.declared_type = Some(Box::new(declared_type));
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Record the resolved type of the annotation at `position`
+    pub fn set_annotation_type(&mut self, position: SourcePosition, annotation: Type) {
+        self.annotations.insert(position, annotation);
+    }
+
+    /// The resolved type of the annotation at `position`
+    pub fn annotation_type(&self, position: &SourcePosition) -> Option<&Type> {
+        self.annotations.get(position)
+    }
+
     /// Mark a symbol in the current scope as global
     pub fn mark_global(&mut self, name: &str) -> bool {
         if let Some(scope) = self.scopes.get_mut(&self.current_scope) {
@@ -291,6 +519,20 @@ impl SymbolTable {
         false
     }
     
+    /// The scope that receives bindings made in the current scope: the current
+    /// scope itself, or the nearest enclosing non-comprehension scope when
+    /// inside a comprehension (where walrus targets bind)
+    pub fn binding_scope_id(&self) -> ScopeId {
+        let mut id = self.current_scope;
+        while let Some(scope) = self.scopes.get(&id) {
+            match (scope.kind, scope.parent) {
+                (ScopeKind::Comprehension, Some(parent)) => id = parent,
+                _ => break,
+            }
+        }
+        id
+    }
+
     /// Get the kind of the current scope
     pub fn current_scope_kind(&self) -> ScopeKind {
         self.scopes.get(&self.current_scope)
@@ -320,8 +562,11 @@ impl SymbolTable {
                     return None;
                 }
                 
-                if let Some(symbol) = scope.lookup(name) {
-                    return Some(symbol);
+                // Class bodies are not enclosing scopes for their methods
+                if scope.kind != ScopeKind::Class {
+                    if let Some(symbol) = scope.lookup(name) {
+                        return Some(symbol);
+                    }
                 }
                 
                 // Try parent scope
@@ -509,7 +754,7 @@ mod tests {
         table.enter_scope(ScopeKind::Function);
         table.declare("b".to_string(), SymbolKind::Variable, pos(2, 1)).unwrap();
 
-        table.enter_scope(ScopeKind::Block);
+        table.enter_scope(ScopeKind::Function);
         table.declare("c".to_string(), SymbolKind::Variable, pos(3, 1)).unwrap();
 
         // All variables should be visible
@@ -517,7 +762,7 @@ mod tests {
         assert!(table.lookup("b").is_some());
         assert!(table.lookup("c").is_some());
 
-        // Exit inner block
+        // Exit inner function
         table.exit_scope();
         assert!(table.lookup("a").is_some());
         assert!(table.lookup("b").is_some());
@@ -529,4 +774,33 @@ mod tests {
         assert!(table.lookup("b").is_none()); // b is out of scope
         assert!(table.lookup("c").is_none());
     }
+
+    #[test]
+    fn test_class_scope_not_visible_from_methods() {
+        let mut table = SymbolTable::new();
+        table.declare("a".to_string(), SymbolKind::Variable, pos(1, 1)).unwrap();
+
+        table.enter_scope(ScopeKind::Class);
+        table.declare("b".to_string(), SymbolKind::Variable, pos(2, 5)).unwrap();
+        assert!(table.lookup("b").is_some());
+
+        table.enter_scope(ScopeKind::Function);
+        assert!(table.lookup("a").is_some());
+        assert!(table.lookup("b").is_none());
+        assert_eq!(table.visible_names(), vec!["a"]);
+    }
+
+    #[test]
+    fn test_binding_scope_skips_comprehensions() {
+        let mut table = SymbolTable::new();
+        let function = table.enter_scope(ScopeKind::Function);
+        table.enter_scope(ScopeKind::Comprehension);
+        table.enter_scope(ScopeKind::Comprehension);
+        assert_eq!(table.binding_scope_id(), function);
+
+        table.declare_in(function, "y".to_string(), SymbolKind::Variable, pos(3, 5)).unwrap();
+        assert!(table.add_definition_in(function, "y", pos(4, 5)));
+        assert!(table.lookup_current_scope("y").is_none());
+        assert_eq!(table.lookup("y").unwrap().definitions.len(), 2);
+    }
 }
@@ -1,9 +1,11 @@
 //! Token definitions for the Mamba lexer
 
+use mamba_error::Span;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
 /// Represents a position in the source code
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct SourcePosition {
     pub line: usize,
     pub column: usize,
@@ -26,6 +28,17 @@ impl SourcePosition {
             offset: 0,
         }
     }
+
+    /// Diagnostic span starting at this position and covering `len` characters
+    pub fn span(&self, len: usize) -> Span {
+        Span::new(self.line, self.column, self.offset, len.max(1))
+    }
+}
+
+impl From<SourcePosition> for Span {
+    fn from(position: SourcePosition) -> Self {
+        position.span(1)
+    }
 }
 
 impl fmt::Display for SourcePosition {
@@ -215,6 +228,14 @@ impl TokenKind {
         )
     }
 
+    /// Source spelling of every keyword, including the `True`/`False`/`None` literals
+    pub const KEYWORDS: &'static [&'static str] = &[
+        "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+        "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in",
+        "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+        "with", "yield", "match", "case", "True", "False", "None",
+    ];
+
     /// Get the keyword token from a string, if it exists
     pub fn keyword_from_str(s: &str) -> Option<Self> {
         match s {
@@ -0,0 +1,865 @@
+//! Types
+//!
+//! This module defines the types the semantic analyzer reasons about and
+//! resolves annotation expressions into them. Annotations follow Python's
+//! typing syntax: builtin classes (`int`, `list[str]`), the special forms of
+//! `typing` (`Optional`, `Union`, `Callable`, `Any`, ...), unions written with
+//! `|`, class names, type variables and string forward references.
+//!
+//! Type variables are solved at call sites by matching the types of the
+//! arguments against the parameters ([`Type::solve`]), and the solutions are
+//! substituted into the return type ([`Type::substitute`]).
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::ast::{BinaryOperator, Expression, Literal, Statement};
+use crate::builtins::{self, BuiltinKind};
+use crate::classes::dotted_name;
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use crate::semantic::SemanticError;
+use crate::token::SourcePosition;
+use serde::{Deserialize, Serialize};
+
+/// A static type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Type {
+    Int,
+    Float,
+    Str,
+    Bool,
+    None,
+    List(Box<Type>),
+    Dict(Box<Type>, Box<Type>),
+    Set(Box<Type>),
+    /// Fixed-length tuple, `tuple[int, str]`; `tuple[()]` is empty
+    Tuple(Vec<Type>),
+    /// Tuple of any length, `tuple[int, ...]`
+    VarTuple(Box<Type>),
+    /// Union of two or more distinct types; `Optional[T]` is `T | None`
+    Union(Vec<Type>),
+    /// Callable with positional parameter types, or any parameters
+    /// (`Callable[..., R]`) when `parameters` is `None`
+    Callable {
+        parameters: Option<Vec<Type>>,
+        returns: Box<Type>,
+    },
+    /// Instance of any other class, with its type arguments if subscripted
+    Instance {
+        class: String,
+        arguments: Vec<Type>,
+    },
+    /// A type variable declared with `TypeVar` or as a type parameter
+    TypeVar(TypeVariable),
+    /// Unknown type, compatible with every type
+    Any,
+    /// Type of expressions that never produce a value
+    Never,
+}
+
+/// A type variable with what may be substituted for it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TypeVariable {
+    pub name: String,
+    /// Upper bound every solution must be assignable to (`bound=` or
+    /// `T: Bound`)
+    pub bound: Option<Box<Type>>,
+    /// Types the variable is restricted to (`TypeVar('T', int, str)` or
+    /// `T: (int, str)`)
+    pub constraints: Vec<Type>,
+}
+
+impl TypeVariable {
+    /// A type variable without bound or constraints
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            bound: None,
+            constraints: Vec::new(),
+        }
+    }
+
+    /// The type substituted for this variable when `solved` is the type of
+    /// the arguments it was matched against, or `None` when that type does
+    /// not satisfy the bound or constraints. A constrained variable becomes
+    /// the first constraint the type is assignable to.
+    pub fn admit(&self, solved: &Type, is_subclass: &dyn Fn(&str, &str) -> Option<bool>) -> Option<Type> {
+        if *solved == Type::Any || matches!(solved, Type::TypeVar(_)) {
+            return Some(solved.clone());
+        }
+        if let Some(bound) = &self.bound {
+            return solved.is_assignable_to(bound, is_subclass).then(|| solved.clone());
+        }
+        if self.constraints.is_empty() {
+            return Some(solved.clone());
+        }
+        self.constraints.iter().find(|constraint| solved.is_assignable_to(constraint, is_subclass)).cloned()
+    }
+
+    /// What a solution must satisfy, for messages: the bound, or the union
+    /// of the constraints
+    pub fn requirement(&self) -> Option<Type> {
+        match &self.bound {
+            Some(bound) => Some((**bound).clone()),
+            None if self.constraints.is_empty() => None,
+            None => Some(Type::union(self.constraints.iter().cloned())),
+        }
+    }
+}
+
+impl Type {
+    /// Instance of a class without type arguments
+    pub fn instance(class: &str) -> Type {
+        Type::Instance {
+            class: class.to_string(),
+            arguments: Vec::new(),
+        }
+    }
+
+    /// `T | None`
+    pub fn optional(inner: Type) -> Type {
+        Type::union([inner, Type::None])
+    }
+
+    /// Union of types, flattening nested unions and dropping duplicates.
+    /// A single remaining type is returned as is; no types at all is `Never`.
+    pub fn union(types: impl IntoIterator<Item = Type>) -> Type {
+        let mut members: Vec<Type> = Vec::new();
+        for member in types {
+            let flattened = match member {
+                Type::Union(inner) => inner,
+                other => vec![other],
+            };
+            for member in flattened {
+                if !members.contains(&member) {
+                    members.push(member);
+                }
+            }
+        }
+        match members.len() {
+            0 => Type::Never,
+            1 => members.remove(0),
+            _ => Type::Union(members),
+        }
+    }
+
+    /// Whether `None` is one of the values of this type
+    pub fn is_optional(&self) -> bool {
+        match self {
+            Type::None => true,
+            Type::Union(members) => members.contains(&Type::None),
+            _ => false,
+        }
+    }
+
+    /// The type without `None`, for a type that may be `None`
+    pub fn without_none(&self) -> Type {
+        match self {
+            Type::Union(members) => Type::union(members.iter().filter(|member| **member != Type::None).cloned()),
+            Type::None => Type::Never,
+            other => other.clone(),
+        }
+    }
+
+    /// The class of the values of this type, for types that have one
+    pub fn class_name(&self) -> Option<&str> {
+        match self {
+            Type::Int => Some("int"),
+            Type::Float => Some("float"),
+            Type::Str => Some("str"),
+            Type::Bool => Some("bool"),
+            Type::None => Some("NoneType"),
+            Type::List(_) => Some("list"),
+            Type::Dict(..) => Some("dict"),
+            Type::Set(_) => Some("set"),
+            Type::Tuple(_) | Type::VarTuple(_) => Some("tuple"),
+            Type::Instance { class, .. } => Some(class),
+            _ => None,
+        }
+    }
+
+    /// Whether a value of this type may be used where `target` is expected
+    ///
+    /// `is_subclass(class, base)` tells whether one class derives from
+    /// another, or `None` when either class is unknown, which is taken as
+    /// compatible. `Any` and unbounded type variables are compatible with
+    /// every type; a bounded type variable is used as its bound.
+    /// Container element types are compared covariantly, since the element
+    /// types of container displays are inferred without context.
+    pub fn is_assignable_to(&self, target: &Type, is_subclass: &dyn Fn(&str, &str) -> Option<bool>) -> bool {
+        let assignable = |value: &Type, target: &Type| value.is_assignable_to(target, is_subclass);
+        match (self, target) {
+            (Type::Any, _) | (_, Type::Any) | (Type::Never, _) => true,
+            _ if self == target => true,
+            // A value of a bounded type variable can be used as its bound
+            (Type::TypeVar(variable), _) => match variable.requirement() {
+                Some(requirement) => assignable(&requirement, target),
+                None => true,
+            },
+            (_, Type::TypeVar(_)) => true,
+            (Type::Union(members), _) => members.iter().all(|member| assignable(member, target)),
+            (_, Type::Union(members)) => members.iter().any(|member| assignable(self, member)),
+            (_, Type::Instance { class, .. }) if class == "object" => true,
+            // Numeric promotion
+            (Type::Bool, Type::Int | Type::Float) | (Type::Int, Type::Float) => true,
+            (Type::List(value), Type::List(target)) | (Type::Set(value), Type::Set(target)) => assignable(value, target),
+            (Type::Dict(key, value), Type::Dict(target_key, target_value)) => {
+                assignable(key, target_key) && assignable(value, target_value)
+            }
+            (Type::Tuple(values), Type::Tuple(targets)) => {
+                values.len() == targets.len() && values.iter().zip(targets).all(|(value, target)| assignable(value, target))
+            }
+            (Type::Tuple(values), Type::VarTuple(target)) => values.iter().all(|value| assignable(value, target)),
+            (Type::VarTuple(value), Type::VarTuple(target)) => assignable(value, target),
+            (
+                Type::Callable { parameters, returns },
+                Type::Callable { parameters: target_parameters, returns: target_returns },
+            ) => {
+                let parameters_match = match (parameters, target_parameters) {
+                    // Parameters are contravariant
+                    (Some(parameters), Some(targets)) => {
+                        parameters.len() == targets.len()
+                            && parameters.iter().zip(targets).all(|(parameter, target)| assignable(target, parameter))
+                    }
+                    _ => true,
+                };
+                parameters_match && assignable(returns, target_returns)
+            }
+            // Classes are called to make instances
+            (Type::Instance { class, .. }, Type::Callable { .. }) if class == "type" => true,
+            (Type::Instance { class, arguments }, Type::Instance { class: target_class, arguments: targets })
+                if class == target_class =>
+            {
+                arguments.is_empty()
+                    || targets.is_empty()
+                    || (arguments.len() == targets.len()
+                        && arguments.iter().zip(targets).all(|(value, target)| assignable(value, target)))
+            }
+            _ => match (self.class_name(), target.class_name()) {
+                (Some(class), Some(base)) => is_subclass(class, base).unwrap_or(true),
+                _ => false,
+            },
+        }
+    }
+}
+
+impl Type {
+    /// The type variables occurring in this type, outermost first
+    pub fn type_vars(&self) -> Vec<&TypeVariable> {
+        let mut found: Vec<&TypeVariable> = Vec::new();
+        self.collect_type_vars(&mut found);
+        found
+    }
+
+    fn collect_type_vars<'a>(&'a self, found: &mut Vec<&'a TypeVariable>) {
+        match self {
+            Type::TypeVar(variable) if !found.iter().any(|known| known.name == variable.name) => found.push(variable),
+            Type::List(element) | Type::Set(element) | Type::VarTuple(element) => element.collect_type_vars(found),
+            Type::Dict(key, value) => {
+                key.collect_type_vars(found);
+                value.collect_type_vars(found);
+            }
+            Type::Tuple(members) | Type::Union(members) | Type::Instance { arguments: members, .. } => {
+                for member in members {
+                    member.collect_type_vars(found);
+                }
+            }
+            Type::Callable { parameters, returns } => {
+                for parameter in parameters.iter().flatten() {
+                    parameter.collect_type_vars(found);
+                }
+                returns.collect_type_vars(found);
+            }
+            _ => {}
+        }
+    }
+
+    /// Replace the type variables that have a solution
+    pub fn substitute(&self, solutions: &HashMap<String, Type>) -> Type {
+        let substitute = |inner: &Type| Box::new(inner.substitute(solutions));
+        match self {
+            Type::TypeVar(variable) => solutions.get(&variable.name).cloned().unwrap_or_else(|| self.clone()),
+            Type::List(element) => Type::List(substitute(element)),
+            Type::Set(element) => Type::Set(substitute(element)),
+            Type::VarTuple(element) => Type::VarTuple(substitute(element)),
+            Type::Dict(key, value) => Type::Dict(substitute(key), substitute(value)),
+            Type::Tuple(members) => Type::Tuple(members.iter().map(|member| member.substitute(solutions)).collect()),
+            Type::Union(members) => Type::union(members.iter().map(|member| member.substitute(solutions))),
+            Type::Instance { class, arguments } => Type::Instance {
+                class: class.clone(),
+                arguments: arguments.iter().map(|argument| argument.substitute(solutions)).collect(),
+            },
+            Type::Callable { parameters, returns } => Type::Callable {
+                parameters: parameters
+                    .as_ref()
+                    .map(|parameters| parameters.iter().map(|parameter| parameter.substitute(solutions)).collect()),
+                returns: substitute(returns),
+            },
+            other => other.clone(),
+        }
+    }
+
+    /// Solve the type variables of this parameter type from the type of an
+    /// argument passed for it, adding to `solutions`. A variable matched
+    /// against several arguments is solved to the union of their types.
+    pub fn solve(&self, argument: &Type, solutions: &mut HashMap<String, Type>) {
+        match (self, argument) {
+            (_, Type::Any) | (_, Type::Never) => {}
+            (Type::TypeVar(variable), _) => {
+                let solved = match solutions.remove(&variable.name) {
+                    Some(known) => Type::union([known, argument.clone()]),
+                    None => argument.clone(),
+                };
+                solutions.insert(variable.name.clone(), solved);
+            }
+            (Type::Union(members), _) => {
+                // `T | None` matched against `int | None` solves `T` to `int`
+                let variables: Vec<&Type> = members.iter().filter(|member| !member.type_vars().is_empty()).collect();
+                let [variable] = variables[..] else {
+                    return;
+                };
+                let rest = match argument {
+                    Type::Union(arguments) => Type::union(arguments.iter().filter(|argument| !members.contains(argument)).cloned()),
+                    argument if members.contains(argument) => Type::Never,
+                    argument => argument.clone(),
+                };
+                variable.solve(&rest, solutions);
+            }
+            (Type::List(parameter), Type::List(argument))
+            | (Type::Set(parameter), Type::Set(argument))
+            | (Type::VarTuple(parameter), Type::VarTuple(argument)) => parameter.solve(argument, solutions),
+            (Type::VarTuple(parameter), Type::Tuple(arguments)) => {
+                for argument in arguments {
+                    parameter.solve(argument, solutions);
+                }
+            }
+            (Type::Dict(key, value), Type::Dict(argument_key, argument_value)) => {
+                key.solve(argument_key, solutions);
+                value.solve(argument_value, solutions);
+            }
+            (Type::Tuple(parameters), Type::Tuple(arguments)) if parameters.len() == arguments.len() => {
+                for (parameter, argument) in parameters.iter().zip(arguments) {
+                    parameter.solve(argument, solutions);
+                }
+            }
+            (
+                Type::Callable { parameters, returns },
+                Type::Callable { parameters: argument_parameters, returns: argument_returns },
+            ) => {
+                if let (Some(parameters), Some(argument_parameters)) = (parameters, argument_parameters) {
+                    for (parameter, argument) in parameters.iter().zip(argument_parameters) {
+                        parameter.solve(argument, solutions);
+                    }
+                }
+                returns.solve(argument_returns, solutions);
+            }
+            (Type::Instance { class, arguments: parameters }, Type::Instance { class: argument_class, arguments })
+                if class == argument_class && parameters.len() == arguments.len() =>
+            {
+                for (parameter, argument) in parameters.iter().zip(arguments) {
+                    parameter.solve(argument, solutions);
+                }
+            }
+            // `Iterable[T]` and the other single-parameter collection
+            // protocols take the element type of a builtin container
+            (Type::Instance { class, arguments: parameters }, _) if parameters.len() == 1 && is_collection_protocol(class) => {
+                if let Some(element) = builtin_element(argument) {
+                    parameters[0].solve(&element, solutions);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Generic protocols of `typing` and `collections.abc` over the elements of
+/// a collection
+fn is_collection_protocol(class: &str) -> bool {
+    matches!(
+        class,
+        "Iterable" | "Iterator" | "Collection" | "Sequence" | "MutableSequence" | "AbstractSet" | "MutableSet" | "Container" | "Reversible"
+    )
+}
+
+/// The element type of a builtin container, as iterating over it produces
+fn builtin_element(container: &Type) -> Option<Type> {
+    match container {
+        Type::List(element) | Type::Set(element) | Type::VarTuple(element) | Type::Dict(element, _) => {
+            Some((**element).clone())
+        }
+        Type::Tuple(elements) => Some(Type::union(elements.iter().cloned())),
+        Type::Str => Some(Type::Str),
+        Type::Instance { class, arguments } if class == "frozenset" || is_collection_protocol(class) => {
+            arguments.first().cloned()
+        }
+        _ => None,
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Int => write!(f, "int"),
+            Type::Float => write!(f, "float"),
+            Type::Str => write!(f, "str"),
+            Type::Bool => write!(f, "bool"),
+            Type::None => write!(f, "None"),
+            Type::List(element) => write!(f, "list[{}]", element),
+            Type::Dict(key, value) => write!(f, "dict[{}, {}]", key, value),
+            Type::Set(element) => write!(f, "set[{}]", element),
+            Type::Tuple(elements) if elements.is_empty() => write!(f, "tuple[()]"),
+            Type::Tuple(elements) => write!(f, "tuple[{}]", joined(elements)),
+            Type::VarTuple(element) => write!(f, "tuple[{}, ...]", element),
+            Type::Union(members) => {
+                let members: Vec<String> = members.iter().map(Type::to_string).collect();
+                write!(f, "{}", members.join(" | "))
+            }
+            Type::Callable { parameters: None, returns } => write!(f, "Callable[..., {}]", returns),
+            Type::Callable { parameters: Some(parameters), returns } => {
+                write!(f, "Callable[[{}], {}]", joined(parameters), returns)
+            }
+            Type::Instance { class, arguments } if arguments.is_empty() => write!(f, "{}", class),
+            Type::Instance { class, arguments } => write!(f, "{}[{}]", class, joined(arguments)),
+            Type::TypeVar(variable) => write!(f, "{}", variable.name),
+            Type::Any => write!(f, "Any"),
+            Type::Never => write!(f, "Never"),
+        }
+    }
+}
+
+fn joined(types: &[Type]) -> String {
+    types.iter().map(Type::to_string).collect::<Vec<_>>().join(", ")
+}
+
+/// Modules whose members are understood in annotations
+pub const TYPING_MODULES: &[&str] = &["typing", "typing_extensions", "collections.abc"];
+
+/// Special forms of `typing` with a meaning of their own in annotations.
+/// Other members of the typing modules (`Iterable`, `Sequence`, ...) are
+/// treated as classes.
+pub const SPECIAL_FORMS: &[&str] = &[
+    "Any", "Callable", "Dict", "FrozenSet", "List", "Never", "NoReturn", "Optional", "Set", "Tuple", "Type", "Union",
+];
+
+/// Whether a member of a typing module is a special form
+pub fn is_special_form(name: &str) -> bool {
+    SPECIAL_FORMS.contains(&name)
+}
+
+/// What a (possibly dotted) name used in an annotation refers to
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeName {
+    /// A builtin or user-defined class
+    Class(String),
+    /// A type variable
+    TypeVar(TypeVariable),
+    /// A special form of `typing`, by its name in that module
+    Special(String),
+    /// A binding that is not a type, described as e.g. "a function"
+    NotAType(&'static str),
+    /// A binding whose value is not known statically (a variable that may
+    /// be a type alias, a module attribute); resolves to `Any`
+    Opaque,
+    /// Not bound anywhere
+    Undefined,
+}
+
+/// Resolves annotation expressions into types, collecting an error for each
+/// unknown name or malformed annotation
+pub struct AnnotationResolver<'a> {
+    lookup: &'a dyn Fn(&str) -> TypeName,
+    errors: Vec<SemanticError>,
+    /// The string literal being resolved as a forward reference; errors
+    /// inside it are reported at the string
+    forward_reference: Option<SourcePosition>,
+}
+
+impl<'a> AnnotationResolver<'a> {
+    /// Create a resolver that looks names up with `lookup`
+    pub fn new(lookup: &'a dyn Fn(&str) -> TypeName) -> Self {
+        Self {
+            lookup,
+            errors: Vec::new(),
+            forward_reference: None,
+        }
+    }
+
+    /// The errors found so far
+    pub fn into_errors(self) -> Vec<SemanticError> {
+        self.errors
+    }
+
+    /// Resolve an annotation. Parts that are malformed or unknown resolve to
+    /// `Any` after reporting an error.
+    pub fn resolve(&mut self, annotation: &Expression) -> Type {
+        match annotation {
+            Expression::Literal(Literal::None { .. }) => Type::None,
+            Expression::Literal(Literal::String { value, position }) => self.resolve_string(value, position),
+            Expression::Parenthesized { expr, .. } => self.resolve(expr),
+            Expression::BinaryOp { left, op: BinaryOperator::BitwiseOr, right, .. } => {
+                Type::union([self.resolve(left), self.resolve(right)])
+            }
+            Expression::Identifier { .. } | Expression::Attribute { .. } => match dotted_name(annotation) {
+                Some(name) => self.named(&name, None, annotation.position()),
+                None => self.invalid("expression is not a valid type", annotation.position()),
+            },
+            Expression::Subscript { object, index, position } => match dotted_name(object) {
+                Some(name) => self.named(&name, Some(index), position),
+                None => self.invalid("expression is not a valid type", position),
+            },
+            _ => self.invalid("expression is not a valid type", annotation.position()),
+        }
+    }
+
+    /// Resolve a string annotation, which is parsed as an expression
+    fn resolve_string(&mut self, source: &str, position: &SourcePosition) -> Type {
+        let expression = Lexer::new(source.trim())
+            .tokenize()
+            .ok()
+            .and_then(|tokens| Parser::new(tokens).parse().ok())
+            .and_then(|module| match <[Statement; 1]>::try_from(module.statements) {
+                Ok([Statement::Expression(expression)]) => Some(expression),
+                _ => None,
+            });
+        let Some(expression) = expression else {
+            return self.invalid(&format!("forward reference '{}' is not a valid type", source), position);
+        };
+
+        let enclosing = self.forward_reference.replace(*position);
+        let resolved = self.resolve(&expression);
+        self.forward_reference = enclosing;
+        resolved
+    }
+
+    /// Resolve a name, subscripted with `arguments` if given
+    fn named(&mut self, name: &str, arguments: Option<&Expression>, position: &SourcePosition) -> Type {
+        match (self.lookup)(name) {
+            TypeName::Special(form) => self.special_form(&form, arguments, position),
+            TypeName::Class(class) => self.class(&class, arguments, position),
+            TypeName::TypeVar(variable) => {
+                if arguments.is_some() {
+                    self.invalid(&format!("type variable '{}' cannot take type arguments", variable.name), position);
+                }
+                Type::TypeVar(variable)
+            }
+            TypeName::NotAType(what) => self.invalid(&format!("'{}' is {}, not a type", name, what), position),
+            TypeName::Opaque => {
+                if let Some(arguments) = arguments {
+                    self.type_arguments(arguments);
+                }
+                Type::Any
+            }
+            TypeName::Undefined => {
+                self.errors.push(SemanticError::UnknownType {
+                    name: name.to_string(),
+                    position: *self.forward_reference.as_ref().unwrap_or(position),
+                });
+                Type::Any
+            }
+        }
+    }
+
+    /// Resolve a special form of `typing`
+    fn special_form(&mut self, form: &str, arguments: Option<&Expression>, position: &SourcePosition) -> Type {
+        match form {
+            "Any" | "Never" | "NoReturn" => {
+                if arguments.is_some() {
+                    self.invalid(&format!("'{}' cannot take type arguments", form), position);
+                }
+                if form == "Any" {
+                    Type::Any
+                } else {
+                    Type::Never
+                }
+            }
+            "Optional" => match self.exact_arguments(form, arguments, 1, position) {
+                Some(mut types) => Type::optional(types.remove(0)),
+                None => Type::Any,
+            },
+            "Union" => match arguments.map(|arguments| self.type_arguments(arguments)) {
+                Some(types) if !types.is_empty() => Type::union(types),
+                _ => self.invalid("'Union' requires at least one type argument", position),
+            },
+            "Callable" => self.callable(arguments, position),
+            // `List`, `Dict`, `Set`, `FrozenSet`, `Tuple` and `Type` are
+            // aliases of the builtin classes
+            alias => self.class(&alias.to_lowercase(), arguments, position),
+        }
+    }
+
+    /// Resolve `Callable[[P1, P2], R]` or `Callable[..., R]`
+    fn callable(&mut self, arguments: Option<&Expression>, position: &SourcePosition) -> Type {
+        let Some(arguments) = arguments else {
+            return Type::Callable {
+                parameters: None,
+                returns: Box::new(Type::Any),
+            };
+        };
+        let [parameters, returns] = elements(arguments)[..] else {
+            return self.invalid("'Callable' takes a parameter list and a return type", position);
+        };
+        let parameters = match parameters {
+            Expression::Literal(Literal::Ellipsis { .. }) => None,
+            Expression::List { elements, .. } => Some(elements.iter().map(|element| self.resolve(element)).collect()),
+            other => {
+                self.invalid(
+                    "the parameters of 'Callable' must be a list of types or '...'",
+                    other.position(),
+                );
+                None
+            }
+        };
+        Type::Callable {
+            parameters,
+            returns: Box::new(self.resolve(returns)),
+        }
+    }
+
+    /// Resolve a class, checking the number of type arguments of the
+    /// builtin generic classes
+    fn class(&mut self, class: &str, arguments: Option<&Expression>, position: &SourcePosition) -> Type {
+        match class {
+            "int" | "float" | "str" | "bool" => {
+                if arguments.is_some() {
+                    return self.invalid(&format!("'{}' is not a generic class", class), position);
+                }
+                match class {
+                    "int" => Type::Int,
+                    "float" => Type::Float,
+                    "str" => Type::Str,
+                    _ => Type::Bool,
+                }
+            }
+            "tuple" => match arguments {
+                Some(arguments) => self.tuple(arguments, position),
+                None => Type::VarTuple(Box::new(Type::Any)),
+            },
+            "list" | "set" | "frozenset" | "type" | "dict" => {
+                let count = if class == "dict" { 2 } else { 1 };
+                let mut types = match arguments {
+                    Some(_) => match self.exact_arguments(class, arguments, count, position) {
+                        Some(types) => types,
+                        None => vec![Type::Any; count],
+                    },
+                    None => vec![Type::Any; count],
+                };
+                match class {
+                    "list" => Type::List(Box::new(types.remove(0))),
+                    "set" => Type::Set(Box::new(types.remove(0))),
+                    "dict" => {
+                        let key = types.remove(0);
+                        Type::Dict(Box::new(key), Box::new(types.remove(0)))
+                    }
+                    _ => Type::Instance {
+                        class: class.to_string(),
+                        arguments: types,
+                    },
+                }
+            }
+            _ => {
+                let is_builtin = builtins::lookup(class)
+                    .is_some_and(|builtin| matches!(builtin.kind, BuiltinKind::Class | BuiltinKind::Exception));
+                let arguments = match arguments {
+                    Some(_) if is_builtin => {
+                        return self.invalid(&format!("'{}' is not a generic class", class), position);
+                    }
+                    Some(arguments) => self.type_arguments(arguments),
+                    None => Vec::new(),
+                };
+                Type::Instance {
+                    class: class.to_string(),
+                    arguments,
+                }
+            }
+        }
+    }
+
+    /// Resolve the arguments of `tuple[...]`
+    fn tuple(&mut self, arguments: &Expression, position: &SourcePosition) -> Type {
+        let elements = elements(arguments);
+        match elements[..] {
+            [element, Expression::Literal(Literal::Ellipsis { .. })] => Type::VarTuple(Box::new(self.resolve(element))),
+            _ if elements.iter().any(|element| matches!(element, Expression::Literal(Literal::Ellipsis { .. }))) => {
+                self.invalid("'...' is only allowed as the second of two tuple type arguments", position)
+            }
+            _ => Type::Tuple(elements.iter().map(|element| self.resolve(element)).collect()),
+        }
+    }
+
+    /// Resolve exactly `count` type arguments of `name`, reporting any other
+    /// number
+    fn exact_arguments(
+        &mut self,
+        name: &str,
+        arguments: Option<&Expression>,
+        count: usize,
+        position: &SourcePosition,
+    ) -> Option<Vec<Type>> {
+        let types = arguments.map(|arguments| self.type_arguments(arguments)).unwrap_or_default();
+        if types.len() == count {
+            return Some(types);
+        }
+        self.invalid(
+            &format!(
+                "'{}' expects {} type argument{} but {} {} given",
+                name,
+                count,
+                if count == 1 { "" } else { "s" },
+                types.len(),
+                if types.len() == 1 { "was" } else { "were" }
+            ),
+            position,
+        );
+        None
+    }
+
+    /// Resolve each type argument in a subscript
+    fn type_arguments(&mut self, arguments: &Expression) -> Vec<Type> {
+        elements(arguments).into_iter().map(|argument| self.resolve(argument)).collect()
+    }
+
+    /// Report a malformed annotation, which resolves to `Any`
+    fn invalid(&mut self, message: &str, position: &SourcePosition) -> Type {
+        self.errors.push(SemanticError::InvalidAnnotation {
+            message: message.to_string(),
+            position: *self.forward_reference.as_ref().unwrap_or(position),
+        });
+        Type::Any
+    }
+}
+
+/// The comma-separated parts of a subscript (`dict[str, int]`); `()` has none
+fn elements(index: &Expression) -> Vec<&Expression> {
+    match index {
+        Expression::Tuple { elements, .. } => elements.iter().collect(),
+        other => vec![other],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_annotation(source: &str) -> Expression {
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        match Parser::new(tokens).parse().unwrap().statements.remove(0) {
+            Statement::Expression(expression) => expression,
+            other => panic!("expected an expression, got {:?}", other),
+        }
+    }
+
+    fn lookup(name: &str) -> TypeName {
+        match name {
+            "Optional" | "Union" | "Callable" | "List" | "Any" | "typing.Dict" => {
+                TypeName::Special(name.trim_start_matches("typing.").to_string())
+            }
+            "T" => TypeName::TypeVar(TypeVariable::new("T")),
+            "Node" => TypeName::Class("Node".to_string()),
+            "len" => TypeName::NotAType("a function"),
+            "Alias" => TypeName::Opaque,
+            _ if builtins::lookup(name).is_some() => TypeName::Class(name.to_string()),
+            _ => TypeName::Undefined,
+        }
+    }
+
+    fn resolve(source: &str) -> (String, Vec<String>) {
+        let mut resolver = AnnotationResolver::new(&lookup);
+        let resolved = resolver.resolve(&parse_annotation(source));
+        let errors = resolver.into_errors().iter().map(SemanticError::message).collect();
+        (resolved.to_string(), errors)
+    }
+
+    #[test]
+    fn test_resolve_builtin_and_typing_forms() {
+        let cases = [
+            ("int", "int"),
+            ("list[str]", "list[str]"),
+            ("dict[str, list[int]]", "dict[str, list[int]]"),
+            ("tuple[int, ...]", "tuple[int, ...]"),
+            ("tuple[()]", "tuple[()]"),
+            ("Optional[int]", "int | None"),
+            ("Union[int, Union[str, int]]", "int | str"),
+            ("int | None | None", "int | None"),
+            ("Callable[[int, str], bool]", "Callable[[int, str], bool]"),
+            ("Callable[..., None]", "Callable[..., None]"),
+            ("List[T]", "list[T]"),
+            ("typing.Dict[str, Any]", "dict[str, Any]"),
+            ("'Node'", "Node"),
+            ("Node[int]", "Node[int]"),
+            ("Alias[int]", "Any"),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(resolve(source), (expected.to_string(), Vec::new()), "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_malformed_annotations() {
+        let cases = [
+            ("list[int, str]", "'list' expects 1 type argument but 2 were given"),
+            ("dict[str]", "'dict' expects 2 type arguments but 1 was given"),
+            ("int[str]", "'int' is not a generic class"),
+            ("Callable[int, str]", "the parameters of 'Callable' must be a list of types or '...'"),
+            ("tuple[..., int]", "'...' is only allowed as the second of two tuple type arguments"),
+            ("len", "'len' is a function, not a type"),
+            ("T[int]", "type variable 'T' cannot take type arguments"),
+            ("3", "expression is not a valid type"),
+            ("'not valid ('", "forward reference 'not valid (' is not a valid type"),
+        ];
+        for (source, expected) in cases {
+            let (_, errors) = resolve(source);
+            assert_eq!(errors, vec![expected.to_string()], "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_unknown_type() {
+        assert_eq!(resolve("list['Nod']").1, vec!["unknown type 'Nod'".to_string()]);
+    }
+
+    #[test]
+    fn test_union_helpers() {
+        let optional = Type::optional(Type::Int);
+        assert!(optional.is_optional());
+        assert_eq!(optional.without_none(), Type::Int);
+        assert_eq!(Type::union([Type::Int, Type::union([Type::Str, Type::Int])]).to_string(), "int | str");
+        assert_eq!(Type::union([]), Type::Never);
+    }
+
+    #[test]
+    fn test_assignability() {
+        // `Dog` derives from `Animal`; `Sequence` is unknown
+        let is_subclass = |class: &str, base: &str| match (class, base) {
+            (_, "Sequence") => None,
+            ("Dog", "Animal") => Some(true),
+            _ => Some(class == base),
+        };
+        let assignable = |value: &Type, target: &Type| value.is_assignable_to(target, &is_subclass);
+        let list = |element: Type| Type::List(Box::new(element));
+
+        assert!(assignable(&Type::Int, &Type::Float));
+        assert!(assignable(&Type::Bool, &Type::Int));
+        assert!(!assignable(&Type::Float, &Type::Int));
+        assert!(!assignable(&Type::Str, &Type::Int));
+        assert!(assignable(&Type::None, &Type::optional(Type::Str)));
+        assert!(!assignable(&Type::optional(Type::Str), &Type::Str));
+        assert!(assignable(&Type::Any, &Type::Str) && assignable(&Type::Str, &Type::Any));
+        assert!(assignable(&list(Type::Int), &list(Type::Float)));
+        assert!(!assignable(&list(Type::Str), &list(Type::Int)));
+        assert!(assignable(&Type::Tuple(vec![Type::Int, Type::Int]), &Type::VarTuple(Box::new(Type::Int))));
+        assert!(!assignable(&Type::Tuple(vec![Type::Int]), &Type::Tuple(vec![Type::Int, Type::Int])));
+        assert!(assignable(&Type::instance("Dog"), &Type::instance("Animal")));
+        assert!(!assignable(&Type::instance("Animal"), &Type::instance("Dog")));
+        assert!(!assignable(&Type::Str, &Type::instance("Animal")));
+        assert!(assignable(&Type::Str, &Type::instance("Sequence")));
+        assert!(assignable(&Type::Int, &Type::instance("object")));
+
+        let callable = |parameter: Type, returns: Type| Type::Callable {
+            parameters: Some(vec![parameter]),
+            returns: Box::new(returns),
+        };
+        assert!(assignable(&callable(Type::Float, Type::Int), &callable(Type::Int, Type::Float)));
+        assert!(!assignable(&callable(Type::Int, Type::Int), &callable(Type::Float, Type::Int)));
+    }
+}
@@ -0,0 +1,179 @@
+use mamba_parser::arena::*;
+use mamba_parser::ast::{BinaryOperator, Literal, Module};
+use mamba_parser::lexer::Lexer;
+use mamba_parser::parser::Parser;
+
+/// Helper function to parse a string into an AST
+fn parse(input: &str) -> Module {
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().expect("Tokenize should succeed");
+    let mut parser = Parser::new(tokens);
+    parser.parse().expect("Parse should succeed")
+}
+
+/// Helper function to parse a string straight into an arena
+fn lower(input: &str) -> AstArena {
+    AstArena::from_module(&parse(input))
+}
+
+#[test]
+fn test_empty_module() {
+    let arena = lower("");
+    assert!(arena.is_empty());
+    assert!(arena.root().is_empty());
+}
+
+#[test]
+fn test_assignment_lowering() {
+    let arena = lower("x = 1 + 2\n");
+    assert_eq!(arena.root().len(), 1);
+    // Assignment, target, binary op, two literals
+    assert_eq!(arena.len(), 5);
+
+    let stmt = arena.root()[0];
+    match arena.stmt(stmt) {
+        Some(StmtKind::Assignment { targets, value }) => {
+            assert!(matches!(arena.expr(targets[0]), Some(ExprKind::Identifier { name }) if name == "x"));
+            match arena.expr(*value) {
+                Some(ExprKind::BinaryOp { left, op, right }) => {
+                    assert_eq!(*op, BinaryOperator::Add);
+                    assert!(matches!(arena.expr(*left), Some(ExprKind::Literal(Literal::Integer { value: 1, .. }))));
+                    assert!(matches!(arena.expr(*right), Some(ExprKind::Literal(Literal::Integer { value: 2, .. }))));
+                }
+                other => panic!("Expected BinaryOp, got {:?}", other),
+            }
+        }
+        other => panic!("Expected Assignment, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_ids_follow_preorder() {
+    let arena = lower("x = 1\ny = x\n");
+    let ids: Vec<NodeId> = arena.ids().collect();
+    assert_eq!(ids.len(), arena.len());
+
+    // Every node except top-level statements has a parent with a smaller ID
+    for id in arena.ids() {
+        match arena.parent(id) {
+            Some(parent) => assert!(parent < id, "parent {} should precede child {}", parent, id),
+            None => assert!(arena.root().contains(&id)),
+        }
+    }
+}
+
+#[test]
+fn test_ids_are_stable() {
+    let source = "def add(a, b=1):\n    return a + b\nprint(add(2))\n";
+    let first = lower(source);
+    let second = lower(source);
+    assert_eq!(first, second);
+}
+
+#[test]
+fn test_statement_and_expression_accessors() {
+    let arena = lower("pass\n");
+    let stmt = arena.root()[0];
+    assert_eq!(arena.stmt(stmt), Some(&StmtKind::Pass));
+    assert!(arena.expr(stmt).is_none());
+    assert_eq!(arena.position(stmt).unwrap().line, 1);
+}
+
+#[test]
+fn test_function_def_children() {
+    let arena = lower("@decorator\ndef greet(name: str = 'x') -> str:\n    return name\n");
+    let func = arena.root()[0];
+    match arena.stmt(func) {
+        Some(StmtKind::FunctionDef { name, parameters, body, decorators, return_type, .. }) => {
+            assert_eq!(name, "greet");
+            assert_eq!(parameters.len(), 1);
+            assert!(parameters[0].type_annotation.is_some());
+            assert!(parameters[0].default.is_some());
+            assert_eq!(decorators.len(), 1);
+            assert!(return_type.is_some());
+            assert_eq!(body.len(), 1);
+            assert_eq!(arena.parent(body[0]), Some(func));
+            assert_eq!(arena.parent(parameters[0].default.unwrap()), Some(func));
+        }
+        other => panic!("Expected FunctionDef, got {:?}", other),
+    }
+
+    // decorator, annotation, default, return type, body statement
+    assert_eq!(arena.children(func).len(), 5);
+}
+
+#[test]
+fn test_nested_blocks_parent_links() {
+    let arena = lower("if x:\n    while y:\n        z = 1\n");
+    let if_stmt = arena.root()[0];
+    let while_stmt = match arena.stmt(if_stmt) {
+        Some(StmtKind::If { then_block, .. }) => then_block[0],
+        other => panic!("Expected If, got {:?}", other),
+    };
+    assert_eq!(arena.parent(while_stmt), Some(if_stmt));
+
+    let assign = match arena.stmt(while_stmt) {
+        Some(StmtKind::While { body, .. }) => body[0],
+        other => panic!("Expected While, got {:?}", other),
+    };
+    assert_eq!(arena.parent(assign), Some(while_stmt));
+}
+
+#[test]
+fn test_comprehension_lowering() {
+    let arena = lower("[x * 2 for x in items if x]\n");
+    let stmt = arena.root()[0];
+    let comp = match arena.stmt(stmt) {
+        Some(StmtKind::Expression(expr)) => *expr,
+        other => panic!("Expected expression statement, got {:?}", other),
+    };
+    match arena.expr(comp) {
+        Some(ExprKind::ListComp { generators, .. }) => {
+            assert_eq!(generators.len(), 1);
+            assert_eq!(generators[0].target, "x");
+            assert_eq!(generators[0].conditions.len(), 1);
+            assert!(matches!(arena.expr(generators[0].iter), Some(ExprKind::Identifier { name }) if name == "items"));
+        }
+        other => panic!("Expected ListComp, got {:?}", other),
+    }
+    // element, iter, condition
+    assert_eq!(arena.children(comp).len(), 3);
+}
+
+#[test]
+fn test_children_cover_every_node() {
+    let source = "class A(Base):\n    def m(self, *args, **kw):\n        return {k: v for k in args}\nfor i, j in pairs:\n    del d[i]\nelse:\n    assert i, 'msg'\n";
+    let arena = lower(source);
+
+    // Walking from the root via children() must reach every node exactly once
+    let mut seen = vec![false; arena.len()];
+    let mut stack: Vec<NodeId> = arena.root().to_vec();
+    while let Some(id) = stack.pop() {
+        assert!(!seen[id.index()], "node {} visited twice", id);
+        seen[id.index()] = true;
+        for child in arena.children(id) {
+            assert_eq!(arena.parent(child), Some(id));
+            stack.push(child);
+        }
+    }
+    assert!(seen.iter().all(|visited| *visited));
+}
+
+#[test]
+fn test_side_table() {
+    let arena = lower("a = 1\nb = 'two'\n");
+    let mut kinds: SideTable<&str> = SideTable::new();
+    for id in arena.ids() {
+        if let Some(ExprKind::Literal(literal)) = arena.expr(id) {
+            let kind = match literal {
+                Literal::Integer { .. } => "int",
+                Literal::String { .. } => "str",
+                _ => "other",
+            };
+            kinds.insert(id, kind);
+        }
+    }
+    assert_eq!(kinds.len(), 2);
+    assert!(kinds.values().any(|k| *k == "int"));
+    assert!(kinds.values().any(|k| *k == "str"));
+}
@@ -0,0 +1,110 @@
+use mamba_error::{ErrorCode, MambaError, Severity};
+use mamba_parser::lexer::Lexer;
+use mamba_parser::parser::Parser;
+
+/// Helper to get the first lexer or parser error for a source string
+fn first_error(source: &str) -> MambaError {
+    let mut lexer = Lexer::new(source);
+    let tokens = match lexer.tokenize() {
+        Ok(tokens) => tokens,
+        Err(e) => return e,
+    };
+    let mut parser = Parser::new(tokens);
+    match parser.parse() {
+        Ok(_) => panic!("Expected error but got success"),
+        Err(errors) => errors.into_iter().next().unwrap(),
+    }
+}
+
+#[test]
+fn test_lexer_error_has_code_and_span() {
+    let error = first_error("x = 5 $ 3\n");
+    assert!(matches!(error, MambaError::SyntaxError(_)));
+    let diagnostic = error.diagnostic().unwrap();
+    assert_eq!(diagnostic.code, ErrorCode::UnexpectedCharacter);
+    assert_eq!(diagnostic.severity, Severity::Error);
+    let span = diagnostic.span.unwrap();
+    assert_eq!((span.line, span.column), (1, 7));
+}
+
+#[test]
+fn test_unterminated_string_code() {
+    let error = first_error("name = \"mamba\n");
+    assert_eq!(error.diagnostic().unwrap().code, ErrorCode::UnterminatedString);
+}
+
+#[test]
+fn test_invalid_number_span_covers_literal() {
+    let error = first_error("x = 0b102\n");
+    let diagnostic = error.diagnostic().unwrap();
+    assert_eq!(diagnostic.code, ErrorCode::InvalidNumber);
+    assert!(diagnostic.span.unwrap().len > 1);
+}
+
+#[test]
+fn test_bang_has_help() {
+    let error = first_error("x = !y\n");
+    let diagnostic = error.diagnostic().unwrap();
+    assert_eq!(diagnostic.help.as_deref(), Some("Did you mean '!='?"));
+}
+
+#[test]
+fn test_parse_error_has_code_and_span() {
+    let error = first_error("if x == 5\n    print(x)\n");
+    assert!(matches!(error, MambaError::ParseError(_)));
+    let diagnostic = error.diagnostic().unwrap();
+    assert_eq!(diagnostic.code, ErrorCode::InvalidSyntax);
+    assert!(diagnostic.message.contains("Expected ':'"));
+    assert_eq!(diagnostic.span.unwrap().line, 1);
+}
+
+#[test]
+fn test_invalid_assignment_target_code() {
+    let error = first_error("5 = x\n");
+    assert_eq!(error.diagnostic().unwrap().code, ErrorCode::InvalidAssignmentTarget);
+}
+
+#[test]
+fn test_invalid_parameters_code() {
+    let error = first_error("def f(a=1, b):\n    pass\n");
+    assert_eq!(error.diagnostic().unwrap().code, ErrorCode::InvalidParameters);
+}
+
+#[test]
+fn test_message_no_longer_embeds_position() {
+    let error = first_error("if x == 5\n    print(x)\n");
+    let diagnostic = error.diagnostic().unwrap();
+    assert!(!diagnostic.message.contains(" at 1:"));
+    // The one-line rendering still includes it
+    assert!(error.to_string().contains("at 1:"));
+}
+
+#[test]
+fn test_render_redeclaration_end_to_end() {
+    use mamba_error::Renderer;
+    use mamba_parser::semantic::SemanticAnalyzer;
+
+    let source = "def f(arg, arg):\n    pass\n";
+    let tokens = Lexer::new(source).tokenize().unwrap();
+    let module = Parser::new(tokens).parse().unwrap();
+    let errors = SemanticAnalyzer::new().analyze(&module).unwrap_err();
+    let error: MambaError = errors[0].clone().into();
+
+    let out = Renderer::plain().render_error(&error, "main.mmb", source);
+    assert!(out.starts_with("error[E0201]: Redeclaration of 'arg'"));
+    assert!(out.contains("1 | def f(arg, arg):"));
+    assert!(out.contains("  |            ^^^"));
+    assert!(out.contains("  |       --- first defined here"));
+}
+
+#[test]
+fn test_render_parse_error_end_to_end() {
+    use mamba_error::Renderer;
+
+    let source = "x = 1\nwhile x < 3\n    x += 1\n";
+    let error = first_error(source);
+    let out = Renderer::plain().render_error(&error, "loop.mmb", source);
+    assert!(out.contains("--> loop.mmb:2:"));
+    assert!(out.contains("2 | while x < 3"));
+    assert!(out.contains('^'));
+}
@@ -226,3 +226,17 @@ fn test_multiple_distinct_errors_with_valid_code_between() {
     // Second error about 'while' (after successful parse of y = 5)
     assert!(errors[1].contains("while"));
 }
+
+#[test]
+fn test_recovery_skips_unsupported_statement_keyword() {
+    // 'try' is a recovery point but not yet a statement; recovery must still make progress
+    let err = expect_single_error("try:\n    pass\nx = 1\n");
+    assert!(err.contains("try"));
+}
+
+#[test]
+fn test_comments_are_ignored() {
+    let source = "# leading comment\nx = 1  # trailing comment\nif x:\n    # inside block\n    y = 2\n";
+    let module = parse_with_errors(source).expect("comments should not cause errors");
+    assert_eq!(module.statements.len(), 2);
+}
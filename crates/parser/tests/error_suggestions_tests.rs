@@ -106,3 +106,27 @@ fn test_then_suggestion() {
     // Note: 'then' appears as identifier in expression context, not statement
     // So it may give a different error. Let's just check it's mentioned.
 }
+
+// Tests for edit-distance keyword suggestions
+
+#[test]
+fn test_misspelled_while_suggestion() {
+    let source = "whiel x:\n    pass\n";
+    let err = parse_error(source);
+    assert!(err.contains("whiel"));
+    assert!(err.contains("Did you mean 'while'?"));
+}
+
+#[test]
+fn test_transposed_keyword_suggestion() {
+    let source = "retrun x\n";
+    let err = parse_error(source);
+    assert!(err.contains("Did you mean 'return'?"));
+}
+
+#[test]
+fn test_short_identifier_no_keyword_suggestion() {
+    let source = "x = 5 ab\n";
+    let err = parse_error(source);
+    assert!(!err.contains("Did you mean"));
+}
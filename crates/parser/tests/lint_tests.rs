@@ -0,0 +1,88 @@
+use mamba_error::{ErrorCode, Severity};
+use mamba_parser::ast::Module;
+use mamba_parser::lexer::Lexer;
+use mamba_parser::lint::{self, Lint};
+use mamba_parser::parser::Parser;
+
+/// Helper function to parse a string into an AST
+fn parse(input: &str) -> Module {
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().expect("Tokenize should succeed");
+    let mut parser = Parser::new(tokens);
+    parser.parse().expect("Parse should succeed")
+}
+
+/// Helper to collect rebind-type warning messages
+fn rebind_warnings(input: &str) -> Vec<String> {
+    lint::run(&parse(input), &[Lint::RebindType])
+        .into_iter()
+        .map(|warning| warning.message)
+        .collect()
+}
+
+#[test]
+fn test_lint_names() {
+    assert_eq!(Lint::from_name("rebind-type"), Some(Lint::RebindType));
+    assert_eq!(Lint::from_name("unknown"), None);
+    for lint in Lint::ALL {
+        assert_eq!(Lint::from_name(lint.name()), Some(*lint));
+    }
+}
+
+#[test]
+fn test_no_lints_requested() {
+    assert!(lint::run(&parse("x = 1\nx = 'a'\n"), &[]).is_empty());
+}
+
+#[test]
+fn test_rebind_int_to_str() {
+    let warnings = lint::run(&parse("count = 0\ncount = 'none'\n"), &[Lint::RebindType]);
+    assert_eq!(warnings.len(), 1);
+    let warning = &warnings[0];
+    assert_eq!(warning.code, ErrorCode::TypeChangingRebinding);
+    assert_eq!(warning.severity, Severity::Warning);
+    assert_eq!(warning.message, "'count' is rebound from int to str");
+    assert_eq!(warning.span.unwrap().line, 2);
+    assert_eq!(warning.labels[0].span.line, 1);
+    assert_eq!(warning.labels[0].message, "previously bound to int here");
+}
+
+#[test]
+fn test_same_type_rebinding_is_fine() {
+    assert!(rebind_warnings("total = 0\nfor i in range(3):\n    total = 1\n").is_empty());
+    assert!(rebind_warnings("name = 'a'\nname = str(5)\n").is_empty());
+}
+
+#[test]
+fn test_none_and_numeric_rebinding_is_fine() {
+    assert!(rebind_warnings("x = None\nx = 5\nx = None\n").is_empty());
+    assert!(rebind_warnings("x = 1\nx = 2.5\n").is_empty());
+}
+
+#[test]
+fn test_unknown_value_resets_tracking() {
+    assert!(rebind_warnings("x = 1\nx = compute()\nx = 'a'\n").is_empty());
+}
+
+#[test]
+fn test_annotation_type_change() {
+    assert_eq!(rebind_warnings("x = 10\nx: str = 'hello'\n"), vec!["'x' is rebound from int to str"]);
+}
+
+#[test]
+fn test_function_scopes_are_separate() {
+    let code = "x = 1\ndef f():\n    x = 'local'\n    return x\n";
+    assert!(rebind_warnings(code).is_empty());
+}
+
+#[test]
+fn test_rebind_inside_branch() {
+    let code = "def f(flag):\n    result = []\n    if flag:\n        result = 'none'\n    return result\n";
+    assert_eq!(rebind_warnings(code), vec!["'result' is rebound from list to str"]);
+}
+
+#[test]
+fn test_function_rebound_to_value() {
+    let code = "def helper():\n    pass\nhelper = 3\n";
+    assert_eq!(rebind_warnings(code), vec!["'helper' is rebound from function to int"]);
+}
@@ -1640,6 +1640,33 @@ fn test_parse_function_trailing_comma() {
     }
 }
 
+#[test]
+fn test_parse_type_params() {
+    let ast = parse("def first[T](items: list[T]) -> T:\n    pass\nclass Pair[K: str, V: (int, float),](Base):\n    pass\n").unwrap();
+
+    match &ast.statements[0] {
+        Statement::FunctionDef { type_params, .. } => {
+            assert_eq!(type_params.len(), 1);
+            assert_eq!(type_params[0].name, "T");
+            assert!(type_params[0].bound.is_none());
+        }
+        _ => panic!("Expected function definition"),
+    }
+    match &ast.statements[1] {
+        Statement::ClassDef { type_params, bases, .. } => {
+            let names: Vec<&str> = type_params.iter().map(|param| param.name.as_str()).collect();
+            assert_eq!(names, ["K", "V"]);
+            assert!(matches!(type_params[0].bound, Some(Expression::Identifier { .. })));
+            assert!(matches!(type_params[1].bound, Some(Expression::Tuple { .. })));
+            assert_eq!(bases.len(), 1);
+        }
+        _ => panic!("Expected class definition"),
+    }
+
+    assert!(parse("def f[](x):\n    pass\n").is_err());
+    assert!(parse("def f[T(x):\n    pass\n").is_err());
+}
+
 #[test]
 fn test_parse_function_multiline_body() {
     let result = parse("def compute():\n    x = 1\n    y = 2\n    return x + y\n");
@@ -3479,6 +3506,32 @@ fn test_parse_function_call_trailing_comma() {
     }
 }
 
+#[test]
+fn test_parse_function_call_keyword_arguments() {
+    let module = parse("connect(host, port=80, *rest, **options)\n").unwrap();
+
+    match &module.statements[0] {
+        Statement::Expression(Expression::Call { arguments, keywords, .. }) => {
+            assert_eq!(arguments.len(), 2);
+            assert!(matches!(arguments[1], Expression::Starred { .. }));
+            assert_eq!(keywords.len(), 2);
+            assert_eq!(keywords[0].name.as_deref(), Some("port"));
+            assert!(matches!(keywords[0].value, Expression::Literal(Literal::Integer { value: 80, .. })));
+            assert_eq!(keywords[1].name, None);
+        }
+        _ => panic!("Expected function call"),
+    }
+}
+
+#[test]
+fn test_parse_positional_after_keyword_error() {
+    assert!(parse("f(a=1, 2)\n").is_err());
+    assert!(parse("f(**kw, *args)\n").is_err());
+    // Unpacking may follow keywords, and walrus is not a keyword argument
+    assert!(parse("f(a=1, *args)\n").is_ok());
+    assert!(parse("f((a := 1))\n").is_ok());
+}
+
 #[test]
 fn test_parse_chained_function_calls() {
     let module = parse("get_func()()\n").unwrap();